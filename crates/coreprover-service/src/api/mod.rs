@@ -2,5 +2,6 @@
 
 pub mod routes;
 pub mod handlers;
+pub mod ratelimit;
 
-pub use routes::create_router;
\ No newline at end of file
+pub use routes::{create_router, create_router_with_limiter};
\ No newline at end of file
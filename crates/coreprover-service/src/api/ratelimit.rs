@@ -0,0 +1,167 @@
+//! Rate limiting and admission control for the REST API
+//!
+//! Every request is admitted against the address it came from
+//! (`peer:<ip>`). A request presenting a configured API key in `x-api-key`
+//! is also charged to that key, and only then are the agent id and buyer
+//! identifier in its `x-agent-id` and `x-buyer-id` headers charged too.
+//! Headers of clients without a verified key are ignored, so they can
+//! neither escape their address bucket nor drain someone else's. Requests
+//! served without connection info and without a key share the
+//! `agent:anonymous` key.
+//!
+//! A request holds a session slot for each key while it is in flight, so
+//! `max_sessions` caps concurrent requests per client. Refilled buckets
+//! are dropped by the task [`spawn_pruner`] starts.
+//!
+//! Rejected requests get `429 Too Many Requests` with a `Retry-After`
+//! header. Counters are served at `/metrics/rate_limit`.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use tbc_core::tgp::ratelimit::{LimitKey, RateLimitMetrics, RateLimitRejection, RateLimiter};
+use tokio::task::JoinHandle;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+pub const AGENT_ID_HEADER: &str = "x-agent-id";
+pub const BUYER_ID_HEADER: &str = "x-buyer-id";
+
+/// Agent id charged for requests that identify no client
+pub const ANONYMOUS_AGENT: &str = "anonymous";
+
+/// Default interval between prunes of refilled buckets
+pub const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Limiter and the API keys it trusts
+#[derive(Clone)]
+pub struct RateLimitState {
+    limiter: Arc<RateLimiter>,
+    api_keys: Arc<HashSet<String>>,
+}
+
+impl RateLimitState {
+    /// State trusting no API key
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self {
+            limiter,
+            api_keys: Arc::new(HashSet::new()),
+        }
+    }
+
+    /// Builder method to set the API keys of known clients
+    pub fn with_api_keys(mut self, api_keys: impl IntoIterator<Item = String>) -> Self {
+        self.api_keys = Arc::new(api_keys.into_iter().collect());
+        self
+    }
+
+    pub fn limiter(&self) -> &Arc<RateLimiter> {
+        &self.limiter
+    }
+
+    /// Limit keys of a request from `peer`
+    pub fn limit_keys(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Vec<LimitKey> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+
+        let mut keys = Vec::new();
+        if let Some(peer) = peer {
+            keys.push(LimitKey::peer(peer.to_string()));
+        }
+        if let Some(key) = header(API_KEY_HEADER).filter(|key| self.api_keys.contains(*key)) {
+            keys.push(LimitKey::api_key(key));
+            if let Some(agent) = header(AGENT_ID_HEADER) {
+                keys.push(LimitKey::agent(agent));
+            }
+            if let Some(buyer) = header(BUYER_ID_HEADER) {
+                keys.push(LimitKey::buyer(buyer));
+            }
+        }
+        if keys.is_empty() {
+            keys.push(LimitKey::agent(ANONYMOUS_AGENT));
+        }
+        keys
+    }
+}
+
+/// Middleware admitting each request through the limiter
+///
+/// The peer address comes from `ConnectInfo<SocketAddr>`, so serve the
+/// router with `into_make_service_with_connect_info::<SocketAddr>()`.
+pub async fn enforce(
+    State(limits): State<RateLimitState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    match limits.limiter.admit(limits.limit_keys(request.headers(), peer)) {
+        Ok(_permit) => next.run(request).await,
+        Err(rejection) => {
+            tracing::warn!(
+                "Rejected {} {}: {}",
+                request.method(),
+                request.uri().path(),
+                rejection
+            );
+            rejection_response(&rejection)
+        }
+    }
+}
+
+/// Rate limit counters
+pub async fn metrics(State(limits): State<RateLimitState>) -> impl IntoResponse {
+    Json::<RateLimitMetrics>(limits.limiter.metrics())
+}
+
+/// Prune refilled buckets of `limiter` every `every`
+pub fn spawn_pruner(limiter: Arc<RateLimiter>, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            let pruned = limiter.prune();
+            if pruned > 0 {
+                tracing::debug!("Pruned {} rate limit buckets", pruned);
+            }
+        }
+    })
+}
+
+/// `429` with `Retry-After`
+pub fn rejection_response(rejection: &RateLimitRejection) -> Response {
+    let body = RateLimitedResponse {
+        error: rejection.error_code().as_str().to_string(),
+        message: rejection.to_string(),
+        retry_after_secs: rejection.retry_after_secs,
+    };
+
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(rejection.retry_after_secs));
+    response
+}
+
+#[derive(Serialize)]
+struct RateLimitedResponse {
+    error: String,
+    message: String,
+    retry_after_secs: u64,
+}
@@ -1,19 +1,40 @@
 //! API routes
 
+use std::sync::Arc;
+
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
+use tbc_core::tgp::ratelimit::RateLimiter;
 use tower_http::trace::TraceLayer;
 
-use super::handlers;
+use super::ratelimit::RateLimitState;
+use super::{handlers, ratelimit};
 
-/// Create the API router
+/// Create the API router without rate limits
 pub fn create_router() -> Router {
+    create_router_with_limiter(Arc::new(RateLimiter::default()))
+}
+
+/// Create the API router admitting requests through `limiter`, trusting
+/// no API key
+pub fn create_router_with_limiter(limiter: Arc<RateLimiter>) -> Router {
+    create_router_with_rate_limits(RateLimitState::new(limiter))
+}
+
+/// Create the API router admitting requests through `limits`
+///
+/// `/health` and `/metrics/rate_limit` are not limited.
+pub fn create_router_with_rate_limits(limits: RateLimitState) -> Router {
     Router::new()
-        .route("/health", get(handlers::health_check))
         .route("/escrow/:order_id", get(handlers::get_escrow))
         .route("/escrow", post(handlers::create_escrow))
         .route("/events", get(handlers::query_events))
+        .route_layer(middleware::from_fn_with_state(limits.clone(), ratelimit::enforce))
+        .route("/health", get(handlers::health_check))
+        .route("/metrics/rate_limit", get(ratelimit::metrics))
+        .with_state(limits)
         .layer(TraceLayer::new_for_http())
 }
\ No newline at end of file
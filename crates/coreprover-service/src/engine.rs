@@ -67,7 +67,8 @@ impl CoreProverEngine {
         self.current_block_height += secs / self.block_interval_secs;
     }
 
-    fn now(&self) -> TimeTruth {
+    /// Current engine time on all three clocks
+    pub fn now(&self) -> TimeTruth {
         TimeTruth::new(self.current_mono, self.current_unix)
     }
 
@@ -503,6 +504,10 @@ impl CoreProverEngine {
         Ok(self.get_escrow(order_id)?.state)
     }
 
+    pub fn get_escrow_record(&self, order_id: &[u8; 32]) -> Option<&Escrow> {
+        self.get_escrow(order_id).ok()
+    }
+
     pub fn get_receipt(&self, order_id: &[u8; 32]) -> Option<&ReceiptMetadata> {
         self.receipts.iter().find(|r| &r.session_id == order_id)
     }
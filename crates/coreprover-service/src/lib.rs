@@ -7,7 +7,8 @@ pub mod profiles;
 pub mod engine;
 pub mod types;
 
-pub use api::routes::create_router;
+pub use api::ratelimit::RateLimitState;
+pub use api::routes::{create_router, create_router_with_limiter, create_router_with_rate_limits};
 
 /// Service configuration
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -16,6 +17,12 @@ pub struct Config {
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
     pub blockchain: BlockchainConfig,
+    /// Per-client request limits for the REST API
+    #[serde(default)]
+    pub rate_limit: tbc_core::tgp::ratelimit::RateLimitConfig,
+    /// API keys of known clients; other `x-api-key` values are ignored
+    #[serde(default)]
+    pub api_keys: Vec<String>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
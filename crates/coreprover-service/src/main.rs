@@ -1,7 +1,12 @@
 //! CoreProver Service Entry Point
 
 use anyhow::Result;
-use coreprover_service::{Config, create_router};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use coreprover_service::api::ratelimit::{spawn_pruner, PRUNE_INTERVAL};
+use coreprover_service::{Config, RateLimitState, create_router_with_rate_limits};
+use tbc_core::tgp::ratelimit::RateLimiter;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
@@ -26,7 +31,10 @@ async fn main() -> Result<()> {
     tracing::info!("Server: {}:{}", config.server.host, config.server.port);
 
     // Create router
-    let app = create_router();
+    let limiter = Arc::new(RateLimiter::new(config.rate_limit.clone()));
+    spawn_pruner(limiter.clone(), PRUNE_INTERVAL);
+    let limits = RateLimitState::new(limiter).with_api_keys(config.api_keys.clone());
+    let app = create_router_with_rate_limits(limits);
 
     // Start server
     let addr = format!("{}:{}", config.server.host, config.server.port);
@@ -34,7 +42,7 @@ async fn main() -> Result<()> {
     
     tracing::info!("Listening on {}", addr);
     
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
@@ -57,5 +65,7 @@ fn default_config() -> Config {
             contract_address: "0x0000000000000000000000000000000000000000".to_string(),
             chain_id: 31337,
         },
+        rate_limit: Default::default(),
+        api_keys: Vec::new(),
     }
 }
\ No newline at end of file
@@ -23,6 +23,7 @@
 // ============================================================================
 
 use serde::{Deserialize, Serialize};
+use tbc_core::tgp::pos::SettlementReceipt;
 
 // ============================================================================
 // Escrow State Machine (v0.3)
@@ -142,6 +143,28 @@ pub struct ReceiptMetadata {
     pub seller_block_height: u64,
 }
 
+impl SettlementReceipt for ReceiptMetadata {
+    fn settlement_txid(&self) -> Option<&str> {
+        self.seller_claim_txid
+            .as_deref()
+            .or(self.seller_refund_txid.as_deref())
+    }
+
+    fn settlement_chain_id(&self) -> u64 {
+        self.seller_chain_id
+    }
+
+    /// A receipt is final once settlement is stamped and a claim or refund
+    /// txid is recorded.
+    fn is_finalized(&self) -> bool {
+        self.settlement_unix > 0 && self.settlement_txid().is_some()
+    }
+
+    fn late_fulfilled(&self) -> bool {
+        self.late_fulfilled
+    }
+}
+
 // ============================================================================
 // Escrow Session Record
 // ============================================================================
@@ -0,0 +1,102 @@
+//! REST API rate limiting over a real socket
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use coreprover_service::{create_router_with_rate_limits, RateLimitState};
+use tbc_core::tgp::ratelimit::{LimitKind, RateLimit, RateLimitConfig, RateLimiter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Serve with `limiter`, trusting the API keys `key-alice` and `key-bob`
+async fn serve(limiter: Arc<RateLimiter>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let limits = RateLimitState::new(limiter)
+        .with_api_keys(["key-alice".to_string(), "key-bob".to_string()]);
+    let app = create_router_with_rate_limits(limits);
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .unwrap();
+    });
+    addr
+}
+
+/// Send a GET and return the raw response
+async fn get(addr: &str, path: &str, headers: &[(&str, &str)]) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", path, addr);
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+fn status(response: &str) -> u16 {
+    response.split_whitespace().nth(1).unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_api_key_rate_limited_with_retry_after() {
+    let config = RateLimitConfig::new()
+        .with_rate(LimitKind::ApiKey, RateLimit::per_minute(1).with_burst(2));
+    let addr = serve(Arc::new(RateLimiter::new(config))).await;
+    let alice = [("x-api-key", "key-alice")];
+
+    assert_eq!(status(&get(&addr, "/escrow/order-1", &alice).await), 200);
+    assert_eq!(status(&get(&addr, "/events", &alice).await), 200);
+
+    let rejected = get(&addr, "/escrow/order-1", &alice).await;
+    assert_eq!(status(&rejected), 429);
+    assert!(rejected.to_lowercase().contains("retry-after: 60"), "{}", rejected);
+    assert!(rejected.contains("\"error\":\"RATE_LIMITED\""));
+
+    // Other clients and unlimited routes are unaffected
+    assert_eq!(status(&get(&addr, "/events", &[("x-api-key", "key-bob")]).await), 200);
+    assert_eq!(status(&get(&addr, "/health", &alice).await), 200);
+
+    let metrics = get(&addr, "/metrics/rate_limit", &[]).await;
+    assert!(metrics.contains("\"rate_limited\":1"), "{}", metrics);
+    assert!(metrics.contains("\"admitted\":3"), "{}", metrics);
+}
+
+#[tokio::test]
+async fn test_buyer_keys_need_a_verified_api_key() {
+    let config = RateLimitConfig::new()
+        .with_rate(LimitKind::Buyer, RateLimit::per_minute(60).with_burst(1));
+    let addr = serve(Arc::new(RateLimiter::new(config))).await;
+
+    let buyer = [("x-api-key", "key-alice"), ("x-buyer-id", "buyer://alice")];
+    assert_eq!(status(&get(&addr, "/events", &buyer).await), 200);
+    assert_eq!(status(&get(&addr, "/events", &buyer).await), 429);
+
+    // Without a verified key the buyer header is not charged, so a client
+    // cannot drain another buyer's bucket
+    let forged = [("x-api-key", "key-mallory"), ("x-buyer-id", "buyer://alice")];
+    assert_eq!(status(&get(&addr, "/events", &forged).await), 200);
+    assert_eq!(status(&get(&addr, "/events", &forged).await), 200);
+}
+
+#[tokio::test]
+async fn test_peer_address_limited_whatever_the_headers() {
+    let config = RateLimitConfig::new()
+        .with_rate(LimitKind::Peer, RateLimit::per_minute(60).with_burst(2));
+    let addr = serve(Arc::new(RateLimiter::new(config))).await;
+
+    assert_eq!(status(&get(&addr, "/events", &[]).await), 200);
+    assert_eq!(status(&get(&addr, "/events", &[("x-agent-id", "agent-1")]).await), 200);
+
+    // Fresh self-declared identities do not escape the address bucket
+    for headers in [[("x-agent-id", "agent-2")], [("x-api-key", "key-unknown")]] {
+        let rejected = get(&addr, "/events", &headers).await;
+        assert_eq!(status(&rejected), 429);
+    }
+    let metrics = get(&addr, "/metrics/rate_limit", &[]).await;
+    assert!(metrics.contains("\"peer\":2"), "{}", metrics);
+}
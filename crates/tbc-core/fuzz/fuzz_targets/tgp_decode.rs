@@ -0,0 +1,28 @@
+//! Fuzz target for the TGP message decoder
+//!
+//! Feeds arbitrary bytes to the JSON decoder and checks the same invariants
+//! as `tests/tgp_properties.rs`: decoding and validation never panic, and
+//! any decoded message survives a round trip unchanged.
+//!
+//! Run with: `cargo fuzz run tgp_decode` from `crates/tbc-core`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tbc_core::tgp::messages::TGPMessage;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(message) = serde_json::from_slice::<TGPMessage>(data) else {
+        return;
+    };
+
+    let valid = message.validate().is_ok();
+
+    let json = serde_json::to_vec(&message).expect("decoded message must serialize");
+    let reparsed: TGPMessage = serde_json::from_slice(&json).expect("serialized message must decode");
+    assert_eq!(reparsed, message);
+
+    if valid {
+        assert!(reparsed.validate().is_ok());
+    }
+});
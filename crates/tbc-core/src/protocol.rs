@@ -574,7 +574,7 @@ pub fn with_correlation(
 /// # Examples
 ///
 /// `rust /// use tbc_core::tgp::protocol::ZkProfile; /// /// let profile = ZkProfile::Required; /// assert_eq!(serde_json::to_string(&profile).unwrap(), r#""REQUIRED""#); /// `
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, Default)]
 pub enum ZkProfile {
 /// Buyer does not want CoreProver escrow (direct x402 preferred)
 #[serde(rename = "NONE")]
@@ -582,6 +582,7 @@ None,
 
 /// Buyer is willing to use CoreProver if Controller recommends it
 #[serde(rename = "OPTIONAL")]
+#[default]
 Optional,
 
 /// Buyer demands CoreProver escrow
@@ -603,11 +604,6 @@ pub fn requires_escrow(&self) -> bool {
 
 }
 
-impl Default for ZkProfile {
-fn default() -> Self {
-ZkProfile::Optional
-}
-}
 
 // ============================================================================
 // EconomicEnvelope Structure (§3.6)
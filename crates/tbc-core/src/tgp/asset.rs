@@ -0,0 +1,746 @@
+//# TGP Asset Registry
+
+//**Destination Path:** `crates/tbc-core/src/tgp/asset.rs`
+
+//**Implementation:** M2 - TGP-01 Economic Envelope & Proof-of-Settlement
+
+//! Asset identifiers and the chain-aware asset registry
+//!
+//! TGP-00 messages name assets with bare symbols (`"USDC"`), TGP-01 with
+//! `evm:SYMBOL:chain` and wallets increasingly with CAIP-19 ids. An
+//! [`AssetRegistry`] maps all three to the concrete deployments a
+//! Controller can settle: chain id, contract address and decimals.
+//!
+//! # Supported Forms
+//!
+//! | Form | Example | Chain |
+//! |------|---------|-------|
+//! | Symbol | `USDC` | any deployment |
+//! | TGP-01 | `evm:USDC:8453` | given |
+//! | CAIP-19 token | `eip155:1/erc20:0xa0b8…eb48` | given |
+//! | CAIP-19 native | `eip155:1/slip44:60` | given |
+//!
+//! Symbols compare case-insensitively, contract addresses are lowercased.
+//!
+//! # Configuration
+//!
+//! ```json
+//! {
+//!   "assets": [
+//!     { "symbol": "USDC", "chain_id": 1,
+//!       "contract": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "decimals": 6 },
+//!     { "symbol": "ETH", "chain_id": 1, "decimals": 18 }
+//!   ]
+//! }
+//! ```
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::asset::{AssetInfo, AssetRegistry};
+//! use tbc_core::tgp::messages::QueryMessage;
+//! use tbc_core::tgp::types::ZkProfile;
+//!
+//! const USDC_BASE: &str = "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913";
+//!
+//! let registry = AssetRegistry::new()
+//!     .with_asset(AssetInfo::erc20("USDC", 8453, USDC_BASE, 6))
+//!     .with_asset(AssetInfo::native("PLS", 369, 18));
+//!
+//! let caip19 = "eip155:8453/erc20:0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+//! let usdc = registry.resolve_on(caip19, 8453).unwrap();
+//! assert_eq!(usdc.symbol, "USDC");
+//! assert_eq!(registry.resolve_on("evm:pls:369", 369).unwrap().decimals, 18);
+//!
+//! // USDC is only settled on Base
+//! let query = QueryMessage::new("q-1", "buyer://alice", "seller://bob", "USDC", 1_000,
+//!     ZkProfile::Optional);
+//! assert!(registry.admit_query(&query, Some(8453)).is_ok());
+//! let rejected = registry.admit_query(&query, Some(1)).unwrap_err();
+//! assert_eq!(rejected.code, "UNSUPPORTED_CHAIN");
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use super::errors::TgpErrorCode;
+use super::messages::{ErrorMessage, OfferMessage, QueryMessage};
+use super::validation::{
+    json_pointer, validate_address, validate_non_empty, ValidationCode, ValidationReport,
+};
+
+/// SLIP-44 coin type used for native assets without an explicit one (ETH)
+pub const DEFAULT_SLIP44: u32 = 60;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Asset resolution and registry loading failures
+#[derive(Debug, Error)]
+pub enum AssetError {
+    #[error("asset registry I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid asset registry configuration: {0}")]
+    Config(String),
+
+    #[error("invalid asset identifier {input:?}: {reason}")]
+    InvalidId { input: String, reason: String },
+
+    #[error("unknown asset: {0}")]
+    UnknownAsset(String),
+
+    #[error("asset {asset} cannot be settled on chain {chain_id}")]
+    UnsupportedChain { asset: String, chain_id: u64 },
+}
+
+impl AssetError {
+    fn invalid(input: &str, reason: impl Into<String>) -> Self {
+        AssetError::InvalidId {
+            input: input.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// TGP error code to report this failure with
+    pub fn error_code(&self) -> TgpErrorCode {
+        match self {
+            AssetError::Io(_) | AssetError::Config(_) => TgpErrorCode::Internal,
+            AssetError::InvalidId { .. } | AssetError::UnknownAsset(_) => {
+                TgpErrorCode::UnsupportedAsset
+            }
+            AssetError::UnsupportedChain { .. } => TgpErrorCode::UnsupportedChain,
+        }
+    }
+}
+
+// ============================================================================
+// Asset Identifiers
+// ============================================================================
+
+/// CAIP-19 asset type, e.g. `eip155:1/erc20:0xa0b8…`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Caip19 {
+    pub chain_namespace: String,
+    pub chain_reference: String,
+    pub asset_namespace: String,
+    pub asset_reference: String,
+}
+
+impl Caip19 {
+    /// EVM chain id of an `eip155` asset
+    pub fn chain_id(&self) -> Option<u64> {
+        if self.chain_namespace == "eip155" {
+            self.chain_reference.parse().ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Caip19 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}/{}:{}",
+            self.chain_namespace, self.chain_reference, self.asset_namespace, self.asset_reference
+        )
+    }
+}
+
+/// Any asset identifier accepted in `asset` fields
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AssetId {
+    /// Bare symbol, uppercased
+    Symbol(String),
+
+    /// TGP-01 `evm:SYMBOL:chain`
+    Evm { symbol: String, chain_id: u64 },
+
+    /// CAIP-19 asset type
+    Caip19(Caip19),
+}
+
+impl AssetId {
+    /// Parse and normalize an asset identifier
+    pub fn parse(input: &str) -> Result<Self, AssetError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Err(AssetError::invalid(input, "asset is empty"));
+        }
+
+        if let Some((chain, asset)) = trimmed.split_once('/') {
+            return parse_caip19(input, chain, asset).map(AssetId::Caip19);
+        }
+
+        let parts: Vec<&str> = trimmed.split(':').collect();
+        match parts.as_slice() {
+            [symbol] => parse_symbol(input, symbol).map(AssetId::Symbol),
+            [namespace, symbol, chain] if namespace.eq_ignore_ascii_case("evm") => {
+                let chain_id = chain
+                    .parse()
+                    .map_err(|_| AssetError::invalid(input, "chain id must be numeric"))?;
+                Ok(AssetId::Evm {
+                    symbol: parse_symbol(input, symbol)?,
+                    chain_id,
+                })
+            }
+            _ => Err(AssetError::invalid(input, "unrecognized asset form")),
+        }
+    }
+
+    /// Chain the identifier pins the asset to, if any
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            AssetId::Symbol(_) => None,
+            AssetId::Evm { chain_id, .. } => Some(*chain_id),
+            AssetId::Caip19(caip) => caip.chain_id(),
+        }
+    }
+}
+
+impl fmt::Display for AssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetId::Symbol(symbol) => f.write_str(symbol),
+            AssetId::Evm { symbol, chain_id } => write!(f, "evm:{}:{}", symbol, chain_id),
+            AssetId::Caip19(caip) => caip.fmt(f),
+        }
+    }
+}
+
+impl FromStr for AssetId {
+    type Err = AssetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AssetId::parse(s)
+    }
+}
+
+impl Serialize for AssetId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AssetId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+fn parse_symbol(input: &str, symbol: &str) -> Result<String, AssetError> {
+    let symbol_ok = (1..=16).contains(&symbol.len())
+        && symbol.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if !symbol_ok {
+        return Err(AssetError::invalid(input, "symbol must be 1-16 letters or digits"));
+    }
+    Ok(symbol.to_ascii_uppercase())
+}
+
+/// CAIP-19: `chain_id "/" [-a-z0-9]{3,8} ":" [-.%a-zA-Z0-9]{1,128}`
+fn parse_caip19(input: &str, chain: &str, asset: &str) -> Result<Caip19, AssetError> {
+    let (chain_namespace, chain_reference) = chain
+        .split_once(':')
+        .ok_or_else(|| AssetError::invalid(input, "CAIP-19 chain id must be namespace:reference"))?;
+    let (asset_namespace, asset_reference) = asset
+        .split_once(':')
+        .ok_or_else(|| AssetError::invalid(input, "CAIP-19 asset must be namespace:reference"))?;
+
+    let is_namespace = |s: &str| {
+        (3..=8).contains(&s.len())
+            && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    };
+    if !is_namespace(chain_namespace) || !is_namespace(asset_namespace) {
+        return Err(AssetError::invalid(input, "invalid CAIP-19 namespace"));
+    }
+
+    let reference_ok = (1..=32).contains(&chain_reference.len())
+        && chain_reference.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !reference_ok {
+        return Err(AssetError::invalid(input, "invalid CAIP-19 chain reference"));
+    }
+    if chain_namespace == "eip155" && chain_reference.parse::<u64>().is_err() {
+        return Err(AssetError::invalid(input, "eip155 chain reference must be numeric"));
+    }
+
+    let asset_reference = match asset_namespace {
+        "erc20" | "erc721" => {
+            validate_address(asset_reference, "asset reference")
+                .map_err(|reason| AssetError::invalid(input, reason))?;
+            asset_reference.to_ascii_lowercase()
+        }
+        "slip44" => {
+            asset_reference
+                .parse::<u32>()
+                .map_err(|_| AssetError::invalid(input, "slip44 coin type must be numeric"))?;
+            asset_reference.to_string()
+        }
+        _ => {
+            let reference_ok = (1..=128).contains(&asset_reference.len())
+                && asset_reference
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '%'));
+            if !reference_ok {
+                return Err(AssetError::invalid(input, "invalid CAIP-19 asset reference"));
+            }
+            asset_reference.to_string()
+        }
+    };
+
+    Ok(Caip19 {
+        chain_namespace: chain_namespace.to_string(),
+        chain_reference: chain_reference.to_string(),
+        asset_namespace: asset_namespace.to_string(),
+        asset_reference,
+    })
+}
+
+// ============================================================================
+// AssetInfo
+// ============================================================================
+
+/// One settleable deployment of an asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetInfo {
+    /// Ticker symbol, e.g. `"USDC"`
+    pub symbol: String,
+
+    /// EVM chain id the deployment lives on
+    pub chain_id: u64,
+
+    /// ERC-20 contract address, `None` for the chain's native asset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+
+    /// Decimals of the smallest unit
+    pub decimals: u8,
+
+    /// SLIP-44 coin type of a native asset, defaults to [`DEFAULT_SLIP44`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slip44: Option<u32>,
+}
+
+impl AssetInfo {
+    /// Native asset of `chain_id`
+    pub fn native(symbol: impl Into<String>, chain_id: u64, decimals: u8) -> Self {
+        Self {
+            symbol: symbol.into(),
+            chain_id,
+            contract: None,
+            decimals,
+            slip44: None,
+        }
+    }
+
+    /// ERC-20 token deployed at `contract` on `chain_id`
+    pub fn erc20(
+        symbol: impl Into<String>,
+        chain_id: u64,
+        contract: impl Into<String>,
+        decimals: u8,
+    ) -> Self {
+        Self {
+            contract: Some(contract.into()),
+            ..Self::native(symbol, chain_id, decimals)
+        }
+    }
+
+    /// Builder method to set the SLIP-44 coin type of a native asset
+    pub fn with_slip44(mut self, coin_type: u32) -> Self {
+        self.slip44 = Some(coin_type);
+        self
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.contract.is_none()
+    }
+
+    /// CAIP-19 id of this deployment
+    pub fn caip19(&self) -> Caip19 {
+        let (asset_namespace, asset_reference) = match &self.contract {
+            Some(contract) => ("erc20", contract.to_ascii_lowercase()),
+            None => ("slip44", self.slip44.unwrap_or(DEFAULT_SLIP44).to_string()),
+        };
+        Caip19 {
+            chain_namespace: "eip155".to_string(),
+            chain_reference: self.chain_id.to_string(),
+            asset_namespace: asset_namespace.to_string(),
+            asset_reference,
+        }
+    }
+
+    /// TGP-01 `evm:SYMBOL:chain` id of this deployment
+    pub fn tgp01_id(&self) -> String {
+        format!("evm:{}:{}", self.symbol, self.chain_id)
+    }
+
+    fn matches(&self, id: &AssetId) -> bool {
+        match id {
+            AssetId::Symbol(symbol) => self.symbol.eq_ignore_ascii_case(symbol),
+            AssetId::Evm { symbol, chain_id } => {
+                self.chain_id == *chain_id && self.symbol.eq_ignore_ascii_case(symbol)
+            }
+            AssetId::Caip19(caip) => self.caip19() == *caip,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        validate_non_empty(&self.symbol, "symbol")?;
+        parse_symbol(&self.symbol, &self.symbol).map_err(|e| e.to_string())?;
+        if let Some(contract) = &self.contract {
+            validate_address(contract, "contract")?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// AssetRegistry
+// ============================================================================
+
+/// Assets a Controller can settle, keyed by symbol and chain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetRegistry {
+    #[serde(default)]
+    assets: Vec<AssetInfo>,
+}
+
+impl AssetRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to register a deployment
+    pub fn with_asset(mut self, asset: AssetInfo) -> Self {
+        self.register(asset);
+        self
+    }
+
+    /// Register a deployment, replacing any with the same symbol and chain
+    pub fn register(&mut self, asset: AssetInfo) {
+        self.assets.retain(|existing| {
+            existing.chain_id != asset.chain_id
+                || !existing.symbol.eq_ignore_ascii_case(&asset.symbol)
+        });
+        self.assets.push(asset);
+    }
+
+    /// Parse a JSON registry configuration
+    pub fn from_json(json: &str) -> Result<Self, AssetError> {
+        let parsed: AssetRegistry =
+            serde_json::from_str(json).map_err(|e| AssetError::Config(e.to_string()))?;
+
+        let mut registry = AssetRegistry::new();
+        for (i, asset) in parsed.assets.into_iter().enumerate() {
+            asset
+                .validate()
+                .map_err(|reason| AssetError::Config(format!("assets[{}]: {}", i, reason)))?;
+            if registry.lookup(&asset.symbol, asset.chain_id).is_some() {
+                return Err(AssetError::Config(format!(
+                    "assets[{}]: duplicate {} on chain {}",
+                    i, asset.symbol, asset.chain_id
+                )));
+            }
+            registry.register(asset);
+        }
+        Ok(registry)
+    }
+
+    /// Load a JSON registry configuration file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// All registered deployments
+    pub fn assets(&self) -> &[AssetInfo] {
+        &self.assets
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    fn lookup(&self, symbol: &str, chain_id: u64) -> Option<&AssetInfo> {
+        self.assets
+            .iter()
+            .find(|a| a.chain_id == chain_id && a.symbol.eq_ignore_ascii_case(symbol))
+    }
+
+    /// Every deployment `asset` may refer to, ordered by chain id
+    ///
+    /// # Errors
+    ///
+    /// [`AssetError::InvalidId`] for malformed identifiers,
+    /// [`AssetError::UnknownAsset`] if nothing matches.
+    pub fn resolve(&self, asset: &str) -> Result<Vec<&AssetInfo>, AssetError> {
+        let id = AssetId::parse(asset)?;
+        let mut matches: Vec<&AssetInfo> = self.assets.iter().filter(|a| a.matches(&id)).collect();
+        if matches.is_empty() {
+            return Err(AssetError::UnknownAsset(id.to_string()));
+        }
+        matches.sort_by_key(|a| a.chain_id);
+        Ok(matches)
+    }
+
+    /// The deployment of `asset` on `chain_id`
+    ///
+    /// # Errors
+    ///
+    /// As [`resolve`](Self::resolve), plus [`AssetError::UnsupportedChain`]
+    /// if the asset is known but not settleable on `chain_id`.
+    pub fn resolve_on(&self, asset: &str, chain_id: u64) -> Result<&AssetInfo, AssetError> {
+        self.resolve(asset)?
+            .into_iter()
+            .find(|a| a.chain_id == chain_id)
+            .ok_or_else(|| AssetError::UnsupportedChain {
+                asset: asset.to_string(),
+                chain_id,
+            })
+    }
+
+    /// Chains `asset` can be settled on
+    pub fn chains_for(&self, asset: &str) -> Vec<u64> {
+        self.resolve(asset)
+            .map(|assets| assets.iter().map(|a| a.chain_id).collect())
+            .unwrap_or_default()
+    }
+
+    /// Record an error at `parent/field` if `asset` does not resolve
+    ///
+    /// Empty values are left to the message's own `Required` check.
+    pub fn validate_asset_into(
+        &self,
+        asset: &str,
+        chain_id: Option<u64>,
+        report: &mut ValidationReport,
+        parent: &str,
+        field: &str,
+    ) {
+        if asset.is_empty() {
+            return;
+        }
+        let result = match chain_id {
+            Some(chain_id) => self.resolve_on(asset, chain_id).map(|_| ()),
+            None => self.resolve(asset).map(|_| ()),
+        };
+        if let Err(e) = result {
+            let code = match e {
+                AssetError::InvalidId { .. } => ValidationCode::InvalidFormat,
+                _ => ValidationCode::UnsupportedAsset,
+            };
+            report.error(json_pointer(parent, field), code, e.to_string());
+        }
+    }
+
+    /// Check `QueryMessage.asset` against the registry
+    pub fn validate_query_into(
+        &self,
+        query: &QueryMessage,
+        report: &mut ValidationReport,
+        path: &str,
+    ) {
+        self.validate_asset_into(&query.asset, None, report, path, "asset");
+    }
+
+    /// Check `OfferMessage.asset` and every route's asset on its chain
+    pub fn validate_offer_into(
+        &self,
+        offer: &OfferMessage,
+        report: &mut ValidationReport,
+        path: &str,
+    ) {
+        self.validate_asset_into(&offer.asset, None, report, path, "asset");
+        let routes_path = json_pointer(path, "routes");
+        for (i, route) in offer.routes.iter().enumerate() {
+            let route_path = json_pointer(&routes_path, &i.to_string());
+            let chain_id = Some(route.chain_id);
+            self.validate_asset_into(&route.asset, chain_id, report, &route_path, "asset");
+        }
+    }
+
+    /// Controller admission check for a QUERY
+    ///
+    /// The requested chain is `chain_id` if given, otherwise the chain
+    /// pinned by the asset id, otherwise any chain. Returns the deployments
+    /// the query can settle on, or the ERROR message to send back.
+    #[allow(clippy::result_large_err)]
+    pub fn admit_query(
+        &self,
+        query: &QueryMessage,
+        chain_id: Option<u64>,
+    ) -> Result<Vec<&AssetInfo>, ErrorMessage> {
+        let requested = chain_id.or_else(|| AssetId::parse(&query.asset).ok()?.chain_id());
+        let result = match requested {
+            Some(chain_id) => self.resolve_on(&query.asset, chain_id).map(|a| vec![a]),
+            None => self.resolve(&query.asset),
+        };
+        result.map_err(|e| {
+            let id = format!("err-asset-{}", query.id);
+            ErrorMessage::from_code(id, e.error_code(), e.to_string())
+                .correlated_to(query.id.clone())
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tgp::routes::RouteOption;
+    use crate::tgp::types::{EconomicEnvelope, ZkProfile};
+
+    const USDC_ETH: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+    const USDC_BASE: &str = "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913";
+
+    fn registry() -> AssetRegistry {
+        AssetRegistry::new()
+            .with_asset(AssetInfo::erc20("USDC", 8453, USDC_BASE, 6))
+            .with_asset(AssetInfo::erc20("USDC", 1, USDC_ETH, 6))
+            .with_asset(AssetInfo::native("ETH", 1, 18))
+    }
+
+    fn query(asset: &str) -> QueryMessage {
+        QueryMessage::new("q-1", "buyer://alice", "seller://bob", asset, 1_000, ZkProfile::Optional)
+    }
+
+    #[test]
+    fn test_parse_asset_ids() {
+        assert_eq!(AssetId::parse("usdc").unwrap(), AssetId::Symbol("USDC".to_string()));
+        assert_eq!(
+            AssetId::parse("evm:eth:1").unwrap(),
+            AssetId::Evm {
+                symbol: "ETH".to_string(),
+                chain_id: 1
+            }
+        );
+
+        let caip = AssetId::parse(&format!("eip155:1/erc20:{}", USDC_ETH)).unwrap();
+        assert_eq!(caip.chain_id(), Some(1));
+        assert_eq!(caip.to_string(), format!("eip155:1/erc20:{}", USDC_ETH.to_lowercase()));
+
+        for bad in ["", "evm:ETH:mainnet", "eip155:1/erc20:0x12", "eip155:x/slip44:60", "a:b"] {
+            assert!(AssetId::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let registry = registry();
+
+        let usdc: Vec<u64> = registry.resolve("USDC").unwrap().iter().map(|a| a.chain_id).collect();
+        assert_eq!(usdc, vec![1, 8453]);
+        assert_eq!(registry.resolve_on("evm:USDC:8453", 8453).unwrap().contract.as_deref(),
+            Some(USDC_BASE));
+        assert_eq!(registry.resolve_on("eip155:1/slip44:60", 1).unwrap().symbol, "ETH");
+        assert_eq!(registry.chains_for("ETH"), vec![1]);
+
+        assert!(matches!(registry.resolve("DAI"), Err(AssetError::UnknownAsset(_))));
+        assert!(matches!(
+            registry.resolve_on("ETH", 8453),
+            Err(AssetError::UnsupportedChain { chain_id: 8453, .. })
+        ));
+        assert!(matches!(
+            registry.resolve_on("evm:USDC:8453", 1),
+            Err(AssetError::UnsupportedChain { .. })
+        ));
+    }
+
+    #[test]
+    fn test_caip19_round_trip() {
+        for asset in registry().assets() {
+            let id = asset.caip19().to_string();
+            assert_eq!(registry().resolve_on(&id, asset.chain_id).unwrap(), asset);
+        }
+        assert_eq!(AssetInfo::native("PLS", 369, 18).caip19().to_string(), "eip155:369/slip44:60");
+    }
+
+    #[test]
+    fn test_from_json() {
+        let registry = AssetRegistry::from_json(&format!(
+            r#"{{"assets": [
+                {{"symbol": "USDC", "chain_id": 1, "contract": "{}", "decimals": 6}},
+                {{"symbol": "ETH", "chain_id": 1, "decimals": 18}}
+            ]}}"#,
+            USDC_ETH
+        ))
+        .unwrap();
+        assert_eq!(registry.assets().len(), 2);
+        assert!(registry.resolve_on("ETH", 1).unwrap().is_native());
+
+        let duplicate = r#"{"assets": [
+            {"symbol": "ETH", "chain_id": 1, "decimals": 18},
+            {"symbol": "eth", "chain_id": 1, "decimals": 18}
+        ]}"#;
+        assert!(matches!(AssetRegistry::from_json(duplicate), Err(AssetError::Config(_))));
+
+        let bad_contract = r#"{"assets": [
+            {"symbol": "USDC", "chain_id": 1, "contract": "0x12", "decimals": 6}
+        ]}"#;
+        assert!(matches!(AssetRegistry::from_json(bad_contract), Err(AssetError::Config(_))));
+        assert!(AssetRegistry::from_json("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("assets.json");
+        fs::write(&path, serde_json::to_string(&registry()).unwrap()).unwrap();
+        assert_eq!(AssetRegistry::load(&path).unwrap(), registry());
+        assert!(matches!(AssetRegistry::load(dir.path().join("missing")), Err(AssetError::Io(_))));
+    }
+
+    #[test]
+    fn test_validate_messages() {
+        let registry = registry();
+
+        let mut report = ValidationReport::new();
+        registry.validate_query_into(&query("USDC"), &mut report, "");
+        registry.validate_query_into(&query(""), &mut report, "");
+        assert!(report.is_empty());
+
+        registry.validate_query_into(&query("DAI"), &mut report, "");
+        registry.validate_query_into(&query("evm:ETH"), &mut report, "");
+        let codes: Vec<ValidationCode> = report.errors().map(|i| i.code).collect();
+        assert_eq!(codes, vec![ValidationCode::UnsupportedAsset, ValidationCode::InvalidFormat]);
+
+        let envelope = EconomicEnvelope::new(50);
+        let offer = OfferMessage::new("offer-1", "q-1", "USDC", 1_000, false, envelope)
+            .with_routes(vec![
+                RouteOption::direct("base", "USDC", 1_000, 8453),
+                RouteOption::direct("pls", "USDC", 1_000, 369),
+            ]);
+        let mut report = ValidationReport::new();
+        registry.validate_offer_into(&offer, &mut report, "");
+        let paths: Vec<&str> = report.errors().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, vec!["/routes/1/asset"]);
+    }
+
+    #[test]
+    fn test_admit_query() {
+        let registry = registry();
+
+        assert_eq!(registry.admit_query(&query("USDC"), None).unwrap().len(), 2);
+        assert_eq!(registry.admit_query(&query("USDC"), Some(8453)).unwrap()[0].chain_id, 8453);
+        assert_eq!(registry.admit_query(&query("evm:ETH:1"), None).unwrap()[0].decimals, 18);
+
+        let error = registry.admit_query(&query("ETH"), Some(8453)).unwrap_err();
+        assert_eq!(error.error_code(), Some(TgpErrorCode::UnsupportedChain));
+        assert_eq!(error.correlation_id.as_deref(), Some("q-1"));
+        assert!(error.validate().is_ok());
+
+        let error = registry.admit_query(&query("DAI"), None).unwrap_err();
+        assert_eq!(error.error_code(), Some(TgpErrorCode::UnsupportedAsset));
+    }
+}
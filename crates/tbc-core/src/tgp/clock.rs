@@ -0,0 +1,75 @@
+//# TGP Clock Abstraction
+
+//**Destination Path:** `crates/tbc-core/src/tgp/clock.rs`
+
+//**Implementation:** M1 - TGP Message Parsing & Basic Routing
+
+//! Injectable time source for TGP sessions
+//!
+//! Session deadlines are Unix seconds. Reading them through a [`Clock`]
+//! instead of `SystemTime` lets a controller replay persisted sessions
+//! against a fixed time and lets tests step time forward deterministically.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::clock::{Clock, ManualClock};
+//!
+//! let clock = ManualClock::new(1_731_600_000);
+//! clock.advance(30);
+//! assert_eq!(clock.now_unix(), 1_731_600_030);
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current Unix time in seconds
+pub trait Clock: Send + Sync {
+    /// Seconds since the Unix epoch
+    fn now_unix(&self) -> u64;
+}
+
+/// Wall clock backed by `SystemTime`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time before Unix epoch")
+            .as_secs()
+    }
+}
+
+/// Clock that only moves when told to
+///
+/// Shared through an `Arc`, every holder sees the same time.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new(now_unix: u64) -> Self {
+        Self {
+            now: AtomicU64::new(now_unix),
+        }
+    }
+
+    /// Set the current time
+    pub fn set(&self, now_unix: u64) {
+        self.now.store(now_unix, Ordering::SeqCst);
+    }
+
+    /// Move the current time forward by `seconds`
+    pub fn advance(&self, seconds: u64) {
+        self.now.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_unix(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,356 @@
+//# TGP Error Codes
+
+//**Destination Path:** `crates/tbc-core/src/tgp/errors.rs`
+
+//**Implementation:** M1 - TGP Message Parsing & Basic Routing
+
+//! Standardized TGP error codes
+//!
+//! `ErrorMessage.code` stays a string on the wire so that peers can add
+//! codes without breaking older decoders. [`TgpErrorCode`] is the registry
+//! of codes this implementation emits and understands, with retry hints
+//! and HTTP status mapping for transports such as TxIP over HTTP.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::errors::{RetryHint, TgpErrorCode};
+//! use tbc_core::tgp::messages::ErrorMessage;
+//!
+//! let error = ErrorMessage::from_code("err-1", TgpErrorCode::NoRoute, "no route for DAI");
+//! assert_eq!(error.code, "NO_ROUTE");
+//! assert_eq!(error.error_code(), Some(TgpErrorCode::NoRoute));
+//!
+//! assert_eq!(TgpErrorCode::RateLimited.http_status(), 429);
+//! assert_eq!(TgpErrorCode::RateLimited.retry_hint(), RetryHint::Backoff);
+//! assert_eq!("SESSION_EXPIRED".parse(), Ok(TgpErrorCode::SessionExpired));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// TgpErrorCode
+// ============================================================================
+
+/// Registry of TGP error codes
+///
+/// | Code | HTTP | Retry |
+/// |------|------|-------|
+/// | `INVALID_QUERY` | 400 | no |
+/// | `INVALID_MESSAGE` | 400 | no |
+/// | `UNSUPPORTED_VERSION` | 400 | no |
+/// | `UNSUPPORTED_ASSET` | 422 | no |
+/// | `UNSUPPORTED_CHAIN` | 422 | no |
+/// | `NO_ROUTE` | 422 | backoff |
+/// | `FEE_CAP_EXCEEDED` | 422 | no |
+/// | `POLICY_VIOLATION` | 403 | no |
+/// | `CONTRACT_BLACKLISTED` | 403 | no |
+/// | `UNAUTHENTICATED` | 401 | no |
+/// | `UNAUTHORIZED` | 403 | no |
+/// | `INSUFFICIENT_FUNDS` | 402 | no |
+/// | `ESCROW_UNAVAILABLE` | 503 | backoff |
+/// | `SETTLEMENT_FAILED` | 502 | new session |
+/// | `OFFER_EXPIRED` | 410 | new session |
+/// | `TIMEOUT` | 408 | new session |
+/// | `SESSION_EXPIRED` | 410 | new session |
+/// | `SESSION_NOT_FOUND` | 404 | new session |
+/// | `INVALID_STATE` | 409 | no |
+/// | `REPLAY_DETECTED` | 409 | no |
+/// | `RATE_LIMITED` | 429 | backoff |
+/// | `INTERNAL_ERROR` | 500 | backoff |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TgpErrorCode {
+    /// QUERY failed validation
+    InvalidQuery,
+
+    /// Any other message failed validation
+    InvalidMessage,
+
+    /// No common TGP version
+    UnsupportedVersion,
+
+    /// Asset not accepted by the Controller or seller
+    UnsupportedAsset,
+
+    /// Settlement chain not supported
+    UnsupportedChain,
+
+    /// No settlement route satisfies the QUERY
+    NoRoute,
+
+    /// Fees would exceed `max_fees_bps`
+    FeeCapExceeded,
+
+    /// Rejected by Controller policy
+    PolicyViolation,
+
+    /// Escrow contract is on a deny list
+    ContractBlacklisted,
+
+    /// Caller could not be authenticated
+    Unauthenticated,
+
+    /// Caller is not allowed to perform the operation
+    Unauthorized,
+
+    /// Buyer cannot cover amount plus fees
+    InsufficientFunds,
+
+    /// CoreProver escrow temporarily unavailable
+    EscrowUnavailable,
+
+    /// Settlement transaction failed on chain
+    SettlementFailed,
+
+    /// OFFER passed its expiry
+    OfferExpired,
+
+    /// Session state deadline passed
+    Timeout,
+
+    /// Session no longer accepts messages
+    SessionExpired,
+
+    /// Unknown session id
+    SessionNotFound,
+
+    /// Message not valid in the current session state
+    InvalidState,
+
+    /// Message id or signature seen before
+    ReplayDetected,
+
+    /// Caller exceeded its rate limit
+    RateLimited,
+
+    /// Unexpected Controller failure
+    #[serde(rename = "INTERNAL_ERROR")]
+    Internal,
+}
+
+/// How a client should react to an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryHint {
+    /// Retrying the same request will fail again
+    DoNotRetry,
+
+    /// The same request may succeed later; retry with backoff
+    Backoff,
+
+    /// This session is over; start a new one with a fresh QUERY
+    NewSession,
+}
+
+impl TgpErrorCode {
+    /// Every registered code
+    pub const ALL: &'static [TgpErrorCode] = &[
+        TgpErrorCode::InvalidQuery,
+        TgpErrorCode::InvalidMessage,
+        TgpErrorCode::UnsupportedVersion,
+        TgpErrorCode::UnsupportedAsset,
+        TgpErrorCode::UnsupportedChain,
+        TgpErrorCode::NoRoute,
+        TgpErrorCode::FeeCapExceeded,
+        TgpErrorCode::PolicyViolation,
+        TgpErrorCode::ContractBlacklisted,
+        TgpErrorCode::Unauthenticated,
+        TgpErrorCode::Unauthorized,
+        TgpErrorCode::InsufficientFunds,
+        TgpErrorCode::EscrowUnavailable,
+        TgpErrorCode::SettlementFailed,
+        TgpErrorCode::OfferExpired,
+        TgpErrorCode::Timeout,
+        TgpErrorCode::SessionExpired,
+        TgpErrorCode::SessionNotFound,
+        TgpErrorCode::InvalidState,
+        TgpErrorCode::ReplayDetected,
+        TgpErrorCode::RateLimited,
+        TgpErrorCode::Internal,
+    ];
+
+    /// Stable wire string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TgpErrorCode::InvalidQuery => "INVALID_QUERY",
+            TgpErrorCode::InvalidMessage => "INVALID_MESSAGE",
+            TgpErrorCode::UnsupportedVersion => "UNSUPPORTED_VERSION",
+            TgpErrorCode::UnsupportedAsset => "UNSUPPORTED_ASSET",
+            TgpErrorCode::UnsupportedChain => "UNSUPPORTED_CHAIN",
+            TgpErrorCode::NoRoute => "NO_ROUTE",
+            TgpErrorCode::FeeCapExceeded => "FEE_CAP_EXCEEDED",
+            TgpErrorCode::PolicyViolation => "POLICY_VIOLATION",
+            TgpErrorCode::ContractBlacklisted => "CONTRACT_BLACKLISTED",
+            TgpErrorCode::Unauthenticated => "UNAUTHENTICATED",
+            TgpErrorCode::Unauthorized => "UNAUTHORIZED",
+            TgpErrorCode::InsufficientFunds => "INSUFFICIENT_FUNDS",
+            TgpErrorCode::EscrowUnavailable => "ESCROW_UNAVAILABLE",
+            TgpErrorCode::SettlementFailed => "SETTLEMENT_FAILED",
+            TgpErrorCode::OfferExpired => "OFFER_EXPIRED",
+            TgpErrorCode::Timeout => "TIMEOUT",
+            TgpErrorCode::SessionExpired => "SESSION_EXPIRED",
+            TgpErrorCode::SessionNotFound => "SESSION_NOT_FOUND",
+            TgpErrorCode::InvalidState => "INVALID_STATE",
+            TgpErrorCode::ReplayDetected => "REPLAY_DETECTED",
+            TgpErrorCode::RateLimited => "RATE_LIMITED",
+            TgpErrorCode::Internal => "INTERNAL_ERROR",
+        }
+    }
+
+    /// HTTP status for transports that surface TGP errors as HTTP responses
+    pub fn http_status(&self) -> u16 {
+        match self {
+            TgpErrorCode::InvalidQuery
+            | TgpErrorCode::InvalidMessage
+            | TgpErrorCode::UnsupportedVersion => 400,
+            TgpErrorCode::Unauthenticated => 401,
+            TgpErrorCode::InsufficientFunds => 402,
+            TgpErrorCode::PolicyViolation
+            | TgpErrorCode::ContractBlacklisted
+            | TgpErrorCode::Unauthorized => 403,
+            TgpErrorCode::SessionNotFound => 404,
+            TgpErrorCode::Timeout => 408,
+            TgpErrorCode::InvalidState | TgpErrorCode::ReplayDetected => 409,
+            TgpErrorCode::OfferExpired | TgpErrorCode::SessionExpired => 410,
+            TgpErrorCode::UnsupportedAsset
+            | TgpErrorCode::UnsupportedChain
+            | TgpErrorCode::NoRoute
+            | TgpErrorCode::FeeCapExceeded => 422,
+            TgpErrorCode::RateLimited => 429,
+            TgpErrorCode::Internal => 500,
+            TgpErrorCode::SettlementFailed => 502,
+            TgpErrorCode::EscrowUnavailable => 503,
+        }
+    }
+
+    /// Most general code for an HTTP status
+    ///
+    /// Lossy: several codes share a status, so this returns the most general
+    /// one (e.g. 403 → `UNAUTHORIZED`, 422 → `NO_ROUTE`). Other 4xx map to
+    /// `INVALID_MESSAGE`, other 5xx to `INTERNAL_ERROR`, and non-error
+    /// statuses return `None`.
+    pub fn from_http_status(status: u16) -> Option<TgpErrorCode> {
+        let code = match status {
+            401 => TgpErrorCode::Unauthenticated,
+            402 => TgpErrorCode::InsufficientFunds,
+            403 => TgpErrorCode::Unauthorized,
+            404 => TgpErrorCode::SessionNotFound,
+            408 => TgpErrorCode::Timeout,
+            409 => TgpErrorCode::InvalidState,
+            410 => TgpErrorCode::SessionExpired,
+            422 => TgpErrorCode::NoRoute,
+            429 => TgpErrorCode::RateLimited,
+            502 => TgpErrorCode::SettlementFailed,
+            503 => TgpErrorCode::EscrowUnavailable,
+            400..=499 => TgpErrorCode::InvalidMessage,
+            500..=599 => TgpErrorCode::Internal,
+            _ => return None,
+        };
+        Some(code)
+    }
+
+    /// How a client should react to this error
+    pub fn retry_hint(&self) -> RetryHint {
+        match self {
+            TgpErrorCode::NoRoute
+            | TgpErrorCode::EscrowUnavailable
+            | TgpErrorCode::RateLimited
+            | TgpErrorCode::Internal => RetryHint::Backoff,
+            TgpErrorCode::SettlementFailed
+            | TgpErrorCode::OfferExpired
+            | TgpErrorCode::Timeout
+            | TgpErrorCode::SessionExpired
+            | TgpErrorCode::SessionNotFound => RetryHint::NewSession,
+            _ => RetryHint::DoNotRetry,
+        }
+    }
+
+    /// Whether the same request may succeed if retried
+    pub fn is_retryable(&self) -> bool {
+        self.retry_hint() == RetryHint::Backoff
+    }
+}
+
+impl fmt::Display for TgpErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TgpErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TgpErrorCode::ALL
+            .iter()
+            .copied()
+            .find(|code| code.as_str() == s)
+            .ok_or_else(|| format!("unknown TGP error code: {}", s))
+    }
+}
+
+impl From<TgpErrorCode> for String {
+    fn from(code: TgpErrorCode) -> Self {
+        code.as_str().to_string()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tgp::messages::error_codes;
+
+    #[test]
+    fn test_wire_strings_round_trip() {
+        for code in TgpErrorCode::ALL {
+            assert_eq!(code.as_str().parse::<TgpErrorCode>(), Ok(*code));
+            assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+        }
+        assert!("NOT_A_CODE".parse::<TgpErrorCode>().is_err());
+    }
+
+    #[test]
+    fn test_legacy_constants_are_registered() {
+        for constant in [
+            error_codes::INVALID_QUERY,
+            error_codes::UNSUPPORTED_ASSET,
+            error_codes::POLICY_VIOLATION,
+            error_codes::CONTRACT_BLACKLISTED,
+            error_codes::INSUFFICIENT_FUNDS,
+            error_codes::TIMEOUT,
+            error_codes::SETTLEMENT_FAILED,
+            error_codes::INVALID_STATE,
+        ] {
+            assert!(constant.parse::<TgpErrorCode>().is_ok(), "{}", constant);
+        }
+    }
+
+    #[test]
+    fn test_http_status_mapping() {
+        for code in TgpErrorCode::ALL {
+            let status = code.http_status();
+            assert!((400..600).contains(&status));
+            let generic = TgpErrorCode::from_http_status(status).unwrap();
+            assert_eq!(generic.http_status(), status);
+        }
+        assert_eq!(TgpErrorCode::from_http_status(200), None);
+        assert_eq!(TgpErrorCode::from_http_status(418), Some(TgpErrorCode::InvalidMessage));
+        assert_eq!(TgpErrorCode::from_http_status(504), Some(TgpErrorCode::Internal));
+    }
+
+    #[test]
+    fn test_retry_hints() {
+        assert!(TgpErrorCode::EscrowUnavailable.is_retryable());
+        assert!(!TgpErrorCode::PolicyViolation.is_retryable());
+        assert_eq!(TgpErrorCode::Timeout.retry_hint(), RetryHint::NewSession);
+        assert_eq!(TgpErrorCode::ReplayDetected.retry_hint(), RetryHint::DoNotRetry);
+    }
+}
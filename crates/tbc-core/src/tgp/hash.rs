@@ -0,0 +1,167 @@
+//# TGP Canonical Hashing
+
+//**Destination Path:** `crates/tbc-core/src/tgp/hash.rs`
+
+//**Implementation:** M2 - TGP-01 Economic Envelope & Proof-of-Settlement
+
+//! Canonical serialization and hashing helpers for TGP-01 objects
+//!
+//! TGP-01 binds settlement artifacts together by hash: the Proof-of-Settlement
+//! references the Economic Envelope, the settlement receipt and the TDR. All of
+//! these hashes are taken over a canonical JSON encoding so that independent
+//! implementations arrive at the same digest.
+//!
+//! # Canonical JSON
+//!
+//! - Object keys are sorted lexicographically (by UTF-8 bytes)
+//! - No insignificant whitespace
+//! - Strings and numbers use `serde_json`'s standard encoding
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::hash::{canonical_json, keccak256_hex};
+//! use serde_json::json;
+//!
+//! let value = json!({"b": 1, "a": {"d": 2, "c": 3}});
+//! assert_eq!(canonical_json(&value)?, r#"{"a":{"c":3,"d":2},"b":1}"#);
+//!
+//! let digest = keccak256_hex(canonical_json(&value)?.as_bytes());
+//! assert_eq!(digest.len(), 66);
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+/// Serialize a value to canonical JSON (sorted keys, no whitespace)
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be represented as JSON.
+pub fn canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<String, serde_json::Error> {
+    let value = serde_json::to_value(value)?;
+    let mut out = String::new();
+    write_canonical(&value, &mut out)?;
+    Ok(out)
+}
+
+fn write_canonical(value: &Value, out: &mut String) -> Result<(), serde_json::Error> {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::to_string(key)?);
+                out.push(':');
+                write_canonical(item, out)?;
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&serde_json::to_string(scalar)?),
+    }
+    Ok(())
+}
+
+/// Keccak-256 digest as a 0x-prefixed hex string
+///
+/// Used for `economic_envelope_hash` and `receipt_hash` (TGP-01 §5, §7).
+///
+/// # Examples
+///
+/// ```rust
+/// # use tbc_core::tgp::hash::keccak256_hex;
+/// assert_eq!(
+///     keccak256_hex(b""),
+///     "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+/// );
+/// ```
+pub fn keccak256_hex(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(Keccak256::digest(data)))
+}
+
+/// SHA-256 digest as a 0x-prefixed hex string
+///
+/// Used for `tdr_hash` (TGP-01 §5).
+///
+/// # Examples
+///
+/// ```rust
+/// # use tbc_core::tgp::hash::sha256_hex;
+/// assert_eq!(
+///     sha256_hex(b""),
+///     "0xe3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+/// );
+/// ```
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(Sha256::digest(data)))
+}
+
+/// Keccak-256 over the canonical JSON of a value
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be represented as JSON.
+pub fn canonical_keccak256<T: Serialize + ?Sized>(value: &T) -> Result<String, serde_json::Error> {
+    Ok(keccak256_hex(canonical_json(value)?.as_bytes()))
+}
+
+/// SHA-256 over the canonical JSON of a value
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be represented as JSON.
+pub fn canonical_sha256<T: Serialize + ?Sized>(value: &T) -> Result<String, serde_json::Error> {
+    Ok(sha256_hex(canonical_json(value)?.as_bytes()))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_json_sorts_nested_keys() {
+        let value = json!({
+            "z": [{"b": true, "a": null}],
+            "a": "text",
+            "m": {"y": 1.5, "x": -2}
+        });
+
+        assert_eq!(
+            canonical_json(&value).unwrap(),
+            r#"{"a":"text","m":{"x":-2,"y":1.5},"z":[{"a":null,"b":true}]}"#
+        );
+    }
+
+    #[test]
+    fn test_canonical_hash_is_order_independent() {
+        let a: Value = serde_json::from_str(r#"{"amount":"1.00","asset":"USDC"}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"asset":"USDC","amount":"1.00"}"#).unwrap();
+
+        assert_eq!(canonical_keccak256(&a).unwrap(), canonical_keccak256(&b).unwrap());
+        assert_eq!(canonical_sha256(&a).unwrap(), canonical_sha256(&b).unwrap());
+        assert_ne!(canonical_keccak256(&a).unwrap(), canonical_sha256(&a).unwrap());
+    }
+}
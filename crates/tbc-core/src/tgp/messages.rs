@@ -38,9 +38,16 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::types::{EconomicEnvelope, SettleSource, ZkProfile};
+use super::types::{
+    EconomicEnvelope, RoutingDecision, SellerReputation, SettleSource, ZkProfile,
+};
+use super::errors::TgpErrorCode;
+use super::path::validate_tgp_path;
+use super::routes::{select_route, validate_routes_into, RouteOption, RoutePreferences};
+use super::uri::PartyId;
 use super::validation::{
-    validate_address, validate_non_empty, validate_positive_amount, validate_transaction_hash,
+    json_pointer, validate_address, validate_non_empty, validate_party_id, validate_positive_amount,
+    validate_transaction_hash, ValidationCode, ValidationIssue, ValidationReport,
 };
 
 // ============================================================================
@@ -70,12 +77,16 @@ use super::validation::{
 ///     escrow_from_402: false,
 ///     escrow_contract_from_402: None,
 ///     zk_profile: ZkProfile::Optional,
+///     tgp_path: None,
 /// };
 ///
 /// let message = TGPMessage::Query(query);
 /// let json = serde_json::to_string(&message).unwrap();
 /// // JSON will contain: { "phase": "QUERY", ... }
 /// ```
+// OFFER carries the full economic envelope; messages are short-lived so
+// boxing it would only complicate construction.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "phase")]
 pub enum TGPMessage {
@@ -123,11 +134,19 @@ impl TGPMessage {
     ///
     /// Returns an error string if validation fails.
     pub fn validate(&self) -> Result<(), String> {
+        self.validation_report().to_result()
+    }
+
+    /// Collect every validation issue of the message
+    ///
+    /// Paths are JSON pointers into the serialized message
+    /// (e.g., `/economic_envelope/price/amount`).
+    pub fn validation_report(&self) -> ValidationReport {
         match self {
-            TGPMessage::Query(m) => m.validate(),
-            TGPMessage::Offer(m) => m.validate(),
-            TGPMessage::Settle(m) => m.validate(),
-            TGPMessage::Error(m) => m.validate(),
+            TGPMessage::Query(m) => m.validation_report(),
+            TGPMessage::Offer(m) => m.validation_report(),
+            TGPMessage::Settle(m) => m.validation_report(),
+            TGPMessage::Error(m) => m.validation_report(),
         }
     }
 }
@@ -180,6 +199,9 @@ pub struct QueryMessage {
 
     /// Asset denomination (e.g., "USDC", "ETH")
     ///
+    /// TGP-01 `evm:USDC:8453` and CAIP-19 ids are also accepted; see
+    /// [`AssetRegistry`](super::asset::AssetRegistry).
+    ///
     /// **Spec:** TGP-00 §3.1 - Required field
     pub asset: String,
 
@@ -203,6 +225,10 @@ pub struct QueryMessage {
     ///
     /// **Spec:** TGP-00 §3.1 - Required field (see §3.5)
     pub zk_profile: ZkProfile,
+
+    /// Transaction Areas the QUERY was forwarded through (TGP-TAI §A.5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tgp_path: Option<String>,
 }
 
 impl QueryMessage {
@@ -217,17 +243,81 @@ impl QueryMessage {
     /// - `amount` must be greater than zero
     /// - `escrow_contract_from_402` must be valid address if present
     pub fn validate(&self) -> Result<(), String> {
-        validate_non_empty(&self.id, "id")?;
-        validate_non_empty(&self.from, "from")?;
-        validate_non_empty(&self.to, "to")?;
-        validate_non_empty(&self.asset, "asset")?;
-        validate_positive_amount(self.amount, "amount")?;
+        self.validation_report().to_result()
+    }
+
+    /// Collect every validation issue of the QUERY
+    ///
+    /// `from` and `to` must be valid [`PartyId`]s. In addition to the
+    /// [`validate`](Self::validate) rules, warns when
+    /// `escrow_contract_from_402` is set but `escrow_from_402` is false, and
+    /// when `from` is a seller or `to` a buyer.
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        report.check(path, "id", ValidationCode::Required, validate_non_empty(&self.id, "id"));
+        for (field, value) in [("from", &self.from), ("to", &self.to)] {
+            let present = validate_non_empty(value, field);
+            if report.check(path, field, ValidationCode::Required, present) {
+                report.check(
+                    path,
+                    field,
+                    ValidationCode::InvalidFormat,
+                    validate_party_id(value, field),
+                );
+            }
+        }
+        if let (Ok(from), Ok(to)) = (PartyId::parse(&self.from), PartyId::parse(&self.to)) {
+            if from.is_seller() || to.is_buyer() {
+                report.warning(
+                    json_pointer(path, "from"),
+                    ValidationCode::Inconsistent,
+                    format!("QUERY is sent by the buyer, got from {} to {}", from, to),
+                );
+            }
+        }
+        report.check(
+            path,
+            "asset",
+            ValidationCode::Required,
+            validate_non_empty(&self.asset, "asset"),
+        );
+        report.check(
+            path,
+            "amount",
+            ValidationCode::InvalidAmount,
+            validate_positive_amount(self.amount, "amount"),
+        );
 
         if let Some(ref contract) = self.escrow_contract_from_402 {
-            validate_address(contract, "escrow_contract_from_402")?;
+            report.check(
+                path,
+                "escrow_contract_from_402",
+                ValidationCode::InvalidAddress,
+                validate_address(contract, "escrow_contract_from_402"),
+            );
+            if !self.escrow_from_402 {
+                report.warning(
+                    json_pointer(path, "escrow_from_402"),
+                    ValidationCode::Inconsistent,
+                    "escrow_contract_from_402 is set but escrow_from_402 is false",
+                );
+            }
         }
 
-        Ok(())
+        if let Some(ref tgp_path) = self.tgp_path {
+            report.check(
+                path,
+                "tgp_path",
+                ValidationCode::InvalidFormat,
+                validate_tgp_path(tgp_path, "tgp_path"),
+            );
+        }
     }
 
     /// Create a new QUERY message with required fields
@@ -248,6 +338,7 @@ impl QueryMessage {
             escrow_from_402: false,
             escrow_contract_from_402: None,
             zk_profile,
+            tgp_path: None,
         }
     }
 
@@ -270,8 +361,15 @@ impl QueryMessage {
             escrow_from_402: true,
             escrow_contract_from_402: Some(contract.into()),
             zk_profile,
+            tgp_path: None,
         }
     }
+
+    /// Builder method to set the traversed TAI path
+    pub fn with_tgp_path(mut self, tgp_path: impl Into<String>) -> Self {
+        self.tgp_path = Some(tgp_path.into());
+        self
+    }
 }
 
 // ============================================================================
@@ -345,23 +443,105 @@ pub struct OfferMessage {
     ///
     /// **Spec:** TGP-00 §3.2 - Required field (see §3.6)
     pub economic_envelope: EconomicEnvelope,
+
+    /// Ranked settlement alternatives, best first
+    ///
+    /// When present, the rank 1 route mirrors the top-level asset, amount
+    /// and contract so single-route clients keep working.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteOption>,
+
+    /// Seller fulfillment track record, as known to the Controller
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seller_reputation: Option<SellerReputation>,
+
+    /// Verdicts of the routing stages that produced the OFFER
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decisions: Vec<RoutingDecision>,
+
+    /// Complete TAI path from the buyer's Controller to the seller's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tgp_path: Option<String>,
 }
 
 impl OfferMessage {
     /// Validate the OFFER message structure
     pub fn validate(&self) -> Result<(), String> {
-        validate_non_empty(&self.id, "id")?;
-        validate_non_empty(&self.query_id, "query_id")?;
-        validate_non_empty(&self.asset, "asset")?;
-        validate_positive_amount(self.amount, "amount")?;
+        self.validation_report().to_result()
+    }
+
+    /// Collect every validation issue of the OFFER, including the envelope
+    ///
+    /// Warns when `zk_required` is set without a `coreprover_contract`.
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report
+    }
 
-        if let Some(ref contract) = self.coreprover_contract {
-            validate_address(contract, "coreprover_contract")?;
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        report.check(path, "id", ValidationCode::Required, validate_non_empty(&self.id, "id"));
+        report.check(
+            path,
+            "query_id",
+            ValidationCode::Required,
+            validate_non_empty(&self.query_id, "query_id"),
+        );
+        report.check(
+            path,
+            "asset",
+            ValidationCode::Required,
+            validate_non_empty(&self.asset, "asset"),
+        );
+        report.check(
+            path,
+            "amount",
+            ValidationCode::InvalidAmount,
+            validate_positive_amount(self.amount, "amount"),
+        );
+
+        match self.coreprover_contract {
+            Some(ref contract) => {
+                report.check(
+                    path,
+                    "coreprover_contract",
+                    ValidationCode::InvalidAddress,
+                    validate_address(contract, "coreprover_contract"),
+                );
+            }
+            None if self.zk_required => report.warning(
+                json_pointer(path, "coreprover_contract"),
+                ValidationCode::Inconsistent,
+                "zk_required is set but no coreprover_contract is offered",
+            ),
+            None => {}
         }
 
-        self.economic_envelope.validate()?;
+        if let Some(ref tgp_path) = self.tgp_path {
+            report.check(
+                path,
+                "tgp_path",
+                ValidationCode::InvalidFormat,
+                validate_tgp_path(tgp_path, "tgp_path"),
+            );
+        }
 
-        Ok(())
+        self.economic_envelope
+            .validate_into(report, &json_pointer(path, "economic_envelope"));
+
+        let routes_path = json_pointer(path, "routes");
+        let max_fees_bps = self.economic_envelope.max_fees_bps;
+        validate_routes_into(&self.routes, max_fees_bps, report, &routes_path);
+        if let Some(best) = self.routes.first() {
+            if best.asset != self.asset || best.amount != self.amount {
+                report.error(
+                    json_pointer(&routes_path, "0"),
+                    ValidationCode::Inconsistent,
+                    "rank 1 route must match the offer asset and amount",
+                );
+            }
+        }
     }
 
     /// Create a new OFFER message with required fields
@@ -382,7 +562,35 @@ impl OfferMessage {
             session_id: None,
             zk_required,
             economic_envelope,
+            routes: Vec::new(),
+            seller_reputation: None,
+            decisions: Vec::new(),
+            tgp_path: None,
+        }
+    }
+
+    /// Builder method to attach ranked routes
+    ///
+    /// Copies asset, amount and contract of the rank 1 route into the
+    /// top-level fields. Use `routes::rank_routes` to produce the list.
+    pub fn with_routes(mut self, routes: Vec<RouteOption>) -> Self {
+        if let Some(best) = routes.first() {
+            self.asset = best.asset.clone();
+            self.amount = best.amount;
+            self.coreprover_contract = best.coreprover_contract.clone();
         }
+        self.routes = routes;
+        self
+    }
+
+    /// The recommended (rank 1) route, if the offer carries routes
+    pub fn best_route(&self) -> Option<&RouteOption> {
+        self.routes.first()
+    }
+
+    /// Pick the best-ranked route matching the buyer's preferences
+    pub fn select_route(&self, preferences: &RoutePreferences) -> Option<&RouteOption> {
+        select_route(&self.routes, preferences)
     }
 
     /// Builder method to set CoreProver contract
@@ -396,6 +604,24 @@ impl OfferMessage {
         self.session_id = Some(session_id.into());
         self
     }
+
+    /// Builder method to attach the seller's reputation
+    pub fn with_seller_reputation(mut self, reputation: SellerReputation) -> Self {
+        self.seller_reputation = Some(reputation);
+        self
+    }
+
+    /// Builder method to attach routing stage verdicts
+    pub fn with_decisions(mut self, decisions: Vec<RoutingDecision>) -> Self {
+        self.decisions = decisions;
+        self
+    }
+
+    /// Builder method to set the complete TAI path
+    pub fn with_tgp_path(mut self, tgp_path: impl Into<String>) -> Self {
+        self.tgp_path = Some(tgp_path.into());
+        self
+    }
 }
 
 // ============================================================================
@@ -462,14 +688,34 @@ pub struct SettleMessage {
 impl SettleMessage {
     /// Validate the SETTLE message structure
     pub fn validate(&self) -> Result<(), String> {
-        validate_non_empty(&self.id, "id")?;
-        validate_non_empty(&self.query_or_offer_id, "query_or_offer_id")?;
+        self.validation_report().to_result()
+    }
+
+    /// Collect every validation issue of the SETTLE
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        report.check(path, "id", ValidationCode::Required, validate_non_empty(&self.id, "id"));
+        report.check(
+            path,
+            "query_or_offer_id",
+            ValidationCode::Required,
+            validate_non_empty(&self.query_or_offer_id, "query_or_offer_id"),
+        );
 
         if let Some(ref tx) = self.layer8_tx {
-            validate_transaction_hash(tx, "layer8_tx")?;
+            report.check(
+                path,
+                "layer8_tx",
+                ValidationCode::InvalidHash,
+                validate_transaction_hash(tx, "layer8_tx"),
+            );
         }
-
-        Ok(())
     }
 
     /// Create a new SETTLE message with required fields
@@ -559,15 +805,46 @@ pub struct ErrorMessage {
     /// **Spec:** TGP-00 §3.4 - Optional field
     #[serde(skip_serializing_if = "Option::is_none")]
     pub correlation_id: Option<String>,
+
+    /// Individual validation issues behind the error
+    ///
+    /// **Spec:** Extension - lets a single ERROR list every problem found
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<ValidationIssue>,
+
+    /// Verdicts of the routing stages up to the rejecting one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decisions: Vec<RoutingDecision>,
 }
 
 impl ErrorMessage {
     /// Validate the ERROR message structure
     pub fn validate(&self) -> Result<(), String> {
-        validate_non_empty(&self.id, "id")?;
-        validate_non_empty(&self.code, "code")?;
-        validate_non_empty(&self.message, "message")?;
-        Ok(())
+        self.validation_report().to_result()
+    }
+
+    /// Collect every validation issue of the ERROR
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        report.check(path, "id", ValidationCode::Required, validate_non_empty(&self.id, "id"));
+        report.check(
+            path,
+            "code",
+            ValidationCode::Required,
+            validate_non_empty(&self.code, "code"),
+        );
+        report.check(
+            path,
+            "message",
+            ValidationCode::Required,
+            validate_non_empty(&self.message, "message"),
+        );
     }
 
     /// Create a new ERROR message
@@ -581,9 +858,25 @@ impl ErrorMessage {
             code: code.into(),
             message: message.into(),
             correlation_id: None,
+            details: Vec::new(),
+            decisions: Vec::new(),
         }
     }
 
+    /// Create an ERROR message with a registered error code
+    pub fn from_code(
+        id: impl Into<String>,
+        code: TgpErrorCode,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::new(id, code, message)
+    }
+
+    /// The registered error code, `None` for codes outside the registry
+    pub fn error_code(&self) -> Option<TgpErrorCode> {
+        self.code.parse().ok()
+    }
+
     /// Create an ERROR message with correlation
     pub fn with_correlation(
         id: impl Into<String>,
@@ -596,8 +889,62 @@ impl ErrorMessage {
             code: code.into(),
             message: message.into(),
             correlation_id: Some(correlation_id.into()),
+            details: Vec::new(),
+            decisions: Vec::new(),
         }
     }
+
+    /// Create an ERROR message describing a failed validation
+    ///
+    /// The message summarizes every error in the report and `details`
+    /// carries all of its issues, warnings included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tbc_core::tgp::messages::{error_codes, ErrorMessage, QueryMessage};
+    /// use tbc_core::tgp::types::ZkProfile;
+    ///
+    /// let query = QueryMessage::new("q-1", "", "seller://bob", "", 1000, ZkProfile::Optional);
+    /// let report = query.validation_report();
+    /// let error = ErrorMessage::from_report("err-1", error_codes::INVALID_QUERY, &report)
+    ///     .correlated_to("q-1");
+    ///
+    /// assert_eq!(error.details.len(), 2);
+    /// assert!(error.message.contains("/from"));
+    /// assert!(error.validate().is_ok());
+    /// ```
+    pub fn from_report(
+        id: impl Into<String>,
+        code: impl Into<String>,
+        report: &ValidationReport,
+    ) -> Self {
+        let summary = report.summary();
+        Self {
+            id: id.into(),
+            code: code.into(),
+            message: if summary.is_empty() {
+                "validation failed".to_string()
+            } else {
+                summary
+            },
+            correlation_id: None,
+            details: report.issues.clone(),
+            decisions: Vec::new(),
+        }
+    }
+
+    /// Builder method to set the correlation ID
+    pub fn correlated_to(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// Builder method to attach routing stage verdicts
+    pub fn with_decisions(mut self, decisions: Vec<RoutingDecision>) -> Self {
+        self.decisions = decisions;
+        self
+    }
 }
 
 // ============================================================================
@@ -605,6 +952,8 @@ impl ErrorMessage {
 // ============================================================================
 
 /// Standard TGP error codes (per TGP-00 §3.4)
+///
+/// Wire strings of commonly used codes; the full registry is [`TgpErrorCode`].
 pub mod error_codes {
     pub const INVALID_QUERY: &str = "INVALID_QUERY";
     pub const UNSUPPORTED_ASSET: &str = "UNSUPPORTED_ASSET";
@@ -619,6 +968,8 @@ pub mod error_codes {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tgp::routes::{rank_routes, RankingPolicy, SettlementMethod};
+    use crate::tgp::types::{BuyerFee, EnvelopePrice, RoutingFee};
 
     #[test]
     fn test_query_message_validation() {
@@ -637,6 +988,29 @@ mod tests {
         assert!(invalid.validate().is_err());
     }
 
+    #[test]
+    fn test_query_party_ids() {
+        let query = |from: &str, to: &str| {
+            QueryMessage::new("q-1", from, to, "USDC", 1000, ZkProfile::Optional)
+                .validation_report()
+        };
+
+        assert!(query("buyer://alice.wallet", "tai:7abf92c6").is_empty());
+        let account = "eip155:1:0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+        assert!(query(account, "seller://bob").is_empty());
+
+        let report = query("alice", "seller://bob");
+        assert_eq!(report.first_error().unwrap().path, "/from");
+        assert_eq!(report.first_error().unwrap().code, ValidationCode::InvalidFormat);
+
+        let report = query("buyer://alice", "policy://carrierA/fees");
+        assert_eq!(report.first_error().unwrap().path, "/to");
+
+        let reversed = query("seller://bob", "buyer://alice");
+        assert!(reversed.is_valid());
+        assert_eq!(reversed.warnings().count(), 1);
+    }
+
     #[test]
     fn test_offer_message_validation() {
         let valid = OfferMessage::new(
@@ -650,6 +1024,46 @@ mod tests {
         assert!(valid.validate().is_ok());
     }
 
+    #[test]
+    fn test_offer_with_routes() {
+        const CONTRACT: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+        let envelope = EconomicEnvelope::new(50);
+        let routes = rank_routes(
+            vec![
+                RouteOption::direct("pls", "USDC", 1000, 369).with_fee_estimate_bps(40),
+                RouteOption::escrow("base", "USDC", 1000, 8453, CONTRACT).with_fee_estimate_bps(20),
+            ],
+            &envelope,
+            &RankingPolicy::new(ZkProfile::Optional),
+        );
+        let offer = OfferMessage::new("offer-123", "q-123", "USDC", 1000, false, envelope)
+            .with_routes(routes);
+
+        assert_eq!(offer.best_route().unwrap().route_id, "base");
+        assert!(offer.coreprover_contract.is_some());
+        assert!(offer.validate().is_ok());
+
+        let prefs = RoutePreferences::new().with_method(SettlementMethod::DirectX402);
+        assert_eq!(offer.select_route(&prefs).unwrap().route_id, "pls");
+
+        let json = serde_json::to_string(&TGPMessage::Offer(offer.clone())).unwrap();
+        let decoded: TGPMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, TGPMessage::Offer(offer.clone()));
+
+        let mut mismatched = offer;
+        mismatched.amount = 2000;
+        let report = mismatched.validation_report();
+        assert_eq!(report.first_error().unwrap().path, "/routes/0");
+    }
+
+    #[test]
+    fn test_offer_without_routes_omits_field() {
+        let envelope = EconomicEnvelope::new(50);
+        let offer = OfferMessage::new("offer-123", "q-123", "USDC", 1000, false, envelope);
+        let json = serde_json::to_value(&offer).unwrap();
+        assert!(json.get("routes").is_none());
+    }
+
     #[test]
     fn test_settle_message_validation() {
         let valid = SettleMessage::new(
@@ -667,6 +1081,88 @@ mod tests {
         assert!(valid.validate().is_ok());
     }
 
+    #[test]
+    fn test_validation_report_collects_all_errors() {
+        let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 20_000)
+            .with_buyer_fee(BuyerFee::new("1.5.0", "USDC"))
+            .with_routing_fee(RoutingFee::new("0.10", ""));
+        let mut offer = OfferMessage::new("", "q-123", "USDC", 0, true, envelope);
+        offer.coreprover_contract = Some("0x123".to_string());
+
+        let message = TGPMessage::Offer(offer);
+        let report = message.validation_report();
+        let errors: Vec<(&str, ValidationCode)> =
+            report.errors().map(|i| (i.path.as_str(), i.code)).collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                ("/id", ValidationCode::Required),
+                ("/amount", ValidationCode::InvalidAmount),
+                ("/coreprover_contract", ValidationCode::InvalidAddress),
+                ("/economic_envelope/max_fees_bps", ValidationCode::OutOfRange),
+                ("/economic_envelope/buyer_fee/amount", ValidationCode::InvalidAmount),
+                ("/economic_envelope/routing_fee/asset", ValidationCode::Required),
+            ]
+        );
+        assert_eq!(message.validate().unwrap_err(), report.first_error().unwrap().message);
+    }
+
+    #[test]
+    fn test_validation_report_fee_checks() {
+        let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 10)
+            .with_buyer_fee(BuyerFee::new("1.00", "USDT"));
+        let report = envelope.validation_report();
+        assert_eq!(report.first_error().unwrap().path, "/buyer_fee/asset");
+        assert_eq!(report.first_error().unwrap().code, ValidationCode::AssetMismatch);
+
+        let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 10)
+            .with_buyer_fee(BuyerFee::new("1.00", "USDC"));
+        let report = envelope.validation_report();
+        assert_eq!(report.first_error().unwrap().path, "/max_fees_bps");
+        assert_eq!(report.first_error().unwrap().code, ValidationCode::FeeCapExceeded);
+    }
+
+    #[test]
+    fn test_validation_warnings_do_not_fail() {
+        let mut query = QueryMessage::new(
+            "q-1",
+            "buyer://alice",
+            "seller://bob",
+            "USDC",
+            1000,
+            ZkProfile::Optional,
+        );
+        query.escrow_contract_from_402 =
+            Some("0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0".to_string());
+
+        let report = query.validation_report();
+        assert!(report.is_valid());
+        assert_eq!(report.warnings().next().unwrap().path, "/escrow_from_402");
+        assert!(query.validate().is_ok());
+    }
+
+    #[test]
+    fn test_error_message_from_report() {
+        let settle = SettleMessage::new("", "", true, SettleSource::BuyerNotify).with_tx("0x12");
+        let report = settle.validation_report();
+        let error = ErrorMessage::from_report("err-1", error_codes::INVALID_STATE, &report)
+            .correlated_to("settle-1");
+
+        assert_eq!(error.details.len(), 3);
+        assert_eq!(error.correlation_id.as_deref(), Some("settle-1"));
+
+        let json = serde_json::to_string(&TGPMessage::Error(error.clone())).unwrap();
+        assert!(json.contains(r#""path":"/layer8_tx""#));
+        let parsed: TGPMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, TGPMessage::Error(error));
+
+        // ERROR messages without details keep their original encoding
+        let plain = ErrorMessage::new("err-2", "TIMEOUT", "Session timed out");
+        let plain = serde_json::to_string(&plain).unwrap();
+        assert!(!plain.contains("details"));
+    }
+
     #[test]
     fn test_message_serialization() {
         let query = QueryMessage::new(
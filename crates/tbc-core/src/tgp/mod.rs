@@ -1,8 +1,35 @@
 pub mod state;
+pub mod clock;
+pub mod store;
+pub mod timeout;
+pub mod errors;
+pub mod uri;
+pub mod path;
+pub mod asset;
 pub mod messages;
 pub mod validation;
 pub mod types;
+pub mod hash;
+pub mod pos;
+pub mod tdr;
+pub mod routes;
+pub mod settlement;
+pub mod ratelimit;
+pub mod version;
 
 // Optional: Re-export commonly used items
-pub use state::{TGPState, TGPSession, TGPStateError};
-pub use messages::{TGPMessage, QueryMessage, OfferMessage, SettleMessage, ErrorMessage};
\ No newline at end of file
+pub use state::{TGPState, TGPSession, TGPStateError, TransitionActor, TransitionRecord};
+pub use messages::{TGPMessage, QueryMessage, OfferMessage, SettleMessage, ErrorMessage};
+pub use pos::{ProofOfSettlement, ProofOfSettlementBuilder, ProveProof, SettlementReceipt, PosError};
+pub use tdr::{TransactionDetailRecord, TdrWriter, TdrWriterConfig, TdrFormat, TdrOutcome};
+pub use routes::{RouteOption, SettlementMethod, RankingPolicy, RoutePreferences, rank_routes};
+pub use settlement::{EscrowPolicy, SettlementDecision, SettlementError};
+pub use ratelimit::{LimitKey, RateLimitConfig, RateLimitRejection, RateLimiter};
+pub use version::{TgpVersion, VersionedMessage, negotiate_version, SUPPORTED_TGP_VERSIONS};
+pub use clock::{Clock, SystemClock, ManualClock};
+pub use store::{SessionStore, InMemorySessionStore, FileSessionStore, TGPSessionManager};
+pub use timeout::TimeoutPolicy;
+pub use errors::{TgpErrorCode, RetryHint};
+pub use uri::{PartyId, TgpUri};
+pub use path::TgpPath;
+pub use asset::{AssetId, AssetInfo, AssetRegistry};
@@ -0,0 +1,216 @@
+//# TGP Transaction Area Paths
+
+//**Destination Path:** `crates/tbc-core/src/tgp/path.rs`
+
+//**Implementation:** M1 - TGP Message Parsing & Basic Routing
+
+//! Transaction Area Paths (T-Path)
+//!
+//! A QUERY forwarded between peered TBC Controllers records every
+//! Transaction Area it passes in `tgp_path`, e.g.
+//! `tai:7abf92c6>tai:8bde4411`. The OFFER returns the complete path and
+//! the Proof-of-Settlement commits to it.
+//!
+//! Per TGP-TAI §A.5 a path is loop-free: a Controller must reject a QUERY
+//! whose path already contains its own TAI. [`TgpPath::push`] enforces that,
+//! and [`TgpPath::parse`] rejects paths that repeat a TAI.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::path::TgpPath;
+//!
+//! let mut path = TgpPath::parse("tai:7ABF92C6").unwrap();
+//! path.push("tai:8bde4411").unwrap();
+//! assert_eq!(path.to_string(), "tai:7abf92c6>tai:8bde4411");
+//! assert_eq!(path.len(), 2);
+//!
+//! // Looping back into a traversed area is refused
+//! assert!(path.push("tai:7abf92c6").is_err());
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::uri::{TgpUri, UriParseError};
+
+/// Separator between TAIs in the string form
+pub const PATH_SEPARATOR: char = '>';
+
+/// Ordered, loop-free list of normalized TAIs (`tai:hex`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TgpPath(Vec<String>);
+
+impl TgpPath {
+    /// Empty path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `tai:a>tai:b>…`
+    pub fn parse(input: &str) -> Result<Self, UriParseError> {
+        if input.trim().is_empty() {
+            return Err(UriParseError {
+                input: input.to_string(),
+                reason: "path is empty".to_string(),
+            });
+        }
+
+        let mut path = Self::new();
+        for hop in input.split(PATH_SEPARATOR) {
+            path.push(hop.trim()).map_err(|e| UriParseError {
+                input: input.to_string(),
+                reason: e.reason,
+            })?;
+        }
+        Ok(path)
+    }
+
+    /// Append a TAI
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `tai` is not a TAI or is already on the path.
+    pub fn push(&mut self, tai: &str) -> Result<(), UriParseError> {
+        let tai = match TgpUri::parse(tai)? {
+            TgpUri::Tai(id) => format!("tai:{}", id),
+            other => {
+                return Err(UriParseError {
+                    input: tai.to_string(),
+                    reason: format!("expected a TAI, got a {} identifier", other.kind()),
+                })
+            }
+        };
+        if self.0.contains(&tai) {
+            return Err(UriParseError {
+                reason: format!("{} is already on the path (loop)", tai),
+                input: tai,
+            });
+        }
+        self.0.push(tai);
+        Ok(())
+    }
+
+    /// Copy of the path with `tai` appended
+    pub fn extended(&self, tai: &str) -> Result<Self, UriParseError> {
+        let mut path = self.clone();
+        path.push(tai)?;
+        Ok(path)
+    }
+
+    /// Whether `tai` is on the path
+    ///
+    /// Unparseable input is never on the path.
+    pub fn contains(&self, tai: &str) -> bool {
+        match TgpUri::parse(tai) {
+            Ok(TgpUri::Tai(id)) => self.0.iter().any(|hop| hop[4..] == id),
+            _ => false,
+        }
+    }
+
+    /// Whether the path begins with every hop of `prefix`
+    pub fn starts_with(&self, prefix: &TgpPath) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// TAIs in traversal order
+    pub fn hops(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Number of Transaction Areas traversed
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for TgpPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, hop) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", PATH_SEPARATOR)?;
+            }
+            write!(f, "{}", hop)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TgpPath {
+    type Err = UriParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Serialize for TgpPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TgpPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Validate a `tgp_path` field
+pub fn validate_tgp_path(value: &str, field_name: &str) -> Result<(), String> {
+    TgpPath::parse(value)
+        .map(|_| ())
+        .map_err(|e| format!("{} is not a valid TAI path: {}", field_name, e.reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let path = TgpPath::parse(" tai:7ABF92C6 > tai:8bde4411 ").unwrap();
+        assert_eq!(path.hops(), ["tai:7abf92c6", "tai:8bde4411"]);
+        assert_eq!(path.to_string(), "tai:7abf92c6>tai:8bde4411");
+        assert_eq!(path.to_string().parse::<TgpPath>().unwrap(), path);
+        assert!(TgpPath::new().is_empty());
+        assert_eq!(TgpPath::new().to_string(), "");
+
+        for bad in ["", "tai:7abf92c6>", "seller://bob", "tai:xyz", "tai:aa>tai:AA"] {
+            assert!(TgpPath::parse(bad).is_err(), "{:?} accepted", bad);
+        }
+        let error = TgpPath::parse("tai:aa>tai:bb>tai:aa").unwrap_err();
+        assert!(error.reason.contains("loop"));
+    }
+
+    #[test]
+    fn test_contains_and_extend() {
+        let path = TgpPath::parse("tai:aa>tai:bb").unwrap();
+        assert!(path.contains("TAI:AA"));
+        assert!(!path.contains("tai:cc"));
+        assert!(!path.contains("garbage"));
+
+        let longer = path.extended("tai:cc").unwrap();
+        assert!(longer.starts_with(&path));
+        assert!(!path.starts_with(&longer));
+        assert_eq!(path.len(), 2);
+        assert!(longer.extended("tai:bb").is_err());
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        let path = TgpPath::parse("tai:aa>tai:bb").unwrap();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, "\"tai:aa>tai:bb\"");
+        assert_eq!(serde_json::from_str::<TgpPath>(&json).unwrap(), path);
+        assert!(serde_json::from_str::<TgpPath>("\"tai:aa>tai:aa\"").is_err());
+        assert!(validate_tgp_path("tai:aa>bob", "tgp_path").is_err());
+    }
+}
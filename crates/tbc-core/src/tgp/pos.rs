@@ -0,0 +1,715 @@
+//# TGP Proof-of-Settlement
+
+//**Destination Path:** `crates/tbc-core/src/tgp/pos.rs`
+
+//**Implementation:** M2 - TGP-01 Economic Envelope & Proof-of-Settlement
+
+//! Proof-of-Settlement (PoS) records per TGP-01 §5
+//!
+//! A PoS binds a finalized settlement receipt to the Economic Envelope the
+//! session was negotiated under, the TGP path it traversed and (optionally)
+//! the Transaction Detail Record. The Controller signs the record so buyers,
+//! sellers and peer TBCs can verify it independently.
+//!
+//! # Hash Bindings
+//!
+//! | Field | Digest |
+//! |-------|--------|
+//! | `economic_envelope_hash` | keccak256(canonical EE) - TGP-01 §7 |
+//! | `receipt_hash` | keccak256(canonical receipt) |
+//! | `tdr_hash` | sha256(canonical TDR) |
+//!
+//! The `signature` is an Ed25519 signature over the canonical JSON of the
+//! record with the `signature` field removed.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ed25519_dalek::SigningKey;
+//! use serde::Serialize;
+//! use tbc_core::tgp::pos::{ProofOfSettlementBuilder, SettlementReceipt};
+//! use tbc_core::tgp::types::{EconomicEnvelope, EnvelopePrice};
+//!
+//! #[derive(Serialize)]
+//! struct Receipt {
+//!     claim_txid: String,
+//!     chain_id: u64,
+//! }
+//!
+//! impl SettlementReceipt for Receipt {
+//!     fn settlement_txid(&self) -> Option<&str> {
+//!         Some(&self.claim_txid)
+//!     }
+//!     fn settlement_chain_id(&self) -> u64 {
+//!         self.chain_id
+//!     }
+//! }
+//!
+//! let receipt = Receipt { claim_txid: "0xabc".to_string(), chain_id: 369 };
+//! let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 50);
+//! let key = SigningKey::from_bytes(&[7u8; 32]);
+//!
+//! let pos = ProofOfSettlementBuilder::new("req-123", &receipt, &envelope)
+//!     .tgp_path("tai:7abf92c6>tai:8bde4411")
+//!     .sign(&key)?;
+//!
+//! assert_eq!(pos.settlement_chain, "eip155:369");
+//! assert!(pos.verify(&key.verifying_key(), &receipt, &envelope).is_ok());
+//! # Ok::<(), tbc_core::tgp::pos::PosError>(())
+//! ```
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::hash::{canonical_json, canonical_keccak256};
+use super::tdr::TransactionDetailRecord;
+use super::types::{EconomicEnvelope, ProtocolFeeMode};
+use super::validation::{validate_decimal_amount, validate_non_empty, validate_transaction_hash};
+
+/// PoS schema version defined by TGP-01 §5
+pub const POS_VERSION_1_0: &str = "1.0";
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Errors that can occur while building or verifying a PoS
+///
+/// # Specification Reference
+/// - TGP-01 §5 Proof-of-Settlement
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum PosError {
+    /// The receipt has no settlement transaction yet
+    #[error("Receipt is not finalized: no settlement transaction")]
+    ReceiptNotFinalized,
+
+    /// A required field was not provided or is malformed
+    #[error("Invalid PoS field: {0}")]
+    InvalidField(String),
+
+    /// `prove_proof` is required by the envelope but missing
+    #[error("prove_proof is required when protocol_fee.mode = auto_prove_burn")]
+    MissingProveProof,
+
+    /// A hash in the PoS does not match the referenced object
+    #[error("{field} mismatch: expected {expected}, got {actual}")]
+    HashMismatch {
+        field: &'static str,
+        expected: String,
+        actual: String,
+    },
+
+    /// The PoS signature does not verify
+    #[error("Invalid PoS signature: {0}")]
+    InvalidSignature(String),
+
+    /// Canonical serialization failed
+    #[error("Serialization failed: {0}")]
+    Serialization(String),
+}
+
+impl From<serde_json::Error> for PosError {
+    fn from(err: serde_json::Error) -> Self {
+        PosError::Serialization(err.to_string())
+    }
+}
+
+// ============================================================================
+// Settlement Receipt Abstraction
+// ============================================================================
+
+/// A settlement receipt a PoS can be issued for
+///
+/// Implemented by the CoreProver receipt types so this crate does not need
+/// to depend on the settlement engine. The receipt's canonical JSON is what
+/// `receipt_hash` commits to.
+pub trait SettlementReceipt: Serialize {
+    /// Final settlement transaction (claim or refund), if any
+    fn settlement_txid(&self) -> Option<&str>;
+
+    /// Chain the settlement transaction was executed on
+    fn settlement_chain_id(&self) -> u64;
+
+    /// Whether the receipt is final and can be proven
+    ///
+    /// Defaults to having a settlement transaction.
+    fn is_finalized(&self) -> bool {
+        self.settlement_txid().is_some()
+    }
+
+    /// Whether the seller fulfilled after the deadline
+    ///
+    /// Feeds seller reputation; defaults to `false`.
+    fn late_fulfilled(&self) -> bool {
+        false
+    }
+}
+
+/// Format a chain id as a CAIP-2 identifier (e.g., `eip155:369`)
+pub fn settlement_chain_name(chain_id: u64) -> String {
+    format!("eip155:{}", chain_id)
+}
+
+// ============================================================================
+// ProveProof Structure (§5, §6)
+// ============================================================================
+
+/// Evidence of the Prove Protocol Logic burn for the protocol fee
+///
+/// **Spec:** TGP-01 §5 `prove_proof`, §6.2 - MUST include a verifiable
+/// transaction hash and block height, and MAY originate on another chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProveProof {
+    /// Burn (or lock) transaction hash
+    pub txid: String,
+
+    /// Amount of the prove asset burned (canonical decimal string)
+    pub amount: String,
+
+    /// Prove asset identifier (e.g., "prove")
+    pub asset: String,
+
+    /// Protocol fee mode that triggered the proof
+    pub mode: ProtocolFeeMode,
+
+    /// Block height of the burn transaction
+    pub block_height: u64,
+
+    /// Chain of the burn transaction if different from the settlement chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+}
+
+impl ProveProof {
+    /// Create a prove proof on the settlement chain
+    pub fn new(
+        txid: impl Into<String>,
+        amount: impl Into<String>,
+        asset: impl Into<String>,
+        mode: ProtocolFeeMode,
+        block_height: u64,
+    ) -> Self {
+        Self {
+            txid: txid.into(),
+            amount: amount.into(),
+            asset: asset.into(),
+            mode,
+            block_height,
+            chain: None,
+        }
+    }
+
+    /// Builder method to set the chain the proof originated on
+    pub fn on_chain(mut self, chain: impl Into<String>) -> Self {
+        self.chain = Some(chain.into());
+        self
+    }
+
+    /// Validate transaction hash, amount and block height
+    pub fn validate(&self) -> Result<(), String> {
+        validate_transaction_hash(&self.txid, "prove_proof.txid")?;
+        validate_decimal_amount(&self.amount, "prove_proof.amount")?;
+        validate_non_empty(&self.asset, "prove_proof.asset")?;
+        if self.block_height == 0 {
+            return Err("prove_proof.block_height must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// ProofOfSettlement Structure (§5)
+// ============================================================================
+
+/// Signed Proof-of-Settlement record
+///
+/// # Specification Reference
+/// - TGP-01 §5 Proof-of-Settlement (PoS)
+///
+/// # Fields
+///
+/// | Field | Description |
+/// |-------|-------------|
+/// | `pos_version` | Schema version (`"1.0"`) |
+/// | `request_id` | Session request the PoS is bound to (replay protection, §9) |
+/// | `settlement_txid` | Final claim or refund transaction |
+/// | `settlement_chain` | CAIP-2 chain of the settlement transaction |
+/// | `tgp_path` | TAI path the session traversed (e.g., `tai:a>tai:b`) |
+/// | `economic_envelope_hash` | keccak256 of the canonical envelope |
+/// | `receipt_hash` | keccak256 of the canonical receipt |
+/// | `prove_proof` | Burn evidence, required for `auto_prove_burn` |
+/// | `tdr_hash` | sha256 of the canonical TDR, when recorded |
+/// | `signature` | 0x-prefixed Ed25519 signature |
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProofOfSettlement {
+    pub pos_version: String,
+    pub request_id: String,
+    pub settlement_txid: String,
+    pub settlement_chain: String,
+    pub tgp_path: String,
+    pub economic_envelope_hash: String,
+    pub receipt_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prove_proof: Option<ProveProof>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tdr_hash: Option<String>,
+    pub signature: String,
+}
+
+impl ProofOfSettlement {
+    /// Canonical bytes covered by the signature
+    ///
+    /// The canonical JSON of the record without the `signature` field.
+    pub fn signing_payload(&self) -> Result<Vec<u8>, PosError> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(map) = value.as_object_mut() {
+            map.remove("signature");
+        }
+        Ok(canonical_json(&value)?.into_bytes())
+    }
+
+    /// Hash of the complete signed record (`pos_hash` in TGP-01 §11)
+    pub fn hash(&self) -> Result<String, PosError> {
+        Ok(canonical_keccak256(self)?)
+    }
+
+    /// Sign the record in place
+    pub fn sign(&mut self, key: &SigningKey) -> Result<(), PosError> {
+        let signature = key.sign(&self.signing_payload()?);
+        self.signature = format!("0x{}", hex::encode(signature.to_bytes()));
+        Ok(())
+    }
+
+    /// Verify the Ed25519 signature
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PosError::InvalidSignature`] if the signature is malformed
+    /// or was not produced by `key`.
+    pub fn verify_signature(&self, key: &VerifyingKey) -> Result<(), PosError> {
+        let hex_sig = self
+            .signature
+            .strip_prefix("0x")
+            .ok_or_else(|| PosError::InvalidSignature("missing 0x prefix".to_string()))?;
+        let bytes: [u8; 64] = hex::decode(hex_sig)
+            .map_err(|e| PosError::InvalidSignature(e.to_string()))?
+            .try_into()
+            .map_err(|_| PosError::InvalidSignature("expected 64 bytes".to_string()))?;
+
+        key.verify(&self.signing_payload()?, &Signature::from_bytes(&bytes))
+            .map_err(|e| PosError::InvalidSignature(e.to_string()))
+    }
+
+    /// Verify the record against its receipt, envelope and signer
+    ///
+    /// # Checks
+    ///
+    /// - Structural validity of every field
+    /// - `settlement_txid` and `settlement_chain` match the receipt
+    /// - `receipt_hash` and `economic_envelope_hash` match the objects
+    /// - `prove_proof` is present if the envelope uses `auto_prove_burn`
+    /// - The signature verifies under `key`
+    pub fn verify<R: SettlementReceipt>(
+        &self,
+        key: &VerifyingKey,
+        receipt: &R,
+        envelope: &EconomicEnvelope,
+    ) -> Result<(), PosError> {
+        self.validate().map_err(PosError::InvalidField)?;
+
+        let txid = receipt.settlement_txid().ok_or(PosError::ReceiptNotFinalized)?;
+        check_match("settlement_txid", txid, &self.settlement_txid)?;
+        check_match(
+            "settlement_chain",
+            &settlement_chain_name(receipt.settlement_chain_id()),
+            &self.settlement_chain,
+        )?;
+        check_match("receipt_hash", &canonical_keccak256(receipt)?, &self.receipt_hash)?;
+        check_match(
+            "economic_envelope_hash",
+            &canonical_keccak256(envelope)?,
+            &self.economic_envelope_hash,
+        )?;
+
+        check_prove_proof(envelope, self.prove_proof.as_ref())?;
+
+        self.verify_signature(key)
+    }
+
+    /// Verify that `tdr_hash` commits to the given TDR digest
+    pub fn verify_tdr_hash(&self, expected: &str) -> Result<(), PosError> {
+        let actual = self.tdr_hash.as_deref().unwrap_or_default();
+        check_match("tdr_hash", expected, actual)
+    }
+
+    /// Verify that `tdr_hash` commits to the given TDR
+    pub fn verify_tdr(&self, tdr: &TransactionDetailRecord) -> Result<(), PosError> {
+        self.verify_tdr_hash(&tdr.hash()?)
+    }
+
+    /// Validate the record structure (no cryptographic checks)
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pos_version != POS_VERSION_1_0 {
+            return Err(format!("unsupported pos_version: {}", self.pos_version));
+        }
+        validate_non_empty(&self.request_id, "request_id")?;
+        validate_non_empty(&self.settlement_txid, "settlement_txid")?;
+        validate_non_empty(&self.settlement_chain, "settlement_chain")?;
+        validate_non_empty(&self.tgp_path, "tgp_path")?;
+        validate_transaction_hash(&self.economic_envelope_hash, "economic_envelope_hash")?;
+        validate_transaction_hash(&self.receipt_hash, "receipt_hash")?;
+        if let Some(ref prove_proof) = self.prove_proof {
+            prove_proof.validate()?;
+        }
+        if let Some(ref tdr_hash) = self.tdr_hash {
+            validate_transaction_hash(tdr_hash, "tdr_hash")?;
+        }
+        Ok(())
+    }
+}
+
+fn check_match(field: &'static str, expected: &str, actual: &str) -> Result<(), PosError> {
+    if expected != actual {
+        return Err(PosError::HashMismatch {
+            field,
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Enforce the TGP-01 §5 `prove_proof` rule against the envelope
+fn check_prove_proof(
+    envelope: &EconomicEnvelope,
+    prove_proof: Option<&ProveProof>,
+) -> Result<(), PosError> {
+    if !envelope.requires_prove_proof() {
+        return Ok(());
+    }
+    match prove_proof {
+        None => Err(PosError::MissingProveProof),
+        Some(proof) if proof.mode != ProtocolFeeMode::AutoProveBurn => Err(
+            PosError::InvalidField(format!("prove_proof.mode must be auto_prove_burn, got {:?}", proof.mode)),
+        ),
+        Some(_) => Ok(()),
+    }
+}
+
+// ============================================================================
+// Builder
+// ============================================================================
+
+/// Builds and signs a [`ProofOfSettlement`] for a finalized receipt
+///
+/// See the [module documentation](self) for an example.
+pub struct ProofOfSettlementBuilder<'a, R: SettlementReceipt> {
+    request_id: String,
+    receipt: &'a R,
+    envelope: &'a EconomicEnvelope,
+    tgp_path: Option<String>,
+    prove_proof: Option<ProveProof>,
+    tdr_hash: Option<String>,
+}
+
+impl<'a, R: SettlementReceipt> ProofOfSettlementBuilder<'a, R> {
+    /// Start a PoS for `receipt` under the session's `envelope`
+    pub fn new(request_id: impl Into<String>, receipt: &'a R, envelope: &'a EconomicEnvelope) -> Self {
+        Self {
+            request_id: request_id.into(),
+            receipt,
+            envelope,
+            tgp_path: None,
+            prove_proof: None,
+            tdr_hash: None,
+        }
+    }
+
+    /// Set the TAI path the session traversed
+    pub fn tgp_path(mut self, tgp_path: impl Into<String>) -> Self {
+        self.tgp_path = Some(tgp_path.into());
+        self
+    }
+
+    /// Attach the Prove Protocol Logic burn evidence
+    pub fn prove_proof(mut self, prove_proof: ProveProof) -> Self {
+        self.prove_proof = Some(prove_proof);
+        self
+    }
+
+    /// Attach the hash of the session's TDR
+    pub fn tdr_hash(mut self, tdr_hash: impl Into<String>) -> Self {
+        self.tdr_hash = Some(tdr_hash.into());
+        self
+    }
+
+    /// Attach the session's TDR by hash (see [`TransactionDetailRecord::hash`])
+    pub fn tdr(self, tdr: &TransactionDetailRecord) -> Result<Self, PosError> {
+        Ok(self.tdr_hash(tdr.hash()?))
+    }
+
+    /// Build the unsigned record
+    ///
+    /// # Errors
+    ///
+    /// - [`PosError::ReceiptNotFinalized`] if the receipt has no settlement
+    /// - [`PosError::MissingProveProof`] if the envelope requires one
+    /// - [`PosError::InvalidField`] if a field fails validation
+    pub fn build(self) -> Result<ProofOfSettlement, PosError> {
+        if !self.receipt.is_finalized() {
+            return Err(PosError::ReceiptNotFinalized);
+        }
+        let settlement_txid = self
+            .receipt
+            .settlement_txid()
+            .ok_or(PosError::ReceiptNotFinalized)?
+            .to_string();
+
+        check_prove_proof(self.envelope, self.prove_proof.as_ref())?;
+
+        let pos = ProofOfSettlement {
+            pos_version: POS_VERSION_1_0.to_string(),
+            request_id: self.request_id,
+            settlement_txid,
+            settlement_chain: settlement_chain_name(self.receipt.settlement_chain_id()),
+            tgp_path: self.tgp_path.unwrap_or_default(),
+            economic_envelope_hash: canonical_keccak256(self.envelope)?,
+            receipt_hash: canonical_keccak256(self.receipt)?,
+            prove_proof: self.prove_proof,
+            tdr_hash: self.tdr_hash,
+            signature: String::new(),
+        };
+
+        pos.validate().map_err(PosError::InvalidField)?;
+        Ok(pos)
+    }
+
+    /// Build the record and sign it with the Controller key
+    pub fn sign(self, key: &SigningKey) -> Result<ProofOfSettlement, PosError> {
+        let mut pos = self.build()?;
+        pos.sign(key)?;
+        Ok(pos)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tgp::types::{BuyerFee, EnvelopePrice, ProtocolFee};
+
+    #[derive(Serialize)]
+    struct TestReceipt {
+        session_id: String,
+        claim_txid: Option<String>,
+        chain_id: u64,
+    }
+
+    impl SettlementReceipt for TestReceipt {
+        fn settlement_txid(&self) -> Option<&str> {
+            self.claim_txid.as_deref()
+        }
+
+        fn settlement_chain_id(&self) -> u64 {
+            self.chain_id
+        }
+    }
+
+    const CLAIM_TX: &str = "0xcccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc";
+    const BURN_TX: &str = "0xdddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd";
+
+    fn receipt() -> TestReceipt {
+        TestReceipt {
+            session_id: "sess-123".to_string(),
+            claim_txid: Some(CLAIM_TX.to_string()),
+            chain_id: 369,
+        }
+    }
+
+    fn prove_burn_envelope() -> EconomicEnvelope {
+        EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 500).with_buyer_fee(
+            BuyerFee::new("0.50", "USDC").with_protocol_fee(ProtocolFee::new(
+                "0.2",
+                "tgp://proverouter",
+                ProtocolFeeMode::AutoProveBurn,
+            )),
+        )
+    }
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[42u8; 32])
+    }
+
+    #[test]
+    fn test_build_and_verify() {
+        let receipt = receipt();
+        let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 50);
+
+        let pos = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope)
+            .tgp_path("tai:7abf92c6")
+            .sign(&key())
+            .unwrap();
+
+        assert_eq!(pos.pos_version, POS_VERSION_1_0);
+        assert_eq!(pos.settlement_txid, CLAIM_TX);
+        assert_eq!(pos.settlement_chain, "eip155:369");
+        assert!(pos.prove_proof.is_none());
+        assert!(pos.verify(&key().verifying_key(), &receipt, &envelope).is_ok());
+
+        // Survives a JSON round trip
+        let parsed: ProofOfSettlement = serde_json::from_str(&serde_json::to_string(&pos).unwrap()).unwrap();
+        assert!(parsed.verify(&key().verifying_key(), &receipt, &envelope).is_ok());
+    }
+
+    #[test]
+    fn test_unfinalized_receipt_rejected() {
+        let receipt = TestReceipt { claim_txid: None, ..receipt() };
+        let envelope = EconomicEnvelope::new(50);
+
+        let result = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope)
+            .tgp_path("tai:7abf92c6")
+            .build();
+        assert_eq!(result.unwrap_err(), PosError::ReceiptNotFinalized);
+    }
+
+    #[test]
+    fn test_missing_tgp_path_rejected() {
+        let receipt = receipt();
+        let envelope = EconomicEnvelope::new(50);
+
+        let result = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope).build();
+        assert!(matches!(result, Err(PosError::InvalidField(_))));
+    }
+
+    #[test]
+    fn test_prove_proof_required_for_auto_prove_burn() {
+        let receipt = receipt();
+        let envelope = prove_burn_envelope();
+
+        let missing = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope)
+            .tgp_path("tai:7abf92c6")
+            .build();
+        assert_eq!(missing.unwrap_err(), PosError::MissingProveProof);
+
+        let pos = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope)
+            .tgp_path("tai:7abf92c6")
+            .prove_proof(ProveProof::new(BURN_TX, "1.234", "prove", ProtocolFeeMode::AutoProveBurn, 1200))
+            .sign(&key())
+            .unwrap();
+        assert!(pos.verify(&key().verifying_key(), &receipt, &envelope).is_ok());
+
+        // Stripping the proof after signing fails verification on the rule
+        let mut stripped = pos.clone();
+        stripped.prove_proof = None;
+        assert_eq!(
+            stripped.verify(&key().verifying_key(), &receipt, &envelope).unwrap_err(),
+            PosError::MissingProveProof
+        );
+    }
+
+    #[test]
+    fn test_hash_mismatches_detected() {
+        let receipt = receipt();
+        let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 50);
+        let pos = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope)
+            .tgp_path("tai:7abf92c6")
+            .sign(&key())
+            .unwrap();
+
+        let other_envelope = EconomicEnvelope::v1(EnvelopePrice::new("31.00", "USDC"), 50);
+        assert!(matches!(
+            pos.verify(&key().verifying_key(), &receipt, &other_envelope),
+            Err(PosError::HashMismatch { field: "economic_envelope_hash", .. })
+        ));
+
+        let other_receipt = TestReceipt { session_id: "sess-999".to_string(), ..receipt };
+        assert!(matches!(
+            pos.verify(&key().verifying_key(), &other_receipt, &envelope),
+            Err(PosError::HashMismatch { field: "receipt_hash", .. })
+        ));
+    }
+
+    #[test]
+    fn test_signature_checks() {
+        let receipt = receipt();
+        let envelope = EconomicEnvelope::new(50);
+        let pos = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope)
+            .tgp_path("tai:7abf92c6")
+            .sign(&key())
+            .unwrap();
+
+        // Wrong key
+        let other = SigningKey::from_bytes(&[1u8; 32]);
+        assert!(matches!(
+            pos.verify_signature(&other.verifying_key()),
+            Err(PosError::InvalidSignature(_))
+        ));
+
+        // Tampered field
+        let mut tampered = pos.clone();
+        tampered.tgp_path = "tai:ffffffff".to_string();
+        assert!(matches!(
+            tampered.verify_signature(&key().verifying_key()),
+            Err(PosError::InvalidSignature(_))
+        ));
+
+        // Malformed signature
+        let mut malformed = pos;
+        malformed.signature = "0x1234".to_string();
+        assert!(matches!(
+            malformed.verify_signature(&key().verifying_key()),
+            Err(PosError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_tdr_hash_binding() {
+        let receipt = receipt();
+        let envelope = EconomicEnvelope::new(50);
+        let tdr_hash = crate::tgp::hash::sha256_hex(b"tdr");
+        let pos = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope)
+            .tgp_path("tai:7abf92c6")
+            .tdr_hash(tdr_hash.clone())
+            .sign(&key())
+            .unwrap();
+
+        assert!(pos.verify_tdr_hash(&tdr_hash).is_ok());
+        assert!(pos.verify_tdr_hash(&crate::tgp::hash::sha256_hex(b"other")).is_err());
+        assert_ne!(pos.hash().unwrap(), tdr_hash);
+    }
+
+    #[test]
+    fn test_tdr_binding() {
+        use crate::tgp::tdr::{TdrOutcome, TdrTimestamp};
+
+        let receipt = receipt();
+        let envelope = EconomicEnvelope::new(50);
+        let tdr = TransactionDetailRecord::new(
+            "sess-123",
+            vec!["tai:7abf92c6".to_string()],
+            "buyer://alice",
+            "seller://bob",
+            "30.00",
+            "USDC",
+            TdrTimestamp::new(1, 1_700_000_000, "2023-11-14T22:13:20Z"),
+            TdrOutcome::Claimed,
+        );
+
+        let pos = ProofOfSettlementBuilder::new("req-1", &receipt, &envelope)
+            .tgp_path(tdr.tgp_path())
+            .tdr(&tdr)
+            .unwrap()
+            .sign(&key())
+            .unwrap();
+
+        assert_eq!(pos.tdr_hash, Some(tdr.hash().unwrap()));
+        assert!(pos.verify_tdr(&tdr).is_ok());
+
+        let other = TransactionDetailRecord { outcome: TdrOutcome::Refunded, ..tdr };
+        assert!(pos.verify_tdr(&other).is_err());
+    }
+}
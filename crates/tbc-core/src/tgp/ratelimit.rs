@@ -0,0 +1,791 @@
+//# TGP Rate Limiting & Admission Control
+
+//**Destination Path:** `crates/tbc-core/src/tgp/ratelimit.rs`
+
+//**Implementation:** M1 - TGP Message Parsing & Basic Routing
+
+//! Rate limiting and admission control
+//!
+//! Protects a controller from abusive agents. Every request is admitted
+//! against the limits of each [`LimitKey`] it carries: the agent id, the
+//! buyer identifier, the API key and the client's network address.
+//!
+//! - **Rate**: a token bucket per key. Each request takes one token; tokens
+//!   refill at `requests_per_minute` up to `burst`.
+//! - **Concurrency**: a quota of open sessions per key, taken by
+//!   [`RateLimiter::open_session`] and returned by
+//!   [`RateLimiter::close_session`] (or by dropping a [`SessionPermit`]).
+//!
+//! A request is admitted only if every key passes, and then charges every
+//! key. Rejections map to `RATE_LIMITED` (HTTP 429) and carry the number of
+//! seconds to wait for the `Retry-After` header. Admissions and rejections
+//! are counted in [`RateLimitMetrics`].
+//!
+//! Buckets live until [`RateLimiter::prune`] drops the full ones; servers
+//! call it periodically.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use tbc_core::tgp::clock::ManualClock;
+//! use tbc_core::tgp::ratelimit::{
+//!     LimitKey, LimitKind, RateLimit, RateLimitConfig, RateLimiter,
+//! };
+//!
+//! let clock = Arc::new(ManualClock::new(1_000));
+//! let config = RateLimitConfig::new()
+//!     .with_rate(LimitKind::Buyer, RateLimit::per_minute(60).with_burst(2));
+//! let limiter = RateLimiter::new(config).with_clock(clock.clone());
+//!
+//! let keys = [LimitKey::buyer("buyer://alice")];
+//! assert!(limiter.check(&keys).is_ok());
+//! assert!(limiter.check(&keys).is_ok());
+//!
+//! // Burst spent: wait one second for the next token
+//! let rejection = limiter.check(&keys).unwrap_err();
+//! assert_eq!(rejection.retry_after_secs, 1);
+//!
+//! clock.advance(1);
+//! assert!(limiter.check(&keys).is_ok());
+//! assert_eq!(limiter.metrics().rate_limited, 1);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::clock::{Clock, SystemClock};
+use super::errors::TgpErrorCode;
+
+/// Default `Retry-After` for a rejected session, in seconds
+pub const DEFAULT_SESSION_RETRY_AFTER_SECS: u64 = 5;
+
+// ============================================================================
+// Keys
+// ============================================================================
+
+/// What a limit is keyed by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    /// TxIP agent id from HELLO
+    Agent,
+    /// Buyer identifier (`from` of the QUERY)
+    Buyer,
+    /// API key presented by the client
+    ApiKey,
+    /// Network address the request came from
+    Peer,
+}
+
+impl LimitKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitKind::Agent => "agent",
+            LimitKind::Buyer => "buyer",
+            LimitKind::ApiKey => "api_key",
+            LimitKind::Peer => "peer",
+        }
+    }
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Identity a request is counted against
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LimitKey {
+    pub kind: LimitKind,
+    pub id: String,
+}
+
+impl LimitKey {
+    pub fn new(kind: LimitKind, id: impl Into<String>) -> Self {
+        Self {
+            kind,
+            id: id.into(),
+        }
+    }
+
+    pub fn agent(id: impl Into<String>) -> Self {
+        Self::new(LimitKind::Agent, id)
+    }
+
+    pub fn buyer(id: impl Into<String>) -> Self {
+        Self::new(LimitKind::Buyer, id)
+    }
+
+    pub fn api_key(key: impl Into<String>) -> Self {
+        Self::new(LimitKind::ApiKey, key)
+    }
+
+    pub fn peer(addr: impl Into<String>) -> Self {
+        Self::new(LimitKind::Peer, addr)
+    }
+}
+
+/// `kind:id`; API keys are shortened to their first four characters so
+/// they can be logged
+impl fmt::Display for LimitKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            LimitKind::ApiKey => {
+                let prefix: String = self.id.chars().take(4).collect();
+                write!(f, "{}:{}…", self.kind, prefix)
+            }
+            _ => write!(f, "{}:{}", self.kind, self.id),
+        }
+    }
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Token bucket parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Sustained rate
+    pub requests_per_minute: u32,
+    /// Bucket size: requests allowed back to back
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// `requests_per_minute` with a burst of the same size
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        Self {
+            requests_per_minute,
+            burst: requests_per_minute,
+        }
+    }
+
+    /// Builder method to set the bucket size
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        f64::from(self.requests_per_minute) / 60.0
+    }
+}
+
+/// Limits applied to every key of one [`LimitKind`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyLimits {
+    /// Token bucket per key; `None` disables rate limiting
+    pub rate: Option<RateLimit>,
+    /// Open sessions per key; `None` disables the quota
+    pub max_sessions: Option<u32>,
+}
+
+/// Limits per [`LimitKind`]
+///
+/// Deserializes from e.g.
+///
+/// ```toml
+/// [rate_limit.buyer]
+/// rate = { requests_per_minute = 120, burst = 20 }
+/// max_sessions = 4
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub agent: KeyLimits,
+    pub buyer: KeyLimits,
+    pub api_key: KeyLimits,
+    pub peer: KeyLimits,
+    /// `Retry-After` for requests over a session quota
+    pub session_retry_after_secs: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            agent: KeyLimits::default(),
+            buyer: KeyLimits::default(),
+            api_key: KeyLimits::default(),
+            peer: KeyLimits::default(),
+            session_retry_after_secs: DEFAULT_SESSION_RETRY_AFTER_SECS,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to rate limit every key of `kind`
+    pub fn with_rate(mut self, kind: LimitKind, rate: RateLimit) -> Self {
+        self.limits_mut(kind).rate = Some(rate);
+        self
+    }
+
+    /// Builder method to cap open sessions per key of `kind`
+    pub fn with_max_sessions(mut self, kind: LimitKind, max_sessions: u32) -> Self {
+        self.limits_mut(kind).max_sessions = Some(max_sessions);
+        self
+    }
+
+    pub fn limits(&self, kind: LimitKind) -> &KeyLimits {
+        match kind {
+            LimitKind::Agent => &self.agent,
+            LimitKind::Buyer => &self.buyer,
+            LimitKind::ApiKey => &self.api_key,
+            LimitKind::Peer => &self.peer,
+        }
+    }
+
+    fn limits_mut(&mut self, kind: LimitKind) -> &mut KeyLimits {
+        match kind {
+            LimitKind::Agent => &mut self.agent,
+            LimitKind::Buyer => &mut self.buyer,
+            LimitKind::ApiKey => &mut self.api_key,
+            LimitKind::Peer => &mut self.peer,
+        }
+    }
+}
+
+// ============================================================================
+// Rejections & Metrics
+// ============================================================================
+
+/// Which limit was hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "limit", rename_all = "snake_case")]
+pub enum RejectionReason {
+    Rate { requests_per_minute: u32 },
+    Sessions { max_sessions: u32 },
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectionReason::Rate {
+                requests_per_minute,
+            } => write!(f, "exceeded {} requests per minute", requests_per_minute),
+            RejectionReason::Sessions { max_sessions } => {
+                write!(f, "already has {} open sessions", max_sessions)
+            }
+        }
+    }
+}
+
+/// Request refused by the [`RateLimiter`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{key} {reason}; retry after {retry_after_secs}s")]
+pub struct RateLimitRejection {
+    pub key: LimitKey,
+    pub reason: RejectionReason,
+    /// Seconds until the request would be admitted (`Retry-After`)
+    pub retry_after_secs: u64,
+}
+
+impl RateLimitRejection {
+    /// TGP error code to answer with
+    pub fn error_code(&self) -> TgpErrorCode {
+        TgpErrorCode::RateLimited
+    }
+}
+
+/// Admission counters since the limiter was created
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitMetrics {
+    /// Requests admitted by [`RateLimiter::check`]
+    pub admitted: u64,
+    /// Requests refused by a token bucket
+    pub rate_limited: u64,
+    /// Sessions refused by a quota
+    pub session_rejected: u64,
+    /// Rejections of either kind per [`LimitKind`]
+    pub rejected_by_kind: BTreeMap<LimitKind, u64>,
+}
+
+impl RateLimitMetrics {
+    /// Total rejections
+    pub fn rejected(&self) -> u64 {
+        self.rate_limited + self.session_rejected
+    }
+
+    fn record(&mut self, rejection: &RateLimitRejection) {
+        match rejection.reason {
+            RejectionReason::Rate { .. } => self.rate_limited += 1,
+            RejectionReason::Sessions { .. } => self.session_rejected += 1,
+        }
+        *self.rejected_by_kind.entry(rejection.key.kind).or_default() += 1;
+    }
+}
+
+// ============================================================================
+// RateLimiter
+// ============================================================================
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated: u64,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: u64) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, limit: &RateLimit, now: u64) {
+        if now > self.updated {
+            let elapsed = (now - self.updated) as f64;
+            self.tokens = (self.tokens + elapsed * limit.refill_per_sec())
+                .min(f64::from(limit.burst));
+            self.updated = now;
+        }
+    }
+
+    fn is_full(&self, limit: &RateLimit) -> bool {
+        self.tokens >= f64::from(limit.burst)
+    }
+
+    /// Whole seconds until a token is available
+    fn wait_secs(&self, limit: &RateLimit) -> u64 {
+        let rate = limit.refill_per_sec();
+        if self.tokens >= 1.0 {
+            0
+        } else if rate <= 0.0 {
+            u64::MAX
+        } else {
+            ((1.0 - self.tokens) / rate).ceil().max(1.0) as u64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    buckets: HashMap<LimitKey, TokenBucket>,
+    sessions: HashMap<LimitKey, u32>,
+    metrics: RateLimitMetrics,
+}
+
+/// Token buckets and session quotas per [`LimitKey`]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    clock: Arc<dyn Clock>,
+    state: Mutex<LimiterState>,
+}
+
+impl RateLimiter {
+    /// Limiter reading the system clock
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            clock: Arc::new(SystemClock),
+            state: Mutex::new(LimiterState::default()),
+        }
+    }
+
+    /// Builder method to inject the clock buckets refill against
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Admit one request charged to every key
+    ///
+    /// Keys without a configured rate always pass. On rejection no bucket
+    /// is charged.
+    pub fn check(&self, keys: &[LimitKey]) -> Result<(), RateLimitRejection> {
+        let now = self.clock.now_unix();
+        let mut state = self.state.lock().unwrap();
+
+        let mut rejection: Option<RateLimitRejection> = None;
+        for key in keys {
+            let Some(limit) = self.config.limits(key.kind).rate else {
+                continue;
+            };
+            let bucket = state
+                .buckets
+                .entry(key.clone())
+                .or_insert_with(|| TokenBucket::full(&limit, now));
+            bucket.refill(&limit, now);
+
+            let wait = bucket.wait_secs(&limit);
+            if wait > 0 && rejection.as_ref().is_none_or(|r| wait > r.retry_after_secs) {
+                rejection = Some(RateLimitRejection {
+                    key: key.clone(),
+                    reason: RejectionReason::Rate {
+                        requests_per_minute: limit.requests_per_minute,
+                    },
+                    retry_after_secs: wait,
+                });
+            }
+        }
+
+        if let Some(rejection) = rejection {
+            state.metrics.record(&rejection);
+            return Err(rejection);
+        }
+
+        for key in keys {
+            if let Some(bucket) = state.buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        state.metrics.admitted += 1;
+        Ok(())
+    }
+
+    /// Count a new session against every key
+    ///
+    /// Each successful call must be paired with [`close_session`] for the
+    /// same keys.
+    ///
+    /// [`close_session`]: RateLimiter::close_session
+    pub fn open_session(&self, keys: &[LimitKey]) -> Result<(), RateLimitRejection> {
+        let mut state = self.state.lock().unwrap();
+
+        for key in keys {
+            let Some(max_sessions) = self.config.limits(key.kind).max_sessions else {
+                continue;
+            };
+            if state.sessions.get(key).copied().unwrap_or(0) >= max_sessions {
+                let rejection = RateLimitRejection {
+                    key: key.clone(),
+                    reason: RejectionReason::Sessions { max_sessions },
+                    retry_after_secs: self.config.session_retry_after_secs,
+                };
+                state.metrics.record(&rejection);
+                return Err(rejection);
+            }
+        }
+
+        for key in keys {
+            *state.sessions.entry(key.clone()).or_default() += 1;
+        }
+        Ok(())
+    }
+
+    /// Return a session opened with [`open_session`](RateLimiter::open_session)
+    pub fn close_session(&self, keys: &[LimitKey]) {
+        let mut state = self.state.lock().unwrap();
+        for key in keys {
+            if let Some(count) = state.sessions.get_mut(key) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    state.sessions.remove(key);
+                }
+            }
+        }
+    }
+
+    /// Give back the tokens a [`check`](RateLimiter::check) took
+    ///
+    /// For requests admitted by the buckets but refused afterwards, e.g.
+    /// by a session quota, so the refusal costs the client nothing.
+    pub fn refund(&self, keys: &[LimitKey]) {
+        let mut state = self.state.lock().unwrap();
+        for key in keys {
+            let Some(limit) = self.config.limits(key.kind).rate else {
+                continue;
+            };
+            if let Some(bucket) = state.buckets.get_mut(key) {
+                bucket.tokens = (bucket.tokens + 1.0).min(f64::from(limit.burst));
+            }
+        }
+        state.metrics.admitted = state.metrics.admitted.saturating_sub(1);
+    }
+
+    /// [`check`](RateLimiter::check) and [`open_session`](RateLimiter::open_session)
+    /// in one step; the session closes when the permit is dropped
+    ///
+    /// A request refused by a session quota is [refunded](RateLimiter::refund).
+    pub fn admit(
+        self: &Arc<Self>,
+        keys: Vec<LimitKey>,
+    ) -> Result<SessionPermit, RateLimitRejection> {
+        self.check(&keys)?;
+        if let Err(rejection) = self.open_session(&keys) {
+            self.refund(&keys);
+            return Err(rejection);
+        }
+        Ok(SessionPermit {
+            limiter: Arc::clone(self),
+            keys,
+        })
+    }
+
+    /// Sessions currently open for `key`
+    pub fn open_sessions(&self, key: &LimitKey) -> u32 {
+        self.state
+            .lock()
+            .unwrap()
+            .sessions
+            .get(key)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Snapshot of the admission counters
+    pub fn metrics(&self) -> RateLimitMetrics {
+        self.state.lock().unwrap().metrics.clone()
+    }
+
+    /// Forget buckets that have refilled completely
+    ///
+    /// A full bucket is indistinguishable from a new one, so this only
+    /// bounds memory. Returns the number of buckets dropped.
+    pub fn prune(&self) -> usize {
+        let now = self.clock.now_unix();
+        let mut state = self.state.lock().unwrap();
+        let before = state.buckets.len();
+        state.buckets.retain(|key, bucket| match self.config.limits(key.kind).rate {
+            Some(limit) => {
+                bucket.refill(&limit, now);
+                !bucket.is_full(&limit)
+            }
+            None => false,
+        });
+        before - state.buckets.len()
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+/// Open session returned by [`RateLimiter::admit`], closed on drop
+pub struct SessionPermit {
+    limiter: Arc<RateLimiter>,
+    keys: Vec<LimitKey>,
+}
+
+impl SessionPermit {
+    pub fn keys(&self) -> &[LimitKey] {
+        &self.keys
+    }
+}
+
+impl Drop for SessionPermit {
+    fn drop(&mut self) {
+        self.limiter.close_session(&self.keys);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tgp::clock::ManualClock;
+
+    fn limiter(config: RateLimitConfig) -> (Arc<ManualClock>, RateLimiter) {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let limiter = RateLimiter::new(config).with_clock(clock.clone());
+        (clock, limiter)
+    }
+
+    #[test]
+    fn test_token_bucket_refills_at_rate() {
+        let config = RateLimitConfig::new()
+            .with_rate(LimitKind::Agent, RateLimit::per_minute(6).with_burst(3));
+        let (clock, limiter) = limiter(config);
+        let keys = [LimitKey::agent("buyer://alice")];
+
+        for _ in 0..3 {
+            limiter.check(&keys).unwrap();
+        }
+        let rejection = limiter.check(&keys).unwrap_err();
+        assert_eq!(rejection.reason, RejectionReason::Rate { requests_per_minute: 6 });
+        assert_eq!(rejection.retry_after_secs, 10);
+        assert_eq!(rejection.error_code(), TgpErrorCode::RateLimited);
+
+        clock.advance(9);
+        assert_eq!(limiter.check(&keys).unwrap_err().retry_after_secs, 1);
+        clock.advance(1);
+        limiter.check(&keys).unwrap();
+
+        // Refill stops at the burst size
+        clock.advance(3_600);
+        for _ in 0..3 {
+            limiter.check(&keys).unwrap();
+        }
+        assert!(limiter.check(&keys).is_err());
+    }
+
+    #[test]
+    fn test_keys_are_independent_and_unlimited_kinds_pass() {
+        let config = RateLimitConfig::new()
+            .with_rate(LimitKind::Buyer, RateLimit::per_minute(60).with_burst(1));
+        let (_clock, limiter) = limiter(config);
+
+        limiter.check(&[LimitKey::buyer("buyer://alice")]).unwrap();
+        limiter.check(&[LimitKey::buyer("buyer://carol")]).unwrap();
+        assert!(limiter.check(&[LimitKey::buyer("buyer://alice")]).is_err());
+
+        for _ in 0..100 {
+            limiter.check(&[LimitKey::api_key("k-1")]).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rejection_charges_no_key() {
+        let config = RateLimitConfig::new()
+            .with_rate(LimitKind::Agent, RateLimit::per_minute(60).with_burst(5))
+            .with_rate(LimitKind::ApiKey, RateLimit::per_minute(60).with_burst(1));
+        let (_clock, limiter) = limiter(config);
+        let agent = LimitKey::agent("agent-1");
+        let key = LimitKey::api_key("secret-key");
+
+        limiter.check(&[agent.clone(), key.clone()]).unwrap();
+        for _ in 0..10 {
+            let rejection = limiter.check(&[agent.clone(), key.clone()]).unwrap_err();
+            assert_eq!(rejection.key, key);
+        }
+        // The agent bucket still holds its remaining four tokens
+        for _ in 0..4 {
+            limiter.check(std::slice::from_ref(&agent)).unwrap();
+        }
+        assert!(limiter.check(&[agent]).is_err());
+    }
+
+    #[test]
+    fn test_session_quota() {
+        let config = RateLimitConfig::new().with_max_sessions(LimitKind::Agent, 2);
+        let (_clock, limiter) = limiter(config);
+        let keys = [LimitKey::agent("agent-1")];
+
+        limiter.open_session(&keys).unwrap();
+        limiter.open_session(&keys).unwrap();
+        let rejection = limiter.open_session(&keys).unwrap_err();
+        assert_eq!(rejection.reason, RejectionReason::Sessions { max_sessions: 2 });
+        assert_eq!(rejection.retry_after_secs, DEFAULT_SESSION_RETRY_AFTER_SECS);
+        assert_eq!(limiter.open_sessions(&keys[0]), 2);
+
+        limiter.close_session(&keys);
+        limiter.open_session(&keys).unwrap();
+
+        // Closing more than was opened never underflows
+        for _ in 0..5 {
+            limiter.close_session(&keys);
+        }
+        assert_eq!(limiter.open_sessions(&keys[0]), 0);
+    }
+
+    #[test]
+    fn test_permit_closes_session_on_drop() {
+        let config = RateLimitConfig::new().with_max_sessions(LimitKind::ApiKey, 1);
+        let limiter = Arc::new(RateLimiter::new(config));
+        let keys = vec![LimitKey::api_key("k-1")];
+
+        let permit = limiter.admit(keys.clone()).unwrap();
+        assert_eq!(permit.keys(), keys.as_slice());
+        assert!(limiter.admit(keys.clone()).is_err());
+        drop(permit);
+        assert!(limiter.admit(keys).is_ok());
+    }
+
+    #[test]
+    fn test_session_rejection_refunds_token() {
+        let config = RateLimitConfig::new()
+            .with_rate(LimitKind::Peer, RateLimit::per_minute(1).with_burst(2))
+            .with_max_sessions(LimitKind::Peer, 1);
+        let (_clock, limiter) = limiter(config);
+        let limiter = Arc::new(limiter);
+        let keys = vec![LimitKey::peer("10.0.0.1")];
+
+        let permit = limiter.admit(keys.clone()).unwrap();
+        for _ in 0..3 {
+            let Err(rejection) = limiter.admit(keys.clone()) else {
+                panic!("session over quota admitted");
+            };
+            assert_eq!(rejection.reason, RejectionReason::Sessions { max_sessions: 1 });
+        }
+        assert_eq!(limiter.metrics().admitted, 1);
+
+        // The second token is still there once the session closes
+        drop(permit);
+        assert!(limiter.admit(keys.clone()).is_ok());
+        let Err(rejection) = limiter.admit(keys) else {
+            panic!("request over rate admitted");
+        };
+        assert_eq!(rejection.reason, RejectionReason::Rate { requests_per_minute: 1 });
+    }
+
+    #[test]
+    fn test_metrics_count_rejections() {
+        let config = RateLimitConfig::new()
+            .with_rate(LimitKind::Buyer, RateLimit::per_minute(1).with_burst(1))
+            .with_max_sessions(LimitKind::Agent, 0);
+        let (_clock, limiter) = limiter(config);
+
+        limiter.check(&[LimitKey::buyer("b")]).unwrap();
+        assert!(limiter.check(&[LimitKey::buyer("b")]).is_err());
+        assert!(limiter.check(&[LimitKey::buyer("b")]).is_err());
+        assert!(limiter.open_session(&[LimitKey::agent("a")]).is_err());
+
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.admitted, 1);
+        assert_eq!(metrics.rate_limited, 2);
+        assert_eq!(metrics.session_rejected, 1);
+        assert_eq!(metrics.rejected(), 3);
+        assert_eq!(metrics.rejected_by_kind[&LimitKind::Buyer], 2);
+        assert_eq!(metrics.rejected_by_kind[&LimitKind::Agent], 1);
+    }
+
+    #[test]
+    fn test_prune_drops_full_buckets() {
+        let config = RateLimitConfig::new()
+            .with_rate(LimitKind::Buyer, RateLimit::per_minute(60).with_burst(2));
+        let (clock, limiter) = limiter(config);
+
+        limiter.check(&[LimitKey::buyer("a")]).unwrap();
+        limiter.check(&[LimitKey::buyer("b")]).unwrap();
+        assert_eq!(limiter.prune(), 0);
+
+        clock.advance(1);
+        assert_eq!(limiter.prune(), 2);
+    }
+
+    #[test]
+    fn test_config_and_display() {
+        let config: RateLimitConfig = serde_json::from_str(
+            r#"{"buyer": {"rate": {"requests_per_minute": 120, "burst": 20}, "max_sessions": 4}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.limits(LimitKind::Buyer).rate,
+            Some(RateLimit::per_minute(120).with_burst(20))
+        );
+        assert_eq!(config.buyer.max_sessions, Some(4));
+        assert_eq!(config.agent, KeyLimits::default());
+        assert_eq!(config.session_retry_after_secs, DEFAULT_SESSION_RETRY_AFTER_SECS);
+
+        assert_eq!(LimitKey::buyer("buyer://alice").to_string(), "buyer:buyer://alice");
+        assert_eq!(LimitKey::api_key("sk_live_123456").to_string(), "api_key:sk_l…");
+
+        let rejection = RateLimitRejection {
+            key: LimitKey::agent("a"),
+            reason: RejectionReason::Sessions { max_sessions: 2 },
+            retry_after_secs: 5,
+        };
+        assert_eq!(
+            rejection.to_string(),
+            "agent:a already has 2 open sessions; retry after 5s"
+        );
+    }
+}
@@ -0,0 +1,606 @@
+//# TGP Route Options
+
+//**Destination Path:** `crates/tbc-core/src/tgp/routes.rs`
+
+//**Implementation:** M2 - TGP-01 Economic Envelope & Proof-of-Settlement
+
+//! Multi-route OFFER support
+//!
+//! A Controller can answer a QUERY with several settlement alternatives,
+//! e.g. USDC on Base through CoreProver escrow vs. direct x402 on PulseChain.
+//! Each alternative is a [`RouteOption`]; the OFFER carries them ranked best
+//! first in `OfferMessage::routes`.
+//!
+//! # Controller Side
+//!
+//! [`rank_routes`] filters the candidates against the buyer's
+//! [`ZkProfile`] and the fee envelope, then orders them by a
+//! [`RankingPolicy`] and assigns ranks `1..=n`.
+//!
+//! # Buyer Side
+//!
+//! [`select_route`] picks the best-ranked route that satisfies the buyer's
+//! [`RoutePreferences`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::routes::{
+//!     rank_routes, select_route, RankingPolicy, RouteOption, RoutePreferences,
+//! };
+//! use tbc_core::tgp::types::{EconomicEnvelope, ZkProfile};
+//!
+//! const CONTRACT: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+//!
+//! let candidates = vec![
+//!     RouteOption::direct("pls-direct", "USDC", 1_000_000, 369).with_fee_estimate_bps(10),
+//!     RouteOption::escrow("base-escrow", "USDC", 1_000_000, 8453, CONTRACT)
+//!         .with_fee_estimate_bps(30),
+//! ];
+//!
+//! // Controller: buyer is fine with escrow, policy prefers it
+//! let policy = RankingPolicy::new(ZkProfile::Optional).prefer_escrow(true);
+//! let ranked = rank_routes(candidates, &EconomicEnvelope::new(50), &policy);
+//! assert_eq!(ranked[0].route_id, "base-escrow");
+//! assert_eq!(ranked[0].rank, 1);
+//!
+//! // Buyer: only holds funds on PulseChain
+//! let prefs = RoutePreferences::new().with_chains(vec![369]);
+//! assert_eq!(select_route(&ranked, &prefs).unwrap().route_id, "pls-direct");
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{EconomicEnvelope, ZkProfile};
+use super::validation::{
+    json_pointer, validate_address, validate_non_empty, validate_positive_amount,
+    validate_rfc3339_format, ValidationCode, ValidationReport,
+};
+
+// ============================================================================
+// RouteOption Structure
+// ============================================================================
+
+/// How a route settles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementMethod {
+    /// CoreProver escrow (requires `coreprover_contract`)
+    Escrow,
+
+    /// Direct x402 payment to the seller
+    DirectX402,
+}
+
+impl SettlementMethod {
+    pub fn is_escrow(&self) -> bool {
+        matches!(self, SettlementMethod::Escrow)
+    }
+}
+
+/// One settlement alternative in an OFFER
+///
+/// # Fields
+///
+/// | Field | Description |
+/// |-------|-------------|
+/// | `route_id` | Identifier unique within the OFFER |
+/// | `rank` | Position in the OFFER, 1 = recommended |
+/// | `asset` / `amount` | Payment asset and amount in smallest unit |
+/// | `chain_id` | Settlement chain |
+/// | `method` | Escrow or direct x402 |
+/// | `coreprover_contract` | Escrow contract (escrow routes only) |
+/// | `fee_estimate_bps` | Estimated total fees in basis points of the amount |
+/// | `expiry` | RFC3339 time after which the route is withdrawn |
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RouteOption {
+    pub route_id: String,
+
+    #[serde(default)]
+    pub rank: u32,
+
+    pub asset: String,
+
+    pub amount: u64,
+
+    pub chain_id: u64,
+
+    pub method: SettlementMethod,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coreprover_contract: Option<String>,
+
+    #[serde(default)]
+    pub fee_estimate_bps: u32,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<String>,
+}
+
+impl RouteOption {
+    /// Create a CoreProver escrow route
+    pub fn escrow(
+        route_id: impl Into<String>,
+        asset: impl Into<String>,
+        amount: u64,
+        chain_id: u64,
+        coreprover_contract: impl Into<String>,
+    ) -> Self {
+        Self {
+            route_id: route_id.into(),
+            rank: 0,
+            asset: asset.into(),
+            amount,
+            chain_id,
+            method: SettlementMethod::Escrow,
+            coreprover_contract: Some(coreprover_contract.into()),
+            fee_estimate_bps: 0,
+            expiry: None,
+        }
+    }
+
+    /// Create a direct x402 route
+    pub fn direct(
+        route_id: impl Into<String>,
+        asset: impl Into<String>,
+        amount: u64,
+        chain_id: u64,
+    ) -> Self {
+        Self {
+            route_id: route_id.into(),
+            rank: 0,
+            asset: asset.into(),
+            amount,
+            chain_id,
+            method: SettlementMethod::DirectX402,
+            coreprover_contract: None,
+            fee_estimate_bps: 0,
+            expiry: None,
+        }
+    }
+
+    /// Builder method to set the fee estimate
+    pub fn with_fee_estimate_bps(mut self, fee_estimate_bps: u32) -> Self {
+        self.fee_estimate_bps = fee_estimate_bps;
+        self
+    }
+
+    /// Builder method to set the expiry
+    pub fn with_expiry(mut self, expiry: impl Into<String>) -> Self {
+        self.expiry = Some(expiry.into());
+        self
+    }
+
+    /// Check if the route has expired at `now` (RFC3339, UTC)
+    ///
+    /// Uses the same lexical comparison as `EconomicEnvelope::is_expired`.
+    pub fn is_expired(&self, now_rfc3339: &str) -> bool {
+        self.expiry.as_deref().is_some_and(|expiry| now_rfc3339 > expiry)
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        report.check(
+            path,
+            "route_id",
+            ValidationCode::Required,
+            validate_non_empty(&self.route_id, "route_id"),
+        );
+        report.check(
+            path,
+            "asset",
+            ValidationCode::Required,
+            validate_non_empty(&self.asset, "route.asset"),
+        );
+        report.check(
+            path,
+            "amount",
+            ValidationCode::InvalidAmount,
+            validate_positive_amount(self.amount, "route.amount"),
+        );
+
+        match (self.method, &self.coreprover_contract) {
+            (SettlementMethod::Escrow, None) => report.error(
+                json_pointer(path, "coreprover_contract"),
+                ValidationCode::Required,
+                "escrow routes require a coreprover_contract",
+            ),
+            (SettlementMethod::Escrow, Some(contract)) => {
+                report.check(
+                    path,
+                    "coreprover_contract",
+                    ValidationCode::InvalidAddress,
+                    validate_address(contract, "route.coreprover_contract"),
+                );
+            }
+            (SettlementMethod::DirectX402, Some(_)) => report.warning(
+                json_pointer(path, "coreprover_contract"),
+                ValidationCode::Inconsistent,
+                "coreprover_contract is ignored for direct_x402 routes",
+            ),
+            (SettlementMethod::DirectX402, None) => {}
+        }
+
+        if let Some(ref expiry) = self.expiry {
+            report.check(
+                path,
+                "expiry",
+                ValidationCode::InvalidTimestamp,
+                validate_rfc3339_format(expiry, "route.expiry"),
+            );
+        }
+    }
+}
+
+/// Validate a ranked route list under the JSON pointer `path`
+///
+/// Checks each route, that route ids are unique, that ranks are exactly
+/// `1..=n` in order, and that no fee estimate exceeds `max_fees_bps`.
+pub fn validate_routes_into(
+    routes: &[RouteOption],
+    max_fees_bps: u32,
+    report: &mut ValidationReport,
+    path: &str,
+) {
+    let mut seen = HashSet::new();
+    for (i, route) in routes.iter().enumerate() {
+        let route_path = json_pointer(path, &i.to_string());
+        route.validate_into(report, &route_path);
+
+        if !route.route_id.is_empty() && !seen.insert(route.route_id.as_str()) {
+            report.error(
+                json_pointer(&route_path, "route_id"),
+                ValidationCode::Inconsistent,
+                format!("duplicate route_id: {}", route.route_id),
+            );
+        }
+        if route.rank as usize != i + 1 {
+            report.error(
+                json_pointer(&route_path, "rank"),
+                ValidationCode::Inconsistent,
+                format!(
+                    "routes must be ordered by rank starting at 1, expected {} got {}",
+                    i + 1,
+                    route.rank
+                ),
+            );
+        }
+        if route.fee_estimate_bps > max_fees_bps {
+            report.error(
+                json_pointer(&route_path, "fee_estimate_bps"),
+                ValidationCode::FeeCapExceeded,
+                format!(
+                    "fee_estimate_bps {} exceeds max_fees_bps {}",
+                    route.fee_estimate_bps, max_fees_bps
+                ),
+            );
+        }
+    }
+}
+
+// ============================================================================
+// Controller-Side Ranking
+// ============================================================================
+
+/// Controller policy for ordering route options
+///
+/// Routes are compared on these criteria in order:
+///
+/// 1. Settlement method, if the buyer's profile is `OPTIONAL` and the
+///    policy has an escrow preference
+/// 2. Position of the asset in `preferred_assets` (unlisted last)
+/// 3. Position of the chain in `preferred_chains` (unlisted last)
+/// 4. Lower `fee_estimate_bps`
+/// 5. `route_id`, so the order is deterministic
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RankingPolicy {
+    /// Buyer's escrow preference from the QUERY
+    pub zk_profile: ZkProfile,
+
+    /// `Some(true)` ranks escrow first, `Some(false)` direct first
+    pub escrow_preference: Option<bool>,
+
+    pub preferred_assets: Vec<String>,
+
+    pub preferred_chains: Vec<u64>,
+}
+
+impl RankingPolicy {
+    pub fn new(zk_profile: ZkProfile) -> Self {
+        Self {
+            zk_profile,
+            ..Self::default()
+        }
+    }
+
+    /// Builder method to rank escrow (or direct) routes first
+    pub fn prefer_escrow(mut self, prefer: bool) -> Self {
+        self.escrow_preference = Some(prefer);
+        self
+    }
+
+    /// Builder method to set the asset preference order
+    pub fn with_preferred_assets(mut self, assets: Vec<String>) -> Self {
+        self.preferred_assets = assets;
+        self
+    }
+
+    /// Builder method to set the chain preference order
+    pub fn with_preferred_chains(mut self, chains: Vec<u64>) -> Self {
+        self.preferred_chains = chains;
+        self
+    }
+
+    /// Whether the buyer's profile permits the route's settlement method
+    pub fn permits(&self, route: &RouteOption) -> bool {
+        if route.method.is_escrow() {
+            self.zk_profile.allows_escrow()
+        } else {
+            !self.zk_profile.requires_escrow()
+        }
+    }
+
+    /// Order of two routes under the criteria above
+    ///
+    /// The escrow preference only breaks ties for `OPTIONAL` buyers; under
+    /// `REQUIRED` or `NONE` the profile already fixed the method.
+    fn compare(&self, a: &RouteOption, b: &RouteOption) -> Ordering {
+        let method_key = |route: &RouteOption| match self.escrow_preference {
+            Some(prefer_escrow) if self.zk_profile == ZkProfile::Optional => {
+                route.method.is_escrow() != prefer_escrow
+            }
+            _ => false,
+        };
+        let asset_key =
+            |route: &RouteOption| position_or_last(&self.preferred_assets, &route.asset);
+        let chain_key =
+            |route: &RouteOption| position_or_last(&self.preferred_chains, &route.chain_id);
+
+        method_key(a)
+            .cmp(&method_key(b))
+            .then_with(|| asset_key(a).cmp(&asset_key(b)))
+            .then_with(|| chain_key(a).cmp(&chain_key(b)))
+            .then_with(|| a.fee_estimate_bps.cmp(&b.fee_estimate_bps))
+            .then_with(|| a.route_id.cmp(&b.route_id))
+    }
+}
+
+fn position_or_last<T: PartialEq>(preferences: &[T], value: &T) -> usize {
+    preferences
+        .iter()
+        .position(|p| p == value)
+        .unwrap_or(preferences.len())
+}
+
+/// Rank candidate routes for an OFFER
+///
+/// Drops routes the buyer's `zk_profile` does not permit and routes whose
+/// fee estimate exceeds `envelope.max_fees_bps`, orders the rest by
+/// `policy` and assigns ranks starting at 1.
+pub fn rank_routes(
+    candidates: Vec<RouteOption>,
+    envelope: &EconomicEnvelope,
+    policy: &RankingPolicy,
+) -> Vec<RouteOption> {
+    let mut routes: Vec<RouteOption> = candidates
+        .into_iter()
+        .filter(|route| policy.permits(route))
+        .filter(|route| route.fee_estimate_bps <= envelope.max_fees_bps)
+        .collect();
+
+    routes.sort_by(|a, b| policy.compare(a, b));
+    for (i, route) in routes.iter_mut().enumerate() {
+        route.rank = i as u32 + 1;
+    }
+    routes
+}
+
+// ============================================================================
+// Buyer-Side Selection
+// ============================================================================
+
+/// Buyer constraints for choosing among offered routes
+///
+/// Empty lists and `None` mean "no constraint".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutePreferences {
+    /// Assets the buyer can pay with
+    pub assets: Vec<String>,
+
+    /// Chains the buyer holds funds on
+    pub chains: Vec<u64>,
+
+    /// Required settlement method
+    pub method: Option<SettlementMethod>,
+
+    /// Highest acceptable fee estimate
+    pub max_fee_bps: Option<u32>,
+
+    /// Current time (RFC3339) used to skip expired routes
+    pub now: Option<String>,
+}
+
+impl RoutePreferences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_assets(mut self, assets: Vec<String>) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    pub fn with_chains(mut self, chains: Vec<u64>) -> Self {
+        self.chains = chains;
+        self
+    }
+
+    pub fn with_method(mut self, method: SettlementMethod) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    pub fn with_max_fee_bps(mut self, max_fee_bps: u32) -> Self {
+        self.max_fee_bps = Some(max_fee_bps);
+        self
+    }
+
+    pub fn at(mut self, now_rfc3339: impl Into<String>) -> Self {
+        self.now = Some(now_rfc3339.into());
+        self
+    }
+
+    /// Whether a route satisfies every constraint
+    pub fn accepts(&self, route: &RouteOption) -> bool {
+        (self.assets.is_empty() || self.assets.contains(&route.asset))
+            && (self.chains.is_empty() || self.chains.contains(&route.chain_id))
+            && self.method.is_none_or(|method| method == route.method)
+            && self.max_fee_bps.is_none_or(|max| route.fee_estimate_bps <= max)
+            && self.now.as_deref().is_none_or(|now| !route.is_expired(now))
+    }
+}
+
+/// Pick the best-ranked route the buyer accepts
+pub fn select_route<'a>(
+    routes: &'a [RouteOption],
+    preferences: &RoutePreferences,
+) -> Option<&'a RouteOption> {
+    routes
+        .iter()
+        .filter(|route| preferences.accepts(route))
+        .min_by_key(|route| route.rank)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+
+    fn candidates() -> Vec<RouteOption> {
+        vec![
+            RouteOption::direct("pls-usdc", "USDC", 1_000, 369).with_fee_estimate_bps(5),
+            RouteOption::escrow("base-usdc", "USDC", 1_000, 8453, CONTRACT)
+                .with_fee_estimate_bps(30),
+            RouteOption::escrow("eth-usdt", "USDT", 1_000, 1, CONTRACT).with_fee_estimate_bps(20),
+            RouteOption::direct("eth-usdc-pricey", "USDC", 1_000, 1).with_fee_estimate_bps(80),
+        ]
+    }
+
+    fn ids(routes: &[RouteOption]) -> Vec<&str> {
+        routes.iter().map(|r| r.route_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_rank_by_fee_within_envelope() {
+        let policy = RankingPolicy::new(ZkProfile::Optional);
+        let ranked = rank_routes(candidates(), &EconomicEnvelope::new(50), &policy);
+
+        assert_eq!(ids(&ranked), vec!["pls-usdc", "eth-usdt", "base-usdc"]);
+        assert_eq!(ranked.iter().map(|r| r.rank).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rank_respects_zk_profile() {
+        let envelope = EconomicEnvelope::new(100);
+
+        let required =
+            rank_routes(candidates(), &envelope, &RankingPolicy::new(ZkProfile::Required));
+        assert!(required.iter().all(|r| r.method.is_escrow()));
+
+        let none = rank_routes(candidates(), &envelope, &RankingPolicy::new(ZkProfile::None));
+        assert!(none.iter().all(|r| !r.method.is_escrow()));
+    }
+
+    #[test]
+    fn test_rank_by_policy_preferences() {
+        let policy = RankingPolicy::new(ZkProfile::Optional)
+            .prefer_escrow(true)
+            .with_preferred_assets(vec!["USDC".to_string()])
+            .with_preferred_chains(vec![8453]);
+        let ranked = rank_routes(candidates(), &EconomicEnvelope::new(100), &policy);
+
+        assert_eq!(ids(&ranked), vec!["base-usdc", "eth-usdt", "pls-usdc", "eth-usdc-pricey"]);
+    }
+
+    #[test]
+    fn test_escrow_preference_only_for_optional_buyers() {
+        let routes = candidates();
+        let (direct, escrow) = (&routes[0], &routes[2]);
+
+        let optional = RankingPolicy::new(ZkProfile::Optional).prefer_escrow(true);
+        assert_eq!(optional.compare(escrow, direct), Ordering::Less);
+
+        // Without an OPTIONAL profile the fee decides
+        let required = RankingPolicy::new(ZkProfile::Required).prefer_escrow(true);
+        assert_eq!(required.compare(escrow, direct), Ordering::Greater);
+        let none = RankingPolicy::new(ZkProfile::None).prefer_escrow(false);
+        assert_eq!(none.compare(direct, escrow), Ordering::Less);
+        assert_eq!(none.compare(escrow, direct), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_select_route() {
+        let policy = RankingPolicy::new(ZkProfile::Optional);
+        let ranked = rank_routes(candidates(), &EconomicEnvelope::new(100), &policy);
+
+        assert_eq!(select_route(&ranked, &RoutePreferences::new()).unwrap().route_id, "pls-usdc");
+
+        let prefs = RoutePreferences::new()
+            .with_method(SettlementMethod::Escrow)
+            .with_chains(vec![8453]);
+        assert_eq!(select_route(&ranked, &prefs).unwrap().route_id, "base-usdc");
+
+        let prefs = RoutePreferences::new().with_assets(vec!["DAI".to_string()]);
+        assert!(select_route(&ranked, &prefs).is_none());
+    }
+
+    #[test]
+    fn test_select_skips_expired() {
+        let routes = rank_routes(
+            vec![
+                RouteOption::direct("a", "USDC", 1_000, 369).with_expiry("2025-01-01T00:00:00Z"),
+                RouteOption::direct("b", "USDC", 1_000, 369).with_fee_estimate_bps(10),
+            ],
+            &EconomicEnvelope::new(50),
+            &RankingPolicy::new(ZkProfile::Optional),
+        );
+
+        let prefs = RoutePreferences::new().at("2025-06-01T00:00:00Z");
+        assert_eq!(select_route(&routes, &prefs).unwrap().route_id, "b");
+    }
+
+    #[test]
+    fn test_validate_routes() {
+        let policy = RankingPolicy::new(ZkProfile::Optional);
+        let mut routes = rank_routes(candidates(), &EconomicEnvelope::new(100), &policy);
+        let mut report = ValidationReport::new();
+        validate_routes_into(&routes, 100, &mut report, "/routes");
+        assert!(report.is_empty());
+
+        routes[1].route_id = routes[0].route_id.clone();
+        routes[2].coreprover_contract = None;
+        routes.swap(2, 3);
+        let mut report = ValidationReport::new();
+        validate_routes_into(&routes, 50, &mut report, "/routes");
+
+        let errors: Vec<&str> = report.errors().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "/routes/1/route_id",
+                "/routes/2/rank",
+                "/routes/2/fee_estimate_bps",
+                "/routes/3/coreprover_contract",
+                "/routes/3/rank",
+            ]
+        );
+    }
+}
@@ -0,0 +1,467 @@
+//# TGP Settlement Decision
+
+//**Destination Path:** `crates/tbc-core/src/tgp/settlement.rs`
+
+//**Implementation:** M2 - TGP-01 Economic Envelope & Proof-of-Settlement
+
+//! Escrow vs. direct x402 settlement decision
+//!
+//! Decides whether an OFFER settles through CoreProver escrow or by direct
+//! x402 payment, from the 402 metadata and [`ZkProfile`] of the QUERY.
+//!
+//! # Rules (TGP-00 §3.5)
+//!
+//! | `zk_profile` | Settlement |
+//! |--------------|------------|
+//! | `REQUIRED` | Escrow; `ESCROW_UNAVAILABLE` if the 402 response advertised no contract |
+//! | `NONE` | Direct x402 |
+//! | `OPTIONAL` | Escrow preference of the [`RankingPolicy`]; escrow if it has none |
+//!
+//! Escrow counts as advertised only when `escrow_from_402` is set and
+//! `escrow_contract_from_402` carries the contract. An `OPTIONAL` QUERY
+//! without it always settles directly.
+//!
+//! The [`EscrowPolicy`] thresholds (amount, seller reputation) turn on the
+//! escrow preference of the controller's [`RankingPolicy`], the same
+//! preference that orders the routes of a multi-route OFFER. Like the
+//! ranking, the preference only matters for `OPTIONAL` buyers.
+//!
+//! The resulting [`SettlementDecision`] fills `coreprover_contract` and
+//! `zk_required` of the OFFER and records why it was taken. `zk_required`
+//! is only set for `REQUIRED` buyers.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::messages::{OfferMessage, QueryMessage};
+//! use tbc_core::tgp::routes::RankingPolicy;
+//! use tbc_core::tgp::settlement::{DecisionReason, EscrowPolicy};
+//! use tbc_core::tgp::types::{EconomicEnvelope, SellerReputation, ZkProfile};
+//!
+//! const CONTRACT: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+//!
+//! let query = QueryMessage::with_escrow_from_402(
+//!     "q-1", "buyer://alice", "seller://bob", "USDC", 5_000_000, CONTRACT,
+//!     ZkProfile::Optional,
+//! );
+//!
+//! // Direct by default, escrow above 1 USDC or for sellers late over 10% of the time
+//! let ranking = RankingPolicy::default().prefer_escrow(false);
+//! let policy = EscrowPolicy::new().with_escrow_above(1_000_000).with_min_on_time_bps(9_000);
+//!
+//! let reputation = SellerReputation::new(100, 1);
+//! let decision = policy.decide(&query, Some(&reputation), &ranking).unwrap();
+//! assert!(decision.is_escrow());
+//! assert_eq!(
+//!     decision.reason,
+//!     DecisionReason::AmountAboveThreshold { amount: 5_000_000, threshold: 1_000_000 }
+//! );
+//!
+//! let offer = decision.apply(OfferMessage::new(
+//!     "offer-1", "q-1", "USDC", 5_000_000, false, EconomicEnvelope::new(50),
+//! ));
+//! assert_eq!(offer.coreprover_contract.as_deref(), Some(CONTRACT));
+//! assert!(!offer.zk_required);
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::errors::TgpErrorCode;
+use super::messages::{OfferMessage, QueryMessage};
+use super::routes::{RankingPolicy, SettlementMethod};
+use super::types::{SellerReputation, ZkProfile};
+
+// ============================================================================
+// EscrowPolicy
+// ============================================================================
+
+/// Controller thresholds for `OPTIONAL` QUERYs
+///
+/// Escrow is preferred when the amount exceeds `escrow_above` or the
+/// seller's on-time rate is below `min_on_time_bps` (sellers without history
+/// count as below). Otherwise the [`RankingPolicy`] escrow preference
+/// decides.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscrowPolicy {
+    /// Amount (smallest unit) above which escrow is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub escrow_above: Option<u64>,
+
+    /// Seller on-time rate in basis points below which escrow is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_on_time_bps: Option<u32>,
+}
+
+impl EscrowPolicy {
+    /// Policy without thresholds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to use escrow for amounts above `amount`
+    pub fn with_escrow_above(mut self, amount: u64) -> Self {
+        self.escrow_above = Some(amount);
+        self
+    }
+
+    /// Builder method to use escrow for sellers below `bps` on-time
+    pub fn with_min_on_time_bps(mut self, bps: u32) -> Self {
+        self.min_on_time_bps = Some(bps);
+        self
+    }
+
+    /// Decide how `query` settles
+    ///
+    /// `reputation` is the seller's track record, if known. `ranking` is the
+    /// controller's route ranking; its escrow preference decides when no
+    /// threshold applies.
+    pub fn decide(
+        &self,
+        query: &QueryMessage,
+        reputation: Option<&SellerReputation>,
+        ranking: &RankingPolicy,
+    ) -> Result<SettlementDecision, SettlementError> {
+        let (ranking, reason) = self.ranking(query, reputation, ranking);
+        SettlementDecision::from_ranking(query, &ranking, reason)
+    }
+
+    /// Ranking policy for the routes of `query`
+    ///
+    /// `base` with the buyer's profile, and the escrow preference turned on
+    /// when a threshold applies.
+    pub fn ranking(
+        &self,
+        query: &QueryMessage,
+        reputation: Option<&SellerReputation>,
+        base: &RankingPolicy,
+    ) -> (RankingPolicy, DecisionReason) {
+        let ranking = RankingPolicy {
+            zk_profile: query.zk_profile,
+            ..base.clone()
+        };
+        match self.threshold(query.amount, reputation) {
+            Some(reason) => (ranking.prefer_escrow(true), reason),
+            None => (ranking, DecisionReason::PolicyDefault),
+        }
+    }
+
+    fn threshold(
+        &self,
+        amount: u64,
+        reputation: Option<&SellerReputation>,
+    ) -> Option<DecisionReason> {
+        if let Some(threshold) = self.escrow_above.filter(|threshold| amount > *threshold) {
+            return Some(DecisionReason::AmountAboveThreshold { amount, threshold });
+        }
+
+        let min_on_time_bps = self.min_on_time_bps?;
+        match reputation.filter(|r| r.settled > 0) {
+            None => Some(DecisionReason::UnprovenSeller),
+            Some(r) if r.on_time_bps < min_on_time_bps => {
+                Some(DecisionReason::SellerBelowThreshold {
+                    on_time_bps: r.on_time_bps,
+                    min_on_time_bps,
+                })
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+// ============================================================================
+// SettlementDecision
+// ============================================================================
+
+/// Why a settlement method was chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum DecisionReason {
+    /// Buyer sent `zk_profile: REQUIRED`
+    ProfileRequired,
+
+    /// Buyer sent `zk_profile: NONE`
+    ProfileNone,
+
+    /// The 402 response advertised no CoreProver contract
+    EscrowNotAdvertised,
+
+    /// Amount exceeds [`EscrowPolicy::escrow_above`]
+    AmountAboveThreshold { amount: u64, threshold: u64 },
+
+    /// Seller on-time rate is below [`EscrowPolicy::min_on_time_bps`]
+    SellerBelowThreshold { on_time_bps: u32, min_on_time_bps: u32 },
+
+    /// Seller has no settlement history
+    UnprovenSeller,
+
+    /// An earlier routing stage required escrow
+    PolicyRequired,
+
+    /// No threshold applied, the [`RankingPolicy`] escrow preference decided
+    PolicyDefault,
+}
+
+impl fmt::Display for DecisionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecisionReason::ProfileRequired => write!(f, "buyer requires escrow"),
+            DecisionReason::ProfileNone => write!(f, "buyer declined escrow"),
+            DecisionReason::EscrowNotAdvertised => write!(f, "seller advertised no escrow"),
+            DecisionReason::AmountAboveThreshold { amount, threshold } => {
+                write!(f, "amount {} above {}", amount, threshold)
+            }
+            DecisionReason::SellerBelowThreshold { on_time_bps, min_on_time_bps } => write!(
+                f,
+                "seller on-time {} bps below {} bps",
+                on_time_bps, min_on_time_bps
+            ),
+            DecisionReason::UnprovenSeller => write!(f, "seller has no settlement history"),
+            DecisionReason::PolicyRequired => write!(f, "policy requires escrow"),
+            DecisionReason::PolicyDefault => write!(f, "policy default"),
+        }
+    }
+}
+
+/// Settlement method chosen for an OFFER, with its explanation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementDecision {
+    pub method: SettlementMethod,
+
+    /// Escrow contract (escrow only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coreprover_contract: Option<String>,
+
+    /// Value for `OfferMessage::zk_required`: the buyer demanded escrow
+    pub zk_required: bool,
+
+    #[serde(flatten)]
+    pub reason: DecisionReason,
+}
+
+impl SettlementDecision {
+    /// Decide how `query` settles under `ranking`
+    ///
+    /// The buyer's profile fixes the method for `REQUIRED` and `NONE`. An
+    /// `OPTIONAL` buyer settles through escrow when the seller advertised
+    /// it, unless `ranking` prefers direct x402; `reason` explains that
+    /// choice.
+    pub fn from_ranking(
+        query: &QueryMessage,
+        ranking: &RankingPolicy,
+        reason: DecisionReason,
+    ) -> Result<Self, SettlementError> {
+        let contract = query
+            .escrow_contract_from_402
+            .as_ref()
+            .filter(|_| query.escrow_from_402);
+
+        let (escrow, reason) = match (query.zk_profile, contract) {
+            (ZkProfile::Required, None) => return Err(SettlementError::EscrowUnavailable),
+            (ZkProfile::Required, Some(_)) => (true, DecisionReason::ProfileRequired),
+            (ZkProfile::None, _) => (false, DecisionReason::ProfileNone),
+            (ZkProfile::Optional, None) => (false, DecisionReason::EscrowNotAdvertised),
+            (ZkProfile::Optional, Some(_)) => (ranking.escrow_preference != Some(false), reason),
+        };
+
+        Ok(SettlementDecision {
+            method: if escrow {
+                SettlementMethod::Escrow
+            } else {
+                SettlementMethod::DirectX402
+            },
+            coreprover_contract: contract.filter(|_| escrow).cloned(),
+            zk_required: query.zk_profile.requires_escrow(),
+            reason,
+        })
+    }
+
+    pub fn is_escrow(&self) -> bool {
+        self.method.is_escrow()
+    }
+
+    /// Write the decision into the OFFER's settlement fields
+    pub fn apply(&self, mut offer: OfferMessage) -> OfferMessage {
+        offer.coreprover_contract = self.coreprover_contract.clone();
+        offer.zk_required = self.zk_required;
+        offer
+    }
+}
+
+impl fmt::Display for SettlementDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let method = if self.is_escrow() { "escrow" } else { "direct x402" };
+        write!(f, "{} ({})", method, self.reason)
+    }
+}
+
+/// Why no settlement method can satisfy the QUERY
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SettlementError {
+    #[error("escrow required but no CoreProver contract is available")]
+    EscrowUnavailable,
+}
+
+impl SettlementError {
+    /// TGP error code to answer the QUERY with
+    pub fn error_code(&self) -> TgpErrorCode {
+        match self {
+            SettlementError::EscrowUnavailable => TgpErrorCode::EscrowUnavailable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tgp::types::EconomicEnvelope;
+
+    const CONTRACT: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+
+    fn query(zk_profile: ZkProfile, amount: u64) -> QueryMessage {
+        QueryMessage::with_escrow_from_402(
+            "q-1",
+            "buyer://alice",
+            "seller://bob",
+            "USDC",
+            amount,
+            CONTRACT,
+            zk_profile,
+        )
+    }
+
+    fn decide(
+        policy: &EscrowPolicy,
+        query: &QueryMessage,
+        reputation: Option<&SellerReputation>,
+    ) -> Result<SettlementDecision, SettlementError> {
+        policy.decide(query, reputation, &RankingPolicy::default())
+    }
+
+    #[test]
+    fn test_profile_rules() {
+        let policy = EscrowPolicy::new();
+
+        let decision = decide(&policy, &query(ZkProfile::Required, 1), None).unwrap();
+        assert_eq!(decision.method, SettlementMethod::Escrow);
+        assert_eq!(decision.coreprover_contract.as_deref(), Some(CONTRACT));
+        assert!(decision.zk_required);
+        assert_eq!(decision.reason, DecisionReason::ProfileRequired);
+
+        let decision = decide(&policy, &query(ZkProfile::None, 1), None).unwrap();
+        assert_eq!(decision.method, SettlementMethod::DirectX402);
+        assert!(decision.coreprover_contract.is_none());
+        assert!(!decision.zk_required);
+        assert_eq!(decision.reason, DecisionReason::ProfileNone);
+
+        let decision = decide(&policy, &query(ZkProfile::Optional, 1), None).unwrap();
+        assert!(decision.is_escrow());
+        assert!(!decision.zk_required);
+        assert_eq!(decision.reason, DecisionReason::PolicyDefault);
+    }
+
+    #[test]
+    fn test_ranking_preference_only_for_optional_buyers() {
+        let policy = EscrowPolicy::new().with_escrow_above(0);
+        let direct = RankingPolicy::default().prefer_escrow(false);
+
+        let decision = policy.decide(&query(ZkProfile::Optional, 1), None, &direct).unwrap();
+        assert!(decision.is_escrow());
+        assert!(!decision.zk_required);
+        let (ranking, _) = policy.ranking(&query(ZkProfile::Optional, 1), None, &direct);
+        assert_eq!(ranking.zk_profile, ZkProfile::Optional);
+        assert_eq!(ranking.escrow_preference, Some(true));
+
+        let decision = EscrowPolicy::new()
+            .decide(&query(ZkProfile::Optional, 1), None, &direct)
+            .unwrap();
+        assert!(!decision.is_escrow());
+
+        // Forcing escrow never overrides the buyer's profile
+        let escrow = RankingPolicy::default().prefer_escrow(true);
+        let forced = |zk_profile| {
+            let reason = DecisionReason::PolicyRequired;
+            SettlementDecision::from_ranking(&query(zk_profile, 1), &escrow, reason).unwrap()
+        };
+        let decision = forced(ZkProfile::Optional);
+        assert!(decision.is_escrow());
+        assert!(!decision.zk_required);
+        assert_eq!(decision.to_string(), "escrow (policy requires escrow)");
+        assert!(!forced(ZkProfile::None).is_escrow());
+    }
+
+    #[test]
+    fn test_escrow_not_advertised() {
+        let policy = EscrowPolicy::new();
+        let mut unflagged = query(ZkProfile::Required, 1);
+        unflagged.escrow_from_402 = false;
+        assert_eq!(decide(&policy, &unflagged, None), Err(SettlementError::EscrowUnavailable));
+        assert_eq!(
+            SettlementError::EscrowUnavailable.error_code(),
+            TgpErrorCode::EscrowUnavailable
+        );
+
+        let mut direct = query(ZkProfile::Optional, 1);
+        direct.escrow_contract_from_402 = None;
+        let decision = decide(&policy, &direct, None).unwrap();
+        assert!(!decision.is_escrow());
+        assert_eq!(decision.reason, DecisionReason::EscrowNotAdvertised);
+    }
+
+    #[test]
+    fn test_optional_thresholds() {
+        let policy = EscrowPolicy::new().with_escrow_above(1_000).with_min_on_time_bps(9_000);
+        let ranking = RankingPolicy::default().prefer_escrow(false);
+        let reliable = SellerReputation::new(100, 5);
+        let decide = |amount, reputation: Option<&SellerReputation>| {
+            policy.decide(&query(ZkProfile::Optional, amount), reputation, &ranking)
+        };
+
+        let decision = decide(1_000, Some(&reliable)).unwrap();
+        assert!(!decision.is_escrow());
+        assert_eq!(decision.reason, DecisionReason::PolicyDefault);
+
+        assert_eq!(
+            decide(1_001, Some(&reliable)).unwrap().reason,
+            DecisionReason::AmountAboveThreshold { amount: 1_001, threshold: 1_000 }
+        );
+        assert_eq!(
+            decide(10, Some(&SellerReputation::new(10, 2))).unwrap().reason,
+            DecisionReason::SellerBelowThreshold { on_time_bps: 8_000, min_on_time_bps: 9_000 }
+        );
+        assert_eq!(decide(10, None).unwrap().reason, DecisionReason::UnprovenSeller);
+        assert_eq!(
+            decide(10, Some(&SellerReputation::new(0, 0))).unwrap().reason,
+            DecisionReason::UnprovenSeller
+        );
+    }
+
+    #[test]
+    fn test_apply_and_explain() {
+        let offer = OfferMessage::new("offer-1", "q-1", "USDC", 1, true, EconomicEnvelope::new(50))
+            .with_coreprover(CONTRACT);
+
+        let decision = decide(&EscrowPolicy::new(), &query(ZkProfile::None, 1), None).unwrap();
+        let offer = decision.apply(offer);
+        assert!(offer.coreprover_contract.is_none());
+        assert!(!offer.zk_required);
+        assert!(offer.validation_report().is_empty());
+        assert_eq!(decision.to_string(), "direct x402 (buyer declined escrow)");
+
+        let policy = EscrowPolicy::new().with_escrow_above(0);
+        let decision = decide(&policy, &query(ZkProfile::Optional, 5), None).unwrap();
+        assert_eq!(decision.to_string(), "escrow (amount 5 above 0)");
+
+        let json = serde_json::to_value(SettlementDecision {
+            method: SettlementMethod::Escrow,
+            coreprover_contract: None,
+            zk_required: false,
+            reason: DecisionReason::AmountAboveThreshold { amount: 5, threshold: 1 },
+        })
+        .unwrap();
+        assert_eq!(json["reason"], "amount_above_threshold");
+        assert_eq!(json["threshold"], 1);
+    }
+}
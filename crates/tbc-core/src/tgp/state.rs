@@ -61,11 +61,19 @@
 //! session.transition(TGPState::Finalizing).unwrap();
 //! session.transition(TGPState::Settled).unwrap();
 //! ```
+//!
+//! # Time
+//!
+//! Every time-dependent method has an `_at` variant taking the current Unix
+//! time, so sessions can be driven by an injected [`Clock`]. The plain
+//! methods read [`SystemClock`].
 
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+use super::clock::{Clock, SystemClock};
+use super::timeout::TimeoutPolicy;
+
 // ============================================================================
 // Error Types
 // ============================================================================
@@ -139,12 +147,13 @@ pub enum TGPStateError {
 /// let state = TGPState::Settled;
 /// assert!(state.is_terminal());
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, Default)]
 pub enum TGPState {
     /// No active session, ready to initiate
     ///
     /// **Entry:** Session created
     /// **Exit:** QUERY message sent
+    #[default]
     Idle,
 
     /// QUERY message sent, waiting for OFFER
@@ -263,7 +272,8 @@ impl TGPState {
 
     /// Get the typical timeout for this state (in seconds)
     ///
-    /// Returns the recommended timeout duration per TGP-00 §4.
+    /// Returns the recommended timeout duration per TGP-00 §4. Deployments
+    /// override these defaults with a [`TimeoutPolicy`].
     ///
     /// # Examples
     ///
@@ -299,10 +309,60 @@ impl TGPState {
     }
 }
 
-impl Default for TGPState {
-    fn default() -> Self {
-        TGPState::Idle
-    }
+// ============================================================================
+// Transition Log
+// ============================================================================
+
+/// Party responsible for a state transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TransitionActor {
+    Buyer,
+    Seller,
+    Controller,
+
+    /// Timeouts, forced errors and callers that do not say
+    #[default]
+    System,
+}
+
+/// One entry of a session's transition log
+///
+/// # Examples
+///
+/// ```rust
+/// use tbc_core::tgp::state::{TGPSession, TGPState, TransitionActor};
+///
+/// let mut session = TGPSession::new_at("sess-123", 1_000);
+/// session
+///     .transition_by_at(TGPState::QuerySent, TransitionActor::Buyer, Some("q-1"), 1_001)
+///     .unwrap();
+/// let controller = TransitionActor::Controller;
+/// session
+///     .transition_by_at(TGPState::OfferReceived, controller, Some("offer-1"), 1_002)
+///     .unwrap();
+///
+/// // "Did the buyer ever accept that offer?"
+/// assert!(session.transition_into(TGPState::AcceptSent).is_none());
+///
+/// let offer = session.transition_into(TGPState::OfferReceived).unwrap();
+/// assert_eq!(offer.cause.as_deref(), Some("offer-1"));
+/// assert_eq!(offer.at, 1_002);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TransitionRecord {
+    pub from: TGPState,
+    pub to: TGPState,
+
+    /// Unix timestamp of the transition (seconds since epoch)
+    pub at: u64,
+
+    /// ID of the message that caused the transition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+
+    #[serde(default)]
+    pub actor: TransitionActor,
 }
 
 // ============================================================================
@@ -329,6 +389,7 @@ impl Default for TGPState {
 /// | `created_at` | Unix timestamp of session creation |
 /// | `updated_at` | Unix timestamp of last state change |
 /// | `timeout_at` | Unix timestamp when session expires |
+/// | `history` | Ordered transition log, see [`history`](Self::history) |
 ///
 /// # Examples
 ///
@@ -392,6 +453,31 @@ pub struct TGPSession {
     ///
     /// **None:** For states without timeouts (Idle, terminal states)
     pub timeout_at: Option<u64>,
+
+    /// Settlement chain, once known
+    ///
+    /// **Spec:** Used by [`TimeoutPolicy`] for per-chain timeouts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+
+    /// Settlement asset, once known
+    ///
+    /// **Spec:** Used by [`TimeoutPolicy`] for per-asset timeouts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+
+    /// Id of the policy rule that decided the session, if any
+    ///
+    /// **Spec:** Recorded by the Layer-10 policy stage for audit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_rule: Option<String>,
+
+    /// Every transition the session went through, oldest first
+    ///
+    /// **Present:** Serialized with the session so the audit trail survives
+    /// persistence; append-only through the transition methods
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    history: Vec<TransitionRecord>,
 }
 
 impl TGPSession {
@@ -409,7 +495,20 @@ impl TGPSession {
     /// assert_eq!(session.session_id, "sess-abc123");
     /// ```
     pub fn new(session_id: impl Into<String>) -> Self {
-        let now = current_timestamp();
+        Self::new_at(session_id, current_timestamp())
+    }
+
+    /// Create a new session in Idle state, created at `now` (Unix seconds)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use tbc_core::tgp::state::TGPSession;
+    /// let session = TGPSession::new_at("sess-abc123", 1_731_600_000);
+    /// assert_eq!(session.created_at, 1_731_600_000);
+    /// assert_eq!(session.age_at(1_731_600_042), 42);
+    /// ```
+    pub fn new_at(session_id: impl Into<String>, now: u64) -> Self {
         Self {
             session_id: session_id.into(),
             state: TGPState::Idle,
@@ -418,6 +517,10 @@ impl TGPSession {
             created_at: now,
             updated_at: now,
             timeout_at: None,
+            chain_id: None,
+            asset: None,
+            policy_rule: None,
+            history: Vec::new(),
         }
     }
 
@@ -450,8 +553,37 @@ impl TGPSession {
     /// assert!(session.transition(TGPState::Settled).is_err());
     /// ```
     pub fn transition(&mut self, new_state: TGPState) -> Result<(), TGPStateError> {
+        self.transition_at(new_state, current_timestamp())
+    }
+
+    /// Transition to a new state at `now` (Unix seconds)
+    ///
+    /// Same rules as [`transition`](Self::transition); the timeout check and
+    /// the new deadline use `now` instead of the system clock.
+    pub fn transition_at(&mut self, new_state: TGPState, now: u64) -> Result<(), TGPStateError> {
+        self.transition_by_at(new_state, TransitionActor::System, None, now)
+    }
+
+    /// Transition to a new state, recording who caused it and with which message
+    pub fn transition_by(
+        &mut self,
+        new_state: TGPState,
+        actor: TransitionActor,
+        cause: Option<&str>,
+    ) -> Result<(), TGPStateError> {
+        self.transition_by_at(new_state, actor, cause, current_timestamp())
+    }
+
+    /// Transition at `now` (Unix seconds), recording actor and cause
+    pub fn transition_by_at(
+        &mut self,
+        new_state: TGPState,
+        actor: TransitionActor,
+        cause: Option<&str>,
+        now: u64,
+    ) -> Result<(), TGPStateError> {
         // Check if session has timed out
-        if self.is_timed_out() {
+        if self.is_timed_out_at(now) {
             let timeout = self.timeout_at.unwrap_or(0);
             return Err(TGPStateError::SessionTimeout(timeout));
         }
@@ -474,7 +606,8 @@ impl TGPSession {
         // Perform transition
         let old_state = self.state;
         self.state = new_state;
-        self.updated_at = current_timestamp();
+        self.updated_at = now;
+        self.record(old_state, actor, cause);
 
         // Set timeout for new state
         if let Some(timeout_seconds) = new_state.timeout_seconds() {
@@ -504,8 +637,13 @@ impl TGPSession {
     /// assert!(!session.is_timed_out());
     /// ```
     pub fn is_timed_out(&self) -> bool {
+        self.is_timed_out_at(current_timestamp())
+    }
+
+    /// Check if the session has timed out at `now` (Unix seconds)
+    pub fn is_timed_out_at(&self, now: u64) -> bool {
         if let Some(timeout) = self.timeout_at {
-            current_timestamp() > timeout
+            now > timeout
         } else {
             false
         }
@@ -526,7 +664,12 @@ impl TGPSession {
     /// assert!(session.timeout_at.is_some());
     /// ```
     pub fn set_timeout(&mut self, seconds: u64) {
-        self.timeout_at = Some(current_timestamp() + seconds);
+        self.set_timeout_from(current_timestamp(), seconds);
+    }
+
+    /// Set the timeout deadline to `seconds` after `now` (Unix seconds)
+    pub fn set_timeout_from(&mut self, now: u64, seconds: u64) {
+        self.timeout_at = Some(now + seconds);
     }
 
     /// Clear the timeout deadline
@@ -551,8 +694,12 @@ impl TGPSession {
     /// }
     /// ```
     pub fn remaining_timeout(&self) -> Option<u64> {
+        self.remaining_timeout_at(current_timestamp())
+    }
+
+    /// Get the remaining time until timeout at `now` (Unix seconds)
+    pub fn remaining_timeout_at(&self, now: u64) -> Option<u64> {
         if let Some(timeout) = self.timeout_at {
-            let now = current_timestamp();
             if now < timeout {
                 Some(timeout - now)
             } else {
@@ -567,7 +714,12 @@ impl TGPSession {
     ///
     /// Returns the number of seconds since the session was created.
     pub fn age(&self) -> u64 {
-        current_timestamp() - self.created_at
+        self.age_at(current_timestamp())
+    }
+
+    /// Get the session age at `now` (Unix seconds)
+    pub fn age_at(&self, now: u64) -> u64 {
+        now.saturating_sub(self.created_at)
     }
 
     /// Check if session is in a terminal state
@@ -590,10 +742,16 @@ impl TGPSession {
     /// assert_eq!(session.state, TGPState::Errored);
     /// ```
     pub fn force_error(&mut self) {
+        self.force_error_at(current_timestamp());
+    }
+
+    /// Force transition to Errored state at `now` (Unix seconds)
+    pub fn force_error_at(&mut self, now: u64) {
         let old_state = self.state;
         self.state = TGPState::Errored;
-        self.updated_at = current_timestamp();
+        self.updated_at = now;
         self.timeout_at = None;
+        self.record(old_state, TransitionActor::System, None);
 
         log::warn!(
             "TGP session {} force-transitioned to Errored from {:?}",
@@ -601,6 +759,55 @@ impl TGPSession {
             old_state
         );
     }
+
+    /// Recompute `timeout_at` for the current state from `policy`
+    ///
+    /// The deadline counts from `updated_at`, the time the current state
+    /// was entered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use tbc_core::tgp::state::{TGPSession, TGPState};
+    /// # use tbc_core::tgp::timeout::TimeoutPolicy;
+    /// let policy = TimeoutPolicy::default().with_chain(1, TGPState::QuerySent, 90);
+    ///
+    /// let mut session = TGPSession::new_at("sess-123", 1_000);
+    /// session.chain_id = Some(1);
+    /// session.transition_at(TGPState::QuerySent, 1_000).unwrap();
+    /// session.apply_timeout_policy(&policy);
+    /// assert_eq!(session.timeout_at, Some(1_090));
+    /// ```
+    pub fn apply_timeout_policy(&mut self, policy: &TimeoutPolicy) {
+        self.timeout_at = policy
+            .timeout_for_session(self)
+            .map(|seconds| self.updated_at + seconds);
+    }
+
+    /// Ordered transition log, oldest first
+    pub fn history(&self) -> &[TransitionRecord] {
+        &self.history
+    }
+
+    /// Most recent transition, if any
+    pub fn last_transition(&self) -> Option<&TransitionRecord> {
+        self.history.last()
+    }
+
+    /// The transition that entered `state`, if the session ever reached it
+    pub fn transition_into(&self, state: TGPState) -> Option<&TransitionRecord> {
+        self.history.iter().find(|record| record.to == state)
+    }
+
+    fn record(&mut self, from: TGPState, actor: TransitionActor, cause: Option<&str>) {
+        self.history.push(TransitionRecord {
+            from,
+            to: self.state,
+            at: self.updated_at,
+            cause: cause.map(str::to_string),
+            actor,
+        });
+    }
 }
 
 // ============================================================================
@@ -611,10 +818,7 @@ impl TGPSession {
 ///
 /// Returns seconds since Unix epoch (January 1, 1970).
 fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("System time before Unix epoch")
-        .as_secs()
+    SystemClock.now_unix()
 }
 
 // ============================================================================
@@ -723,7 +927,7 @@ mod tests {
         // QuerySent should set timeout
         session.transition(TGPState::QuerySent).unwrap();
         assert!(session.timeout_at.is_some());
-        assert_eq!(session.remaining_timeout().is_some(), true);
+        assert!(session.remaining_timeout().is_some());
     }
 
     #[test]
@@ -835,6 +1039,67 @@ mod tests {
         assert!(session.timeout_at.is_none());
     }
 
+    #[test]
+    fn test_injected_time() {
+        let mut session = TGPSession::new_at("sess-test", 1_000);
+        session.transition_at(TGPState::QuerySent, 1_010).unwrap();
+        assert_eq!(session.updated_at, 1_010);
+        assert_eq!(session.timeout_at, Some(1_040));
+        assert_eq!(session.remaining_timeout_at(1_030), Some(10));
+        assert!(!session.is_timed_out_at(1_040));
+        assert!(session.is_timed_out_at(1_041));
+
+        let result = session.transition_at(TGPState::OfferReceived, 1_041);
+        assert_eq!(result, Err(TGPStateError::SessionTimeout(1_040)));
+
+        session.force_error_at(1_050);
+        assert_eq!(session.updated_at, 1_050);
+        assert_eq!(session.age_at(1_060), 60);
+        assert_eq!(session.age_at(0), 0);
+    }
+
+    #[test]
+    fn test_transition_history() {
+        let mut session = TGPSession::new_at("sess-test", 1_000);
+        session
+            .transition_by_at(TGPState::QuerySent, TransitionActor::Buyer, Some("q-1"), 1_001)
+            .unwrap();
+        session.transition_at(TGPState::OfferReceived, 1_002).unwrap();
+        // rejected transitions are not logged
+        assert!(session.transition_at(TGPState::Settled, 1_003).is_err());
+        session.force_error_at(1_004);
+
+        let history = session.history();
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history[0],
+            TransitionRecord {
+                from: TGPState::Idle,
+                to: TGPState::QuerySent,
+                at: 1_001,
+                cause: Some("q-1".to_string()),
+                actor: TransitionActor::Buyer,
+            }
+        );
+        assert_eq!(history[1].actor, TransitionActor::System);
+        assert_eq!(history[2].from, TGPState::OfferReceived);
+        assert_eq!(session.last_transition().unwrap().to, TGPState::Errored);
+
+        // the log is persisted with the session
+        let json = serde_json::to_value(&session).unwrap();
+        assert_eq!(json["history"][0]["actor"], "buyer");
+        let decoded: TGPSession = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.history(), session.history());
+    }
+
+    #[test]
+    fn test_session_without_history_decodes() {
+        let json = r#"{"session_id":"sess-1","state":"Idle","query_id":null,"offer_id":null,
+            "created_at":1,"updated_at":1,"timeout_at":null}"#;
+        let session: TGPSession = serde_json::from_str(json).unwrap();
+        assert!(session.history().is_empty());
+    }
+
     #[test]
     fn test_timestamps_updated() {
         let mut session = TGPSession::new("sess-test");
@@ -0,0 +1,718 @@
+//# TGP Session Store
+
+//**Destination Path:** `crates/tbc-core/src/tgp/store.rs`
+
+//**Implementation:** M1 - TGP Message Parsing & Basic Routing
+
+//! Persistent TGP sessions and session resume
+//!
+//! A [`TGPSession`] on its own lives in memory, so a controller restart
+//! would lose every in-flight negotiation. [`TGPSessionManager`] keeps each
+//! session in a [`SessionStore`] and drives its transitions with an
+//! injected [`Clock`].
+//!
+//! # Backends
+//!
+//! - [`InMemorySessionStore`] - process-local, for tests and single-shot tools
+//! - [`FileSessionStore`] - one JSON file per session, survives restarts
+//!
+//! # Resume
+//!
+//! A buyer that reconnects with its session id calls
+//! [`TGPSessionManager::resume`]. Sessions waiting on the buyer
+//! (`OfferReceived`, together with the stored OFFER) or on settlement
+//! (`Finalizing`) can be resumed; anything else cannot. Only the party the
+//! session was created for ([`TGPSessionManager::create_for`]) may resume
+//! it; knowing the session id is not enough.
+//!
+//! # Timeouts
+//!
+//! Deadlines come from the manager's [`TimeoutPolicy`]. Controllers call
+//! [`TGPSessionManager::sweep_timeouts`] periodically to move expired
+//! sessions to `Errored` and obtain the `TIMEOUT` ERROR messages to send.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use tbc_core::tgp::clock::ManualClock;
+//! use tbc_core::tgp::messages::OfferMessage;
+//! use tbc_core::tgp::state::TGPState;
+//! use tbc_core::tgp::store::{InMemorySessionStore, TGPSessionManager};
+//! use tbc_core::tgp::types::EconomicEnvelope;
+//!
+//! let store = Arc::new(InMemorySessionStore::new());
+//! let clock = Arc::new(ManualClock::new(1_731_600_000));
+//! let manager = TGPSessionManager::new(store.clone(), clock.clone());
+//!
+//! manager.create_for("sess-1", "buyer://alice").unwrap();
+//! manager.transition("sess-1", TGPState::QuerySent).unwrap();
+//! let envelope = EconomicEnvelope::new(50);
+//! let offer = OfferMessage::new("offer-1", "q-1", "USDC", 1_000, false, envelope);
+//! manager.record_offer("sess-1", offer).unwrap();
+//!
+//! // The controller restarts; a new manager over the same store picks up
+//! let restarted = TGPSessionManager::new(store, clock);
+//! let resumed = restarted.resume("sess-1", "buyer://alice").unwrap();
+//! assert_eq!(resumed.session.state, TGPState::OfferReceived);
+//! assert_eq!(resumed.offer.unwrap().id, "offer-1");
+//! assert!(restarted.resume("sess-1", "buyer://mallory").is_err());
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::clock::{Clock, SystemClock};
+use super::messages::{error_codes, ErrorMessage, OfferMessage};
+use super::state::{TGPSession, TGPState, TGPStateError, TransitionActor};
+use super::timeout::TimeoutPolicy;
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Errors from session storage and resume
+#[derive(Debug, Error)]
+pub enum SessionStoreError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Invalid session id: {0:?}")]
+    InvalidSessionId(String),
+
+    #[error("Session not found: {0}")]
+    NotFound(String),
+
+    #[error("Session already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Session {0} cannot be resumed from state {1:?}")]
+    NotResumable(String, TGPState),
+
+    #[error("Session {0} was not opened by {1}")]
+    NotOwner(String, String),
+
+    #[error("Session {0} timed out at {1}")]
+    Expired(String, u64),
+
+    #[error(transparent)]
+    State(#[from] TGPStateError),
+}
+
+// ============================================================================
+// SessionRecord
+// ============================================================================
+
+/// What is persisted per session
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionRecord {
+    pub session: TGPSession,
+
+    /// Party the session was created for; the only one that may resume it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+
+    /// Last OFFER sent for the session, replayed on resume
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offer: Option<OfferMessage>,
+}
+
+impl SessionRecord {
+    pub fn new(session: TGPSession) -> Self {
+        Self {
+            session,
+            owner: None,
+            offer: None,
+        }
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session.session_id
+    }
+}
+
+// ============================================================================
+// SessionStore Trait
+// ============================================================================
+
+/// Storage backend for TGP sessions
+///
+/// Implementations only store and fetch records; transition rules and
+/// timeouts are applied by [`TGPSessionManager`].
+pub trait SessionStore: Send + Sync {
+    /// Fetch a session, `None` if unknown
+    fn load(&self, session_id: &str) -> Result<Option<SessionRecord>, SessionStoreError>;
+
+    /// Insert or replace a session
+    fn save(&self, record: &SessionRecord) -> Result<(), SessionStoreError>;
+
+    /// Delete a session, returning it if it existed
+    fn remove(&self, session_id: &str) -> Result<Option<SessionRecord>, SessionStoreError>;
+
+    /// All stored sessions, in no particular order
+    fn list(&self) -> Result<Vec<SessionRecord>, SessionStoreError>;
+}
+
+/// Process-local session store
+#[derive(Debug, Default)]
+pub struct InMemorySessionStore {
+    sessions: RwLock<HashMap<String, SessionRecord>>,
+}
+
+impl InMemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn load(&self, session_id: &str) -> Result<Option<SessionRecord>, SessionStoreError> {
+        Ok(self.sessions.read().unwrap().get(session_id).cloned())
+    }
+
+    fn save(&self, record: &SessionRecord) -> Result<(), SessionStoreError> {
+        self.sessions
+            .write()
+            .unwrap()
+            .insert(record.session_id().to_string(), record.clone());
+        Ok(())
+    }
+
+    fn remove(&self, session_id: &str) -> Result<Option<SessionRecord>, SessionStoreError> {
+        Ok(self.sessions.write().unwrap().remove(session_id))
+    }
+
+    fn list(&self) -> Result<Vec<SessionRecord>, SessionStoreError> {
+        Ok(self.sessions.read().unwrap().values().cloned().collect())
+    }
+}
+
+/// Durable session store with one `{session_id}.json` file per session
+///
+/// Writes go to a temporary file that is renamed into place, so a crash
+/// mid-write leaves the previous version intact. Session ids are used as
+/// file names and are therefore restricted to ASCII letters, digits, `-`,
+/// `_` and `.` (not leading).
+#[derive(Debug, Clone)]
+pub struct FileSessionStore {
+    directory: PathBuf,
+}
+
+impl FileSessionStore {
+    /// Open (and create if needed) a store rooted at `directory`
+    pub fn open(directory: impl Into<PathBuf>) -> Result<Self, SessionStoreError> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        Ok(Self { directory })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn path_for(&self, session_id: &str) -> Result<PathBuf, SessionStoreError> {
+        let valid = !session_id.is_empty()
+            && !session_id.starts_with('.')
+            && session_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(SessionStoreError::InvalidSessionId(session_id.to_string()));
+        }
+        Ok(self.directory.join(format!("{}.json", session_id)))
+    }
+
+    fn read(path: &Path) -> Result<Option<SessionRecord>, SessionStoreError> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(&self, session_id: &str) -> Result<Option<SessionRecord>, SessionStoreError> {
+        Self::read(&self.path_for(session_id)?)
+    }
+
+    fn save(&self, record: &SessionRecord) -> Result<(), SessionStoreError> {
+        let path = self.path_for(record.session_id())?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(record)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn remove(&self, session_id: &str) -> Result<Option<SessionRecord>, SessionStoreError> {
+        let path = self.path_for(session_id)?;
+        let record = Self::read(&path)?;
+        if record.is_some() {
+            fs::remove_file(&path)?;
+        }
+        Ok(record)
+    }
+
+    fn list(&self) -> Result<Vec<SessionRecord>, SessionStoreError> {
+        let mut records = Vec::new();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                records.extend(Self::read(&path)?);
+            }
+        }
+        Ok(records)
+    }
+}
+
+// ============================================================================
+// TGPSessionManager
+// ============================================================================
+
+/// Drives stored sessions through the TGP state machine
+///
+/// Every mutation is load → transition at `clock.now_unix()` → apply the
+/// timeout policy → save, and mutations are serialized so concurrent
+/// callers cannot lose updates.
+pub struct TGPSessionManager {
+    store: Arc<dyn SessionStore>,
+    clock: Arc<dyn Clock>,
+    timeout_policy: TimeoutPolicy,
+    write_lock: Mutex<()>,
+}
+
+impl TGPSessionManager {
+    pub fn new(store: Arc<dyn SessionStore>, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            store,
+            clock,
+            timeout_policy: TimeoutPolicy::default(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Builder method to set the timeout policy
+    pub fn with_timeout_policy(mut self, timeout_policy: TimeoutPolicy) -> Self {
+        self.timeout_policy = timeout_policy;
+        self
+    }
+
+    /// Create a manager reading the system clock
+    pub fn with_system_clock(store: Arc<dyn SessionStore>) -> Self {
+        Self::new(store, Arc::new(SystemClock))
+    }
+
+    pub fn store(&self) -> &Arc<dyn SessionStore> {
+        &self.store
+    }
+
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    pub fn timeout_policy(&self) -> &TimeoutPolicy {
+        &self.timeout_policy
+    }
+
+    /// Start a new session in Idle
+    ///
+    /// The session has no owner and cannot be resumed; see
+    /// [`TGPSessionManager::create_for`].
+    pub fn create(&self, session_id: &str) -> Result<TGPSession, SessionStoreError> {
+        self.create_record(session_id, None)
+    }
+
+    /// Start a new session in Idle that `owner` may resume
+    pub fn create_for(
+        &self,
+        session_id: &str,
+        owner: &str,
+    ) -> Result<TGPSession, SessionStoreError> {
+        self.create_record(session_id, Some(owner))
+    }
+
+    fn create_record(
+        &self,
+        session_id: &str,
+        owner: Option<&str>,
+    ) -> Result<TGPSession, SessionStoreError> {
+        let _guard = self.write_lock.lock().unwrap();
+        if self.store.load(session_id)?.is_some() {
+            return Err(SessionStoreError::AlreadyExists(session_id.to_string()));
+        }
+        let mut session = TGPSession::new_at(session_id, self.clock.now_unix());
+        session.apply_timeout_policy(&self.timeout_policy);
+        let mut record = SessionRecord::new(session.clone());
+        record.owner = owner.map(str::to_string);
+        self.store.save(&record)?;
+        Ok(session)
+    }
+
+    /// Fetch a stored session
+    pub fn get(&self, session_id: &str) -> Result<SessionRecord, SessionStoreError> {
+        self.store
+            .load(session_id)?
+            .ok_or_else(|| SessionStoreError::NotFound(session_id.to_string()))
+    }
+
+    /// Apply a state transition and persist it
+    pub fn transition(
+        &self,
+        session_id: &str,
+        state: TGPState,
+    ) -> Result<TGPSession, SessionStoreError> {
+        self.transition_by(session_id, state, TransitionActor::System, None)
+    }
+
+    /// Apply a state transition caused by `actor` and message `cause`
+    pub fn transition_by(
+        &self,
+        session_id: &str,
+        state: TGPState,
+        actor: TransitionActor,
+        cause: Option<&str>,
+    ) -> Result<TGPSession, SessionStoreError> {
+        self.update(session_id, |record, now| {
+            record.session.transition_by_at(state, actor, cause, now)
+        })
+    }
+
+    /// Record the OFFER for a session and move it to `OfferReceived`
+    ///
+    /// The session takes its asset, and the chain of the recommended route
+    /// if the OFFER carries routes, as context for the timeout policy.
+    pub fn record_offer(
+        &self,
+        session_id: &str,
+        offer: OfferMessage,
+    ) -> Result<TGPSession, SessionStoreError> {
+        self.update(session_id, |record, now| {
+            record.session.transition_by_at(
+                TGPState::OfferReceived,
+                TransitionActor::Controller,
+                Some(&offer.id),
+                now,
+            )?;
+            record.session.query_id.get_or_insert_with(|| offer.query_id.clone());
+            record.session.offer_id = Some(offer.id.clone());
+            record.session.asset = Some(offer.asset.clone());
+            if let Some(route) = offer.best_route() {
+                record.session.chain_id = Some(route.chain_id);
+            }
+            record.offer = Some(offer);
+            Ok(())
+        })
+    }
+
+    /// Record the policy rule that decided the session
+    pub fn record_policy_rule(
+        &self,
+        session_id: &str,
+        rule_id: &str,
+    ) -> Result<TGPSession, SessionStoreError> {
+        self.update(session_id, |record, _| {
+            record.session.policy_rule = Some(rule_id.to_string());
+            Ok(())
+        })
+    }
+
+    /// Resume a session after `party` reconnects
+    ///
+    /// # Errors
+    ///
+    /// - [`SessionStoreError::NotFound`] for unknown ids
+    /// - [`SessionStoreError::NotOwner`] unless the session was created for
+    ///   `party`; the session is left untouched
+    /// - [`SessionStoreError::Expired`] if the session passed its deadline;
+    ///   the stored session is moved to `Errored`
+    /// - [`SessionStoreError::NotResumable`] unless the session is in
+    ///   `OfferReceived` or `Finalizing`
+    pub fn resume(
+        &self,
+        session_id: &str,
+        party: &str,
+    ) -> Result<SessionRecord, SessionStoreError> {
+        let _guard = self.write_lock.lock().unwrap();
+        let mut record = self.get(session_id)?;
+        if record.owner.as_deref() != Some(party) {
+            return Err(SessionStoreError::NotOwner(session_id.to_string(), party.to_string()));
+        }
+        let now = self.clock.now_unix();
+
+        if record.session.is_timed_out_at(now) {
+            let deadline = record.session.timeout_at.unwrap_or(0);
+            record.session.force_error_at(now);
+            self.store.save(&record)?;
+            return Err(SessionStoreError::Expired(session_id.to_string(), deadline));
+        }
+
+        match record.session.state {
+            TGPState::OfferReceived | TGPState::Finalizing => Ok(record),
+            state => Err(SessionStoreError::NotResumable(session_id.to_string(), state)),
+        }
+    }
+
+    /// Move every timed-out session to `Errored`
+    ///
+    /// Returns one `TIMEOUT` ERROR per expired session, correlated to the
+    /// last message of the session (the OFFER, else the QUERY). The ERROR
+    /// id is derived from the session id, so a sweep interrupted before the
+    /// messages are sent yields the same ids when the session is found
+    /// again.
+    pub fn sweep_timeouts(&self) -> Result<Vec<ErrorMessage>, SessionStoreError> {
+        let _guard = self.write_lock.lock().unwrap();
+        let now = self.clock.now_unix();
+        let mut errors = Vec::new();
+
+        for mut record in self.store.list()? {
+            let session = &mut record.session;
+            if session.is_terminal() || !session.is_timed_out_at(now) {
+                continue;
+            }
+
+            let state = session.state;
+            let deadline = session.timeout_at.unwrap_or(now);
+            session.force_error_at(now);
+
+            let mut error = ErrorMessage::new(
+                format!("err-timeout-{}", session.session_id),
+                error_codes::TIMEOUT,
+                format!("session {} timed out in {:?} at {}", session.session_id, state, deadline),
+            );
+            error.correlation_id = session.offer_id.clone().or_else(|| session.query_id.clone());
+
+            self.store.save(&record)?;
+            errors.push(error);
+        }
+
+        errors.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(errors)
+    }
+
+    fn update<F>(&self, session_id: &str, apply: F) -> Result<TGPSession, SessionStoreError>
+    where
+        F: FnOnce(&mut SessionRecord, u64) -> Result<(), TGPStateError>,
+    {
+        let _guard = self.write_lock.lock().unwrap();
+        let mut record = self.get(session_id)?;
+        apply(&mut record, self.clock.now_unix())?;
+        record.session.apply_timeout_policy(&self.timeout_policy);
+        self.store.save(&record)?;
+        Ok(record.session)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tgp::clock::ManualClock;
+    use crate::tgp::routes::RouteOption;
+    use crate::tgp::types::EconomicEnvelope;
+
+    const T0: u64 = 1_731_600_000;
+    const ALICE: &str = "buyer://alice";
+
+    fn offer() -> OfferMessage {
+        OfferMessage::new("offer-1", "q-1", "USDC", 1_000, false, EconomicEnvelope::new(50))
+    }
+
+    fn manager(store: Arc<dyn SessionStore>) -> (TGPSessionManager, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(T0));
+        (TGPSessionManager::new(store, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_create_and_transition() {
+        let (manager, clock) = manager(Arc::new(InMemorySessionStore::new()));
+        manager.create("sess-1").unwrap();
+        assert!(matches!(
+            manager.create("sess-1"),
+            Err(SessionStoreError::AlreadyExists(_))
+        ));
+
+        clock.advance(5);
+        let session = manager.transition("sess-1", TGPState::QuerySent).unwrap();
+        assert_eq!(session.updated_at, T0 + 5);
+        assert_eq!(manager.get("sess-1").unwrap().session, session);
+
+        assert!(matches!(
+            manager.transition("sess-1", TGPState::Settled),
+            Err(SessionStoreError::State(TGPStateError::InvalidTransition(..)))
+        ));
+        assert!(matches!(
+            manager.transition("missing", TGPState::QuerySent),
+            Err(SessionStoreError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_resume_states() {
+        let (manager, _clock) = manager(Arc::new(InMemorySessionStore::new()));
+        manager.create_for("sess-1", ALICE).unwrap();
+        manager.transition("sess-1", TGPState::QuerySent).unwrap();
+        assert!(matches!(
+            manager.resume("sess-1", ALICE),
+            Err(SessionStoreError::NotResumable(_, TGPState::QuerySent))
+        ));
+
+        manager.record_offer("sess-1", offer()).unwrap();
+        let resumed = manager.resume("sess-1", ALICE).unwrap();
+        let entered = resumed.session.transition_into(TGPState::OfferReceived).unwrap();
+        assert_eq!(entered.actor, TransitionActor::Controller);
+        assert_eq!(entered.cause.as_deref(), Some("offer-1"));
+        assert_eq!(resumed.session.offer_id.as_deref(), Some("offer-1"));
+        assert_eq!(resumed.session.query_id.as_deref(), Some("q-1"));
+        assert_eq!(resumed.offer, Some(offer()));
+
+        manager.transition("sess-1", TGPState::AcceptSent).unwrap();
+        manager.transition("sess-1", TGPState::Finalizing).unwrap();
+        assert_eq!(manager.resume("sess-1", ALICE).unwrap().session.state, TGPState::Finalizing);
+    }
+
+    #[test]
+    fn test_resume_only_by_owner() {
+        let (manager, clock) = manager(Arc::new(InMemorySessionStore::new()));
+        manager.create_for("sess-1", ALICE).unwrap();
+        manager.transition("sess-1", TGPState::QuerySent).unwrap();
+        manager.record_offer("sess-1", offer()).unwrap();
+
+        assert!(matches!(
+            manager.resume("sess-1", "buyer://mallory"),
+            Err(SessionStoreError::NotOwner(_, party)) if party == "buyer://mallory"
+        ));
+
+        // Another party cannot expire the session either
+        clock.advance(TGPState::OfferReceived.timeout_seconds().unwrap() + 1);
+        assert!(manager.resume("sess-1", "buyer://mallory").is_err());
+        assert_eq!(manager.get("sess-1").unwrap().session.state, TGPState::OfferReceived);
+
+        manager.create("sess-2").unwrap();
+        manager.transition("sess-2", TGPState::QuerySent).unwrap();
+        manager.record_offer("sess-2", offer()).unwrap();
+        assert!(matches!(
+            manager.resume("sess-2", ALICE),
+            Err(SessionStoreError::NotOwner(..))
+        ));
+    }
+
+    #[test]
+    fn test_resume_expired() {
+        let (manager, clock) = manager(Arc::new(InMemorySessionStore::new()));
+        manager.create_for("sess-1", ALICE).unwrap();
+        manager.transition("sess-1", TGPState::QuerySent).unwrap();
+        manager.record_offer("sess-1", offer()).unwrap();
+
+        clock.advance(TGPState::OfferReceived.timeout_seconds().unwrap() + 1);
+        assert!(matches!(
+            manager.resume("sess-1", ALICE),
+            Err(SessionStoreError::Expired(_, deadline)) if deadline == T0 + 300
+        ));
+        assert_eq!(manager.get("sess-1").unwrap().session.state, TGPState::Errored);
+    }
+
+    #[test]
+    fn test_timeout_policy_uses_offer_context() {
+        let policy = TimeoutPolicy::default()
+            .with_chain(1, TGPState::OfferReceived, 30)
+            .with_asset("USDC", TGPState::OfferReceived, 20);
+        let (manager, _clock) = manager(Arc::new(InMemorySessionStore::new()));
+        let manager = manager.with_timeout_policy(policy);
+        manager.create("sess-1").unwrap();
+        manager.transition("sess-1", TGPState::QuerySent).unwrap();
+
+        let routed = offer().with_routes(vec![RouteOption {
+            rank: 1,
+            ..RouteOption::direct("eth", "USDC", 1_000, 1)
+        }]);
+        let session = manager.record_offer("sess-1", routed).unwrap();
+        assert_eq!(session.chain_id, Some(1));
+        assert_eq!(session.asset.as_deref(), Some("USDC"));
+        assert_eq!(session.timeout_at, Some(T0 + 30));
+    }
+
+    #[test]
+    fn test_sweep_timeouts() {
+        let policy = TimeoutPolicy::default().with_state(TGPState::QuerySent, 10);
+        let (manager, clock) = manager(Arc::new(InMemorySessionStore::new()));
+        let manager = manager.with_timeout_policy(policy);
+
+        manager.create("sess-query").unwrap();
+        manager
+            .transition_by("sess-query", TGPState::QuerySent, TransitionActor::Buyer, Some("q-9"))
+            .unwrap();
+        manager.create("sess-offer").unwrap();
+        manager.transition("sess-offer", TGPState::QuerySent).unwrap();
+        manager.record_offer("sess-offer", offer()).unwrap();
+        manager.create("sess-idle").unwrap();
+
+        clock.advance(11);
+        let errors = manager.sweep_timeouts().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].id, "err-timeout-sess-query");
+        assert_eq!(errors[0].code, error_codes::TIMEOUT);
+        assert!(errors[0].validate().is_ok());
+
+        let swept = manager.get("sess-query").unwrap().session;
+        assert_eq!(swept.state, TGPState::Errored);
+        assert_eq!(swept.last_transition().unwrap().from, TGPState::QuerySent);
+
+        clock.advance(300);
+        let errors = manager.sweep_timeouts().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].correlation_id.as_deref(), Some("offer-1"));
+        assert_eq!(manager.get("sess-idle").unwrap().session.state, TGPState::Idle);
+
+        assert!(manager.sweep_timeouts().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_file_store_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = Arc::new(FileSessionStore::open(dir.path()).unwrap());
+            let (manager, _clock) = manager(store);
+            manager.create_for("sess-1", ALICE).unwrap();
+            manager.transition("sess-1", TGPState::QuerySent).unwrap();
+            manager.record_offer("sess-1", offer()).unwrap();
+            manager.create("sess-2").unwrap();
+        }
+
+        let store = Arc::new(FileSessionStore::open(dir.path()).unwrap());
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        let (manager, _clock) = manager(store.clone());
+        let resumed = manager.resume("sess-1", ALICE).unwrap();
+        assert_eq!(resumed.offer, Some(offer()));
+
+        assert!(store.remove("sess-2").unwrap().is_some());
+        assert!(store.remove("sess-2").unwrap().is_none());
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_file_store_rejects_unsafe_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileSessionStore::open(dir.path()).unwrap();
+
+        for id in ["", "../escape", ".hidden", "a/b", "sess 1"] {
+            assert!(matches!(
+                store.load(id),
+                Err(SessionStoreError::InvalidSessionId(_))
+            ));
+        }
+        assert!(store.load("sess-abc_1.2").unwrap().is_none());
+    }
+}
@@ -0,0 +1,678 @@
+//# TGP Transaction Detail Records
+
+//**Destination Path:** `crates/tbc-core/src/tgp/tdr.rs`
+
+//**Implementation:** M2 - TGP-01 Economic Envelope & Proof-of-Settlement
+
+//! Transaction Detail Records (TDR) per TGP-01 §8
+//!
+//! A TDR is the per-transaction accounting record for a settled TGP session,
+//! analogous to a telecom Call Detail Record. It captures the route, parties,
+//! amounts, fees, triple-clock timings, chain transactions and the final
+//! outcome. TDRs are appended to rotating files and anchored by hash in the
+//! Proof-of-Settlement (`tdr_hash`).
+//!
+//! # Hashing
+//!
+//! [`TransactionDetailRecord::hash`] is sha256 over the canonical JSON of the
+//! record (see [`crate::tgp::hash`]), which is the value carried in
+//! [`ProofOfSettlement::tdr_hash`](crate::tgp::pos::ProofOfSettlement).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::tdr::{TdrOutcome, TdrTimestamp, TransactionDetailRecord};
+//!
+//! let tdr = TransactionDetailRecord::new(
+//!     "sess-abc123",
+//!     vec!["tai:7abf92c6".to_string(), "tai:8bde4411".to_string()],
+//!     "buyer://alice",
+//!     "seller://bob",
+//!     "30.00",
+//!     "USDC",
+//!     TdrTimestamp::new(100, 1_700_000_000, "2023-11-14T22:13:20Z"),
+//!     TdrOutcome::Claimed,
+//! );
+//!
+//! assert_eq!(tdr.tgp_path(), "tai:7abf92c6>tai:8bde4411");
+//! assert!(tdr.hash()?.starts_with("0x"));
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::hash::{canonical_keccak256, canonical_sha256};
+use super::types::EconomicEnvelope;
+
+/// TDR schema version
+pub const TDR_VERSION_1_0: &str = "1.0";
+
+/// Separator between hops in a rendered TGP path
+pub const TGP_PATH_SEPARATOR: &str = ">";
+
+// ============================================================================
+// TDR Structure (§8)
+// ============================================================================
+
+/// Final outcome of a TGP session
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TdrOutcome {
+    /// Seller claimed the escrowed payment
+    Claimed,
+    /// Payment was refunded to the buyer
+    Refunded,
+    /// Buyer withdrew before seller acceptance
+    Withdrawn,
+    /// Session expired without settlement
+    TimedOut,
+    /// Session terminated with an error
+    Failed,
+}
+
+/// Triple-clock timestamp as recorded by the settlement engine
+///
+/// **Spec:** CoreProver v0.3 triple-clock model (mono, unix, iso)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TdrTimestamp {
+    /// Monotonic seconds (engine epoch)
+    pub mono: u64,
+    /// Unix seconds
+    pub unix: u64,
+    /// RFC3339 rendering of `unix`
+    pub iso: String,
+}
+
+impl TdrTimestamp {
+    pub fn new(mono: u64, unix: u64, iso: impl Into<String>) -> Self {
+        Self {
+            mono,
+            unix,
+            iso: iso.into(),
+        }
+    }
+}
+
+/// Timings of the session lifecycle
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TdrTimings {
+    /// Session creation (QUERY received)
+    pub created: TdrTimestamp,
+    /// Seller fulfillment, if it happened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fulfilled: Option<TdrTimestamp>,
+    /// Final settlement (claim, refund or withdrawal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settled: Option<TdrTimestamp>,
+}
+
+/// Fees charged on the session, as canonical decimal strings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TdrFees {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buyer_fee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_fee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing_fee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<String>,
+}
+
+impl TdrFees {
+    /// Extract the fee breakdown from a TGP-01 envelope
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the envelope's fee amounts cannot be summed.
+    pub fn from_envelope(envelope: &EconomicEnvelope) -> Result<Self, String> {
+        let buyer_fee = envelope.buyer_fee.as_ref();
+        let protocol_fee = match buyer_fee {
+            Some(fee) => fee.protocol_fee_amount()?,
+            None => None,
+        };
+
+        Ok(Self {
+            buyer_fee: buyer_fee.map(|fee| fee.amount.clone()),
+            protocol_fee,
+            routing_fee: envelope.routing_fee.as_ref().map(|fee| fee.amount.clone()),
+            total: envelope.total_fees()?,
+        })
+    }
+}
+
+/// On-chain transactions of the session
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TdrTxids {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buyer_commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seller_accept: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seller_fulfill: Option<String>,
+    /// Final claim or refund transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settlement: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buyer_withdraw: Option<String>,
+}
+
+/// Transaction Detail Record for one TGP session
+///
+/// # Specification Reference
+/// - TGP-01 §8 TDR Anchoring
+/// - TGP-01 §5 `tdr_hash`
+///
+/// # Fields
+///
+/// | Field | Description |
+/// |-------|-------------|
+/// | `session_id` | TGP session identifier |
+/// | `route` | TAI hops the session traversed, in order |
+/// | `buyer` / `seller` | Party identifiers |
+/// | `amount` / `asset` | Settled price |
+/// | `fees` | Fee breakdown from the Economic Envelope |
+/// | `economic_envelope_hash` | keccak256 of the canonical envelope |
+/// | `timings` | Triple-clock lifecycle timestamps |
+/// | `buyer_chain_id` / `seller_chain_id` | Chains of each side |
+/// | `txids` | On-chain provenance |
+/// | `outcome` | Final session outcome |
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TransactionDetailRecord {
+    pub tdr_version: String,
+    pub session_id: String,
+    pub route: Vec<String>,
+    pub buyer: String,
+    pub seller: String,
+    pub amount: String,
+    pub asset: String,
+    #[serde(default)]
+    pub fees: TdrFees,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub economic_envelope_hash: Option<String>,
+    pub timings: TdrTimings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buyer_chain_id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seller_chain_id: Option<u64>,
+    #[serde(default)]
+    pub txids: TdrTxids,
+    pub outcome: TdrOutcome,
+}
+
+impl TransactionDetailRecord {
+    /// Create a TDR with the required fields
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        session_id: impl Into<String>,
+        route: Vec<String>,
+        buyer: impl Into<String>,
+        seller: impl Into<String>,
+        amount: impl Into<String>,
+        asset: impl Into<String>,
+        created: TdrTimestamp,
+        outcome: TdrOutcome,
+    ) -> Self {
+        Self {
+            tdr_version: TDR_VERSION_1_0.to_string(),
+            session_id: session_id.into(),
+            route,
+            buyer: buyer.into(),
+            seller: seller.into(),
+            amount: amount.into(),
+            asset: asset.into(),
+            fees: TdrFees::default(),
+            economic_envelope_hash: None,
+            timings: TdrTimings {
+                created,
+                fulfilled: None,
+                settled: None,
+            },
+            buyer_chain_id: None,
+            seller_chain_id: None,
+            txids: TdrTxids::default(),
+            outcome,
+        }
+    }
+
+    /// Builder method to record the envelope's fees and hash
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fees cannot be summed or the envelope cannot
+    /// be serialized.
+    pub fn with_envelope(mut self, envelope: &EconomicEnvelope) -> Result<Self, String> {
+        self.fees = TdrFees::from_envelope(envelope)?;
+        self.economic_envelope_hash = Some(canonical_keccak256(envelope).map_err(|e| e.to_string())?);
+        Ok(self)
+    }
+
+    /// Builder method to set the fulfillment and settlement timestamps
+    pub fn with_timings(mut self, fulfilled: Option<TdrTimestamp>, settled: Option<TdrTimestamp>) -> Self {
+        self.timings.fulfilled = fulfilled;
+        self.timings.settled = settled;
+        self
+    }
+
+    /// Builder method to set the buyer and seller chains
+    pub fn with_chains(mut self, buyer_chain_id: u64, seller_chain_id: u64) -> Self {
+        self.buyer_chain_id = Some(buyer_chain_id);
+        self.seller_chain_id = Some(seller_chain_id);
+        self
+    }
+
+    /// Builder method to set the on-chain transactions
+    pub fn with_txids(mut self, txids: TdrTxids) -> Self {
+        self.txids = txids;
+        self
+    }
+
+    /// Render the route as a PoS `tgp_path` (e.g., `tai:a>tai:b`)
+    pub fn tgp_path(&self) -> String {
+        self.route.join(TGP_PATH_SEPARATOR)
+    }
+
+    /// sha256 over the canonical JSON of the record
+    ///
+    /// **Spec:** TGP-01 §5 `tdr_hash`
+    pub fn hash(&self) -> Result<String, serde_json::Error> {
+        canonical_sha256(self)
+    }
+}
+
+// ============================================================================
+// TDR File Writer
+// ============================================================================
+
+/// Errors that can occur while writing TDRs
+#[derive(Debug, Error)]
+pub enum TdrError {
+    #[error("TDR I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("TDR serialization failed: {0}")]
+    Serialization(String),
+}
+
+/// On-disk TDR encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TdrFormat {
+    /// One JSON object per line (full record)
+    Jsonl,
+    /// Flattened CSV row with a header per file
+    Csv,
+}
+
+impl TdrFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TdrFormat::Jsonl => "jsonl",
+            TdrFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Configuration for [`TdrWriter`]
+#[derive(Debug, Clone)]
+pub struct TdrWriterConfig {
+    /// Directory the TDR files are written to
+    pub directory: PathBuf,
+    /// File name prefix (files are named `{prefix}-{seq:06}.{ext}`)
+    pub prefix: String,
+    /// Output encoding
+    pub format: TdrFormat,
+    /// Rotate once a file would exceed this many bytes
+    pub max_file_bytes: u64,
+    /// Rotate once a file holds this many records
+    pub max_records_per_file: Option<u64>,
+}
+
+impl TdrWriterConfig {
+    /// Default rotation size (64 MiB)
+    pub const DEFAULT_MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+    pub fn new(directory: impl Into<PathBuf>, format: TdrFormat) -> Self {
+        Self {
+            directory: directory.into(),
+            prefix: "tdr".to_string(),
+            format,
+            max_file_bytes: Self::DEFAULT_MAX_FILE_BYTES,
+            max_records_per_file: None,
+        }
+    }
+
+    /// Builder method to set the file name prefix
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Builder method to set the rotation size
+    pub fn with_max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.max_file_bytes = max_file_bytes;
+        self
+    }
+
+    /// Builder method to rotate after a fixed number of records
+    pub fn with_max_records_per_file(mut self, max_records: u64) -> Self {
+        self.max_records_per_file = Some(max_records);
+        self
+    }
+}
+
+/// Append-only, rotating TDR file writer
+///
+/// Records are only ever appended. On startup the writer resumes the newest
+/// existing file for its prefix and format, so restarts never overwrite
+/// earlier TDRs. A new file is started when the size or record limit would
+/// be exceeded; a single record is never split across files.
+///
+/// Record counts for a resumed file start from zero, so
+/// `max_records_per_file` bounds records written per file by this writer.
+#[derive(Debug)]
+pub struct TdrWriter {
+    config: TdrWriterConfig,
+    file: File,
+    path: PathBuf,
+    sequence: u64,
+    bytes_written: u64,
+    records_written: u64,
+}
+
+impl TdrWriter {
+    /// Open a writer, resuming the newest existing file if any
+    pub fn open(config: TdrWriterConfig) -> Result<Self, TdrError> {
+        fs::create_dir_all(&config.directory)?;
+        let sequence = latest_sequence(&config)?.unwrap_or(1);
+        let path = file_path(&config, sequence);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let bytes_written = file.metadata()?.len();
+
+        Ok(Self {
+            config,
+            file,
+            path,
+            sequence,
+            bytes_written,
+            records_written: 0,
+        })
+    }
+
+    /// Append a record, rotating first if needed
+    pub fn append(&mut self, tdr: &TransactionDetailRecord) -> Result<(), TdrError> {
+        if self.should_rotate(&self.encode(tdr, false)?) {
+            self.rotate()?;
+        }
+
+        let bytes = self.encode(tdr, self.bytes_written == 0)?;
+        self.file.write_all(&bytes)?;
+        self.bytes_written += bytes.len() as u64;
+        self.records_written += 1;
+        Ok(())
+    }
+
+    /// Flush buffered data to disk
+    pub fn flush(&mut self) -> Result<(), TdrError> {
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// Path of the file currently being written
+    pub fn current_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Start the next file in the sequence
+    pub fn rotate(&mut self) -> Result<(), TdrError> {
+        self.file.flush()?;
+        self.sequence += 1;
+        self.path = file_path(&self.config, self.sequence);
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.bytes_written = self.file.metadata()?.len();
+        self.records_written = 0;
+        Ok(())
+    }
+
+    fn should_rotate(&self, record: &[u8]) -> bool {
+        if self.bytes_written == 0 {
+            return false;
+        }
+        let over_size = self.bytes_written + record.len() as u64 > self.config.max_file_bytes;
+        let over_count = self
+            .config
+            .max_records_per_file
+            .is_some_and(|max| self.records_written >= max);
+        over_size || over_count
+    }
+
+    fn encode(&self, tdr: &TransactionDetailRecord, with_header: bool) -> Result<Vec<u8>, TdrError> {
+        match self.config.format {
+            TdrFormat::Jsonl => {
+                let mut line =
+                    serde_json::to_vec(tdr).map_err(|e| TdrError::Serialization(e.to_string()))?;
+                line.push(b'\n');
+                Ok(line)
+            }
+            TdrFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(with_header)
+                    .from_writer(Vec::new());
+                writer
+                    .serialize(TdrCsvRow::from(tdr))
+                    .map_err(|e| TdrError::Serialization(e.to_string()))?;
+                writer
+                    .into_inner()
+                    .map_err(|e| TdrError::Serialization(e.to_string()))
+            }
+        }
+    }
+}
+
+fn file_path(config: &TdrWriterConfig, sequence: u64) -> PathBuf {
+    config.directory.join(format!(
+        "{}-{:06}.{}",
+        config.prefix,
+        sequence,
+        config.format.extension()
+    ))
+}
+
+/// Highest existing sequence number for the configured prefix and format
+fn latest_sequence(config: &TdrWriterConfig) -> Result<Option<u64>, TdrError> {
+    let prefix = format!("{}-", config.prefix);
+    let suffix = format!(".{}", config.format.extension());
+    let mut latest = None;
+
+    for entry in fs::read_dir(&config.directory)? {
+        let name = entry?.file_name();
+        let sequence = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|rest| rest.strip_suffix(&suffix))
+            .and_then(|seq| seq.parse::<u64>().ok());
+        if let Some(sequence) = sequence {
+            latest = latest.max(Some(sequence));
+        }
+    }
+    Ok(latest)
+}
+
+/// Flattened CSV representation of a TDR
+#[derive(Serialize)]
+struct TdrCsvRow<'a> {
+    tdr_version: &'a str,
+    session_id: &'a str,
+    tgp_path: String,
+    buyer: &'a str,
+    seller: &'a str,
+    amount: &'a str,
+    asset: &'a str,
+    buyer_fee: Option<&'a str>,
+    protocol_fee: Option<&'a str>,
+    routing_fee: Option<&'a str>,
+    total_fees: Option<&'a str>,
+    economic_envelope_hash: Option<&'a str>,
+    created_unix: u64,
+    created_iso: &'a str,
+    fulfilled_unix: Option<u64>,
+    settled_unix: Option<u64>,
+    settled_iso: Option<&'a str>,
+    buyer_chain_id: Option<u64>,
+    seller_chain_id: Option<u64>,
+    buyer_commit_txid: Option<&'a str>,
+    seller_accept_txid: Option<&'a str>,
+    seller_fulfill_txid: Option<&'a str>,
+    settlement_txid: Option<&'a str>,
+    buyer_withdraw_txid: Option<&'a str>,
+    outcome: TdrOutcome,
+}
+
+impl<'a> From<&'a TransactionDetailRecord> for TdrCsvRow<'a> {
+    fn from(tdr: &'a TransactionDetailRecord) -> Self {
+        Self {
+            tdr_version: &tdr.tdr_version,
+            session_id: &tdr.session_id,
+            tgp_path: tdr.tgp_path(),
+            buyer: &tdr.buyer,
+            seller: &tdr.seller,
+            amount: &tdr.amount,
+            asset: &tdr.asset,
+            buyer_fee: tdr.fees.buyer_fee.as_deref(),
+            protocol_fee: tdr.fees.protocol_fee.as_deref(),
+            routing_fee: tdr.fees.routing_fee.as_deref(),
+            total_fees: tdr.fees.total.as_deref(),
+            economic_envelope_hash: tdr.economic_envelope_hash.as_deref(),
+            created_unix: tdr.timings.created.unix,
+            created_iso: &tdr.timings.created.iso,
+            fulfilled_unix: tdr.timings.fulfilled.as_ref().map(|t| t.unix),
+            settled_unix: tdr.timings.settled.as_ref().map(|t| t.unix),
+            settled_iso: tdr.timings.settled.as_ref().map(|t| t.iso.as_str()),
+            buyer_chain_id: tdr.buyer_chain_id,
+            seller_chain_id: tdr.seller_chain_id,
+            buyer_commit_txid: tdr.txids.buyer_commit.as_deref(),
+            seller_accept_txid: tdr.txids.seller_accept.as_deref(),
+            seller_fulfill_txid: tdr.txids.seller_fulfill.as_deref(),
+            settlement_txid: tdr.txids.settlement.as_deref(),
+            buyer_withdraw_txid: tdr.txids.buyer_withdraw.as_deref(),
+            outcome: tdr.outcome,
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tgp::types::{BuyerFee, EnvelopePrice, ProtocolFee, ProtocolFeeMode, RoutingFee};
+
+    fn sample_tdr(session_id: &str) -> TransactionDetailRecord {
+        TransactionDetailRecord::new(
+            session_id,
+            vec!["tai:7abf92c6".to_string(), "tai:8bde4411".to_string()],
+            "buyer://alice",
+            "seller://bob",
+            "30.00",
+            "USDC",
+            TdrTimestamp::new(100, 1_700_000_000, "2023-11-14T22:13:20Z"),
+            TdrOutcome::Claimed,
+        )
+        .with_chains(369, 369)
+        .with_timings(
+            Some(TdrTimestamp::new(160, 1_700_000_060, "2023-11-14T22:14:20Z")),
+            Some(TdrTimestamp::new(220, 1_700_000_120, "2023-11-14T22:15:20Z")),
+        )
+        .with_txids(TdrTxids {
+            settlement: Some("0xcc".to_string()),
+            ..TdrTxids::default()
+        })
+    }
+
+    #[test]
+    fn test_fees_from_envelope() {
+        let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 500)
+            .with_buyer_fee(BuyerFee::new("0.50", "USDC").with_protocol_fee(ProtocolFee::new(
+                "0.2",
+                "tgp://proverouter",
+                ProtocolFeeMode::AutoBuyBurn,
+            )))
+            .with_routing_fee(RoutingFee::new("0.10", "USDC"));
+
+        let tdr = sample_tdr("sess-1").with_envelope(&envelope).unwrap();
+        assert_eq!(tdr.fees.buyer_fee.as_deref(), Some("0.50"));
+        assert_eq!(tdr.fees.protocol_fee.as_deref(), Some("0.100"));
+        assert_eq!(tdr.fees.routing_fee.as_deref(), Some("0.10"));
+        assert_eq!(tdr.fees.total.as_deref(), Some("0.60"));
+        assert_eq!(tdr.economic_envelope_hash, Some(canonical_keccak256(&envelope).unwrap()));
+    }
+
+    #[test]
+    fn test_hash_matches_pos_binding() {
+        let tdr = sample_tdr("sess-1");
+        let json = serde_json::to_string(&tdr).unwrap();
+        let parsed: TransactionDetailRecord = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, tdr);
+        assert_eq!(parsed.hash().unwrap(), canonical_sha256(&tdr).unwrap());
+        assert_ne!(sample_tdr("sess-2").hash().unwrap(), tdr.hash().unwrap());
+    }
+
+    #[test]
+    fn test_jsonl_writer_rotates_and_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let line_len = serde_json::to_vec(&sample_tdr("sess-0")).unwrap().len() as u64 + 1;
+        let config = TdrWriterConfig::new(dir.path(), TdrFormat::Jsonl).with_max_file_bytes(line_len * 2);
+
+        let mut writer = TdrWriter::open(config.clone()).unwrap();
+        for i in 0..5 {
+            writer.append(&sample_tdr(&format!("sess-{}", i))).unwrap();
+        }
+        writer.flush().unwrap();
+        assert!(writer.current_path().ends_with("tdr-000003.jsonl"));
+
+        let first = fs::read_to_string(dir.path().join("tdr-000001.jsonl")).unwrap();
+        let records: Vec<TransactionDetailRecord> =
+            first.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].session_id, "sess-0");
+
+        // Reopening appends to the newest file
+        drop(writer);
+        let mut writer = TdrWriter::open(config).unwrap();
+        writer.append(&sample_tdr("sess-5")).unwrap();
+        let last = fs::read_to_string(dir.path().join("tdr-000003.jsonl")).unwrap();
+        assert_eq!(last.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_csv_writer_writes_header_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = TdrWriterConfig::new(dir.path(), TdrFormat::Csv)
+            .with_prefix("node-a")
+            .with_max_records_per_file(2);
+
+        let mut writer = TdrWriter::open(config).unwrap();
+        for i in 0..3 {
+            writer.append(&sample_tdr(&format!("sess-{}", i))).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let first = fs::read_to_string(dir.path().join("node-a-000001.csv")).unwrap();
+        let lines: Vec<&str> = first.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("tdr_version,session_id,tgp_path"));
+        assert!(lines[1].contains("tai:7abf92c6>tai:8bde4411"));
+        assert!(lines[1].ends_with(",claimed"));
+
+        let second = fs::read_to_string(dir.path().join("node-a-000002.csv")).unwrap();
+        assert_eq!(second.lines().count(), 2);
+    }
+}
@@ -0,0 +1,182 @@
+//# TGP Timeout Policy
+
+//**Destination Path:** `crates/tbc-core/src/tgp/timeout.rs`
+
+//**Implementation:** M1 - TGP Message Parsing & Basic Routing
+
+//! Per-deployment TGP state timeouts
+//!
+//! [`TGPState::timeout_seconds`] gives the TGP-00 §4 defaults. A
+//! [`TimeoutPolicy`] overrides them per state, per settlement chain and per
+//! asset, e.g. to give Ethereum mainnet a longer `Finalizing` window.
+//!
+//! # Resolution
+//!
+//! For a session in `state` settling `asset` on `chain_id`:
+//!
+//! 1. Terminal states never time out
+//! 2. If chain and/or asset overrides exist for the state, the longest wins
+//! 3. Otherwise the state override, if any
+//! 4. Otherwise [`TGPState::timeout_seconds`]
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::state::TGPState;
+//! use tbc_core::tgp::timeout::TimeoutPolicy;
+//!
+//! let policy = TimeoutPolicy::default()
+//!     .with_state(TGPState::QuerySent, 15)
+//!     .with_chain(1, TGPState::Finalizing, 1_800);
+//!
+//! assert_eq!(policy.timeout_for(TGPState::QuerySent, None, None), Some(15));
+//! assert_eq!(policy.timeout_for(TGPState::Finalizing, Some(1), None), Some(1_800));
+//! assert_eq!(policy.timeout_for(TGPState::Finalizing, Some(369), None), Some(600));
+//! assert_eq!(policy.timeout_for(TGPState::Settled, Some(1), None), None);
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::state::{TGPSession, TGPState};
+
+/// Timeout overrides for one scope, in seconds per state
+pub type StateTimeouts = HashMap<TGPState, u64>;
+
+/// Configurable state timeouts
+///
+/// Deserializes from deployment configuration; every map may be omitted.
+///
+/// ```json
+/// {
+///   "states": { "OfferReceived": 120 },
+///   "chains": { "1": { "Finalizing": 1800 } },
+///   "assets": { "WBTC": { "Finalizing": 3600 } }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeoutPolicy {
+    /// Overrides of the TGP-00 defaults
+    #[serde(default)]
+    pub states: StateTimeouts,
+
+    /// Overrides keyed by settlement chain id
+    #[serde(default)]
+    pub chains: HashMap<u64, StateTimeouts>,
+
+    /// Overrides keyed by asset
+    #[serde(default)]
+    pub assets: HashMap<String, StateTimeouts>,
+}
+
+impl TimeoutPolicy {
+    /// Builder method to override the timeout of `state`
+    pub fn with_state(mut self, state: TGPState, seconds: u64) -> Self {
+        self.states.insert(state, seconds);
+        self
+    }
+
+    /// Builder method to override the timeout of `state` on `chain_id`
+    pub fn with_chain(mut self, chain_id: u64, state: TGPState, seconds: u64) -> Self {
+        self.chains.entry(chain_id).or_default().insert(state, seconds);
+        self
+    }
+
+    /// Builder method to override the timeout of `state` for `asset`
+    pub fn with_asset(mut self, asset: impl Into<String>, state: TGPState, seconds: u64) -> Self {
+        self.assets.entry(asset.into()).or_default().insert(state, seconds);
+        self
+    }
+
+    /// Timeout in seconds for `state`, `None` if the state does not time out
+    pub fn timeout_for(
+        &self,
+        state: TGPState,
+        chain_id: Option<u64>,
+        asset: Option<&str>,
+    ) -> Option<u64> {
+        if state.is_terminal() {
+            return None;
+        }
+
+        let chain = chain_id
+            .and_then(|id| self.chains.get(&id))
+            .and_then(|timeouts| timeouts.get(&state));
+        let asset = asset
+            .and_then(|a| self.assets.get(a))
+            .and_then(|timeouts| timeouts.get(&state));
+
+        chain
+            .into_iter()
+            .chain(asset)
+            .max()
+            .or_else(|| self.states.get(&state))
+            .copied()
+            .or_else(|| state.timeout_seconds())
+    }
+
+    /// Timeout in seconds for the session's current state and context
+    pub fn timeout_for_session(&self, session: &TGPSession) -> Option<u64> {
+        self.timeout_for(session.state, session.chain_id, session.asset.as_deref())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_state_machine() {
+        let policy = TimeoutPolicy::default();
+        for state in [
+            TGPState::Idle,
+            TGPState::QuerySent,
+            TGPState::OfferReceived,
+            TGPState::AcceptSent,
+            TGPState::Finalizing,
+            TGPState::Settled,
+            TGPState::Errored,
+        ] {
+            assert_eq!(policy.timeout_for(state, Some(1), Some("USDC")), state.timeout_seconds());
+        }
+    }
+
+    #[test]
+    fn test_override_precedence() {
+        let policy = TimeoutPolicy::default()
+            .with_state(TGPState::Finalizing, 900)
+            .with_chain(1, TGPState::Finalizing, 1_800)
+            .with_asset("WBTC", TGPState::Finalizing, 3_600)
+            .with_asset("USDC", TGPState::Finalizing, 60)
+            .with_state(TGPState::Settled, 10);
+
+        assert_eq!(policy.timeout_for(TGPState::Finalizing, None, None), Some(900));
+        assert_eq!(policy.timeout_for(TGPState::Finalizing, Some(1), None), Some(1_800));
+        assert_eq!(policy.timeout_for(TGPState::Finalizing, Some(1), Some("WBTC")), Some(3_600));
+        assert_eq!(policy.timeout_for(TGPState::Finalizing, Some(1), Some("USDC")), Some(1_800));
+        assert_eq!(policy.timeout_for(TGPState::Finalizing, None, Some("USDC")), Some(60));
+        assert_eq!(policy.timeout_for(TGPState::QuerySent, Some(1), Some("WBTC")), Some(30));
+        assert_eq!(policy.timeout_for(TGPState::Settled, None, None), None);
+    }
+
+    #[test]
+    fn test_deserialize_config() {
+        let policy: TimeoutPolicy = serde_json::from_str(
+            r#"{"states": {"OfferReceived": 120}, "chains": {"1": {"Finalizing": 1800}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            policy,
+            TimeoutPolicy::default()
+                .with_state(TGPState::OfferReceived, 120)
+                .with_chain(1, TGPState::Finalizing, 1_800)
+        );
+        assert_eq!(serde_json::from_str::<TimeoutPolicy>("{}").unwrap(), TimeoutPolicy::default());
+    }
+}
@@ -11,8 +11,11 @@
 //! # Types
 //!
 //! - [`ZkProfile`] - §3.5: Buyer's ZK proof preference
-//! - [`EconomicEnvelope`] - §3.6: Economic constraints for offers
+//! - [`EconomicEnvelope`] - §3.6: Economic constraints for offers (TGP-01 §4 extended)
+//! - [`EnvelopePrice`], [`BuyerFee`], [`ProtocolFee`], [`RoutingFee`] - TGP-01 §4.1 envelope components
 //! - [`SettleSource`] - §3.7: Settlement reporter identity
+//! - [`SellerReputation`] - §3.2: Seller fulfillment track record (Layer 9)
+//! - [`RoutingDecision`] - Per-stage verdict of the Controller's routing pipeline
 //!
 //! # Examples
 //!
@@ -26,6 +29,11 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::validation::{
+    json_pointer, validate_decimal_amount, validate_hex_string, validate_non_empty,
+    validate_rfc3339_format, ValidationCode, ValidationReport,
+};
+
 // ============================================================================
 // ZkProfile Enumeration (§3.5)
 // ============================================================================
@@ -58,7 +66,7 @@ use serde::{Deserialize, Serialize};
 /// let json = serde_json::to_string(&profile).unwrap();
 /// assert_eq!(json, r#""REQUIRED""#);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, Default)]
 pub enum ZkProfile {
     /// Buyer does not want CoreProver escrow (direct x402 preferred)
     ///
@@ -74,6 +82,7 @@ pub enum ZkProfile {
     ///
     /// **Spec:** TGP-00 §3.5
     #[serde(rename = "OPTIONAL")]
+    #[default]
     Optional,
 
     /// Buyer demands CoreProver escrow
@@ -128,12 +137,6 @@ impl ZkProfile {
     }
 }
 
-impl Default for ZkProfile {
-    fn default() -> Self {
-        ZkProfile::Optional
-    }
-}
-
 impl std::fmt::Display for ZkProfile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -145,23 +148,43 @@ impl std::fmt::Display for ZkProfile {
 }
 
 // ============================================================================
-// EconomicEnvelope Structure (§3.6)
+// EconomicEnvelope Structure (TGP-00 §3.6, TGP-01 §4)
 // ============================================================================
 
+/// Economic Envelope version defined by TGP-01 §4.1
+pub const EE_VERSION_1_0: &str = "1.0";
+
+/// Upper bound for `max_fees_bps` (100%)
+pub const MAX_FEES_BPS_LIMIT: u32 = 10_000;
+
 /// Economic constraints for an OFFER
 ///
 /// Encodes fee limits and validity constraints that the Buyer must
 /// accept when proceeding with the offered settlement path.
 ///
+/// TGP-00 only defines `max_fees_bps` and `expiry`. TGP-01 §4 extends the
+/// envelope with the full price/fee breakdown, a policy binding and a
+/// signature. Envelopes carrying `ee_version` are validated against the
+/// TGP-01 §4.2 rules; envelopes without it are treated as the legacy
+/// TGP-00 form, so both shapes decode into this type.
+///
 /// # Specification Reference
 /// - TGP-00 §3.6 EconomicEnvelope Structure
+/// - TGP-01 §4 Economic Envelope (EE)
 ///
 /// # Fields
 ///
 /// | Field | Type | Required | Description |
 /// |-------|------|----------|-------------|
-/// | `max_fees_bps` | u32 | ✓ | Max fees in basis points (e.g., 50 = 0.50%) |
+/// | `max_fees_bps` | u32 | ✓ (TGP-00) | Max fees in basis points (e.g., 50 = 0.50%) |
 /// | `expiry` | string? | optional | RFC3339 timestamp for offer expiry |
+/// | `ee_version` | string? | ✓ (TGP-01) | Envelope schema version (`"1.0"`) |
+/// | `price` | [`EnvelopePrice`]? | ✓ (TGP-01) | Price of goods or services |
+/// | `buyer_fee` | [`BuyerFee`]? | optional | Seller-defined fee paid by the buyer |
+/// | `routing_fee` | [`RoutingFee`]? | optional | Fee paid to the TBC operator |
+/// | `timestamp` | string? | optional | RFC3339 time the envelope was issued |
+/// | `policy_root` | string? | optional | 0x-prefixed hash of the governing policy set |
+/// | `sig` | string? | optional | Signature over the canonical envelope JSON |
 ///
 /// # Examples
 ///
@@ -174,6 +197,32 @@ impl std::fmt::Display for ZkProfile {
 /// let with_expiry = EconomicEnvelope::with_expiry(50, "2025-11-10T23:59:59Z");
 /// assert!(with_expiry.validate().is_ok());
 /// ```
+///
+/// A TGP-01 envelope with a full fee breakdown:
+///
+/// ```rust
+/// use tbc_core::tgp::types::{
+///     BuyerFee, EconomicEnvelope, EnvelopePrice, ProtocolFee, ProtocolFeeMode, RoutingFee,
+/// };
+///
+/// let envelope = EconomicEnvelope::v1(EnvelopePrice::new("10000.00", "evm:ETH:1"), 100)
+///     .with_buyer_fee(
+///         BuyerFee::new("50.00", "evm:ETH:1").with_protocol_fee(ProtocolFee::new(
+///             "0.20",
+///             "tgp://proverouter",
+///             ProtocolFeeMode::AutoProveBurn,
+///         )),
+///     )
+///     .with_routing_fee(
+///         RoutingFee::new("10.00", "evm:ETH:1")
+///             .with_policy_ref("policy://carrierA/us-ca/routing-fee-v1"),
+///     );
+///
+/// assert!(envelope.validate().is_ok());
+/// assert_eq!(envelope.total_fees().unwrap().as_deref(), Some("60.00"));
+/// assert_eq!(envelope.total_fees_bps().unwrap(), Some(60));
+/// assert!(envelope.requires_prove_proof());
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EconomicEnvelope {
     /// Maximum acceptable total fees in basis points
@@ -185,6 +234,10 @@ pub struct EconomicEnvelope {
     /// **Example:** 50 = 0.50%, 100 = 1.00%
     ///
     /// **Validation:** Must not exceed 10000 (100%)
+    ///
+    /// **Decoding:** TGP-01 envelopes may omit this field, in which case
+    /// the cap defaults to 10000 (no limit beyond the price itself)
+    #[serde(default = "default_max_fees_bps")]
     pub max_fees_bps: u32,
 
     /// RFC3339 timestamp after which the offer is invalid
@@ -194,8 +247,58 @@ pub struct EconomicEnvelope {
     /// **Format:** RFC3339 (e.g., "2025-11-10T23:59:59Z")
     ///
     /// **Validation:** Must be valid RFC3339 and in the future
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expiry: Option<String>,
+
+    /// Envelope schema version
+    ///
+    /// **Spec:** TGP-01 §4.1 - Present on every TGP-01 envelope
+    ///
+    /// **None:** Legacy TGP-00 envelope
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ee_version: Option<String>,
+
+    /// Price of the goods or services
+    ///
+    /// **Spec:** TGP-01 §4.2 - MUST exist on TGP-01 envelopes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<EnvelopePrice>,
+
+    /// Seller-defined fee paid by the buyer
+    ///
+    /// **Spec:** TGP-01 §4.2 - MAY exist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buyer_fee: Option<BuyerFee>,
+
+    /// Routing service fee paid to the TBC operator
+    ///
+    /// **Spec:** TGP-01 §4.2 - MAY exist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing_fee: Option<RoutingFee>,
+
+    /// RFC3339 timestamp at which the envelope was issued
+    ///
+    /// **Spec:** TGP-01 §4.1 - Optional field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+
+    /// Hash of the Transaction Area policy set the envelope was issued under
+    ///
+    /// **Spec:** TGP-01 §4.1, §8 - Optional field
+    ///
+    /// **Format:** 0x-prefixed hex string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_root: Option<String>,
+
+    /// Signature over the canonical JSON of the envelope
+    ///
+    /// **Spec:** TGP-01 §4.1, §9 - Optional field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sig: Option<String>,
+}
+
+fn default_max_fees_bps() -> u32 {
+    MAX_FEES_BPS_LIMIT
 }
 
 impl EconomicEnvelope {
@@ -206,6 +309,18 @@ impl EconomicEnvelope {
     /// - `max_fees_bps` must not exceed 10000 (100%)
     /// - `expiry` must be valid RFC3339 format if present
     ///
+    /// # Validation Rules (per TGP-01 §4.2, when `ee_version` is set)
+    ///
+    /// - `ee_version` must be a supported version
+    /// - `price` must exist
+    /// - Monetary values must be canonical decimal strings
+    /// - `protocol_fee.dest` must identify a Prove Router (TAI or URI)
+    /// - Fees must be denominated in the price asset and must not
+    ///   exceed `max_fees_bps` of the price
+    ///
+    /// TGP-01 components present on a legacy envelope are checked for
+    /// well-formedness as well.
+    ///
     /// # Errors
     ///
     /// Returns a descriptive error string if validation fails.
@@ -221,25 +336,135 @@ impl EconomicEnvelope {
     /// assert!(invalid.validate().is_err());
     /// ```
     pub fn validate(&self) -> Result<(), String> {
-        if self.max_fees_bps > 10000 {
-            return Err(format!(
-                "max_fees_bps cannot exceed 10000 (100%), got: {}",
-                self.max_fees_bps
-            ));
+        self.validation_report().to_result()
+    }
+
+    /// Collect every validation issue of the envelope
+    ///
+    /// Applies the same rules as [`validate`](Self::validate) but reports all
+    /// violations, with JSON-pointer paths relative to the envelope.
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        let errors_before = report.error_count();
+
+        if self.max_fees_bps > MAX_FEES_BPS_LIMIT {
+            report.error(
+                json_pointer(path, "max_fees_bps"),
+                ValidationCode::OutOfRange,
+                format!(
+                    "max_fees_bps cannot exceed 10000 (100%), got: {}",
+                    self.max_fees_bps
+                ),
+            );
         }
 
         // Validate RFC3339 format if expiry is present
         if let Some(ref expiry) = self.expiry {
             // Simple format check (full validation would require chrono)
             if !expiry.contains('T') || (!expiry.ends_with('Z') && !expiry.contains('+') && !expiry.contains('-')) {
-                return Err(format!(
-                    "expiry must be in RFC3339 format (e.g., 2025-11-10T23:59:59Z): {}",
-                    expiry
-                ));
+                report.error(
+                    json_pointer(path, "expiry"),
+                    ValidationCode::InvalidTimestamp,
+                    format!(
+                        "expiry must be in RFC3339 format (e.g., 2025-11-10T23:59:59Z): {}",
+                        expiry
+                    ),
+                );
             }
         }
 
-        Ok(())
+        if let Some(ref version) = self.ee_version {
+            if version != EE_VERSION_1_0 {
+                report.error(
+                    json_pointer(path, "ee_version"),
+                    ValidationCode::UnsupportedVersion,
+                    format!("unsupported ee_version: {}", version),
+                );
+            }
+            if self.price.is_none() {
+                report.error(
+                    json_pointer(path, "price"),
+                    ValidationCode::Required,
+                    format!("price is required for ee_version {}", version),
+                );
+            }
+        }
+
+        if let Some(ref price) = self.price {
+            price.validate_into(report, &json_pointer(path, "price"));
+        }
+        if let Some(ref buyer_fee) = self.buyer_fee {
+            buyer_fee.validate_into(report, &json_pointer(path, "buyer_fee"));
+        }
+        if let Some(ref routing_fee) = self.routing_fee {
+            routing_fee.validate_into(report, &json_pointer(path, "routing_fee"));
+        }
+        if let Some(ref timestamp) = self.timestamp {
+            report.check(
+                path,
+                "timestamp",
+                ValidationCode::InvalidTimestamp,
+                validate_rfc3339_format(timestamp, "timestamp"),
+            );
+        }
+        if let Some(ref policy_root) = self.policy_root {
+            report.check(
+                path,
+                "policy_root",
+                ValidationCode::InvalidHash,
+                validate_hex_string(policy_root, "policy_root"),
+            );
+        }
+        if let Some(ref sig) = self.sig {
+            report.check(path, "sig", ValidationCode::Required, validate_non_empty(sig, "sig"));
+        }
+
+        // Fee totals are only meaningful once every component is well-formed
+        if report.error_count() == errors_before {
+            self.validate_fees_into(report, path);
+        }
+    }
+
+    /// Check fee assets against the price, then the fee cap
+    fn validate_fees_into(&self, report: &mut ValidationReport, path: &str) {
+        let Some(ref price) = self.price else {
+            return;
+        };
+
+        let components = [
+            self.buyer_fee.as_ref().map(|f| (&f.asset, "buyer_fee")),
+            self.routing_fee.as_ref().map(|f| (&f.asset, "routing_fee")),
+        ];
+        let mut assets_match = true;
+        for (asset, name) in components.into_iter().flatten() {
+            if asset != &price.asset {
+                assets_match = false;
+                report.error(
+                    json_pointer(&json_pointer(path, name), "asset"),
+                    ValidationCode::AssetMismatch,
+                    format!(
+                        "{}.asset {} does not match price.asset {}",
+                        name, asset, price.asset
+                    ),
+                );
+            }
+        }
+        if !assets_match {
+            return;
+        }
+
+        let (code, message) = match self.fee_cap_violation() {
+            Ok(None) => return,
+            Ok(Some(message)) => (ValidationCode::FeeCapExceeded, message),
+            Err(message) => (ValidationCode::OutOfRange, message),
+        };
+        report.error(json_pointer(path, "max_fees_bps"), code, message);
     }
 
     /// Create a new EconomicEnvelope with required fields
@@ -256,6 +481,13 @@ impl EconomicEnvelope {
         Self {
             max_fees_bps,
             expiry: None,
+            ee_version: None,
+            price: None,
+            buyer_fee: None,
+            routing_fee: None,
+            timestamp: None,
+            policy_root: None,
+            sig: None,
         }
     }
 
@@ -270,9 +502,233 @@ impl EconomicEnvelope {
     /// ```
     pub fn with_expiry(max_fees_bps: u32, expiry: impl Into<String>) -> Self {
         Self {
-            max_fees_bps,
             expiry: Some(expiry.into()),
+            ..Self::new(max_fees_bps)
+        }
+    }
+
+    /// Create a TGP-01 envelope with a price and fee cap
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use tbc_core::tgp::types::{EconomicEnvelope, EnvelopePrice};
+    /// let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 50);
+    /// assert!(envelope.is_v1());
+    /// assert!(envelope.validate().is_ok());
+    /// ```
+    pub fn v1(price: EnvelopePrice, max_fees_bps: u32) -> Self {
+        Self {
+            ee_version: Some(EE_VERSION_1_0.to_string()),
+            price: Some(price),
+            ..Self::new(max_fees_bps)
+        }
+    }
+
+    /// Builder method to set the buyer fee
+    pub fn with_buyer_fee(mut self, buyer_fee: BuyerFee) -> Self {
+        self.buyer_fee = Some(buyer_fee);
+        self
+    }
+
+    /// Builder method to set the routing fee
+    pub fn with_routing_fee(mut self, routing_fee: RoutingFee) -> Self {
+        self.routing_fee = Some(routing_fee);
+        self
+    }
+
+    /// Builder method to set the issue timestamp
+    pub fn with_timestamp(mut self, timestamp: impl Into<String>) -> Self {
+        self.timestamp = Some(timestamp.into());
+        self
+    }
+
+    /// Builder method to set the policy root
+    pub fn with_policy_root(mut self, policy_root: impl Into<String>) -> Self {
+        self.policy_root = Some(policy_root.into());
+        self
+    }
+
+    /// Builder method to set the envelope signature
+    pub fn with_sig(mut self, sig: impl Into<String>) -> Self {
+        self.sig = Some(sig.into());
+        self
+    }
+
+    /// Check whether this is a TGP-01 envelope
+    pub fn is_v1(&self) -> bool {
+        self.ee_version.is_some()
+    }
+
+    /// Check whether the envelope activates Prove Protocol Logic
+    ///
+    /// Returns `true` when `buyer_fee.protocol_fee.mode` is
+    /// `auto_prove_burn` (TGP-01 §4.2, §5).
+    pub fn requires_prove_proof(&self) -> bool {
+        self.buyer_fee
+            .as_ref()
+            .and_then(|fee| fee.protocol_fee.as_ref())
+            .map(|pf| pf.mode == ProtocolFeeMode::AutoProveBurn)
+            .unwrap_or(false)
+    }
+
+    /// Add one hop's routing fee to the envelope's routing fee
+    ///
+    /// A QUERY forwarded across peered Controllers pays a routing fee per
+    /// hop; the envelope carries their sum. The routing fee is created if
+    /// absent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an amount is malformed, the assets differ, or
+    /// the sum overflows.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use tbc_core::tgp::types::{EconomicEnvelope, EnvelopePrice, RoutingFee};
+    /// let mut envelope = EconomicEnvelope::v1(EnvelopePrice::new("1000", "USDC"), 50)
+    ///     .with_routing_fee(RoutingFee::new("1.5", "USDC"));
+    /// envelope.add_routing_fee("2", "USDC").unwrap();
+    /// assert_eq!(envelope.routing_fee.unwrap().amount, "3.5");
+    /// ```
+    pub fn add_routing_fee(&mut self, amount: &str, asset: &str) -> Result<(), String> {
+        let hop_fee = Decimal::parse(amount, "routing_fee.amount")?;
+        match self.routing_fee {
+            Some(ref mut fee) => {
+                if fee.asset != asset {
+                    return Err(format!(
+                        "routing_fee.asset {} does not match hop fee asset {}",
+                        fee.asset, asset
+                    ));
+                }
+                let total = Decimal::parse(&fee.amount, "routing_fee.amount")?
+                    .checked_add(hop_fee)
+                    .ok_or_else(|| "fee computation overflow".to_string())?;
+                fee.amount = total.to_string();
+            }
+            None => self.routing_fee = Some(RoutingFee::new(hop_fee.to_string(), asset)),
         }
+        Ok(())
+    }
+
+    /// Sum of buyer fee and routing fee as a canonical decimal string
+    ///
+    /// Returns `Ok(None)` when the envelope has no price. Fees must be
+    /// denominated in the price asset to be summed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an amount is malformed, a fee uses a different
+    /// asset than the price, or the sum overflows.
+    pub fn total_fees(&self) -> Result<Option<String>, String> {
+        Ok(self.fee_totals()?.map(|(_, fees)| fees.to_string()))
+    }
+
+    /// Total fees as basis points of the price, rounded up
+    ///
+    /// Returns `Ok(None)` when the envelope has no price.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use tbc_core::tgp::types::{EconomicEnvelope, EnvelopePrice, RoutingFee};
+    /// let envelope = EconomicEnvelope::v1(EnvelopePrice::new("200", "USDC"), 50)
+    ///     .with_routing_fee(RoutingFee::new("1", "USDC"));
+    /// assert_eq!(envelope.total_fees_bps().unwrap(), Some(50));
+    /// ```
+    pub fn total_fees_bps(&self) -> Result<Option<u32>, String> {
+        let Some((price, fees)) = self.fee_totals()? else {
+            return Ok(None);
+        };
+
+        if price.is_zero() {
+            return Ok(Some(if fees.is_zero() { 0 } else { u32::MAX }));
+        }
+
+        let scale = price.scale.max(fees.scale);
+        let overflow = || "fee computation overflow".to_string();
+        let price = price.rescale(scale).ok_or_else(overflow)?;
+        let fees = fees
+            .rescale(scale)
+            .and_then(|f| f.checked_mul(10_000))
+            .ok_or_else(overflow)?;
+
+        let bps = fees.div_ceil(price);
+        Ok(Some(u32::try_from(bps).unwrap_or(u32::MAX)))
+    }
+
+    /// Check that the total fees do not exceed `max_fees_bps` of the price
+    ///
+    /// Envelopes without a price always pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fees exceed the cap or cannot be computed.
+    pub fn check_fee_cap(&self) -> Result<(), String> {
+        match self.fee_cap_violation()? {
+            Some(message) => Err(message),
+            None => Ok(()),
+        }
+    }
+
+    /// Describe the fee cap violation, if any
+    ///
+    /// Returns `Err` only if the fees cannot be computed.
+    fn fee_cap_violation(&self) -> Result<Option<String>, String> {
+        let Some((price, fees)) = self.fee_totals()? else {
+            return Ok(None);
+        };
+
+        // fees * 10000 <= max_fees_bps * price, compared at a common scale
+        let scale = price.scale.max(fees.scale);
+        let overflow = || "fee computation overflow".to_string();
+        let lhs = fees
+            .rescale(scale)
+            .and_then(|f| f.checked_mul(10_000))
+            .ok_or_else(overflow)?;
+        let rhs = price
+            .rescale(scale)
+            .and_then(|p| p.checked_mul(self.max_fees_bps as u128))
+            .ok_or_else(overflow)?;
+
+        if lhs.mantissa > rhs.mantissa {
+            return Ok(Some(format!(
+                "total fees {} exceed max_fees_bps {} of price {}",
+                fees, self.max_fees_bps, price
+            )));
+        }
+
+        Ok(None)
+    }
+
+    /// Parse the price and the sum of all fees
+    fn fee_totals(&self) -> Result<Option<(Decimal, Decimal)>, String> {
+        let Some(ref price) = self.price else {
+            return Ok(None);
+        };
+        let price_amount = Decimal::parse(&price.amount, "price.amount")?;
+        let mut fees = Decimal::ZERO;
+
+        let components = [
+            self.buyer_fee.as_ref().map(|f| (&f.amount, &f.asset, "buyer_fee")),
+            self.routing_fee.as_ref().map(|f| (&f.amount, &f.asset, "routing_fee")),
+        ];
+
+        for (amount, asset, name) in components.into_iter().flatten() {
+            if asset != &price.asset {
+                return Err(format!(
+                    "{}.asset {} does not match price.asset {}",
+                    name, asset, price.asset
+                ));
+            }
+            let amount = Decimal::parse(amount, &format!("{}.amount", name))?;
+            fees = fees
+                .checked_add(amount)
+                .ok_or_else(|| "fee computation overflow".to_string())?;
+        }
+
+        Ok(Some((price_amount, fees)))
     }
 
     /// Get the maximum fee as a percentage (0.0 to 100.0)
@@ -315,6 +771,484 @@ impl EconomicEnvelope {
     }
 }
 
+// ============================================================================
+// Economic Envelope Components (TGP-01 §4.1)
+// ============================================================================
+
+/// Party paying or receiving an envelope component
+///
+/// # Specification Reference
+/// - TGP-01 §4.1 (`payer` / `payee`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeParty {
+    /// The buying party
+    Buyer,
+
+    /// The selling party
+    Seller,
+
+    /// The operator of the routing TBC
+    TbcOperator,
+}
+
+/// Price of the goods or services
+///
+/// **Spec:** TGP-01 §4.1 `price` - flows directly from payer to payee
+///
+/// # Examples
+///
+/// ```rust
+/// use tbc_core::tgp::types::{EnvelopePrice, FeeParty};
+///
+/// let price = EnvelopePrice::new("10000.00", "evm:ETH:1");
+/// assert_eq!(price.payer, FeeParty::Buyer);
+/// assert!(price.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnvelopePrice {
+    /// Canonical decimal string (e.g., "10000.00")
+    pub amount: String,
+
+    /// Asset identifier (e.g., "evm:ETH:1")
+    pub asset: String,
+
+    /// Paying party
+    pub payer: FeeParty,
+
+    /// Receiving party
+    pub payee: FeeParty,
+}
+
+impl EnvelopePrice {
+    /// Create a price paid by the buyer to the seller
+    pub fn new(amount: impl Into<String>, asset: impl Into<String>) -> Self {
+        Self {
+            amount: amount.into(),
+            asset: asset.into(),
+            payer: FeeParty::Buyer,
+            payee: FeeParty::Seller,
+        }
+    }
+
+    /// Validate amount and asset
+    pub fn validate(&self) -> Result<(), String> {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report.to_result()
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        report.check(
+            path,
+            "amount",
+            ValidationCode::InvalidAmount,
+            validate_decimal_amount(&self.amount, "price.amount"),
+        );
+        report.check(
+            path,
+            "asset",
+            ValidationCode::Required,
+            validate_non_empty(&self.asset, "price.asset"),
+        );
+    }
+}
+
+/// Seller-defined fee paid by the buyer
+///
+/// **Spec:** TGP-01 §4.1 `buyer_fee` - may carry a [`ProtocolFee`] share
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BuyerFee {
+    /// Canonical decimal string (e.g., "50.00")
+    pub amount: String,
+
+    /// Asset identifier
+    pub asset: String,
+
+    /// Paying party
+    pub payer: FeeParty,
+
+    /// Receiving party
+    pub payee: FeeParty,
+
+    /// Share of the buyer fee diverted to the Prove Router
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_fee: Option<ProtocolFee>,
+}
+
+impl BuyerFee {
+    /// Create a buyer fee paid by the buyer to the seller
+    pub fn new(amount: impl Into<String>, asset: impl Into<String>) -> Self {
+        Self {
+            amount: amount.into(),
+            asset: asset.into(),
+            payer: FeeParty::Buyer,
+            payee: FeeParty::Seller,
+            protocol_fee: None,
+        }
+    }
+
+    /// Builder method to set the protocol fee
+    pub fn with_protocol_fee(mut self, protocol_fee: ProtocolFee) -> Self {
+        self.protocol_fee = Some(protocol_fee);
+        self
+    }
+
+    /// Validate amount, asset and protocol fee
+    pub fn validate(&self) -> Result<(), String> {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report.to_result()
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        report.check(
+            path,
+            "amount",
+            ValidationCode::InvalidAmount,
+            validate_decimal_amount(&self.amount, "buyer_fee.amount"),
+        );
+        report.check(
+            path,
+            "asset",
+            ValidationCode::Required,
+            validate_non_empty(&self.asset, "buyer_fee.asset"),
+        );
+        if let Some(ref protocol_fee) = self.protocol_fee {
+            protocol_fee.validate_into(report, &json_pointer(path, "protocol_fee"));
+        }
+    }
+
+    /// Protocol fee amount (`amount × protocol_fee.ratio`, TGP-01 §6.2)
+    ///
+    /// Returns `Ok(None)` when no protocol fee is set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use tbc_core::tgp::types::{BuyerFee, ProtocolFee, ProtocolFeeMode};
+    /// let fee = BuyerFee::new("50.00", "USDC").with_protocol_fee(ProtocolFee::new(
+    ///     "0.20",
+    ///     "tgp://proverouter",
+    ///     ProtocolFeeMode::AutoProveBurn,
+    /// ));
+    /// assert_eq!(fee.protocol_fee_amount().unwrap().as_deref(), Some("10.0000"));
+    /// ```
+    pub fn protocol_fee_amount(&self) -> Result<Option<String>, String> {
+        let Some(ref protocol_fee) = self.protocol_fee else {
+            return Ok(None);
+        };
+        let amount = Decimal::parse(&self.amount, "buyer_fee.amount")?;
+        let ratio = Decimal::parse(&protocol_fee.ratio, "protocol_fee.ratio")?;
+        let product = amount
+            .checked_mul_decimal(ratio)
+            .ok_or_else(|| "fee computation overflow".to_string())?;
+        Ok(Some(product.to_string()))
+    }
+}
+
+/// Processing mode for a protocol fee
+///
+/// **Spec:** TGP-01 §4.1 `protocol_fee.mode`, §6
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolFeeMode {
+    /// Swap to the prove asset and burn; activates Prove Protocol Logic
+    AutoProveBurn,
+
+    /// Swap through the BurnRouter and burn (TGP-01 §7.3)
+    AutoBuyBurn,
+}
+
+/// Share of the buyer fee routed to a Prove Router
+///
+/// **Spec:** TGP-01 §4.1 `buyer_fee.protocol_fee`
+///
+/// The `ratio` is kept as a canonical decimal string; JSON numbers such as
+/// `0.20` are accepted on decode and normalized to their string form.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProtocolFee {
+    /// Fraction of the buyer fee (0 to 1, e.g., "0.20")
+    #[serde(deserialize_with = "deserialize_decimal_string")]
+    pub ratio: String,
+
+    /// Prove Router endpoint (TAI or URI, e.g., "tgp://proverouter")
+    pub dest: String,
+
+    /// Processing mode
+    pub mode: ProtocolFeeMode,
+
+    /// Asset the fee is converted into (e.g., "prove")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+}
+
+impl ProtocolFee {
+    /// Create a protocol fee
+    pub fn new(ratio: impl Into<String>, dest: impl Into<String>, mode: ProtocolFeeMode) -> Self {
+        Self {
+            ratio: ratio.into(),
+            dest: dest.into(),
+            mode,
+            asset: None,
+        }
+    }
+
+    /// Builder method to set the destination asset
+    pub fn with_asset(mut self, asset: impl Into<String>) -> Self {
+        self.asset = Some(asset.into());
+        self
+    }
+
+    /// Validate ratio and destination
+    ///
+    /// - `ratio` must be a canonical decimal between 0 and 1
+    /// - `dest` must be a TAI (`tai:...`) or a URI (`scheme://...`)
+    pub fn validate(&self) -> Result<(), String> {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report.to_result()
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        let ratio_ok = report.check(
+            path,
+            "ratio",
+            ValidationCode::InvalidAmount,
+            validate_decimal_amount(&self.ratio, "protocol_fee.ratio"),
+        );
+        if ratio_ok {
+            match Decimal::parse(&self.ratio, "protocol_fee.ratio") {
+                Ok(ratio) if ratio.exceeds(Decimal::ONE) => report.error(
+                    json_pointer(path, "ratio"),
+                    ValidationCode::OutOfRange,
+                    format!(
+                        "protocol_fee.ratio must be between 0 and 1, got: {}",
+                        self.ratio
+                    ),
+                ),
+                Ok(_) => {}
+                Err(message) => {
+                    report.error(json_pointer(path, "ratio"), ValidationCode::OutOfRange, message)
+                }
+            }
+        }
+
+        let dest_ok = report.check(
+            path,
+            "dest",
+            ValidationCode::Required,
+            validate_non_empty(&self.dest, "protocol_fee.dest"),
+        );
+        if dest_ok {
+            let is_tai = self.dest.strip_prefix("tai:").is_some_and(|rest| !rest.is_empty());
+            let is_uri = self
+                .dest
+                .split_once("://")
+                .is_some_and(|(scheme, rest)| !scheme.is_empty() && !rest.is_empty());
+            if !is_tai && !is_uri {
+                report.error(
+                    json_pointer(path, "dest"),
+                    ValidationCode::InvalidFormat,
+                    format!(
+                        "protocol_fee.dest must be a TAI (tai:...) or URI (scheme://...): {}",
+                        self.dest
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Routing service fee paid to the TBC operator
+///
+/// **Spec:** TGP-01 §4.1 `routing_fee`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutingFee {
+    /// Canonical decimal string (e.g., "10.00")
+    pub amount: String,
+
+    /// Asset identifier
+    pub asset: String,
+
+    /// Paying party
+    pub payer: FeeParty,
+
+    /// Receiving party
+    pub payee: FeeParty,
+
+    /// Policy the fee was computed under
+    /// (e.g., "policy://carrierA/us-ca/routing-fee-v1")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_ref: Option<String>,
+}
+
+impl RoutingFee {
+    /// Create a routing fee paid by the buyer to the TBC operator
+    pub fn new(amount: impl Into<String>, asset: impl Into<String>) -> Self {
+        Self {
+            amount: amount.into(),
+            asset: asset.into(),
+            payer: FeeParty::Buyer,
+            payee: FeeParty::TbcOperator,
+            policy_ref: None,
+        }
+    }
+
+    /// Builder method to set the policy reference
+    pub fn with_policy_ref(mut self, policy_ref: impl Into<String>) -> Self {
+        self.policy_ref = Some(policy_ref.into());
+        self
+    }
+
+    /// Validate amount, asset and policy reference
+    pub fn validate(&self) -> Result<(), String> {
+        let mut report = ValidationReport::new();
+        self.validate_into(&mut report, "");
+        report.to_result()
+    }
+
+    /// Record validation issues under the JSON pointer `path`
+    pub fn validate_into(&self, report: &mut ValidationReport, path: &str) {
+        report.check(
+            path,
+            "amount",
+            ValidationCode::InvalidAmount,
+            validate_decimal_amount(&self.amount, "routing_fee.amount"),
+        );
+        report.check(
+            path,
+            "asset",
+            ValidationCode::Required,
+            validate_non_empty(&self.asset, "routing_fee.asset"),
+        );
+        if let Some(ref policy_ref) = self.policy_ref {
+            report.check(
+                path,
+                "policy_ref",
+                ValidationCode::Required,
+                validate_non_empty(policy_ref, "routing_fee.policy_ref"),
+            );
+        }
+    }
+}
+
+/// Accept a decimal as either a JSON string or a JSON number
+fn deserialize_decimal_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Ok(s),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        other => Err(serde::de::Error::custom(format!(
+            "expected decimal string or number, got: {}",
+            other
+        ))),
+    }
+}
+
+// ============================================================================
+// Decimal Arithmetic (internal)
+// ============================================================================
+
+/// Fixed-point decimal used for fee computations
+///
+/// Stores `mantissa × 10^-scale`; all operations are checked so oversized
+/// inputs surface as errors instead of wrapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Decimal {
+    mantissa: u128,
+    scale: u32,
+}
+
+impl Decimal {
+    const ZERO: Decimal = Decimal { mantissa: 0, scale: 0 };
+    const ONE: Decimal = Decimal { mantissa: 1, scale: 0 };
+
+    /// Parse a canonical decimal string
+    fn parse(value: &str, field_name: &str) -> Result<Self, String> {
+        validate_decimal_amount(value, field_name)?;
+        let (int_part, frac_part) = value.split_once('.').unwrap_or((value, ""));
+        let digits = format!("{}{}", int_part, frac_part);
+        let mantissa = digits
+            .parse::<u128>()
+            .map_err(|_| format!("{} is too large: {}", field_name, value))?;
+        Ok(Self {
+            mantissa,
+            scale: frac_part.len() as u32,
+        })
+    }
+
+    fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    /// Express the value with `scale` fractional digits (scale must not shrink)
+    fn rescale(self, scale: u32) -> Option<Self> {
+        let factor = 10u128.checked_pow(scale.checked_sub(self.scale)?)?;
+        Some(Self {
+            mantissa: self.mantissa.checked_mul(factor)?,
+            scale,
+        })
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let scale = self.scale.max(other.scale);
+        let a = self.rescale(scale)?;
+        let b = other.rescale(scale)?;
+        Some(Self {
+            mantissa: a.mantissa.checked_add(b.mantissa)?,
+            scale,
+        })
+    }
+
+    fn checked_mul(self, factor: u128) -> Option<Self> {
+        Some(Self {
+            mantissa: self.mantissa.checked_mul(factor)?,
+            scale: self.scale,
+        })
+    }
+
+    fn checked_mul_decimal(self, other: Self) -> Option<Self> {
+        Some(Self {
+            mantissa: self.mantissa.checked_mul(other.mantissa)?,
+            scale: self.scale.checked_add(other.scale)?,
+        })
+    }
+
+    /// Numeric comparison independent of scale
+    fn exceeds(self, other: Self) -> bool {
+        let scale = self.scale.max(other.scale);
+        match (self.rescale(scale), other.rescale(scale)) {
+            (Some(a), Some(b)) => a.mantissa > b.mantissa,
+            // Only the larger operand can overflow when rescaled
+            (None, _) => true,
+            (_, None) => false,
+        }
+    }
+
+    /// Integer quotient rounded up; both operands must share a scale
+    fn div_ceil(self, other: Self) -> u128 {
+        debug_assert_eq!(self.scale, other.scale);
+        self.mantissa.div_ceil(other.mantissa)
+    }
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.scale == 0 {
+            return write!(f, "{}", self.mantissa);
+        }
+        let digits = format!("{:0>width$}", self.mantissa, width = self.scale as usize + 1);
+        let (int_part, frac_part) = digits.split_at(digits.len() - self.scale as usize);
+        write!(f, "{}.{}", int_part, frac_part)
+    }
+}
+
 // ============================================================================
 // SettleSource Enumeration (§3.7)
 // ============================================================================
@@ -419,6 +1353,125 @@ impl std::fmt::Display for SettleSource {
     }
 }
 
+// ============================================================================
+// SellerReputation (§3.2, Layer 9)
+// ============================================================================
+
+/// Seller fulfillment track record attached to OFFERs
+///
+/// Computed by the Controller from settled CoreProver receipts; a receipt
+/// counts as late when the seller fulfilled after the deadline
+/// (`late_fulfilled`).
+///
+/// # Examples
+///
+/// ```rust
+/// use tbc_core::tgp::types::SellerReputation;
+///
+/// let reputation = SellerReputation::new(40, 2);
+/// assert_eq!(reputation.on_time_bps, 9_500);
+/// assert_eq!(reputation.on_time_rate(), 0.95);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SellerReputation {
+    /// Settled orders observed
+    pub settled: u64,
+
+    /// Orders fulfilled after the deadline
+    pub late: u64,
+
+    /// Share of on-time fulfillments in basis points (10000 with no history)
+    pub on_time_bps: u32,
+}
+
+impl SellerReputation {
+    pub fn new(settled: u64, late: u64) -> Self {
+        let late = late.min(settled);
+        let on_time_bps = if settled == 0 {
+            10_000
+        } else {
+            ((settled - late) as u128 * 10_000 / settled as u128) as u32
+        };
+        Self {
+            settled,
+            late,
+            on_time_bps,
+        }
+    }
+
+    /// Share of on-time fulfillments (0.0 to 1.0)
+    pub fn on_time_rate(&self) -> f64 {
+        self.on_time_bps as f64 / 10_000.0
+    }
+}
+
+// ============================================================================
+// RoutingDecision (L8/L9/L10 pipeline)
+// ============================================================================
+
+/// Verdict of one routing stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionOutcome {
+    /// The stage let the QUERY through
+    Pass,
+
+    /// The stage rejected the QUERY
+    Reject,
+}
+
+/// What one routing stage decided about a QUERY
+///
+/// Attached to the resulting OFFER or ERROR so buyers and auditors can see
+/// which stage, and which rule within it, shaped the answer.
+///
+/// # Examples
+///
+/// ```rust
+/// use tbc_core::tgp::types::{DecisionOutcome, RoutingDecision};
+///
+/// let decision = RoutingDecision::pass("policy").with_detail("rule big-escrow");
+/// assert_eq!(decision.outcome, DecisionOutcome::Pass);
+/// assert_eq!(
+///     serde_json::to_string(&decision).unwrap(),
+///     r#"{"stage":"policy","outcome":"pass","detail":"rule big-escrow"}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingDecision {
+    /// Stage name, e.g. `"identity"`, `"policy"`, `"economic"`
+    pub stage: String,
+
+    pub outcome: DecisionOutcome,
+
+    /// Free-form explanation, e.g. the matching rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl RoutingDecision {
+    pub fn pass(stage: impl Into<String>) -> Self {
+        Self {
+            stage: stage.into(),
+            outcome: DecisionOutcome::Pass,
+            detail: None,
+        }
+    }
+
+    pub fn reject(stage: impl Into<String>) -> Self {
+        Self {
+            outcome: DecisionOutcome::Reject,
+            ..Self::pass(stage)
+        }
+    }
+
+    /// Builder method to set the explanation
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -478,6 +1531,141 @@ mod tests {
         assert_eq!(envelope.calculate_max_fee(100_000_000), 500_000);
     }
 
+    #[test]
+    fn test_economic_envelope_legacy_decoding() {
+        let json = r#"{"max_fees_bps":50,"expiry":"2025-11-10T23:59:59Z"}"#;
+        let envelope: EconomicEnvelope = serde_json::from_str(json).unwrap();
+
+        assert_eq!(envelope, EconomicEnvelope::with_expiry(50, "2025-11-10T23:59:59Z"));
+        assert!(!envelope.is_v1());
+        assert!(envelope.validate().is_ok());
+
+        // Legacy envelopes re-serialize without TGP-01 fields
+        assert_eq!(serde_json::to_string(&envelope).unwrap(), json);
+    }
+
+    #[test]
+    fn test_economic_envelope_v1_decoding() {
+        let json = r#"{
+            "ee_version": "1.0",
+            "price": {"amount": "10000.00", "asset": "evm:ETH:1", "payer": "buyer", "payee": "seller"},
+            "buyer_fee": {
+                "amount": "50.00", "asset": "evm:ETH:1", "payer": "buyer", "payee": "seller",
+                "protocol_fee": {"ratio": 0.20, "dest": "tgp://proverouter", "mode": "auto_prove_burn", "asset": "prove"}
+            },
+            "routing_fee": {
+                "amount": "10.00", "asset": "evm:ETH:1", "payer": "buyer", "payee": "tbc_operator",
+                "policy_ref": "policy://carrierA/us-ca/routing-fee-v1"
+            },
+            "timestamp": "2025-11-08T12:00:00Z",
+            "policy_root": "0x9341aa23",
+            "sig": "ed25519:abcd"
+        }"#;
+        let envelope: EconomicEnvelope = serde_json::from_str(json).unwrap();
+
+        assert!(envelope.is_v1());
+        assert_eq!(envelope.max_fees_bps, MAX_FEES_BPS_LIMIT);
+        assert_eq!(envelope.routing_fee.as_ref().unwrap().payee, FeeParty::TbcOperator);
+
+        let protocol_fee = envelope.buyer_fee.as_ref().unwrap().protocol_fee.as_ref().unwrap();
+        assert_eq!(protocol_fee.ratio, "0.2");
+        assert_eq!(protocol_fee.mode, ProtocolFeeMode::AutoProveBurn);
+
+        assert!(envelope.validate().is_ok());
+        assert!(envelope.requires_prove_proof());
+        assert_eq!(envelope.total_fees().unwrap().as_deref(), Some("60.00"));
+        assert_eq!(envelope.total_fees_bps().unwrap(), Some(60));
+
+        let reparsed: EconomicEnvelope =
+            serde_json::from_str(&serde_json::to_string(&envelope).unwrap()).unwrap();
+        assert_eq!(envelope, reparsed);
+    }
+
+    #[test]
+    fn test_economic_envelope_v1_rules() {
+        let price = EnvelopePrice::new("100.00", "USDC");
+
+        // price MUST exist
+        let mut missing_price = EconomicEnvelope::v1(price.clone(), 100);
+        missing_price.price = None;
+        assert!(missing_price.validate().is_err());
+
+        // unknown version
+        let mut unknown_version = EconomicEnvelope::v1(price.clone(), 100);
+        unknown_version.ee_version = Some("9.9".to_string());
+        assert!(unknown_version.validate().is_err());
+
+        // monetary values MUST be canonical decimals
+        let non_canonical = EconomicEnvelope::v1(EnvelopePrice::new("1e2", "USDC"), 100);
+        assert!(non_canonical.validate().is_err());
+
+        // protocol_fee.dest must be a TAI or URI
+        let bad_dest = EconomicEnvelope::v1(price.clone(), 100).with_buyer_fee(
+            BuyerFee::new("0.50", "USDC")
+                .with_protocol_fee(ProtocolFee::new("0.2", "proverouter", ProtocolFeeMode::AutoProveBurn)),
+        );
+        assert!(bad_dest.validate().is_err());
+
+        let tai_dest = EconomicEnvelope::v1(price.clone(), 100).with_buyer_fee(
+            BuyerFee::new("0.50", "USDC")
+                .with_protocol_fee(ProtocolFee::new("0.2", "tai:7abf92c6", ProtocolFeeMode::AutoBuyBurn)),
+        );
+        assert!(tai_dest.validate().is_ok());
+        assert!(!tai_dest.requires_prove_proof());
+
+        // ratio above 1
+        let bad_ratio = EconomicEnvelope::v1(price.clone(), 100).with_buyer_fee(
+            BuyerFee::new("0.50", "USDC")
+                .with_protocol_fee(ProtocolFee::new("1.5", "tai:7abf92c6", ProtocolFeeMode::AutoBuyBurn)),
+        );
+        assert!(bad_ratio.validate().is_err());
+
+        // fee in a different asset than the price
+        let mixed_assets = EconomicEnvelope::v1(price, 100)
+            .with_routing_fee(RoutingFee::new("0.10", "DAI"));
+        assert!(mixed_assets.validate().is_err());
+        assert!(mixed_assets.total_fees().is_err());
+    }
+
+    #[test]
+    fn test_economic_envelope_fee_cap() {
+        let price = EnvelopePrice::new("100.00", "USDC");
+
+        // 0.50 + 0.10 = 0.60 on 100.00 is exactly 60 bps
+        let at_cap = EconomicEnvelope::v1(price.clone(), 60)
+            .with_buyer_fee(BuyerFee::new("0.50", "USDC"))
+            .with_routing_fee(RoutingFee::new("0.1", "USDC"));
+        assert_eq!(at_cap.total_fees().unwrap().as_deref(), Some("0.60"));
+        assert_eq!(at_cap.total_fees_bps().unwrap(), Some(60));
+        assert!(at_cap.check_fee_cap().is_ok());
+        assert!(at_cap.validate().is_ok());
+
+        let over_cap = EconomicEnvelope { max_fees_bps: 59, ..at_cap };
+        assert!(over_cap.check_fee_cap().is_err());
+        assert!(over_cap.validate().is_err());
+
+        // Fractional bps round up
+        let rounding = EconomicEnvelope::v1(EnvelopePrice::new("3", "USDC"), 100)
+            .with_routing_fee(RoutingFee::new("0.01", "USDC"));
+        assert_eq!(rounding.total_fees_bps().unwrap(), Some(34));
+
+        // No price means nothing to compute
+        let legacy = EconomicEnvelope::new(50);
+        assert_eq!(legacy.total_fees().unwrap(), None);
+        assert_eq!(legacy.total_fees_bps().unwrap(), None);
+        assert!(legacy.check_fee_cap().is_ok());
+    }
+
+    #[test]
+    fn test_protocol_fee_amount() {
+        let fee = BuyerFee::new("50.00", "USDC")
+            .with_protocol_fee(ProtocolFee::new("0.2", "tgp://proverouter", ProtocolFeeMode::AutoProveBurn));
+        assert_eq!(fee.protocol_fee_amount().unwrap().as_deref(), Some("10.000"));
+
+        let no_protocol_fee = BuyerFee::new("50.00", "USDC");
+        assert_eq!(no_protocol_fee.protocol_fee_amount().unwrap(), None);
+    }
+
     #[test]
     fn test_settle_source_serialization() {
         assert_eq!(
@@ -0,0 +1,470 @@
+//# TGP Identifier Schemes
+
+//**Destination Path:** `crates/tbc-core/src/tgp/uri.rs`
+
+//**Implementation:** M1 - TGP Message Parsing & Basic Routing
+
+//! Typed parsing of TGP party and service identifiers
+//!
+//! # Supported Forms
+//!
+//! | Form | Example | Normalization |
+//! |------|---------|---------------|
+//! | `buyer://name` | `buyer://alice.wallet` | scheme and name lowercased |
+//! | `seller://name` | `seller://bob` | scheme and name lowercased |
+//! | `tgp://name` | `tgp://proverouter` | scheme and name lowercased |
+//! | `policy://path` | `policy://carrierA/us-ca/fee-v1` | scheme lowercased, trailing `/` removed |
+//! | `tai:hex` | `tai:7abf92c6` | lowercased |
+//! | CAIP-10 | `eip155:1:0xab16…` | EVM addresses lowercased |
+//! | EVM address | `0xab16…` | lowercased |
+//!
+//! Names are ASCII letters, digits, `.`, `_` and `-`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::uri::{PartyId, TgpUri};
+//!
+//! let buyer: TgpUri = "Buyer://Alice.Wallet".parse().unwrap();
+//! assert_eq!(buyer, TgpUri::Buyer("alice.wallet".to_string()));
+//! assert_eq!(buyer.to_string(), "buyer://alice.wallet");
+//!
+//! let account: PartyId = "eip155:1:0xAB5801a7D398351b8bE11C439e05C5B3259aeC9B".parse().unwrap();
+//! assert_eq!(account.to_string(), "eip155:1:0xab5801a7d398351b8be11c439e05c5b3259aec9b");
+//!
+//! // Policy references identify rules, not parties
+//! assert!("policy://carrierA/us-ca".parse::<PartyId>().is_err());
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// Identifier parse failure
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("invalid TGP identifier {input:?}: {reason}")]
+pub struct UriParseError {
+    pub input: String,
+    pub reason: String,
+}
+
+impl UriParseError {
+    fn new(input: &str, reason: impl Into<String>) -> Self {
+        Self {
+            input: input.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+// ============================================================================
+// TgpUri
+// ============================================================================
+
+/// Any identifier that can appear in a TGP message
+///
+/// Variants hold the normalized form without the scheme.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TgpUri {
+    /// `buyer://name`
+    Buyer(String),
+
+    /// `seller://name`
+    Seller(String),
+
+    /// `tgp://name`, a TGP service such as a Prove Router
+    Tgp(String),
+
+    /// `policy://path`, a policy reference
+    Policy(String),
+
+    /// `tai:hex`, a Transaction Area Identifier
+    Tai(String),
+
+    /// CAIP-10 account id `namespace:reference:address`
+    Caip10 {
+        namespace: String,
+        reference: String,
+        address: String,
+    },
+
+    /// Raw 0x-prefixed EVM address
+    Evm(String),
+}
+
+impl TgpUri {
+    /// Parse and normalize an identifier
+    pub fn parse(input: &str) -> Result<Self, UriParseError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Err(UriParseError::new(input, "identifier is empty"));
+        }
+
+        if let Some((scheme, rest)) = trimmed.split_once("://") {
+            return match scheme.to_ascii_lowercase().as_str() {
+                "buyer" => parse_name(input, rest).map(TgpUri::Buyer),
+                "seller" => parse_name(input, rest).map(TgpUri::Seller),
+                "tgp" => parse_name(input, rest).map(TgpUri::Tgp),
+                "policy" => parse_policy_path(input, rest).map(TgpUri::Policy),
+                other => Err(UriParseError::new(input, format!("unsupported scheme {:?}", other))),
+            };
+        }
+
+        if trimmed.starts_with("0x") || trimmed.starts_with("0X") {
+            return parse_evm_address(input, trimmed).map(TgpUri::Evm);
+        }
+
+        let parts: Vec<&str> = trimmed.split(':').collect();
+        match parts.as_slice() {
+            [scheme, id] if scheme.eq_ignore_ascii_case("tai") => {
+                if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(UriParseError::new(input, "TAI id must be hex"));
+                }
+                Ok(TgpUri::Tai(id.to_ascii_lowercase()))
+            }
+            [namespace, reference, address] => parse_caip10(input, namespace, reference, address),
+            _ => Err(UriParseError::new(input, "unrecognized identifier form")),
+        }
+    }
+
+    /// Scheme or kind name, e.g. `"buyer"`, `"tai"`, `"caip10"`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TgpUri::Buyer(_) => "buyer",
+            TgpUri::Seller(_) => "seller",
+            TgpUri::Tgp(_) => "tgp",
+            TgpUri::Policy(_) => "policy",
+            TgpUri::Tai(_) => "tai",
+            TgpUri::Caip10 { .. } => "caip10",
+            TgpUri::Evm(_) => "evm",
+        }
+    }
+
+    /// EVM chain id of an `eip155` CAIP-10 account
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            TgpUri::Caip10 {
+                namespace,
+                reference,
+                ..
+            } if namespace == "eip155" => reference.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// On-chain address of an EVM address or CAIP-10 account
+    pub fn address(&self) -> Option<&str> {
+        match self {
+            TgpUri::Evm(address) | TgpUri::Caip10 { address, .. } => Some(address),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TgpUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TgpUri::Buyer(name) => write!(f, "buyer://{}", name),
+            TgpUri::Seller(name) => write!(f, "seller://{}", name),
+            TgpUri::Tgp(name) => write!(f, "tgp://{}", name),
+            TgpUri::Policy(path) => write!(f, "policy://{}", path),
+            TgpUri::Tai(id) => write!(f, "tai:{}", id),
+            TgpUri::Caip10 {
+                namespace,
+                reference,
+                address,
+            } => write!(f, "{}:{}:{}", namespace, reference, address),
+            TgpUri::Evm(address) => f.write_str(address),
+        }
+    }
+}
+
+impl FromStr for TgpUri {
+    type Err = UriParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TgpUri::parse(s)
+    }
+}
+
+impl Serialize for TgpUri {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TgpUri {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')
+}
+
+fn parse_name(input: &str, name: &str) -> Result<String, UriParseError> {
+    if name.is_empty() {
+        return Err(UriParseError::new(input, "name is empty"));
+    }
+    if !name.chars().all(is_name_char) {
+        return Err(UriParseError::new(
+            input,
+            "name may only contain letters, digits, '.', '_' and '-'",
+        ));
+    }
+    Ok(name.to_ascii_lowercase())
+}
+
+fn parse_policy_path(input: &str, path: &str) -> Result<String, UriParseError> {
+    let path = path.trim_end_matches('/');
+    if path.is_empty() {
+        return Err(UriParseError::new(input, "policy path is empty"));
+    }
+    if !path.split('/').all(|segment| !segment.is_empty() && segment.chars().all(is_name_char)) {
+        return Err(UriParseError::new(input, "invalid policy path segment"));
+    }
+    Ok(path.to_string())
+}
+
+fn parse_evm_address(input: &str, address: &str) -> Result<String, UriParseError> {
+    let hex = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .filter(|hex| hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .ok_or_else(|| {
+            UriParseError::new(input, "EVM address must be 0x followed by 40 hex digits")
+        })?;
+    Ok(format!("0x{}", hex.to_ascii_lowercase()))
+}
+
+/// CAIP-10: `[-a-z0-9]{3,8}:[-_a-zA-Z0-9]{1,32}:[-.%a-zA-Z0-9]{1,128}`
+fn parse_caip10(
+    input: &str,
+    namespace: &str,
+    reference: &str,
+    address: &str,
+) -> Result<TgpUri, UriParseError> {
+    let namespace_ok = (3..=8).contains(&namespace.len())
+        && namespace
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !namespace_ok {
+        return Err(UriParseError::new(input, "invalid CAIP-10 namespace"));
+    }
+
+    let reference_ok = (1..=32).contains(&reference.len())
+        && reference
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !reference_ok {
+        return Err(UriParseError::new(input, "invalid CAIP-10 chain reference"));
+    }
+
+    let address = if namespace == "eip155" {
+        if reference.parse::<u64>().is_err() {
+            return Err(UriParseError::new(input, "eip155 chain reference must be numeric"));
+        }
+        parse_evm_address(input, address)?
+    } else {
+        let address_ok = (1..=128).contains(&address.len())
+            && address
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '%'));
+        if !address_ok {
+            return Err(UriParseError::new(input, "invalid CAIP-10 account address"));
+        }
+        address.to_string()
+    };
+
+    Ok(TgpUri::Caip10 {
+        namespace: namespace.to_string(),
+        reference: reference.to_string(),
+        address,
+    })
+}
+
+// ============================================================================
+// PartyId
+// ============================================================================
+
+/// Identifier of a party in a TGP session (`QueryMessage.from` / `to`)
+///
+/// Any [`TgpUri`] except `policy://`, which names rules rather than parties.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct PartyId(TgpUri);
+
+impl PartyId {
+    pub fn parse(input: &str) -> Result<Self, UriParseError> {
+        Self::try_from(TgpUri::parse(input)?)
+    }
+
+    pub fn uri(&self) -> &TgpUri {
+        &self.0
+    }
+
+    pub fn into_uri(self) -> TgpUri {
+        self.0
+    }
+
+    pub fn is_buyer(&self) -> bool {
+        matches!(self.0, TgpUri::Buyer(_))
+    }
+
+    pub fn is_seller(&self) -> bool {
+        matches!(self.0, TgpUri::Seller(_))
+    }
+}
+
+impl TryFrom<TgpUri> for PartyId {
+    type Error = UriParseError;
+
+    fn try_from(uri: TgpUri) -> Result<Self, Self::Error> {
+        match uri {
+            TgpUri::Policy(_) => Err(UriParseError::new(
+                &uri.to_string(),
+                "policy references cannot identify a party",
+            )),
+            uri => Ok(PartyId(uri)),
+        }
+    }
+}
+
+impl fmt::Display for PartyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for PartyId {
+    type Err = UriParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PartyId::parse(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for PartyId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDR: &str = "0xAB5801a7D398351b8bE11C439e05C5B3259aeC9B";
+    const ADDR_LOWER: &str = "0xab5801a7d398351b8be11c439e05c5b3259aec9b";
+
+    #[test]
+    fn test_parse_all_forms() {
+        let cases = [
+            ("buyer://alice.wallet", TgpUri::Buyer("alice.wallet".into())),
+            ("SELLER://Bob", TgpUri::Seller("bob".into())),
+            ("tgp://proverouter", TgpUri::Tgp("proverouter".into())),
+            (
+                "policy://carrierA/us-ca/routing-fee-v1/",
+                TgpUri::Policy("carrierA/us-ca/routing-fee-v1".into()),
+            ),
+            ("tai:7ABF92C6", TgpUri::Tai("7abf92c6".into())),
+            (ADDR, TgpUri::Evm(ADDR_LOWER.into())),
+            (
+                "cosmos:cosmoshub-3:cosmos1t2uflqwqe0fsj0shcfkrvpukewcw40yjj6hdc0",
+                TgpUri::Caip10 {
+                    namespace: "cosmos".into(),
+                    reference: "cosmoshub-3".into(),
+                    address: "cosmos1t2uflqwqe0fsj0shcfkrvpukewcw40yjj6hdc0".into(),
+                },
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(TgpUri::parse(input).unwrap(), expected, "{}", input);
+        }
+
+        let account = TgpUri::parse(&format!("eip155:369:{}", ADDR)).unwrap();
+        assert_eq!(account.chain_id(), Some(369));
+        assert_eq!(account.address(), Some(ADDR_LOWER));
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        for input in [
+            "",
+            "buyer://",
+            "buyer://alice wallet",
+            "mailto://alice",
+            "tai:",
+            "tai:xyz",
+            "0x123",
+            "eip155:mainnet:0xab5801a7d398351b8be11c439e05c5b3259aec9b",
+            "eip155:1:0x123",
+            "EIP155:1:0xab5801a7d398351b8be11c439e05c5b3259aec9b",
+            "policy://a//b",
+            "alice",
+        ] {
+            assert!(TgpUri::parse(input).is_err(), "{:?} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_caip10_address_without_panic() {
+        for input in [
+            "eip155:1:",
+            "eip155:1:a",
+            "eip155:1:0",
+            "eip155:1:ab5801a7d398351b8be11c439e05c5b3259aec9b00",
+            "eip155:1:0xé",
+            "eip155:1:éé",
+            "eip155:1:0xab5801a7d398351b8be11c439e05c5b3259aec9é",
+            "eip155:1:0xg05801a7d398351b8be11c439e05c5b3259aec9b",
+        ] {
+            assert!(TgpUri::parse(input).is_err(), "{:?} should be rejected", input);
+        }
+
+        let upper = format!("eip155:1:0X{}", &ADDR_LOWER[2..]);
+        assert_eq!(TgpUri::parse(&upper).unwrap().address(), Some(ADDR_LOWER));
+    }
+
+    #[test]
+    fn test_display_and_serde_round_trip() {
+        for input in [
+            "buyer://alice.wallet",
+            "seller://bob",
+            "tgp://proverouter",
+            "policy://carrierA/us-ca/routing-fee-v1",
+            "tai:7abf92c6",
+            ADDR_LOWER,
+            "eip155:1:0xab5801a7d398351b8be11c439e05c5b3259aec9b",
+        ] {
+            let uri = TgpUri::parse(input).unwrap();
+            assert_eq!(uri.to_string(), input);
+            assert_eq!(TgpUri::parse(&uri.to_string()).unwrap(), uri);
+
+            let json = serde_json::to_string(&uri).unwrap();
+            assert_eq!(json, format!("\"{}\"", input));
+            assert_eq!(serde_json::from_str::<TgpUri>(&json).unwrap(), uri);
+        }
+    }
+
+    #[test]
+    fn test_party_id() {
+        let buyer = PartyId::parse("buyer://alice").unwrap();
+        assert!(buyer.is_buyer());
+        assert!(!buyer.is_seller());
+        assert_eq!(serde_json::to_string(&buyer).unwrap(), "\"buyer://alice\"");
+        assert_eq!(serde_json::from_str::<PartyId>("\"BUYER://Alice\"").unwrap(), buyer);
+
+        assert!(PartyId::parse("tai:7abf92c6").is_ok());
+        assert!(serde_json::from_str::<PartyId>("\"policy://x\"").is_err());
+    }
+}
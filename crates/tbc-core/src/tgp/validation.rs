@@ -16,6 +16,16 @@
 //! - [`validate_address`] - Check Ethereum address format
 //! - [`validate_transaction_hash`] - Check transaction hash format
 //! - [`validate_id_format`] - Check message ID format (optional)
+//! - [`validate_decimal_amount`] - Check canonical decimal strings (TGP-01 §4.2)
+//! - [`validate_hex_string`] - Check 0x-prefixed hex strings
+//! - [`validate_party_id`] - Check party identifiers (`buyer://`, `tai:`, CAIP-10, ...)
+//!
+//! # Validation Reports
+//!
+//! [`ValidationReport`] collects every violation of a message, each with a
+//! JSON-pointer path, a [`ValidationCode`] and a [`Severity`]. The message
+//! types expose it via `validation_report()`; `validate()` returns the first
+//! error of the same report.
 //!
 //! # Examples
 //!
@@ -33,6 +43,10 @@
 //! # Ok::<(), String>(())
 //! ```
 
+use serde::{Deserialize, Serialize};
+
+use super::uri::PartyId;
+
 // ============================================================================
 // Basic Validation Functions
 // ============================================================================
@@ -201,6 +215,109 @@ pub fn validate_transaction_hash(hash: &str, field_name: &str) -> Result<(), Str
     Ok(())
 }
 
+/// Validate a 0x-prefixed hex string of any non-zero length
+///
+/// Used for hashes and roots whose length is not fixed by the
+/// specification (e.g., `policy_root`).
+///
+/// # Arguments
+///
+/// * `value` - The hex string to validate
+/// * `field_name` - Name of the field (for error messages)
+///
+/// # Errors
+///
+/// Returns an error if the prefix is missing, no digits follow it, or a
+/// non-hex character is present.
+///
+/// # Examples
+///
+/// ```rust
+/// # use tbc_core::tgp::validation::validate_hex_string;
+/// assert!(validate_hex_string("0x9341aa23", "policy_root").is_ok());
+/// assert!(validate_hex_string("0x", "policy_root").is_err());
+/// assert!(validate_hex_string("9341aa23", "policy_root").is_err());
+/// ```
+pub fn validate_hex_string(value: &str, field_name: &str) -> Result<(), String> {
+    let Some(hex_part) = value.strip_prefix("0x") else {
+        return Err(format!(
+            "{} must be a hex string starting with 0x: {}",
+            field_name, value
+        ));
+    };
+
+    if hex_part.is_empty() || !hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "{} must contain only hexadecimal characters after 0x: {}",
+            field_name, value
+        ));
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Monetary Validation (TGP-01)
+// ============================================================================
+
+/// Validate a canonical decimal string
+///
+/// TGP-01 §4.2 requires monetary values to be canonical string decimals.
+/// A canonical decimal:
+/// - Consists of ASCII digits with at most one `.` separator
+/// - Has no sign, exponent, whitespace or thousands separators
+/// - Has no leading zeros in the integer part (except a single `0`)
+/// - Has at least one digit on each side of the `.`
+///
+/// Trailing fractional zeros are allowed (`"10000.00"`) because the
+/// specification uses them to convey precision.
+///
+/// # Arguments
+///
+/// * `value` - The decimal string to validate
+/// * `field_name` - Name of the field (for error messages)
+///
+/// # Errors
+///
+/// Returns an error if the string is not a canonical decimal.
+///
+/// # Examples
+///
+/// ```rust
+/// # use tbc_core::tgp::validation::validate_decimal_amount;
+/// assert!(validate_decimal_amount("10000.00", "price.amount").is_ok());
+/// assert!(validate_decimal_amount("0.5", "price.amount").is_ok());
+/// assert!(validate_decimal_amount("1e6", "price.amount").is_err());
+/// assert!(validate_decimal_amount("007", "price.amount").is_err());
+/// assert!(validate_decimal_amount("-1", "price.amount").is_err());
+/// ```
+pub fn validate_decimal_amount(value: &str, field_name: &str) -> Result<(), String> {
+    validate_non_empty(value, field_name)?;
+
+    let (int_part, frac_part) = match value.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (value, None),
+    };
+
+    let all_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    if !all_digits(int_part) || !frac_part.is_none_or(all_digits) {
+        return Err(format!(
+            "{} must be a canonical decimal string (e.g., \"10.00\"): {}",
+            field_name, value
+        ));
+    }
+
+    if int_part.len() > 1 && int_part.starts_with('0') {
+        return Err(format!(
+            "{} must not have leading zeros: {}",
+            field_name, value
+        ));
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // Optional Advanced Validation
 // ============================================================================
@@ -296,6 +413,35 @@ pub fn validate_url_format(url: &str, field_name: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Validate a party identifier
+///
+/// Accepts every [`PartyId`] form: `buyer://`, `seller://`, `tgp://`,
+/// `tai:`, CAIP-10 account ids and raw EVM addresses.
+///
+/// # Arguments
+///
+/// * `value` - The identifier to validate
+/// * `field_name` - Name of the field (for error messages)
+///
+/// # Errors
+///
+/// Returns an error if the identifier does not parse.
+///
+/// # Examples
+///
+/// ```rust
+/// # use tbc_core::tgp::validation::validate_party_id;
+/// assert!(validate_party_id("buyer://alice.wallet", "from").is_ok());
+/// assert!(validate_party_id("tai:7abf92c6", "to").is_ok());
+/// assert!(validate_party_id("alice", "from").is_err());
+/// ```
+pub fn validate_party_id(value: &str, field_name: &str) -> Result<(), String> {
+    validate_non_empty(value, field_name)?;
+    PartyId::parse(value)
+        .map(|_| ())
+        .map_err(|e| format!("{} {}", field_name, e))
+}
+
 /// Validate RFC3339 timestamp format (basic check)
 ///
 /// Performs a basic format check for RFC3339 timestamps.
@@ -390,6 +536,217 @@ pub fn validate_correlation_id(
     Ok(())
 }
 
+// ============================================================================
+// Validation Reports
+// ============================================================================
+
+/// Severity of a validation issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The message must be rejected
+    Error,
+
+    /// The message is accepted but likely not what the sender intended
+    Warning,
+}
+
+/// Machine-readable validation issue code
+///
+/// Serialized in SCREAMING_SNAKE_CASE to match TGP error codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ValidationCode {
+    /// Required field is missing or empty
+    Required,
+    /// Amount is zero or not a canonical decimal
+    InvalidAmount,
+    /// Malformed Ethereum address
+    InvalidAddress,
+    /// Malformed transaction hash or hex string
+    InvalidHash,
+    /// Malformed RFC3339 timestamp
+    InvalidTimestamp,
+    /// Value does not match the expected format
+    InvalidFormat,
+    /// Value outside its permitted range
+    OutOfRange,
+    /// Schema version not supported
+    UnsupportedVersion,
+    /// Fee denominated in a different asset than the price
+    AssetMismatch,
+    /// Fees exceed `max_fees_bps` of the price
+    FeeCapExceeded,
+    /// Fields contradict each other
+    Inconsistent,
+    /// Asset unknown to the registry or not settleable on the chain
+    UnsupportedAsset,
+}
+
+/// A single validation issue
+///
+/// `path` is a JSON pointer (RFC 6901) to the offending field of the
+/// message, e.g. `/economic_envelope/buyer_fee/amount`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub path: String,
+    pub code: ValidationCode,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Collects every validation issue of a message
+///
+/// Unlike the `validate()` methods, which stop at the first failure, a
+/// report lists all violations so a single ERROR message can describe every
+/// problem.
+///
+/// # Examples
+///
+/// ```rust
+/// use tbc_core::tgp::messages::QueryMessage;
+/// use tbc_core::tgp::types::ZkProfile;
+/// use tbc_core::tgp::validation::ValidationCode;
+///
+/// let query = QueryMessage::new("", "buyer://alice", "", "USDC", 0, ZkProfile::Optional);
+/// let report = query.validation_report();
+///
+/// assert!(!report.is_valid());
+/// let paths: Vec<&str> = report.errors().map(|i| i.path.as_str()).collect();
+/// assert_eq!(paths, vec!["/id", "/to", "/amount"]);
+/// assert_eq!(report.errors().last().unwrap().code, ValidationCode::InvalidAmount);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an error
+    pub fn error(
+        &mut self,
+        path: impl Into<String>,
+        code: ValidationCode,
+        message: impl Into<String>,
+    ) {
+        self.push(path.into(), code, Severity::Error, message.into());
+    }
+
+    /// Record a warning
+    pub fn warning(
+        &mut self,
+        path: impl Into<String>,
+        code: ValidationCode,
+        message: impl Into<String>,
+    ) {
+        self.push(path.into(), code, Severity::Warning, message.into());
+    }
+
+    /// Record the outcome of a field validator as an error at `parent/field`
+    ///
+    /// Returns `true` if the validator passed.
+    pub fn check(
+        &mut self,
+        parent: &str,
+        field: &str,
+        code: ValidationCode,
+        result: Result<(), String>,
+    ) -> bool {
+        match result {
+            Ok(()) => true,
+            Err(message) => {
+                self.error(json_pointer(parent, field), code, message);
+                false
+            }
+        }
+    }
+
+    fn push(&mut self, path: String, code: ValidationCode, severity: Severity, message: String) {
+        self.issues.push(ValidationIssue {
+            path,
+            code,
+            severity,
+            message,
+        });
+    }
+
+    /// Append all issues of another report
+    pub fn merge(&mut self, other: ValidationReport) {
+        self.issues.extend(other.issues);
+    }
+
+    /// `true` if there are no errors (warnings are allowed)
+    pub fn is_valid(&self) -> bool {
+        self.error_count() == 0
+    }
+
+    /// `true` if there are no issues at all
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning)
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.errors().count()
+    }
+
+    /// First error, if any
+    pub fn first_error(&self) -> Option<&ValidationIssue> {
+        self.errors().next()
+    }
+
+    /// Convert to the single-error form used by the `validate()` methods
+    ///
+    /// # Errors
+    ///
+    /// Returns the message of the first error.
+    pub fn to_result(&self) -> Result<(), String> {
+        match self.first_error() {
+            Some(issue) => Err(issue.message.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// Human-readable summary of all errors (`path: message; ...`)
+    pub fn summary(&self) -> String {
+        self.errors()
+            .map(|i| format!("{}: {}", i.path, i.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.summary())
+    }
+}
+
+/// Append `field` to a JSON pointer, escaping per RFC 6901
+///
+/// # Examples
+///
+/// ```rust
+/// # use tbc_core::tgp::validation::json_pointer;
+/// assert_eq!(json_pointer("", "id"), "/id");
+/// assert_eq!(json_pointer("/economic_envelope", "price"), "/economic_envelope/price");
+/// assert_eq!(json_pointer("", "a/b~c"), "/a~1b~0c");
+/// ```
+pub fn json_pointer(parent: &str, field: &str) -> String {
+    format!("{}/{}", parent, field.replace('~', "~0").replace('/', "~1"))
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -435,6 +792,37 @@ mod tests {
         assert!(validate_transaction_hash("9f2d8e7c3b1a5f4e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7a6f5e4d3c2b1a0f9e", "tx").is_err()); // No 0x
     }
 
+    #[test]
+    fn test_validate_hex_string() {
+        assert!(validate_hex_string("0x9341aa23", "policy_root").is_ok());
+        assert!(validate_hex_string("0xABCDEF", "policy_root").is_ok());
+
+        assert!(validate_hex_string("", "policy_root").is_err()); // Empty
+        assert!(validate_hex_string("0x", "policy_root").is_err()); // No digits
+        assert!(validate_hex_string("9341aa23", "policy_root").is_err()); // No 0x
+        assert!(validate_hex_string("0x9341..aa23", "policy_root").is_err()); // Invalid hex
+    }
+
+    #[test]
+    fn test_validate_decimal_amount() {
+        // Canonical decimals
+        assert!(validate_decimal_amount("0", "amount").is_ok());
+        assert!(validate_decimal_amount("10", "amount").is_ok());
+        assert!(validate_decimal_amount("10000.00", "amount").is_ok());
+        assert!(validate_decimal_amount("0.20", "amount").is_ok());
+
+        // Non-canonical decimals
+        assert!(validate_decimal_amount("", "amount").is_err()); // Empty
+        assert!(validate_decimal_amount("01", "amount").is_err()); // Leading zero
+        assert!(validate_decimal_amount(".5", "amount").is_err()); // No integer part
+        assert!(validate_decimal_amount("5.", "amount").is_err()); // No fraction digits
+        assert!(validate_decimal_amount("+5", "amount").is_err()); // Sign
+        assert!(validate_decimal_amount("1e3", "amount").is_err()); // Exponent
+        assert!(validate_decimal_amount("1,000", "amount").is_err()); // Separator
+        assert!(validate_decimal_amount("1.2.3", "amount").is_err()); // Two dots
+        assert!(validate_decimal_amount(" 1", "amount").is_err()); // Whitespace
+    }
+
     #[test]
     fn test_validate_id_format() {
         // Valid IDs with prefix
@@ -475,6 +863,46 @@ mod tests {
         assert!(validate_rfc3339_format("2025-11-10T23:59:59", "expiry").is_err()); // No timezone
     }
 
+    #[test]
+    fn test_validation_report() {
+        let mut report = ValidationReport::new();
+        assert!(report.is_valid() && report.is_empty());
+        assert_eq!(report.to_result(), Ok(()));
+
+        assert!(report.check("", "id", ValidationCode::Required, validate_non_empty("q-1", "id")));
+        report.warning("/escrow_from_402", ValidationCode::Inconsistent, "flag not set");
+        assert!(report.is_valid());
+
+        let amount = validate_decimal_amount("01", "price.amount");
+        assert!(!report.check("/price", "amount", ValidationCode::InvalidAmount, amount));
+        report.error("/asset", ValidationCode::Required, "asset cannot be empty");
+
+        assert!(!report.is_valid());
+        assert_eq!(report.error_count(), 2);
+        assert_eq!(report.warnings().count(), 1);
+        assert_eq!(report.first_error().unwrap().path, "/price/amount");
+        assert_eq!(
+            report.to_result(),
+            Err("price.amount must not have leading zeros: 01".to_string())
+        );
+        assert_eq!(
+            report.summary(),
+            "/price/amount: price.amount must not have leading zeros: 01; /asset: asset cannot be empty"
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["issues"][0]["severity"], "warning");
+        assert_eq!(json["issues"][1]["code"], "INVALID_AMOUNT");
+    }
+
+    #[test]
+    fn test_json_pointer_escaping() {
+        assert_eq!(json_pointer("", "id"), "/id");
+        assert_eq!(json_pointer("/a", "b"), "/a/b");
+        assert_eq!(json_pointer("", "x/y"), "/x~1y");
+        assert_eq!(json_pointer("", "m~n"), "/m~0n");
+    }
+
     #[test]
     fn test_validate_correlation_id() {
         // Valid correlation IDs
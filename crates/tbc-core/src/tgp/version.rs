@@ -0,0 +1,486 @@
+//# TGP Protocol Versions
+
+//**Destination Path:** `crates/tbc-core/src/tgp/version.rs`
+
+//**Implementation:** M2 - TGP-01 Economic Envelope & Proof-of-Settlement
+
+//! TGP version negotiation and compatibility shims
+//!
+//! TGP-01 is a strict superset of TGP-00: every addition is an optional
+//! field, so a TGP-01 peer can always read a TGP-00 message. The reverse
+//! is not true for strict TGP-00 agents, which is what the shims here are
+//! for.
+//!
+//! | Version | Additions |
+//! |---------|-----------|
+//! | `TGP-00` | QUERY / OFFER / SETTLE / ERROR, envelope `max_fees_bps` + `expiry` |
+//! | `TGP-01` | Envelope fee breakdown (§4), OFFER and ERROR extensions below |
+//!
+//! TGP-01 QUERY extensions are `tgp_path`; OFFER extensions are `routes`,
+//! `seller_reputation`, `decisions` and `tgp_path`; ERROR extensions are
+//! `details` and `decisions`.
+//!
+//! # Wire Format
+//!
+//! [`VersionedMessage`] adds a `tgp_version` field next to `phase`.
+//! Messages without it are read as TGP-00, which is what every agent built
+//! before versioning sends. Those agents advertise `"2.0"` in HELLO, which
+//! is accepted as a legacy alias of TGP-00.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::version::{negotiate_version, TgpVersion, SUPPORTED_TGP_VERSIONS};
+//!
+//! // An older buyer agent only speaks TGP-00
+//! let negotiated = negotiate_version(SUPPORTED_TGP_VERSIONS, &["TGP-00"]).unwrap();
+//! assert_eq!(negotiated, TgpVersion::Tgp00);
+//!
+//! let negotiated = negotiate_version(SUPPORTED_TGP_VERSIONS, &["TGP-01", "TGP-00"]).unwrap();
+//! assert_eq!(negotiated, TgpVersion::Tgp01);
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use super::messages::TGPMessage;
+use super::types::EconomicEnvelope;
+use super::validation::{json_pointer, ValidationCode, ValidationReport};
+
+// ============================================================================
+// TgpVersion
+// ============================================================================
+
+/// TGP protocol version
+///
+/// Ordered oldest to newest, so `max()` picks the most capable version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum TgpVersion {
+    /// TGP-00 base protocol
+    #[default]
+    Tgp00,
+
+    /// TGP-01 economic envelope extensions
+    Tgp01,
+}
+
+/// Versions this implementation can speak, oldest first
+pub const SUPPORTED_TGP_VERSIONS: &[TgpVersion] = &[TgpVersion::Tgp00, TgpVersion::Tgp01];
+
+impl TgpVersion {
+    /// Newest version this implementation emits
+    pub const CURRENT: TgpVersion = TgpVersion::Tgp01;
+
+    /// Wire identifier (`"TGP-00"`, `"TGP-01"`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TgpVersion::Tgp00 => "TGP-00",
+            TgpVersion::Tgp01 => "TGP-01",
+        }
+    }
+}
+
+impl fmt::Display for TgpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Version advertised by agents built before TGP-00/TGP-01 versioning
+pub const LEGACY_TGP_VERSION: &str = "2.0";
+
+impl FromStr for TgpVersion {
+    type Err = VersionError;
+
+    /// Parse a wire identifier, case-insensitively
+    ///
+    /// The pre-versioning identifier `"2.0"` parses as TGP-00.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "TGP-00" | LEGACY_TGP_VERSION => Ok(TgpVersion::Tgp00),
+            "TGP-01" => Ok(TgpVersion::Tgp01),
+            _ => Err(VersionError::Unknown(s.to_string())),
+        }
+    }
+}
+
+impl Serialize for TgpVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TgpVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Version negotiation errors
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum VersionError {
+    #[error("unknown TGP version: {0}")]
+    Unknown(String),
+
+    #[error("no common TGP version (local: {local}, remote: {remote})")]
+    NoCommonVersion { local: String, remote: String },
+}
+
+// ============================================================================
+// Negotiation
+// ============================================================================
+
+/// Pick the highest version both sides support
+///
+/// `remote` is taken as sent on the wire (e.g. `HelloPayload.supported_tgp_versions`);
+/// identifiers this implementation does not know are ignored.
+///
+/// # Errors
+///
+/// Returns [`VersionError::NoCommonVersion`] if the sets do not intersect.
+pub fn negotiate_version<S: AsRef<str>>(
+    local: &[TgpVersion],
+    remote: &[S],
+) -> Result<TgpVersion, VersionError> {
+    remote
+        .iter()
+        .filter_map(|v| v.as_ref().parse::<TgpVersion>().ok())
+        .filter(|v| local.contains(v))
+        .max()
+        .ok_or_else(|| VersionError::NoCommonVersion {
+            local: local.iter().map(|v| v.as_str()).collect::<Vec<_>>().join(","),
+            remote: remote.iter().map(|v| v.as_ref()).collect::<Vec<_>>().join(","),
+        })
+}
+
+// ============================================================================
+// Feature Detection
+// ============================================================================
+
+/// JSON pointers of every TGP-01 addition present in `message`
+///
+/// An empty result means the message is expressible in TGP-00.
+pub fn tgp01_fields(message: &TGPMessage) -> Vec<String> {
+    let mut fields = Vec::new();
+    match message {
+        TGPMessage::Query(query) => {
+            if query.tgp_path.is_some() {
+                fields.push("/tgp_path".to_string());
+            }
+        }
+        TGPMessage::Settle(_) => {}
+        TGPMessage::Offer(offer) => {
+            envelope_tgp01_fields(&offer.economic_envelope, "/economic_envelope", &mut fields);
+            if !offer.routes.is_empty() {
+                fields.push("/routes".to_string());
+            }
+            if offer.seller_reputation.is_some() {
+                fields.push("/seller_reputation".to_string());
+            }
+            if !offer.decisions.is_empty() {
+                fields.push("/decisions".to_string());
+            }
+            if offer.tgp_path.is_some() {
+                fields.push("/tgp_path".to_string());
+            }
+        }
+        TGPMessage::Error(error) => {
+            if !error.details.is_empty() {
+                fields.push("/details".to_string());
+            }
+            if !error.decisions.is_empty() {
+                fields.push("/decisions".to_string());
+            }
+        }
+    }
+    fields
+}
+
+fn envelope_tgp01_fields(envelope: &EconomicEnvelope, path: &str, fields: &mut Vec<String>) {
+    let present = [
+        ("ee_version", envelope.ee_version.is_some()),
+        ("price", envelope.price.is_some()),
+        ("buyer_fee", envelope.buyer_fee.is_some()),
+        ("routing_fee", envelope.routing_fee.is_some()),
+        ("timestamp", envelope.timestamp.is_some()),
+        ("policy_root", envelope.policy_root.is_some()),
+        ("sig", envelope.sig.is_some()),
+    ];
+    fields.extend(
+        present
+            .iter()
+            .filter(|(_, is_set)| *is_set)
+            .map(|(field, _)| json_pointer(path, field)),
+    );
+}
+
+/// Lowest version able to carry `message` without loss
+pub fn required_version(message: &TGPMessage) -> TgpVersion {
+    if tgp01_fields(message).is_empty() {
+        TgpVersion::Tgp00
+    } else {
+        TgpVersion::Tgp01
+    }
+}
+
+// ============================================================================
+// VersionedMessage
+// ============================================================================
+
+/// A TGP message tagged with the version it is written in
+///
+/// # Examples
+///
+/// ```rust
+/// use tbc_core::tgp::messages::{OfferMessage, TGPMessage};
+/// use tbc_core::tgp::types::{EconomicEnvelope, EnvelopePrice};
+/// use tbc_core::tgp::version::{TgpVersion, VersionedMessage};
+///
+/// let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 50);
+/// let offer = OfferMessage::new("offer-1", "q-1", "USDC", 30_000_000, false, envelope);
+/// let current = VersionedMessage::current(TGPMessage::Offer(offer));
+///
+/// // Answering a buyer that negotiated TGP-00
+/// let adapted = current.adapt(TgpVersion::Tgp00);
+/// assert_eq!(adapted.message.tgp_version, TgpVersion::Tgp00);
+/// assert!(adapted.dropped.contains(&"/economic_envelope/price".to_string()));
+/// assert!(adapted.message.validation_report().is_valid());
+///
+/// // Legacy agents do not send `tgp_version`
+/// let legacy: VersionedMessage = serde_json::from_str(
+///     r#"{"phase":"ERROR","id":"err-1","code":"TIMEOUT","message":"expired"}"#,
+/// ).unwrap();
+/// assert_eq!(legacy.tgp_version, TgpVersion::Tgp00);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VersionedMessage {
+    /// Version the message is written in; absent means TGP-00
+    #[serde(default)]
+    pub tgp_version: TgpVersion,
+
+    #[serde(flatten)]
+    pub message: TGPMessage,
+}
+
+/// Result of adapting a message to another version
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adapted {
+    pub message: VersionedMessage,
+
+    /// JSON pointers of fields removed by a downgrade
+    pub dropped: Vec<String>,
+}
+
+impl VersionedMessage {
+    pub fn new(tgp_version: TgpVersion, message: TGPMessage) -> Self {
+        Self { tgp_version, message }
+    }
+
+    /// Tag a message with [`TgpVersion::CURRENT`]
+    pub fn current(message: TGPMessage) -> Self {
+        Self::new(TgpVersion::CURRENT, message)
+    }
+
+    /// Validate the message and check it only uses fields of its version
+    ///
+    /// TGP-01 fields in a message declared TGP-00 are reported as
+    /// `UNSUPPORTED_VERSION` errors.
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut report = self.message.validation_report();
+        if self.tgp_version < TgpVersion::Tgp01 {
+            for field in tgp01_fields(&self.message) {
+                report.error(
+                    field,
+                    ValidationCode::UnsupportedVersion,
+                    format!("field requires TGP-01, message is {}", self.tgp_version),
+                );
+            }
+        }
+        report
+    }
+
+    /// Convert the message to `target`
+    ///
+    /// Upgrading only relabels, since TGP-01 adds nothing mandatory.
+    /// Downgrading strips the fields `target` cannot carry; the top-level
+    /// OFFER fields already mirror the rank 1 route, and `max_fees_bps`
+    /// still bounds the fees the buyer accepts.
+    pub fn adapt(&self, target: TgpVersion) -> Adapted {
+        let mut message = self.message.clone();
+        let dropped = if target < TgpVersion::Tgp01 {
+            let dropped = tgp01_fields(&message);
+            strip_tgp01(&mut message);
+            dropped
+        } else {
+            Vec::new()
+        };
+
+        Adapted {
+            message: VersionedMessage::new(target, message),
+            dropped,
+        }
+    }
+
+    /// Convert the message to `target`, dropping fields `target` lacks
+    pub fn downgrade(&self, target: TgpVersion) -> Adapted {
+        self.adapt(target.min(self.tgp_version))
+    }
+
+    /// Relabel the message as `target` if it is newer
+    pub fn upgrade(&self, target: TgpVersion) -> VersionedMessage {
+        self.adapt(target.max(self.tgp_version)).message
+    }
+}
+
+fn strip_tgp01(message: &mut TGPMessage) {
+    match message {
+        TGPMessage::Query(query) => query.tgp_path = None,
+        TGPMessage::Settle(_) => {}
+        TGPMessage::Offer(offer) => {
+            let envelope = &mut offer.economic_envelope;
+            *envelope = EconomicEnvelope {
+                expiry: envelope.expiry.take(),
+                ..EconomicEnvelope::new(envelope.max_fees_bps)
+            };
+            offer.routes.clear();
+            offer.seller_reputation = None;
+            offer.decisions.clear();
+            offer.tgp_path = None;
+        }
+        TGPMessage::Error(error) => {
+            error.details.clear();
+            error.decisions.clear();
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tgp::messages::{ErrorMessage, OfferMessage, QueryMessage};
+    use crate::tgp::routes::RouteOption;
+    use crate::tgp::types::{BuyerFee, EnvelopePrice, ZkProfile};
+
+    fn v1_offer() -> TGPMessage {
+        let envelope = EconomicEnvelope::v1(EnvelopePrice::new("30.00", "USDC"), 50)
+            .with_buyer_fee(BuyerFee::new("0.10", "USDC"));
+        let offer = OfferMessage::new("offer-1", "q-1", "USDC", 30_000_000, false, envelope)
+            .with_routes(vec![RouteOption {
+                rank: 1,
+                ..RouteOption::direct("pls", "USDC", 30_000_000, 369)
+            }]);
+        TGPMessage::Offer(offer)
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("TGP-00".parse::<TgpVersion>().unwrap(), TgpVersion::Tgp00);
+        assert_eq!("tgp-01".parse::<TgpVersion>().unwrap(), TgpVersion::Tgp01);
+        assert_eq!("2.0".parse::<TgpVersion>().unwrap(), TgpVersion::Tgp00);
+        assert!("3.0".parse::<TgpVersion>().is_err());
+        assert_eq!(TgpVersion::Tgp01.to_string(), "TGP-01");
+        assert!(TgpVersion::Tgp00 < TgpVersion::Tgp01);
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(
+            negotiate_version(SUPPORTED_TGP_VERSIONS, &["TGP-00", "TGP-01", "TGP-99"]).unwrap(),
+            TgpVersion::Tgp01
+        );
+        assert_eq!(
+            negotiate_version(&[TgpVersion::Tgp00], &["TGP-01", "TGP-00"]).unwrap(),
+            TgpVersion::Tgp00
+        );
+
+        assert_eq!(
+            negotiate_version(SUPPORTED_TGP_VERSIONS, &["2.0"]).unwrap(),
+            TgpVersion::Tgp00
+        );
+        let err = negotiate_version(SUPPORTED_TGP_VERSIONS, &["3.0"]).unwrap_err();
+        assert!(matches!(err, VersionError::NoCommonVersion { .. }));
+    }
+
+    #[test]
+    fn test_required_version() {
+        let query = QueryMessage::new("q-1", "buyer://a", "seller://b", "USDC", 1, ZkProfile::None);
+        assert_eq!(required_version(&TGPMessage::Query(query.clone())), TgpVersion::Tgp00);
+
+        let forwarded = TGPMessage::Query(query.with_tgp_path("tai:7abf92c6"));
+        assert_eq!(tgp01_fields(&forwarded), vec!["/tgp_path"]);
+        let adapted = VersionedMessage::current(forwarded).downgrade(TgpVersion::Tgp00);
+        assert_eq!(required_version(&adapted.message.message), TgpVersion::Tgp00);
+        assert_eq!(required_version(&v1_offer()), TgpVersion::Tgp01);
+        assert_eq!(
+            tgp01_fields(&v1_offer()),
+            vec![
+                "/economic_envelope/ee_version",
+                "/economic_envelope/price",
+                "/economic_envelope/buyer_fee",
+                "/routes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_downgrade_offer() {
+        let current = VersionedMessage::current(v1_offer());
+        let adapted = current.downgrade(TgpVersion::Tgp00);
+
+        assert_eq!(adapted.dropped.len(), 4);
+        assert_eq!(required_version(&adapted.message.message), TgpVersion::Tgp00);
+        assert!(adapted.message.validation_report().is_valid());
+
+        let TGPMessage::Offer(ref offer) = adapted.message.message else {
+            panic!("expected OFFER");
+        };
+        assert_eq!(offer.economic_envelope, EconomicEnvelope::new(50));
+        assert_eq!(offer.amount, 30_000_000);
+    }
+
+    #[test]
+    fn test_mislabelled_message_is_rejected() {
+        let mislabelled = VersionedMessage::new(TgpVersion::Tgp00, v1_offer());
+        let report = mislabelled.validation_report();
+
+        assert_eq!(report.error_count(), 4);
+        assert!(report.errors().all(|i| i.code == ValidationCode::UnsupportedVersion));
+    }
+
+    #[test]
+    fn test_upgrade_is_lossless() {
+        let error = ErrorMessage::new("err-1", "TIMEOUT", "expired");
+        let legacy = VersionedMessage::new(TgpVersion::Tgp00, TGPMessage::Error(error));
+        let upgraded = legacy.upgrade(TgpVersion::Tgp01);
+
+        assert_eq!(upgraded.tgp_version, TgpVersion::Tgp01);
+        assert_eq!(upgraded.message, legacy.message);
+        // never downgrades
+        assert_eq!(upgraded.upgrade(TgpVersion::Tgp00).tgp_version, TgpVersion::Tgp01);
+    }
+
+    #[test]
+    fn test_wire_format() {
+        let versioned = VersionedMessage::current(v1_offer());
+        let json = serde_json::to_value(&versioned).unwrap();
+        assert_eq!(json["tgp_version"], "TGP-01");
+        assert_eq!(json["phase"], "OFFER");
+
+        let decoded: VersionedMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, versioned);
+
+        // plain TGPMessage decoders ignore the extra field
+        let plain: TGPMessage = serde_json::to_string(&versioned)
+            .and_then(|s| serde_json::from_str(&s))
+            .unwrap();
+        assert_eq!(plain, versioned.message);
+    }
+}
@@ -0,0 +1,569 @@
+//! Property-based tests for TGP message decoding and validation
+//!
+//! Complements the handwritten unit tests in `tgp::validation` and
+//! `tgp::messages` with adversarial coverage:
+//!
+//! 1. Every `TGPMessage` survives a JSON round trip unchanged
+//! 2. `validate()` never panics, whatever the field contents
+//! 3. Every message accepted by validation re-serializes to an equal,
+//!    still-valid message
+//! 4. Mutated JSON (dropped keys, replaced values, garbage bytes) never
+//!    panics the decoder, and anything it does decode is stable
+//! 5. Downgrading to TGP-00 removes every TGP-01 field and keeps accepted
+//!    messages accepted
+//!
+//! The strategies deliberately mix well-formed values (so the "accepted"
+//! properties get exercised) with arbitrary strings and numbers.
+
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use serde_json::Value;
+
+use tbc_core::tgp::messages::{
+    ErrorMessage, OfferMessage, QueryMessage, SettleMessage, TGPMessage,
+};
+use tbc_core::tgp::routes::{RouteOption, SettlementMethod};
+use tbc_core::tgp::types::{
+    BuyerFee, EconomicEnvelope, EnvelopePrice, FeeParty, ProtocolFee, ProtocolFeeMode, RoutingFee,
+    SettleSource, ZkProfile,
+};
+use tbc_core::tgp::version::{tgp01_fields, TgpVersion, VersionedMessage};
+
+// ============================================================================
+// Field Strategies
+// ============================================================================
+
+fn any_text() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => "[a-z]{1,6}(-[a-z0-9]{1,12})?",
+        1 => Just(String::new()),
+        1 => ".{0,24}",
+    ]
+}
+
+fn party_uri() -> impl Strategy<Value = String> {
+    prop_oneof![
+        3 => "(buyer|seller)://[a-z]{1,10}",
+        1 => "tai:[0-9a-f]{8}",
+        1 => "eip155:[1-9][0-9]{0,4}:0x[0-9a-fA-F]{40}",
+        1 => any_text(),
+    ]
+}
+
+fn address() -> impl Strategy<Value = String> {
+    prop_oneof![
+        3 => "0x[0-9a-fA-F]{40}",
+        1 => "(0x)?[0-9a-zA-Z]{0,44}",
+    ]
+}
+
+fn tx_hash() -> impl Strategy<Value = String> {
+    prop_oneof![
+        3 => "0x[0-9a-f]{64}",
+        1 => "(0x)?[0-9a-zA-Z]{0,70}",
+    ]
+}
+
+fn asset() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => prop::sample::select(vec!["USDC", "USDT", "ETH", "PLS"]).prop_map(String::from),
+        1 => any_text(),
+    ]
+}
+
+fn decimal() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => "(0|[1-9][0-9]{0,6})\\.[0-9]{2}",
+        1 => "[1-9][0-9]{0,40}(\\.[0-9]{0,40})?",
+        1 => "[0-9.\\-e]{0,12}",
+    ]
+}
+
+fn ratio() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => "0\\.[0-9]{1,3}",
+        1 => Just("1".to_string()),
+        1 => decimal(),
+    ]
+}
+
+fn rfc3339() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => "20[2-3][0-9]-(0[1-9]|1[0-2])-(0[1-9]|1[0-9]|2[0-8])T([01][0-9]|2[0-3]):[0-5][0-9]:[0-5][0-9]Z",
+        1 => any_text(),
+    ]
+}
+
+fn fee_party() -> impl Strategy<Value = FeeParty> {
+    prop_oneof![
+        Just(FeeParty::Buyer),
+        Just(FeeParty::Seller),
+        Just(FeeParty::TbcOperator),
+    ]
+}
+
+fn protocol_fee_mode() -> impl Strategy<Value = ProtocolFeeMode> {
+    prop_oneof![
+        Just(ProtocolFeeMode::AutoProveBurn),
+        Just(ProtocolFeeMode::AutoBuyBurn)
+    ]
+}
+
+fn zk_profile() -> impl Strategy<Value = ZkProfile> {
+    prop_oneof![
+        Just(ZkProfile::None),
+        Just(ZkProfile::Optional),
+        Just(ZkProfile::Required),
+    ]
+}
+
+fn settle_source() -> impl Strategy<Value = SettleSource> {
+    prop_oneof![
+        Just(SettleSource::BuyerNotify),
+        Just(SettleSource::ControllerWatcher),
+        Just(SettleSource::CoreproverIndexer),
+    ]
+}
+
+fn settlement_method() -> impl Strategy<Value = SettlementMethod> {
+    prop_oneof![Just(SettlementMethod::Escrow), Just(SettlementMethod::DirectX402)]
+}
+
+// ============================================================================
+// Envelope Strategies
+// ============================================================================
+
+fn protocol_fee() -> BoxedStrategy<ProtocolFee> {
+    (
+        ratio(),
+        prop_oneof!["tai:[0-9a-f]{8}", "tgp://[a-z]{3,10}", any_text()],
+        protocol_fee_mode(),
+        option::of(asset()),
+    )
+        .prop_map(|(ratio, dest, mode, asset)| ProtocolFee {
+            ratio,
+            dest,
+            mode,
+            asset,
+        })
+        .boxed()
+}
+
+fn envelope_price() -> BoxedStrategy<EnvelopePrice> {
+    (decimal(), asset(), fee_party(), fee_party())
+        .prop_map(|(amount, asset, payer, payee)| EnvelopePrice {
+            amount,
+            asset,
+            payer,
+            payee,
+        })
+        .boxed()
+}
+
+fn buyer_fee() -> BoxedStrategy<BuyerFee> {
+    (
+        decimal(),
+        asset(),
+        fee_party(),
+        fee_party(),
+        option::of(protocol_fee()),
+    )
+        .prop_map(|(amount, asset, payer, payee, protocol_fee)| BuyerFee {
+            amount,
+            asset,
+            payer,
+            payee,
+            protocol_fee,
+        })
+        .boxed()
+}
+
+fn routing_fee() -> BoxedStrategy<RoutingFee> {
+    (
+        decimal(),
+        asset(),
+        fee_party(),
+        fee_party(),
+        option::of(any_text()),
+    )
+        .prop_map(|(amount, asset, payer, payee, policy_ref)| RoutingFee {
+            amount,
+            asset,
+            payer,
+            payee,
+            policy_ref,
+        })
+        .boxed()
+}
+
+fn economic_envelope() -> BoxedStrategy<EconomicEnvelope> {
+    (
+        prop_oneof![4 => 0u32..=10_000, 1 => any::<u32>()],
+        option::of(rfc3339()),
+        option::of(prop_oneof![4 => Just("1.0".to_string()), 1 => any_text()]),
+        option::of(envelope_price()),
+        option::of(buyer_fee()),
+        option::of(routing_fee()),
+        option::of(rfc3339()),
+        option::of(prop_oneof!["0x[0-9a-f]{64}", any_text()]),
+        option::of(any_text()),
+    )
+        .prop_map(
+            |(
+                max_fees_bps,
+                expiry,
+                ee_version,
+                price,
+                buyer_fee,
+                routing_fee,
+                timestamp,
+                policy_root,
+                sig,
+            )| {
+                EconomicEnvelope {
+                    max_fees_bps,
+                    expiry,
+                    ee_version,
+                    price,
+                    buyer_fee,
+                    routing_fee,
+                    timestamp,
+                    policy_root,
+                    sig,
+                }
+            },
+        )
+        .boxed()
+}
+
+// ============================================================================
+// Message Strategies
+// ============================================================================
+
+fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![4 => 1u64..=10_000_000_000, 1 => any::<u64>()]
+}
+
+fn query_message() -> BoxedStrategy<QueryMessage> {
+    (
+        any_text(),
+        party_uri(),
+        party_uri(),
+        asset(),
+        amount(),
+        any::<bool>(),
+        option::of(address()),
+        zk_profile(),
+    )
+        .prop_map(
+            |(
+                id,
+                from,
+                to,
+                asset,
+                amount,
+                escrow_from_402,
+                escrow_contract_from_402,
+                zk_profile,
+            )| {
+                QueryMessage {
+                    id,
+                    from,
+                    to,
+                    asset,
+                    amount,
+                    escrow_from_402,
+                    escrow_contract_from_402,
+                    zk_profile,
+                    tgp_path: None,
+                }
+            },
+        )
+        .boxed()
+}
+
+fn route_option() -> BoxedStrategy<RouteOption> {
+    (
+        any_text(),
+        0u32..=4,
+        asset(),
+        amount(),
+        prop_oneof![Just(1u64), Just(369u64), Just(8453u64), any::<u64>()],
+        settlement_method(),
+        option::of(address()),
+        prop_oneof![4 => 0u32..=10_000, 1 => any::<u32>()],
+        option::of(rfc3339()),
+    )
+        .prop_map(
+            |(
+                route_id,
+                rank,
+                asset,
+                amount,
+                chain_id,
+                method,
+                coreprover_contract,
+                fee_estimate_bps,
+                expiry,
+            )| {
+                RouteOption {
+                    route_id,
+                    rank,
+                    asset,
+                    amount,
+                    chain_id,
+                    method,
+                    coreprover_contract,
+                    fee_estimate_bps,
+                    expiry,
+                }
+            },
+        )
+        .boxed()
+}
+
+fn offer_message() -> BoxedStrategy<OfferMessage> {
+    (
+        any_text(),
+        any_text(),
+        asset(),
+        amount(),
+        option::of(address()),
+        option::of(any_text()),
+        any::<bool>(),
+        economic_envelope(),
+        prop::collection::vec(route_option(), 0..3),
+    )
+        .prop_map(
+            |(
+                id,
+                query_id,
+                asset,
+                amount,
+                coreprover_contract,
+                session_id,
+                zk_required,
+                economic_envelope,
+                routes,
+            )| {
+                OfferMessage {
+                    id,
+                    query_id,
+                    asset,
+                    amount,
+                    coreprover_contract,
+                    session_id,
+                    zk_required,
+                    economic_envelope,
+                    routes,
+                    seller_reputation: None,
+                    tgp_path: None,
+                    decisions: Vec::new(),
+                }
+            },
+        )
+        .boxed()
+}
+
+fn settle_message() -> BoxedStrategy<SettleMessage> {
+    (
+        any_text(),
+        any_text(),
+        any::<bool>(),
+        settle_source(),
+        option::of(tx_hash()),
+        option::of(any_text()),
+    )
+        .prop_map(
+            |(id, query_or_offer_id, success, source, layer8_tx, session_id)| SettleMessage {
+                id,
+                query_or_offer_id,
+                success,
+                source,
+                layer8_tx,
+                session_id,
+            },
+        )
+        .boxed()
+}
+
+fn error_message() -> BoxedStrategy<ErrorMessage> {
+    (any_text(), any_text(), any_text(), option::of(any_text()))
+        .prop_map(|(id, code, message, correlation_id)| ErrorMessage {
+            id,
+            code,
+            message,
+            correlation_id,
+            details: Vec::new(),
+            decisions: Vec::new(),
+        })
+        .boxed()
+}
+
+fn tgp_message() -> BoxedStrategy<TGPMessage> {
+    prop_oneof![
+        query_message().prop_map(TGPMessage::Query),
+        offer_message().prop_map(TGPMessage::Offer),
+        settle_message().prop_map(TGPMessage::Settle),
+        error_message().prop_map(TGPMessage::Error),
+    ]
+    .boxed()
+}
+
+// ============================================================================
+// JSON Mutations
+// ============================================================================
+
+fn json_scalar() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        ".{0,16}".prop_map(Value::String),
+        Just(Value::Array(vec![])),
+        Just(Value::Object(Default::default())),
+    ]
+}
+
+#[derive(Debug, Clone)]
+enum Mutation {
+    Remove(Index),
+    Replace(Index, Value),
+    Insert(String, Value),
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    prop_oneof![
+        any::<Index>().prop_map(Mutation::Remove),
+        (any::<Index>(), json_scalar()).prop_map(|(idx, v)| Mutation::Replace(idx, v)),
+        ("[a-z_]{1,12}", json_scalar()).prop_map(|(k, v)| Mutation::Insert(k, v)),
+    ]
+}
+
+/// Apply a mutation to a key of the object at `value` or any nested object
+fn mutate(value: &mut Value, mutation: &Mutation, depth: &Index) {
+    let Some(map) = value.as_object_mut() else {
+        return;
+    };
+    if map.is_empty() {
+        return;
+    }
+
+    // Occasionally descend into a nested object (e.g., economic_envelope)
+    let keys: Vec<String> = map.keys().cloned().collect();
+    let nested = keys.iter().find(|k| map[k.as_str()].is_object()).cloned();
+    if let Some(key) = nested {
+        if depth.index(2) == 1 {
+            return mutate(map.get_mut(&key).unwrap(), mutation, depth);
+        }
+    }
+
+    match mutation {
+        Mutation::Remove(idx) => {
+            map.remove(idx.get(&keys));
+        }
+        Mutation::Replace(idx, v) => {
+            map.insert(idx.get(&keys).clone(), v.clone());
+        }
+        Mutation::Insert(k, v) => {
+            map.insert(k.clone(), v.clone());
+        }
+    }
+}
+
+/// Decoding anything must be stable: decode(encode(m)) == m
+fn assert_stable(message: &TGPMessage) -> Result<(), TestCaseError> {
+    let json = serde_json::to_string(message).expect("decoded message must serialize");
+    let reparsed: TGPMessage = serde_json::from_str(&json).expect("serialized message must decode");
+    prop_assert_eq!(&reparsed, message);
+    Ok(())
+}
+
+// ============================================================================
+// Properties
+// ============================================================================
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn prop_round_trip(message in tgp_message()) {
+        assert_stable(&message)?;
+    }
+
+    #[test]
+    fn prop_validate_never_panics(message in tgp_message()) {
+        let _ = message.validate();
+    }
+
+    #[test]
+    fn prop_validate_agrees_with_report(message in tgp_message()) {
+        let report = message.validation_report();
+        prop_assert_eq!(message.validate().is_ok(), report.is_valid());
+        for issue in &report.issues {
+            prop_assert!(issue.path.starts_with('/'));
+        }
+    }
+
+    #[test]
+    fn prop_accepted_messages_reserialize_equal(message in tgp_message()) {
+        if message.validate().is_ok() {
+            let value = serde_json::to_value(&message).unwrap();
+            let reparsed: TGPMessage = serde_json::from_value(value).unwrap();
+            prop_assert_eq!(&reparsed, &message);
+            prop_assert!(reparsed.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn prop_json_mutations_never_panic(
+        message in tgp_message(),
+        mutations in prop::collection::vec((mutation(), any::<Index>()), 1..4),
+    ) {
+        let mut value = serde_json::to_value(&message).unwrap();
+        for (mutation, depth) in &mutations {
+            mutate(&mut value, mutation, depth);
+        }
+
+        if let Ok(decoded) = serde_json::from_value::<TGPMessage>(value) {
+            let _ = decoded.validate();
+            assert_stable(&decoded)?;
+        }
+    }
+
+    #[test]
+    fn prop_downgrade_to_tgp00(message in tgp_message()) {
+        let adapted = VersionedMessage::current(message.clone()).downgrade(TgpVersion::Tgp00);
+        prop_assert!(tgp01_fields(&adapted.message.message).is_empty());
+        prop_assert_eq!(adapted.dropped, tgp01_fields(&message));
+        if message.validate().is_ok() {
+            prop_assert!(adapted.message.validation_report().is_valid());
+        }
+
+        let value = serde_json::to_value(&adapted.message).unwrap();
+        let reparsed: VersionedMessage = serde_json::from_value(value).unwrap();
+        prop_assert_eq!(reparsed, adapted.message);
+    }
+
+    #[test]
+    fn prop_caip10_parties_never_panic(
+        message in query_message(),
+        reference in "[0-9]{1,4}|.{0,6}",
+        address in "(0[xX])?.{0,44}",
+    ) {
+        let mut message = message;
+        message.from = format!("eip155:{}:{}", reference, address);
+        message.to = format!("eip155:{}:{}", reference, address);
+        let _ = TGPMessage::Query(message).validate();
+    }
+
+    #[test]
+    fn prop_arbitrary_bytes_never_panic(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        if let Ok(decoded) = serde_json::from_slice::<TGPMessage>(&bytes) {
+            let _ = decoded.validate();
+            assert_stable(&decoded)?;
+        }
+    }
+}
@@ -1,22 +1,82 @@
 //! Agent coordination
+//!
+//! An [`Agent`] works a bounded number of orders at a time. The
+//! [`AgentPool`] tracks registered agents and their heartbeats, assigns
+//! orders under an [`AssignmentStrategy`], releases them on settlement and
+//! fails orders over from agents that stop heartbeating.
+//!
+//! Heartbeats are only checked when [`AgentPool::check_heartbeats`] runs.
+//! Controllers start [`spawn_heartbeat_monitor`] next to the pool; without
+//! it an agent that goes silent keeps its orders.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use tbc_core::tgp::clock::ManualClock;
+//! use tbc_gateway::agent::{Agent, AgentPool};
+//!
+//! let clock = Arc::new(ManualClock::new(1_000));
+//! let pool = AgentPool::new().with_heartbeat_timeout(30).with_clock(clock.clone());
+//! pool.register(Agent::new("agent-a".to_string()).with_capacity(2)).unwrap();
+//! pool.register(Agent::new("agent-b".to_string()).with_capacity(2)).unwrap();
+//!
+//! // Least-loaded: the second order goes to the idle agent
+//! let first = pool.assign("order-1").unwrap();
+//! let second = pool.assign("order-2").unwrap();
+//! assert_ne!(first, second);
+//!
+//! // Only agent-b keeps heartbeating; agent-a's order fails over to it
+//! clock.advance(31);
+//! pool.heartbeat("agent-b").unwrap();
+//! let failovers = pool.check_heartbeats();
+//! assert_eq!(failovers.len(), 1);
+//! assert_eq!(pool.agent_for("order-1").as_deref(), Some("agent-b"));
+//! ```
 
-use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use sha2::{Digest, Sha256};
+use tbc_core::tgp::clock::{Clock, SystemClock};
+use thiserror::Error;
+use tokio::task::JoinHandle;
 
 /// Agent identifier
 pub type AgentId = String;
 
+/// Seconds without a heartbeat after which an agent is considered gone
+pub const DEFAULT_HEARTBEAT_TIMEOUT_SECS: u64 = 30;
+
 /// Agent status
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AgentStatus {
+    /// Accepting orders
     Active,
+
+    /// Not heartbeating; receives no orders
     Inactive,
+
+    /// At capacity
     Busy,
 }
 
 /// Agent coordinator
+#[derive(Debug, Clone)]
 pub struct Agent {
     pub id: AgentId,
     pub status: AgentStatus,
+
+    /// Maximum number of concurrent orders
+    pub capacity: usize,
+
+    /// Orders currently assigned
+    pub orders: BTreeSet<String>,
+
+    /// Unix time of the last heartbeat
+    pub last_heartbeat: u64,
 }
 
 impl Agent {
@@ -24,17 +84,462 @@ impl Agent {
         Self {
             id,
             status: AgentStatus::Active,
+            capacity: 1,
+            orders: BTreeSet::new(),
+            last_heartbeat: 0,
         }
     }
-    
+
+    /// Builder method to set the number of concurrent orders
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
     /// Check if agent is available
     pub fn is_available(&self) -> bool {
         matches!(self.status, AgentStatus::Active)
     }
-    
+
+    /// Number of orders currently assigned
+    pub fn load(&self) -> usize {
+        self.orders.len()
+    }
+
     /// Assign order to agent
-    pub async fn assign_order(&mut self, _order_id: &str) -> Result<()> {
-        self.status = AgentStatus::Busy;
+    pub async fn assign_order(&mut self, order_id: &str) -> Result<()> {
+        if !self.is_available() {
+            bail!("agent {} is {:?}", self.id, self.status);
+        }
+        self.orders.insert(order_id.to_string());
+        self.refresh_status();
+        Ok(())
+    }
+
+    /// Release a finished order
+    ///
+    /// Returns false if the order was not assigned to this agent.
+    pub fn release_order(&mut self, order_id: &str) -> bool {
+        let released = self.orders.remove(order_id);
+        self.refresh_status();
+        released
+    }
+
+    fn refresh_status(&mut self) {
+        if self.status == AgentStatus::Inactive {
+            return;
+        }
+        self.status = if self.load() >= self.capacity {
+            AgentStatus::Busy
+        } else {
+            AgentStatus::Active
+        };
+    }
+}
+
+// ============================================================================
+// AgentPool
+// ============================================================================
+
+/// How the pool picks an agent for a new order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AssignmentStrategy {
+    /// Agent with the lowest load relative to its capacity
+    #[default]
+    LeastLoaded,
+
+    /// Rendezvous hash of order and agent id, so an order keeps landing on
+    /// the same agent while the pool is unchanged
+    ConsistentHash,
+}
+
+/// Errors from pool operations
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PoolError {
+    #[error("agent {0} is already registered")]
+    DuplicateAgent(AgentId),
+
+    #[error("unknown agent {0}")]
+    UnknownAgent(AgentId),
+
+    #[error("no agent has capacity for order {0}")]
+    NoCapacity(String),
+}
+
+/// Order moved off an agent that stopped heartbeating
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failover {
+    pub order_id: String,
+    pub from: AgentId,
+
+    /// New agent, `None` if no agent had capacity and the order was dropped
+    pub to: Option<AgentId>,
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    agents: BTreeMap<AgentId, Agent>,
+    assignments: HashMap<String, AgentId>,
+}
+
+/// Registered agents and their order assignments
+pub struct AgentPool {
+    state: RwLock<PoolState>,
+    strategy: AssignmentStrategy,
+    heartbeat_timeout: u64,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for AgentPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AgentPool {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(PoolState::default()),
+            strategy: AssignmentStrategy::default(),
+            heartbeat_timeout: DEFAULT_HEARTBEAT_TIMEOUT_SECS,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Builder method to set the assignment strategy
+    pub fn with_strategy(mut self, strategy: AssignmentStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Builder method to set the heartbeat timeout in seconds
+    pub fn with_heartbeat_timeout(mut self, seconds: u64) -> Self {
+        self.heartbeat_timeout = seconds;
+        self
+    }
+
+    /// Builder method to inject the clock used for heartbeats
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Add an agent; registration counts as its first heartbeat
+    pub fn register(&self, mut agent: Agent) -> Result<(), PoolError> {
+        let mut state = self.state.write().unwrap();
+        if state.agents.contains_key(&agent.id) {
+            return Err(PoolError::DuplicateAgent(agent.id));
+        }
+        for order_id in &agent.orders {
+            state.assignments.insert(order_id.clone(), agent.id.clone());
+        }
+        agent.last_heartbeat = self.clock.now_unix();
+        agent.status = AgentStatus::Active;
+        agent.refresh_status();
+        state.agents.insert(agent.id.clone(), agent);
+        Ok(())
+    }
+
+    /// Remove an agent, failing its orders over to the others
+    pub fn deregister(&self, agent_id: &str) -> Result<Vec<Failover>, PoolError> {
+        let mut state = self.state.write().unwrap();
+        let agent = state
+            .agents
+            .remove(agent_id)
+            .ok_or_else(|| PoolError::UnknownAgent(agent_id.to_string()))?;
+        Ok(self.fail_over(&mut state, agent))
+    }
+
+    /// Record a heartbeat, reviving an inactive agent
+    pub fn heartbeat(&self, agent_id: &str) -> Result<(), PoolError> {
+        let mut state = self.state.write().unwrap();
+        let agent = state
+            .agents
+            .get_mut(agent_id)
+            .ok_or_else(|| PoolError::UnknownAgent(agent_id.to_string()))?;
+        agent.last_heartbeat = self.clock.now_unix();
+        if agent.status == AgentStatus::Inactive {
+            agent.status = AgentStatus::Active;
+            agent.refresh_status();
+        }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Assign an order, or return the agent already holding it
+    pub fn assign(&self, order_id: &str) -> Result<AgentId, PoolError> {
+        let mut state = self.state.write().unwrap();
+        if let Some(agent_id) = state.assignments.get(order_id) {
+            return Ok(agent_id.clone());
+        }
+        self.place(&mut state, order_id, None)
+            .ok_or_else(|| PoolError::NoCapacity(order_id.to_string()))
+    }
+
+    /// Release a settled order
+    ///
+    /// Returns the agent that held it.
+    pub fn release(&self, order_id: &str) -> Option<AgentId> {
+        let mut state = self.state.write().unwrap();
+        let agent_id = state.assignments.remove(order_id)?;
+        if let Some(agent) = state.agents.get_mut(&agent_id) {
+            agent.release_order(order_id);
+        }
+        Some(agent_id)
+    }
+
+    /// Agent holding an order
+    pub fn agent_for(&self, order_id: &str) -> Option<AgentId> {
+        self.state.read().unwrap().assignments.get(order_id).cloned()
+    }
+
+    /// Snapshot of one agent
+    pub fn agent(&self, agent_id: &str) -> Option<Agent> {
+        self.state.read().unwrap().agents.get(agent_id).cloned()
+    }
+
+    /// Snapshot of all agents, ordered by id
+    pub fn agents(&self) -> Vec<Agent> {
+        self.state.read().unwrap().agents.values().cloned().collect()
+    }
+
+    /// Number of assigned orders
+    pub fn active_orders(&self) -> usize {
+        self.state.read().unwrap().assignments.len()
+    }
+
+    /// Mark agents past the heartbeat timeout inactive and fail their
+    /// orders over to live agents
+    pub fn check_heartbeats(&self) -> Vec<Failover> {
+        let now = self.clock.now_unix();
+        let mut state = self.state.write().unwrap();
+        let stale: Vec<AgentId> = state
+            .agents
+            .values()
+            .filter(|a| a.status != AgentStatus::Inactive && !self.is_alive(a, now))
+            .map(|a| a.id.clone())
+            .collect();
+
+        let mut failovers = Vec::new();
+        for agent_id in stale {
+            let agent = state.agents.get_mut(&agent_id).unwrap();
+            agent.status = AgentStatus::Inactive;
+            let orphaned = Agent {
+                orders: std::mem::take(&mut agent.orders),
+                ..agent.clone()
+            };
+            tracing::warn!(agent = %agent_id, orders = orphaned.load(), "agent missed heartbeats");
+            failovers.extend(self.fail_over(&mut state, orphaned));
+        }
+        failovers
+    }
+
+    fn is_alive(&self, agent: &Agent, now: u64) -> bool {
+        now.saturating_sub(agent.last_heartbeat) <= self.heartbeat_timeout
+    }
+
+    /// Move every order of `agent` (no longer in the pool's candidates)
+    fn fail_over(&self, state: &mut PoolState, agent: Agent) -> Vec<Failover> {
+        agent
+            .orders
+            .iter()
+            .map(|order_id| {
+                state.assignments.remove(order_id);
+                let to = self.place(state, order_id, Some(&agent.id));
+                Failover {
+                    order_id: order_id.clone(),
+                    from: agent.id.clone(),
+                    to,
+                }
+            })
+            .collect()
+    }
+
+    /// Pick an agent for `order_id` and record the assignment
+    fn place(
+        &self,
+        state: &mut PoolState,
+        order_id: &str,
+        exclude: Option<&str>,
+    ) -> Option<AgentId> {
+        let now = self.clock.now_unix();
+        let candidates = state.agents.values().filter(|a| {
+            a.is_available() && self.is_alive(a, now) && Some(a.id.as_str()) != exclude
+        });
+
+        let chosen = match self.strategy {
+            AssignmentStrategy::LeastLoaded => candidates
+                .min_by(|a, b| {
+                    (a.load() * b.capacity)
+                        .cmp(&(b.load() * a.capacity))
+                        .then_with(|| a.id.cmp(&b.id))
+                })?
+                .id
+                .clone(),
+            AssignmentStrategy::ConsistentHash => candidates
+                .max_by_key(|a| rendezvous_score(order_id, &a.id))?
+                .id
+                .clone(),
+        };
+
+        let agent = state.agents.get_mut(&chosen).unwrap();
+        agent.orders.insert(order_id.to_string());
+        agent.refresh_status();
+        state.assignments.insert(order_id.to_string(), chosen.clone());
+        Some(chosen)
+    }
+}
+
+/// Rendezvous (highest random weight) score of an order on an agent
+fn rendezvous_score(order_id: &str, agent_id: &str) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(order_id.as_bytes())
+        .chain_update([0])
+        .chain_update(agent_id.as_bytes())
+        .finalize();
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+/// Check the heartbeats of `pool` every `every`
+///
+/// Half the pool's heartbeat timeout is a sensible interval.
+pub fn spawn_heartbeat_monitor(pool: Arc<AgentPool>, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            for failover in pool.check_heartbeats() {
+                if failover.to.is_none() {
+                    tracing::warn!(order = %failover.order_id, "no agent left for order");
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tbc_core::tgp::clock::ManualClock;
+
+    fn pool(
+        strategy: AssignmentStrategy,
+        agents: &[(&str, usize)],
+    ) -> (AgentPool, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let pool = AgentPool::new().with_strategy(strategy).with_clock(clock.clone());
+        for (id, capacity) in agents {
+            pool.register(Agent::new(id.to_string()).with_capacity(*capacity)).unwrap();
+        }
+        (pool, clock)
+    }
+
+    #[tokio::test]
+    async fn test_agent_capacity() {
+        let mut agent = Agent::new("agent-001".to_string()).with_capacity(2);
+        agent.assign_order("order-1").await.unwrap();
+        assert!(agent.is_available());
+        agent.assign_order("order-2").await.unwrap();
+        assert_eq!(agent.status, AgentStatus::Busy);
+        assert!(agent.assign_order("order-3").await.is_err());
+
+        assert!(agent.release_order("order-1"));
+        assert!(!agent.release_order("order-1"));
+        assert_eq!(agent.status, AgentStatus::Active);
+    }
+
+    #[test]
+    fn test_least_loaded_and_release() {
+        let (pool, _) = pool(AssignmentStrategy::LeastLoaded, &[("a", 1), ("b", 3)]);
+        let duplicate = pool.register(Agent::new("a".to_string()));
+        assert_eq!(duplicate, Err(PoolError::DuplicateAgent("a".into())));
+
+        assert_eq!(pool.assign("o-1").unwrap(), "a");
+        assert_eq!(pool.assign("o-1").unwrap(), "a");
+        assert_eq!(pool.assign("o-2").unwrap(), "b");
+        assert_eq!(pool.assign("o-3").unwrap(), "b");
+        assert_eq!(pool.assign("o-4").unwrap(), "b");
+        assert_eq!(pool.assign("o-5"), Err(PoolError::NoCapacity("o-5".into())));
+        assert_eq!(pool.active_orders(), 4);
+
+        assert_eq!(pool.release("o-1").as_deref(), Some("a"));
+        assert_eq!(pool.release("o-1"), None);
+        assert_eq!(pool.agent("a").unwrap().status, AgentStatus::Active);
+        assert_eq!(pool.assign("o-5").unwrap(), "a");
+    }
+
+    #[test]
+    fn test_consistent_hash_is_stable() {
+        let agents = [("a", 100), ("b", 100), ("c", 100)];
+        let (first, _) = pool(AssignmentStrategy::ConsistentHash, &agents);
+        let (second, _) = pool(AssignmentStrategy::ConsistentHash, &agents);
+
+        let orders: Vec<String> = (0..30).map(|i| format!("order-{}", i)).collect();
+        for order in &orders {
+            assert_eq!(first.assign(order), second.assign(order));
+        }
+        let used: BTreeSet<_> = orders.iter().filter_map(|o| first.agent_for(o)).collect();
+        assert_eq!(used.len(), 3);
+
+        // Removing an agent only moves the orders it held
+        let before: Vec<_> = orders.iter().map(|o| first.agent_for(o).unwrap()).collect();
+        first.deregister("c").unwrap();
+        for (order, agent) in orders.iter().zip(before) {
+            if agent != "c" {
+                assert_eq!(first.agent_for(order).unwrap(), agent);
+            }
+        }
+    }
+
+    #[test]
+    fn test_heartbeat_failover() {
+        let (pool, clock) = pool(AssignmentStrategy::LeastLoaded, &[("a", 1), ("b", 1)]);
+        pool.assign("o-1").unwrap();
+        pool.assign("o-2").unwrap();
+        pool.release("o-2");
+        assert_eq!(pool.agent_for("o-1").as_deref(), Some("a"));
+
+        clock.advance(DEFAULT_HEARTBEAT_TIMEOUT_SECS);
+        pool.heartbeat("b").unwrap();
+        assert!(pool.check_heartbeats().is_empty());
+
+        clock.advance(1);
+        let failovers = pool.check_heartbeats();
+        assert_eq!(
+            failovers,
+            vec![Failover {
+                order_id: "o-1".into(),
+                from: "a".into(),
+                to: Some("b".into())
+            }]
+        );
+        assert_eq!(pool.agent("a").unwrap().status, AgentStatus::Inactive);
+        assert!(pool.check_heartbeats().is_empty());
+
+        // No live agent left: the order is dropped
+        clock.advance(DEFAULT_HEARTBEAT_TIMEOUT_SECS + 1);
+        let failovers = pool.check_heartbeats();
+        assert_eq!(failovers[0].to, None);
+        assert_eq!(pool.active_orders(), 0);
+
+        pool.heartbeat("a").unwrap();
+        assert_eq!(pool.assign("o-3").unwrap(), "a");
+        assert_eq!(pool.heartbeat("z"), Err(PoolError::UnknownAgent("z".into())));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_monitor() {
+        let (pool, clock) = pool(AssignmentStrategy::LeastLoaded, &[("a", 1), ("b", 1)]);
+        let pool = Arc::new(pool);
+        pool.assign("o-1").unwrap();
+        assert_eq!(pool.agent_for("o-1").as_deref(), Some("a"));
+
+        clock.advance(DEFAULT_HEARTBEAT_TIMEOUT_SECS + 1);
+        pool.heartbeat("b").unwrap();
+        let monitor = spawn_heartbeat_monitor(pool.clone(), Duration::from_millis(10));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        monitor.abort();
+
+        assert_eq!(pool.agent("a").unwrap().status, AgentStatus::Inactive);
+        assert_eq!(pool.agent_for("o-1").as_deref(), Some("b"));
+    }
+}
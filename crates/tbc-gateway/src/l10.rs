@@ -0,0 +1,645 @@
+//! Layer-10 policy engine
+//!
+//! TGP-00 §3.3: the L10 stage checks a QUERY against the operator's
+//! declarative policy before any OFFER is made. Policies are JSON files
+//! named by a `policy://` reference, each holding an ordered list of rules:
+//!
+//! ```json
+//! {
+//!   "policy": "policy://carrierA/us-ca/limits-v1",
+//!   "rules": [
+//!     { "id": "usdc-cap", "kind": "amount_cap", "asset": "USDC", "max_amount": 10000000000 },
+//!     { "id": "chains", "kind": "allowed_chains", "chains": [1, 8453, 369] },
+//!     { "id": "big-escrow", "kind": "require_zk", "above": 1000000000, "profile": "REQUIRED" },
+//!     { "id": "sanctions", "kind": "blocked_parties", "parties": ["buyer://mallory"] },
+//!     { "id": "region", "kind": "jurisdiction", "allowed": ["us-ca"], "blocked": [] }
+//!   ]
+//! }
+//! ```
+//!
+//! # Decisions
+//!
+//! Rule assets match every spelling of the asset: `USDC`, `evm:USDC:1` and,
+//! with an [`AssetRegistry`] attached, the CAIP-19 ids of its deployments.
+//! A QUERY asset that cannot be resolved to a symbol is treated as matching
+//! every asset rule, so caps cannot be bypassed with unknown spellings.
+//!
+//! Every rule of every loaded policy is evaluated in order. The first
+//! denying rule wins; otherwise the first rule demanding escrow; otherwise
+//! the QUERY is allowed. The deciding rule id is returned; the gateway
+//! records it on the session with `TGPSessionManager::record_policy_rule`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tbc_core::tgp::messages::QueryMessage;
+//! use tbc_core::tgp::types::ZkProfile;
+//! use tbc_gateway::l10::{PolicyContext, PolicyEngine, PolicyOutcome, PolicySet};
+//!
+//! let set = PolicySet::from_json(r#"{
+//!     "policy": "policy://carrierA/us-ca/limits-v1",
+//!     "rules": [
+//!         { "id": "big-escrow", "kind": "require_zk", "above": 1000, "profile": "REQUIRED" }
+//!     ]
+//! }"#).unwrap();
+//! let engine = PolicyEngine::new().with_set(set);
+//!
+//! let query = QueryMessage::new("q-1", "buyer://alice", "seller://bob", "USDC", 5_000,
+//!     ZkProfile::Optional);
+//! let decision = engine.evaluate(&PolicyContext::new(&query));
+//! assert_eq!(decision.outcome, PolicyOutcome::RequireEscrow);
+//! assert_eq!(decision.rule_id.as_deref(), Some("big-escrow"));
+//! ```
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tbc_core::tgp::asset::{AssetId, AssetRegistry};
+use tbc_core::tgp::errors::TgpErrorCode;
+use tbc_core::tgp::messages::{ErrorMessage, QueryMessage};
+use tbc_core::tgp::types::ZkProfile;
+use tbc_core::tgp::uri::{PartyId, TgpUri};
+use thiserror::Error;
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+/// Condition checked by a rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleKind {
+    /// Deny amounts of `asset` above `max_amount`
+    AmountCap { asset: String, max_amount: u64 },
+
+    /// Deny settlement chains not listed; QUERYs without a known chain pass
+    AllowedChains { chains: Vec<u64> },
+
+    /// Demand at least `profile` for amounts above `above`, optionally per asset
+    RequireZk {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        asset: Option<String>,
+        above: u64,
+        profile: ZkProfile,
+    },
+
+    /// Deny QUERYs from or to any listed party
+    BlockedParties { parties: Vec<String> },
+
+    /// Deny parties outside `allowed` (if non-empty) or inside `blocked`
+    Jurisdiction {
+        #[serde(default)]
+        allowed: Vec<String>,
+        #[serde(default)]
+        blocked: Vec<String>,
+    },
+}
+
+/// One named rule of a policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub id: String,
+
+    #[serde(flatten)]
+    pub kind: RuleKind,
+}
+
+/// What a rule or the engine decided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyOutcome {
+    Allow,
+    RequireEscrow,
+    Deny,
+}
+
+/// Facts a QUERY is evaluated against
+#[derive(Debug, Clone)]
+pub struct PolicyContext<'a> {
+    pub query: &'a QueryMessage,
+
+    /// Requested settlement chain, if known
+    pub chain_id: Option<u64>,
+
+    /// Normalized symbol of the QUERY asset, `None` if it could not be
+    /// resolved
+    pub asset_symbol: Option<String>,
+
+    /// Jurisdiction tags of the parties, e.g. from identity resolution
+    pub jurisdictions: Vec<String>,
+}
+
+impl<'a> PolicyContext<'a> {
+    /// Context for `query`, taking the chain and symbol from the asset id
+    pub fn new(query: &'a QueryMessage) -> Self {
+        let asset = AssetId::parse(&query.asset).ok();
+        Self {
+            query,
+            chain_id: asset.as_ref().and_then(AssetId::chain_id),
+            asset_symbol: asset.as_ref().and_then(asset_symbol),
+            jurisdictions: Vec::new(),
+        }
+    }
+
+    /// Builder method to set the asset symbol, e.g. resolved from a CAIP-19 id
+    pub fn with_asset_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.asset_symbol = Some(symbol.into().to_ascii_uppercase());
+        self
+    }
+
+    /// Builder method to set the requested chain
+    pub fn with_chain(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Builder method to set the jurisdiction tags
+    pub fn with_jurisdictions(mut self, jurisdictions: Vec<String>) -> Self {
+        self.jurisdictions = jurisdictions;
+        self
+    }
+
+    /// Whether a rule naming `asset` applies to the QUERY
+    ///
+    /// A QUERY asset or chain that is not known may be the rule's, so the
+    /// rule applies.
+    fn matches_asset(&self, asset: &str) -> bool {
+        let Some(symbol) = &self.asset_symbol else {
+            return true;
+        };
+        let Ok(rule) = AssetId::parse(asset) else {
+            return false;
+        };
+        asset_symbol(&rule).as_ref() == Some(symbol)
+            && rule
+                .chain_id()
+                .is_none_or(|chain_id| self.chain_id.is_none_or(|c| c == chain_id))
+    }
+}
+
+fn asset_symbol(id: &AssetId) -> Option<String> {
+    match id {
+        AssetId::Symbol(symbol) | AssetId::Evm { symbol, .. } => Some(symbol.clone()),
+        AssetId::Caip19(_) => None,
+    }
+}
+
+fn zk_rank(profile: ZkProfile) -> u8 {
+    match profile {
+        ZkProfile::None => 0,
+        ZkProfile::Optional => 1,
+        ZkProfile::Required => 2,
+    }
+}
+
+impl PolicyRule {
+    /// Evaluate the rule, returning the outcome and a reason unless it allows
+    pub fn evaluate(&self, ctx: &PolicyContext<'_>) -> Option<(PolicyOutcome, String)> {
+        let query = ctx.query;
+        match &self.kind {
+            RuleKind::AmountCap { asset, max_amount } => {
+                if !ctx.matches_asset(asset) || query.amount <= *max_amount {
+                    return None;
+                }
+                let reason = format!("amount {} exceeds the {} cap", query.amount, asset);
+                Some((PolicyOutcome::Deny, reason))
+            }
+            RuleKind::AllowedChains { chains } => {
+                let chain_id = ctx.chain_id.filter(|chain_id| !chains.contains(chain_id))?;
+                Some((PolicyOutcome::Deny, format!("chain {} is not allowed", chain_id)))
+            }
+            RuleKind::RequireZk {
+                asset,
+                above,
+                profile,
+            } => {
+                let applies = asset.as_ref().is_none_or(|a| ctx.matches_asset(a))
+                    && query.amount > *above
+                    && zk_rank(query.zk_profile) < zk_rank(*profile);
+                if !applies {
+                    return None;
+                }
+                // A buyer that accepts escrow can be steered to it
+                let reason = format!("amounts above {} require ZK profile {:?}", above, profile);
+                if query.zk_profile.allows_escrow() {
+                    Some((PolicyOutcome::RequireEscrow, reason))
+                } else {
+                    Some((PolicyOutcome::Deny, reason))
+                }
+            }
+            RuleKind::BlockedParties { parties } => [&query.from, &query.to]
+                .into_iter()
+                .find(|party| parties.contains(&canonical_party(party)))
+                .map(|party| (PolicyOutcome::Deny, format!("party {} is blocked", party))),
+            RuleKind::Jurisdiction { allowed, blocked } => {
+                if let Some(tag) = ctx.jurisdictions.iter().find(|tag| blocked.contains(tag)) {
+                    return Some((PolicyOutcome::Deny, format!("jurisdiction {} is blocked", tag)));
+                }
+                let permitted = allowed.is_empty()
+                    || ctx.jurisdictions.iter().any(|tag| allowed.contains(tag));
+                (!permitted).then(|| (PolicyOutcome::Deny, "no permitted jurisdiction".to_string()))
+            }
+        }
+    }
+}
+
+fn canonical_party(party: &str) -> String {
+    PartyId::parse(party)
+        .map(|id| id.to_string())
+        .unwrap_or_else(|_| party.to_string())
+}
+
+// ============================================================================
+// Policy Sets
+// ============================================================================
+
+/// Policy file loading errors
+#[derive(Debug, Error)]
+pub enum PolicyError {
+    #[error("policy I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("policy parse error: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("invalid policy {policy}: {reason}")]
+    Invalid { policy: String, reason: String },
+}
+
+/// Rules published under one `policy://` reference
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicySet {
+    pub policy: String,
+    pub rules: Vec<PolicyRule>,
+}
+
+impl PolicySet {
+    /// Parse and check a policy file
+    ///
+    /// The reference must be a `policy://` URI, rule ids unique, rule
+    /// assets symbols (optionally `evm:SYMBOL:chain`) and blocked parties
+    /// valid party ids; party ids are normalized.
+    pub fn from_json(json: &str) -> Result<Self, PolicyError> {
+        let mut set: PolicySet = serde_json::from_str(json)?;
+        let invalid = |policy: &str, reason: String| PolicyError::Invalid {
+            policy: policy.to_string(),
+            reason,
+        };
+
+        match TgpUri::parse(&set.policy) {
+            Ok(uri @ TgpUri::Policy(_)) => set.policy = uri.to_string(),
+            _ => return Err(invalid(&set.policy, "reference must be policy://".to_string())),
+        }
+
+        let mut ids = HashSet::new();
+        for rule in &mut set.rules {
+            if rule.id.is_empty() || !ids.insert(rule.id.clone()) {
+                let reason = format!("duplicate or empty rule id {:?}", rule.id);
+                return Err(invalid(&set.policy, reason));
+            }
+            let asset = match &rule.kind {
+                RuleKind::AmountCap { asset, .. } => Some(asset),
+                RuleKind::RequireZk { asset, .. } => asset.as_ref(),
+                _ => None,
+            };
+            if let Some(asset) = asset {
+                let symbol = AssetId::parse(asset).ok().and_then(|id| asset_symbol(&id));
+                if symbol.is_none() {
+                    let reason =
+                        format!("rule {}: asset must be a symbol or evm:SYMBOL:chain", rule.id);
+                    return Err(invalid(&set.policy, reason));
+                }
+            }
+            if let RuleKind::BlockedParties { parties } = &mut rule.kind {
+                for party in parties.iter_mut() {
+                    *party = PartyId::parse(party)
+                        .map_err(|e| invalid(&set.policy, format!("rule {}: {}", rule.id, e)))?
+                        .to_string();
+                }
+            }
+        }
+        Ok(set)
+    }
+
+    /// Load a policy file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+// ============================================================================
+// PolicyEngine
+// ============================================================================
+
+/// Result of evaluating a QUERY
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    pub outcome: PolicyOutcome,
+
+    /// Policy reference of the deciding rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+
+    /// Id of the deciding rule, `None` when no rule objected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl PolicyDecision {
+    pub fn allow() -> Self {
+        Self {
+            outcome: PolicyOutcome::Allow,
+            policy: None,
+            rule_id: None,
+            reason: None,
+        }
+    }
+
+    pub fn is_denied(&self) -> bool {
+        self.outcome == PolicyOutcome::Deny
+    }
+
+    pub fn requires_escrow(&self) -> bool {
+        self.outcome == PolicyOutcome::RequireEscrow
+    }
+
+    /// `POLICY_VIOLATION` ERROR answering `query`, if denied
+    pub fn to_error_message(&self, query: &QueryMessage) -> Option<ErrorMessage> {
+        if !self.is_denied() {
+            return None;
+        }
+        let message = format!(
+            "{} ({})",
+            self.reason.as_deref().unwrap_or("denied by policy"),
+            self.rule_id.as_deref().unwrap_or("unknown rule")
+        );
+        let id = format!("err-{}", query.id);
+        let error = ErrorMessage::from_code(id, TgpErrorCode::PolicyViolation, message)
+            .correlated_to(query.id.clone());
+        Some(error)
+    }
+}
+
+/// Evaluates QUERYs against the loaded policy sets
+#[derive(Debug, Clone, Default)]
+pub struct PolicyEngine {
+    sets: Vec<PolicySet>,
+
+    /// Resolves CAIP-19 QUERY assets to their symbol
+    assets: Option<AssetRegistry>,
+}
+
+impl PolicyEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to add a policy set
+    pub fn with_set(mut self, set: PolicySet) -> Self {
+        self.sets.push(set);
+        self
+    }
+
+    /// Builder method to resolve CAIP-19 asset ids through `assets`
+    pub fn with_asset_registry(mut self, assets: AssetRegistry) -> Self {
+        self.assets = Some(assets);
+        self
+    }
+
+    /// Load every `*.json` policy file in `dir`, in file name order
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, PolicyError> {
+        let mut paths: Vec<_> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+        paths.sort();
+
+        paths
+            .into_iter()
+            .try_fold(Self::new(), |engine, path| Ok(engine.with_set(PolicySet::load(path)?)))
+    }
+
+    pub fn sets(&self) -> &[PolicySet] {
+        &self.sets
+    }
+
+    /// Decide on a QUERY
+    pub fn evaluate(&self, ctx: &PolicyContext<'_>) -> PolicyDecision {
+        let resolved = self.resolve_symbol(ctx);
+        let ctx = resolved.as_ref().unwrap_or(ctx);
+
+        let mut decision = PolicyDecision::allow();
+        for set in &self.sets {
+            for rule in &set.rules {
+                let Some((outcome, reason)) = rule.evaluate(ctx) else {
+                    continue;
+                };
+                let matched = PolicyDecision {
+                    outcome,
+                    policy: Some(set.policy.clone()),
+                    rule_id: Some(rule.id.clone()),
+                    reason: Some(reason),
+                };
+                match outcome {
+                    PolicyOutcome::Deny => return matched,
+                    PolicyOutcome::RequireEscrow if decision.outcome == PolicyOutcome::Allow => {
+                        decision = matched;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        decision
+    }
+
+    /// `ctx` with the symbol of a registered CAIP-19 asset filled in
+    fn resolve_symbol<'a>(&self, ctx: &PolicyContext<'a>) -> Option<PolicyContext<'a>> {
+        if ctx.asset_symbol.is_some() {
+            return None;
+        }
+        let assets = self.assets.as_ref()?.resolve(&ctx.query.asset).ok()?;
+        let symbol = assets.first()?.symbol.clone();
+        Some(ctx.clone().with_asset_symbol(symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tbc_core::tgp::asset::AssetInfo;
+    use tbc_core::tgp::clock::ManualClock;
+    use tbc_core::tgp::store::{InMemorySessionStore, TGPSessionManager};
+
+    const POLICY: &str = r#"{
+        "policy": "policy://carrierA/us-ca/limits-v1",
+        "rules": [
+            { "id": "usdc-cap", "kind": "amount_cap", "asset": "USDC", "max_amount": 10000 },
+            { "id": "chains", "kind": "allowed_chains", "chains": [1, 369] },
+            { "id": "escrow", "kind": "require_zk", "above": 1000, "profile": "REQUIRED" },
+            { "id": "sanctions", "kind": "blocked_parties", "parties": ["Buyer://Mallory"] },
+            { "id": "region", "kind": "jurisdiction", "allowed": ["us-ca"], "blocked": ["kp"] }
+        ]
+    }"#;
+
+    fn engine() -> PolicyEngine {
+        PolicyEngine::new().with_set(PolicySet::from_json(POLICY).unwrap())
+    }
+
+    fn query(from: &str, asset: &str, amount: u64, zk_profile: ZkProfile) -> QueryMessage {
+        QueryMessage::new("q-1", from, "seller://bob", asset, amount, zk_profile)
+    }
+
+    fn decide(query: &QueryMessage, tags: &[&str]) -> (PolicyOutcome, Option<String>) {
+        let tags = tags.iter().map(|t| t.to_string()).collect();
+        let decision = engine().evaluate(&PolicyContext::new(query).with_jurisdictions(tags));
+        (decision.outcome, decision.rule_id)
+    }
+
+    #[test]
+    fn test_rules() {
+        use PolicyOutcome::*;
+        let rule = |id: &str| Some(id.to_string());
+        let us = &["us-ca"][..];
+
+        assert_eq!(
+            decide(&query("buyer://alice", "USDC", 500, ZkProfile::None), us),
+            (Allow, None)
+        );
+        assert_eq!(
+            decide(&query("buyer://alice", "usdc", 20_000, ZkProfile::Required), us),
+            (Deny, rule("usdc-cap"))
+        );
+        assert_eq!(
+            decide(&query("buyer://alice", "evm:USDC:8453", 500, ZkProfile::None), us),
+            (Deny, rule("chains"))
+        );
+        assert_eq!(
+            decide(&query("buyer://alice", "evm:USDC:369", 500, ZkProfile::None), us),
+            (Allow, None)
+        );
+        assert_eq!(
+            decide(&query("buyer://alice", "USDC", 5_000, ZkProfile::Optional), us),
+            (RequireEscrow, rule("escrow"))
+        );
+        assert_eq!(
+            decide(&query("buyer://alice", "USDC", 5_000, ZkProfile::None), us),
+            (Deny, rule("escrow"))
+        );
+        assert_eq!(
+            decide(&query("buyer://mallory", "USDC", 500, ZkProfile::None), us),
+            (Deny, rule("sanctions"))
+        );
+        assert_eq!(
+            decide(&query("buyer://alice", "USDC", 500, ZkProfile::None), &["us-ca", "kp"]),
+            (Deny, rule("region"))
+        );
+        assert_eq!(
+            decide(&query("buyer://alice", "USDC", 500, ZkProfile::None), &[]),
+            (Deny, rule("region"))
+        );
+    }
+
+    #[test]
+    fn test_asset_aliases_hit_the_same_rules() {
+        use PolicyOutcome::*;
+        const CAIP19: &str = "eip155:1/erc20:0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+        let set = PolicySet::from_json(
+            r#"{"policy": "policy://caps", "rules": [
+                {"id": "usdc-cap", "kind": "amount_cap", "asset": "USDC", "max_amount": 1000},
+                {"id": "eth-zk", "kind": "require_zk", "asset": "evm:ETH:1", "above": 10,
+                 "profile": "REQUIRED"}
+            ]}"#,
+        )
+        .unwrap();
+        let registry = AssetRegistry::new().with_asset(AssetInfo::erc20(
+            "USDC",
+            1,
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            6,
+        ));
+        let plain = PolicyEngine::new().with_set(set.clone());
+        let registered = plain.clone().with_asset_registry(registry);
+
+        let decide = |engine: &PolicyEngine, asset: &str, amount: u64| {
+            let query = query("buyer://alice", asset, amount, ZkProfile::Optional);
+            let decision = engine.evaluate(&PolicyContext::new(&query));
+            (decision.outcome, decision.rule_id)
+        };
+        let cap = (Deny, Some("usdc-cap".to_string()));
+        for asset in ["USDC", "usdc", "evm:USDC:1", "evm:usdc:8453", CAIP19] {
+            assert_eq!(decide(&plain, asset, 1_000_000), cap, "{}", asset);
+            assert_eq!(decide(&registered, asset, 1_000_000), cap, "{}", asset);
+        }
+        assert_eq!(decide(&registered, CAIP19, 500), (Allow, None));
+        assert_eq!(decide(&registered, "evm:DAI:1", 1_000_000), (Allow, None));
+
+        // Chain-pinned rule assets only apply on their chain
+        let escrow = (RequireEscrow, Some("eth-zk".to_string()));
+        assert_eq!(decide(&plain, "ETH", 100), escrow);
+        assert_eq!(decide(&plain, "evm:ETH:1", 100), escrow);
+        assert_eq!(decide(&plain, "evm:ETH:8453", 100), (Allow, None));
+
+        let caip_rule = r#"{"policy": "policy://caps", "rules": [{"id": "x",
+            "kind": "amount_cap", "asset": "eip155:1/slip44:60", "max_amount": 1}]}"#;
+        assert!(PolicySet::from_json(caip_rule).is_err());
+    }
+
+    #[test]
+    fn test_deny_wins_over_escrow() {
+        // escrow fires first, sanctions later in the list still denies
+        let query = query("buyer://mallory", "USDC", 5_000, ZkProfile::Optional);
+        assert_eq!(decide(&query, &["us-ca"]), (PolicyOutcome::Deny, Some("sanctions".into())));
+
+        let decision = engine().evaluate(&PolicyContext::new(&query));
+        let error = decision.to_error_message(&query).unwrap();
+        assert_eq!(error.error_code(), Some(TgpErrorCode::PolicyViolation));
+        assert!(error.message.contains("sanctions"));
+        assert!(PolicyDecision::allow().to_error_message(&query).is_none());
+    }
+
+    #[test]
+    fn test_invalid_policies() {
+        let bad = [
+            r#"{"policy": "seller://bob", "rules": []}"#,
+            r#"{"policy": "policy://a", "rules": [
+                {"id": "x", "kind": "allowed_chains", "chains": []},
+                {"id": "x", "kind": "allowed_chains", "chains": []}]}"#,
+            r#"{"policy": "policy://a", "rules": [
+                {"id": "x", "kind": "blocked_parties", "parties": ["policy://b"]}]}"#,
+            r#"{"policy": "policy://a", "rules": [{"id": "x", "kind": "unknown"}]}"#,
+        ];
+        for json in bad {
+            assert!(PolicySet::from_json(json).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_load_dir_and_record() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("10-limits.json"), POLICY).unwrap();
+        fs::write(dir.path().join("README.md"), "not a policy").unwrap();
+        let engine = PolicyEngine::load_dir(dir.path()).unwrap();
+        assert_eq!(engine.sets().len(), 1);
+
+        let query = query("buyer://alice", "USDC", 5_000, ZkProfile::Optional);
+        let ctx = PolicyContext::new(&query).with_jurisdictions(vec!["us-ca".into()]);
+        let decision = engine.evaluate(&ctx);
+
+        let manager = TGPSessionManager::new(
+            Arc::new(InMemorySessionStore::new()),
+            Arc::new(ManualClock::new(1_731_600_000)),
+        );
+        manager.create("sess-1").unwrap();
+        let session = manager
+            .record_policy_rule("sess-1", decision.rule_id.as_deref().unwrap())
+            .unwrap();
+        assert_eq!(session.policy_rule.as_deref(), Some("escrow"));
+    }
+}
@@ -0,0 +1,607 @@
+//! Layer-8 economic routing
+//!
+//! The [`L8Router`] is the economic stage of TGP-00 §3.1: it prices a
+//! QUERY through a [`PriceSource`], applies the best eligible
+//! [`DiscountToken`], adds the seller and routing fees, enforces the fee
+//! cap and picks the settlement method through an [`EscrowPolicy`] and the
+//! escrow preference of a [`RankingPolicy`]. The
+//! result is an OFFER carrying a TGP-01 economic envelope, or an
+//! [`L8Rejection`] to send back as ERROR.
+//!
+//! # Pricing
+//!
+//! ```text
+//! net price   = list price - list price * discount_bps / 10000
+//! routing fee = ceil(net price * routing_fee_bps / 10000)
+//! fees        = seller buyer_fee + routing fee   (must be <= max_fees_bps)
+//! ```
+//!
+//! The QUERY `amount` must cover the net price.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use tbc_core::tgp::messages::QueryMessage;
+//! use tbc_core::tgp::types::ZkProfile;
+//! use tbc_gateway::l8::{DiscountToken, L8Router, PriceQuote, StaticPriceTable};
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let prices = StaticPriceTable::new()
+//!     .with_price("seller://bob", "USDC", PriceQuote::new(1_000_000).with_buyer_fee(1_000));
+//! let router = L8Router::new(Arc::new(prices))
+//!     .with_routing_fee_bps(10)
+//!     .with_discount(DiscountToken::new("WELCOME10", 1_000));
+//!
+//! let query = QueryMessage::new("q-1", "buyer://alice", "seller://bob", "USDC", 1_000_000,
+//!     ZkProfile::None);
+//! let offer = router.route_with_discounts(&query, &["WELCOME10"]).await.unwrap();
+//! assert_eq!(offer.amount, 900_000);
+//! assert_eq!(offer.economic_envelope.routing_fee.unwrap().amount, "900");
+//! # });
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tbc_core::tgp::asset::AssetRegistry;
+use tbc_core::tgp::clock::{Clock, SystemClock};
+use tbc_core::tgp::errors::TgpErrorCode;
+use tbc_core::tgp::messages::{ErrorMessage, OfferMessage, QueryMessage};
+use tbc_core::tgp::routes::RankingPolicy;
+use tbc_core::tgp::settlement::{DecisionReason, EscrowPolicy, SettlementDecision};
+use tbc_core::tgp::types::{
+    BuyerFee, EconomicEnvelope, EnvelopePrice, RoutingFee, SellerReputation,
+};
+use thiserror::Error;
+
+/// Default fee cap applied to every OFFER (1.00%)
+pub const DEFAULT_MAX_FEES_BPS: u32 = 100;
+
+// ============================================================================
+// Price Sources
+// ============================================================================
+
+/// Seller list price for one asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceQuote {
+    /// List price in the asset's smallest unit
+    pub price: u64,
+
+    /// Seller-defined fee charged to the buyer, in the same unit
+    #[serde(default)]
+    pub buyer_fee: u64,
+}
+
+impl PriceQuote {
+    pub fn new(price: u64) -> Self {
+        Self { price, buyer_fee: 0 }
+    }
+
+    /// Builder method to set the seller-defined buyer fee
+    pub fn with_buyer_fee(mut self, buyer_fee: u64) -> Self {
+        self.buyer_fee = buyer_fee;
+        self
+    }
+}
+
+/// Source of seller pricing
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Price `seller` asks for `asset`, `None` if the seller does not sell in it
+    async fn quote(&self, seller: &str, asset: &str) -> Result<Option<PriceQuote>>;
+}
+
+/// Fixed price table keyed by seller and asset
+#[derive(Debug, Clone, Default)]
+pub struct StaticPriceTable {
+    prices: HashMap<(String, String), PriceQuote>,
+}
+
+impl StaticPriceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to add a price
+    pub fn with_price(
+        mut self,
+        seller: impl Into<String>,
+        asset: impl Into<String>,
+        quote: PriceQuote,
+    ) -> Self {
+        self.insert(seller, asset, quote);
+        self
+    }
+
+    /// Add or replace a price
+    pub fn insert(
+        &mut self,
+        seller: impl Into<String>,
+        asset: impl Into<String>,
+        quote: PriceQuote,
+    ) {
+        self.prices.insert((seller.into(), asset.into()), quote);
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticPriceTable {
+    async fn quote(&self, seller: &str, asset: &str) -> Result<Option<PriceQuote>> {
+        Ok(self.prices.get(&(seller.to_string(), asset.to_string())).cloned())
+    }
+}
+
+// ============================================================================
+// Discounts
+// ============================================================================
+
+/// Discount a buyer can redeem by presenting its code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscountToken {
+    pub code: String,
+
+    /// Discount in basis points of the list price
+    pub bps: u32,
+
+    /// Only valid for this seller
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seller: Option<String>,
+
+    /// Only valid for this asset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+
+    /// Unix time after which the token is void
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl DiscountToken {
+    pub fn new(code: impl Into<String>, bps: u32) -> Self {
+        Self {
+            code: code.into(),
+            bps: bps.min(10_000),
+            seller: None,
+            asset: None,
+            expires_at: None,
+        }
+    }
+
+    /// Builder method to restrict the token to one seller
+    pub fn for_seller(mut self, seller: impl Into<String>) -> Self {
+        self.seller = Some(seller.into());
+        self
+    }
+
+    /// Builder method to restrict the token to one asset
+    pub fn for_asset(mut self, asset: impl Into<String>) -> Self {
+        self.asset = Some(asset.into());
+        self
+    }
+
+    /// Builder method to set the expiry
+    pub fn expires_at(mut self, unix: u64) -> Self {
+        self.expires_at = Some(unix);
+        self
+    }
+
+    /// Whether the token applies to `query` at `now`
+    pub fn is_eligible(&self, query: &QueryMessage, now: u64) -> bool {
+        self.seller.as_ref().is_none_or(|s| *s == query.to)
+            && self.asset.as_ref().is_none_or(|a| *a == query.asset)
+            && self.expires_at.is_none_or(|at| now <= at)
+    }
+}
+
+// ============================================================================
+// Rejections
+// ============================================================================
+
+/// Why the L8 stage refused to make an OFFER
+#[derive(Debug, Error)]
+pub enum L8Rejection {
+    #[error("invalid QUERY: {0}")]
+    InvalidQuery(String),
+
+    #[error("{}", .0.message)]
+    UnsupportedAsset(ErrorMessage),
+
+    #[error("seller {seller} has no price for {asset}")]
+    NoPrice { seller: String, asset: String },
+
+    #[error("amount {amount} is below the price {price}")]
+    Underpaid { amount: u64, price: u64 },
+
+    #[error("fee cap exceeded: {0}")]
+    FeeCapExceeded(String),
+
+    #[error("escrow required but no CoreProver contract is available")]
+    EscrowUnavailable,
+
+    #[error("price source failed: {0}")]
+    PriceSource(anyhow::Error),
+}
+
+impl L8Rejection {
+    /// TGP error code of the rejection
+    pub fn code(&self) -> TgpErrorCode {
+        match self {
+            L8Rejection::InvalidQuery(_) => TgpErrorCode::InvalidQuery,
+            L8Rejection::UnsupportedAsset(error) => {
+                error.error_code().unwrap_or(TgpErrorCode::UnsupportedAsset)
+            }
+            L8Rejection::NoPrice { .. } => TgpErrorCode::NoRoute,
+            L8Rejection::Underpaid { .. } => TgpErrorCode::PolicyViolation,
+            L8Rejection::FeeCapExceeded(_) => TgpErrorCode::FeeCapExceeded,
+            L8Rejection::EscrowUnavailable => TgpErrorCode::EscrowUnavailable,
+            L8Rejection::PriceSource(_) => TgpErrorCode::Internal,
+        }
+    }
+
+    /// ERROR message answering `query`
+    pub fn to_error_message(&self, query: &QueryMessage) -> ErrorMessage {
+        match self {
+            L8Rejection::UnsupportedAsset(error) => error.clone(),
+            _ => ErrorMessage::from_code(format!("err-{}", query.id), self.code(), self.to_string())
+                .correlated_to(query.id.clone()),
+        }
+    }
+}
+
+// ============================================================================
+// L8Router
+// ============================================================================
+
+/// Economic routing stage turning a QUERY into an OFFER
+pub struct L8Router {
+    prices: Arc<dyn PriceSource>,
+    max_fees_bps: u32,
+    routing_fee_bps: u32,
+    discounts: Vec<DiscountToken>,
+    assets: Option<AssetRegistry>,
+    escrow_policy: EscrowPolicy,
+    ranking: RankingPolicy,
+    clock: Arc<dyn Clock>,
+}
+
+impl L8Router {
+    pub fn new(prices: Arc<dyn PriceSource>) -> Self {
+        Self {
+            prices,
+            max_fees_bps: DEFAULT_MAX_FEES_BPS,
+            routing_fee_bps: 0,
+            discounts: Vec::new(),
+            assets: None,
+            escrow_policy: EscrowPolicy::default(),
+            ranking: RankingPolicy::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Builder method to set the fee cap written into every OFFER
+    pub fn with_max_fees_bps(mut self, max_fees_bps: u32) -> Self {
+        self.max_fees_bps = max_fees_bps;
+        self
+    }
+
+    /// Builder method to set the TBC operator's routing fee
+    pub fn with_routing_fee_bps(mut self, routing_fee_bps: u32) -> Self {
+        self.routing_fee_bps = routing_fee_bps;
+        self
+    }
+
+    /// Builder method to register a redeemable discount
+    pub fn with_discount(mut self, discount: DiscountToken) -> Self {
+        self.discounts.push(discount);
+        self
+    }
+
+    /// Builder method to reject assets the registry cannot settle
+    pub fn with_asset_registry(mut self, assets: AssetRegistry) -> Self {
+        self.assets = Some(assets);
+        self
+    }
+
+    /// Builder method to set the thresholds for escrow on `OPTIONAL` QUERYs
+    pub fn with_escrow_policy(mut self, escrow_policy: EscrowPolicy) -> Self {
+        self.escrow_policy = escrow_policy;
+        self
+    }
+
+    /// Builder method to set the route ranking
+    ///
+    /// Its escrow preference decides how `OPTIONAL` QUERYs settle when no
+    /// [`EscrowPolicy`] threshold applies.
+    pub fn with_ranking_policy(mut self, ranking: RankingPolicy) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Builder method to inject the clock used for discount expiry
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Route a QUERY without discount codes
+    pub async fn route(&self, query: &QueryMessage) -> Result<OfferMessage, L8Rejection> {
+        self.route_with_discounts(query, &[]).await
+    }
+
+    /// Route a QUERY, redeeming the best eligible discount among `codes`
+    ///
+    /// Discounts do not stack; unknown or ineligible codes are ignored.
+    pub async fn route_with_discounts(
+        &self,
+        query: &QueryMessage,
+        codes: &[&str],
+    ) -> Result<OfferMessage, L8Rejection> {
+        let (offer, _) = self.route_explained(query, codes, None, false).await?;
+        Ok(offer)
+    }
+
+    /// Route a QUERY and return the settlement decision behind the OFFER
+    ///
+    /// `reputation` is the seller's track record, consulted by the
+    /// [`EscrowPolicy`] for `OPTIONAL` QUERYs. `escrow_required` is set when
+    /// an earlier stage demanded escrow; it settles an `OPTIONAL` QUERY
+    /// through escrow but never overrides the buyer's profile.
+    pub async fn route_explained(
+        &self,
+        query: &QueryMessage,
+        codes: &[&str],
+        reputation: Option<&SellerReputation>,
+        escrow_required: bool,
+    ) -> Result<(OfferMessage, SettlementDecision), L8Rejection> {
+        query.validate().map_err(L8Rejection::InvalidQuery)?;
+        if let Some(assets) = &self.assets {
+            assets.admit_query(query, None).map_err(L8Rejection::UnsupportedAsset)?;
+        }
+
+        let quote = self
+            .prices
+            .quote(&query.to, &query.asset)
+            .await
+            .map_err(L8Rejection::PriceSource)?
+            .ok_or_else(|| L8Rejection::NoPrice {
+                seller: query.to.clone(),
+                asset: query.asset.clone(),
+            })?;
+
+        // Tokens built as literals or deserialized skip the clamp in `new`
+        let discount_bps = self.best_discount(query, codes).map_or(0, |d| d.bps.min(10_000));
+        let net_price = quote.price - bps_of(quote.price, discount_bps, false);
+        if query.amount < net_price {
+            return Err(L8Rejection::Underpaid {
+                amount: query.amount,
+                price: net_price,
+            });
+        }
+
+        let mut envelope = EconomicEnvelope::v1(
+            EnvelopePrice::new(net_price.to_string(), &query.asset),
+            self.max_fees_bps,
+        );
+        if quote.buyer_fee > 0 {
+            let buyer_fee = BuyerFee::new(quote.buyer_fee.to_string(), &query.asset);
+            envelope = envelope.with_buyer_fee(buyer_fee);
+        }
+        let routing_fee = bps_of(net_price, self.routing_fee_bps, true);
+        if routing_fee > 0 {
+            let routing_fee = RoutingFee::new(routing_fee.to_string(), &query.asset);
+            envelope = envelope.with_routing_fee(routing_fee);
+        }
+        envelope.check_fee_cap().map_err(L8Rejection::FeeCapExceeded)?;
+
+        let (mut ranking, mut reason) =
+            self.escrow_policy.ranking(query, reputation, &self.ranking);
+        if escrow_required {
+            ranking = ranking.prefer_escrow(true);
+            reason = DecisionReason::PolicyRequired;
+        }
+        let decision = SettlementDecision::from_ranking(query, &ranking, reason)
+            .map_err(|_| L8Rejection::EscrowUnavailable)?;
+
+        let offer = OfferMessage::new(
+            format!("offer-{}", query.id),
+            &query.id,
+            &query.asset,
+            net_price,
+            decision.zk_required,
+            envelope,
+        );
+        Ok((decision.apply(offer), decision))
+    }
+
+    fn best_discount(&self, query: &QueryMessage, codes: &[&str]) -> Option<&DiscountToken> {
+        let now = self.clock.now_unix();
+        self.discounts
+            .iter()
+            .filter(|d| codes.contains(&d.code.as_str()) && d.is_eligible(query, now))
+            .max_by_key(|d| d.bps)
+    }
+}
+
+/// `amount * bps / 10000`, rounded up if `ceil`
+fn bps_of(amount: u64, bps: u32, ceil: bool) -> u64 {
+    let product = amount as u128 * bps as u128;
+    let result = if ceil { product.div_ceil(10_000) } else { product / 10_000 };
+    result.min(u64::MAX as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tbc_core::tgp::asset::AssetInfo;
+    use tbc_core::tgp::clock::ManualClock;
+    use tbc_core::tgp::types::ZkProfile;
+
+    const CONTRACT: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+
+    fn prices() -> Arc<StaticPriceTable> {
+        let bob = PriceQuote::new(1_000_000).with_buyer_fee(2_000);
+        Arc::new(
+            StaticPriceTable::new()
+                .with_price("seller://bob", "USDC", bob)
+                .with_price("seller://carol", "USDC", PriceQuote::new(1_000).with_buyer_fee(500)),
+        )
+    }
+
+    fn query(to: &str, amount: u64, zk_profile: ZkProfile) -> QueryMessage {
+        QueryMessage::new("q-1", "buyer://alice", to, "USDC", amount, zk_profile)
+    }
+
+    #[tokio::test]
+    async fn test_offer_from_price() {
+        let router = L8Router::new(prices()).with_routing_fee_bps(30);
+        let offer = router.route(&query("seller://bob", 1_000_000, ZkProfile::None)).await.unwrap();
+
+        assert_eq!(offer.id, "offer-q-1");
+        assert_eq!(offer.amount, 1_000_000);
+        assert!(!offer.zk_required);
+        assert!(offer.coreprover_contract.is_none());
+        assert_eq!(offer.economic_envelope.total_fees().unwrap().as_deref(), Some("5000"));
+        assert!(offer.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejections() {
+        let router = L8Router::new(prices());
+
+        let reject = |to: &'static str, amount| {
+            let router = &router;
+            async move { router.route(&query(to, amount, ZkProfile::None)).await.unwrap_err() }
+        };
+        assert_eq!(reject("seller://dave", 1_000).await.code(), TgpErrorCode::NoRoute);
+        assert_eq!(reject("seller://bob", 999_999).await.code(), TgpErrorCode::PolicyViolation);
+
+        // 500 / 1000 = 50% in fees against a 1% cap
+        assert_eq!(reject("seller://carol", 1_000).await.code(), TgpErrorCode::FeeCapExceeded);
+
+        let error = router
+            .route(&query("seller://bob", 1_000_000, ZkProfile::Required))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), TgpErrorCode::EscrowUnavailable);
+
+        let message = error.to_error_message(&query("seller://bob", 1, ZkProfile::None));
+        assert_eq!(message.correlation_id.as_deref(), Some("q-1"));
+        assert!(message.validate().is_ok());
+
+        let error = router.route(&query("", 1_000, ZkProfile::None)).await.unwrap_err();
+        assert_eq!(error.code(), TgpErrorCode::InvalidQuery);
+    }
+
+    #[tokio::test]
+    async fn test_discounts() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let router = L8Router::new(prices())
+            .with_clock(clock.clone())
+            .with_discount(DiscountToken::new("TEN", 1_000))
+            .with_discount(DiscountToken::new("CAROL", 5_000).for_seller("seller://carol"))
+            .with_discount(DiscountToken::new("FLASH", 2_000).expires_at(1_500));
+        let query = query("seller://bob", 1_000_000, ZkProfile::None);
+
+        let amount = |codes: &'static [&'static str]| {
+            let router = &router;
+            let query = &query;
+            async move { router.route_with_discounts(query, codes).await.unwrap().amount }
+        };
+        assert_eq!(amount(&[]).await, 1_000_000);
+        assert_eq!(amount(&["TEN", "UNKNOWN"]).await, 900_000);
+        assert_eq!(amount(&["CAROL"]).await, 1_000_000);
+        assert_eq!(amount(&["TEN", "FLASH"]).await, 800_000);
+
+        clock.set(2_000);
+        assert_eq!(amount(&["TEN", "FLASH"]).await, 900_000);
+    }
+
+    #[tokio::test]
+    async fn test_discount_over_full_price_is_clamped() {
+        let token: DiscountToken =
+            serde_json::from_str(r#"{"code": "FREE", "bps": 25000}"#).unwrap();
+        let prices =
+            StaticPriceTable::new().with_price("seller://bob", "USDC", PriceQuote::new(1_000));
+        let router = L8Router::new(Arc::new(prices)).with_discount(token);
+        let query = query("seller://bob", 1_000_000, ZkProfile::None);
+
+        let offer = router.route_with_discounts(&query, &["FREE"]).await.unwrap();
+        assert_eq!(offer.amount, 0);
+    }
+
+    #[tokio::test]
+    async fn test_payment_profile() {
+        let router = L8Router::new(prices());
+        let escrow = |zk_profile| {
+            QueryMessage::with_escrow_from_402(
+                "q-1",
+                "buyer://alice",
+                "seller://bob",
+                "USDC",
+                1_000_000,
+                CONTRACT,
+                zk_profile,
+            )
+        };
+
+        let offer = router.route(&escrow(ZkProfile::Required)).await.unwrap();
+        assert!(offer.zk_required);
+        assert_eq!(offer.coreprover_contract.as_deref(), Some(CONTRACT));
+
+        let offer = router.route(&escrow(ZkProfile::Optional)).await.unwrap();
+        assert!(!offer.zk_required);
+        assert_eq!(offer.coreprover_contract.as_deref(), Some(CONTRACT));
+
+        let offer = router.route(&escrow(ZkProfile::None)).await.unwrap();
+        assert!(offer.coreprover_contract.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_escrow_policy() {
+        let router = L8Router::new(prices())
+            .with_escrow_policy(EscrowPolicy::new().with_min_on_time_bps(9_000))
+            .with_ranking_policy(RankingPolicy::default().prefer_escrow(false));
+        let query = QueryMessage::with_escrow_from_402(
+            "q-1",
+            "buyer://alice",
+            "seller://bob",
+            "USDC",
+            1_000_000,
+            CONTRACT,
+            ZkProfile::Optional,
+        );
+
+        let reliable = SellerReputation::new(50, 0);
+        let explain = |reputation, escrow_required| {
+            router.route_explained(&query, &[], reputation, escrow_required)
+        };
+        let (offer, decision) = explain(Some(&reliable), false).await.unwrap();
+        assert!(!decision.is_escrow());
+        assert!(offer.coreprover_contract.is_none());
+
+        let (offer, decision) = explain(Some(&reliable), true).await.unwrap();
+        assert_eq!(decision.to_string(), "escrow (policy requires escrow)");
+        assert_eq!(offer.coreprover_contract.as_deref(), Some(CONTRACT));
+        assert!(!offer.zk_required);
+
+        let (offer, decision) = explain(None, false).await.unwrap();
+        assert_eq!(decision.to_string(), "escrow (seller has no settlement history)");
+        assert_eq!(offer.coreprover_contract.as_deref(), Some(CONTRACT));
+        assert!(!offer.zk_required);
+    }
+
+    #[tokio::test]
+    async fn test_asset_registry() {
+        let registry = AssetRegistry::new().with_asset(AssetInfo::native("PLS", 369, 18));
+        let router = L8Router::new(prices()).with_asset_registry(registry);
+
+        let query = query("seller://bob", 1_000_000, ZkProfile::None);
+        let error = router.route(&query).await.unwrap_err();
+        assert_eq!(error.code(), TgpErrorCode::UnsupportedAsset);
+        assert_eq!(error.to_error_message(&query).correlation_id.as_deref(), Some("q-1"));
+    }
+}
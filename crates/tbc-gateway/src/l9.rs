@@ -0,0 +1,652 @@
+//! Layer-9 identity routing
+//!
+//! TGP-00 §3.2: the L9 stage resolves the QUERY's `from` and `to` to
+//! on-chain addresses, hides the buyer behind a stable pseudonym when the
+//! QUERY is forwarded to the seller, and attaches the seller's fulfillment
+//! reputation to the OFFER.
+//!
+//! # Identities
+//!
+//! Identifiers that already carry an address (`0x…`, CAIP-10) resolve to
+//! it directly. `buyer://` and `seller://` names, and their pseudonyms, are
+//! looked up in an [`IdentityRegistry`]; [`FileIdentityRegistry`] keeps
+//! the mapping in a JSON file:
+//!
+//! ```json
+//! {
+//!   "pseudonym_salt": "deployment secret",
+//!   "identities": [
+//!     { "id": "buyer://alice", "address": "0x742d35cc6634c0532925a3b844bc9e7595f0beb0",
+//!       "jurisdictions": ["us-ca"] }
+//!   ]
+//! }
+//! ```
+//!
+//! The optional `jurisdictions` tags of both parties are what L10
+//! `jurisdiction` rules are checked against.
+//!
+//! Pseudonyms are `buyer://anon-<hash>` / `seller://anon-<hash>`, where the
+//! hash is the first 16 hex digits of SHA-256 over the salt and the id. A
+//! registry without a salt gets a random one, persisted on open.
+//!
+//! # Reputation
+//!
+//! [`ReputationStore`] consumes settled CoreProver receipts and counts the
+//! `late_fulfilled` ones per seller. The gateway looks up the receipt of
+//! every successful SETTLE in a [`ReceiptSource`], such as the in-process
+//! engine behind [`EngineReceipts`], and counts it only if the escrow pays
+//! the order's seller the OFFER's amount. Each settlement txid counts once,
+//! whichever seller it is presented for.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use coreprover_service::engine::CoreProverEngine;
+use coreprover_service::types::ReceiptMetadata;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tbc_core::tgp::errors::TgpErrorCode;
+use tbc_core::tgp::messages::{ErrorMessage, OfferMessage, QueryMessage};
+use tbc_core::tgp::pos::SettlementReceipt;
+use tbc_core::tgp::types::SellerReputation;
+use tbc_core::tgp::uri::{PartyId, TgpUri, UriParseError};
+use tbc_core::tgp::validation::validate_address;
+use thiserror::Error;
+
+// ============================================================================
+// Identity Registry
+// ============================================================================
+
+/// Address registered for a party
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentityRecord {
+    /// Canonical party id, e.g. `buyer://alice`
+    pub id: String,
+
+    /// 0x-prefixed EVM address
+    pub address: String,
+
+    /// Jurisdiction tags of the party, e.g. `us-ca`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jurisdictions: Vec<String>,
+}
+
+impl IdentityRecord {
+    pub fn new(id: impl Into<String>, address: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            address: address.into(),
+            jurisdictions: Vec::new(),
+        }
+    }
+
+    /// Builder method to set the jurisdiction tags
+    pub fn with_jurisdictions(mut self, jurisdictions: Vec<String>) -> Self {
+        self.jurisdictions = jurisdictions;
+        self
+    }
+}
+
+/// Maps party ids and pseudonyms to addresses
+#[async_trait]
+pub trait IdentityRegistry: Send + Sync {
+    /// Record for `id`, which may be a registered id or one of its pseudonyms
+    async fn lookup(&self, id: &PartyId) -> Result<Option<IdentityRecord>>;
+
+    /// Stable pseudonym for `id`
+    fn pseudonym(&self, id: &PartyId) -> PartyId;
+}
+
+/// Pseudonym of `id` under `salt`
+///
+/// Sellers keep the `seller://` scheme, every other party becomes a
+/// `buyer://` pseudonym.
+pub fn pseudonym_for(salt: &str, id: &PartyId) -> PartyId {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(id.to_string().as_bytes());
+    let name = format!("anon-{}", &hex::encode(hasher.finalize())[..16]);
+
+    let uri = if id.is_seller() {
+        TgpUri::Seller(name)
+    } else {
+        TgpUri::Buyer(name)
+    };
+    PartyId::try_from(uri).expect("buyer and seller URIs are party ids")
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IdentityFile {
+    /// Generated by [`FileIdentityRegistry::open`] when empty
+    #[serde(default)]
+    pseudonym_salt: String,
+
+    #[serde(default)]
+    identities: Vec<IdentityRecord>,
+}
+
+#[derive(Debug, Default)]
+struct IdentityIndex {
+    salt: String,
+    records: HashMap<String, IdentityRecord>,
+    pseudonyms: HashMap<String, String>,
+}
+
+impl IdentityIndex {
+    fn insert(&mut self, record: IdentityRecord) -> Result<(), IdentityError> {
+        let id = PartyId::parse(&record.id)?;
+        validate_address(&record.address, "address").map_err(IdentityError::InvalidAddress)?;
+
+        let record = IdentityRecord::new(id.to_string(), record.address.to_ascii_lowercase())
+            .with_jurisdictions(record.jurisdictions);
+        self.pseudonyms
+            .insert(pseudonym_for(&self.salt, &id).to_string(), record.id.clone());
+        self.records.insert(record.id.clone(), record);
+        Ok(())
+    }
+
+    fn get(&self, id: &PartyId) -> Option<IdentityRecord> {
+        let key = id.to_string();
+        let key = self.pseudonyms.get(&key).unwrap_or(&key);
+        self.records.get(key).cloned()
+    }
+}
+
+/// Identity registry loading errors
+#[derive(Debug, Error)]
+pub enum IdentityError {
+    #[error("identity registry I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("identity registry serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    InvalidId(#[from] UriParseError),
+
+    #[error("{0}")]
+    InvalidAddress(String),
+}
+
+/// Identity registry persisted as one JSON file
+///
+/// A missing file is an empty registry. Registrations are written to a
+/// temporary file that is renamed into place.
+pub struct FileIdentityRegistry {
+    path: PathBuf,
+    index: RwLock<IdentityIndex>,
+}
+
+impl FileIdentityRegistry {
+    /// Load the registry at `path`
+    ///
+    /// Without a `pseudonym_salt` (or without a file) a random salt is
+    /// generated and written back at once: pseudonyms under an empty salt
+    /// could be reversed by hashing candidate ids, and a salt kept only in
+    /// memory would change them on every restart.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, IdentityError> {
+        let path = path.into();
+        let file: IdentityFile = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => IdentityFile::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let generated = file.pseudonym_salt.is_empty();
+        let mut index = IdentityIndex {
+            salt: match generated {
+                true => hex::encode(rand::random::<[u8; 32]>()),
+                false => file.pseudonym_salt,
+            },
+            ..IdentityIndex::default()
+        };
+        for record in file.identities {
+            index.insert(record)?;
+        }
+
+        let registry = Self {
+            path,
+            index: RwLock::new(index),
+        };
+        if generated {
+            registry.persist(&registry.index.read().unwrap())?;
+        }
+        Ok(registry)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Add or replace a record and persist the registry
+    pub fn register(&self, record: IdentityRecord) -> Result<(), IdentityError> {
+        let mut index = self.index.write().unwrap();
+        index.insert(record)?;
+        self.persist(&index)
+    }
+
+    fn persist(&self, index: &IdentityIndex) -> Result<(), IdentityError> {
+        let mut identities: Vec<IdentityRecord> = index.records.values().cloned().collect();
+        identities.sort_by(|a, b| a.id.cmp(&b.id));
+        let file = IdentityFile {
+            pseudonym_salt: index.salt.clone(),
+            identities,
+        };
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&file)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl IdentityRegistry for FileIdentityRegistry {
+    async fn lookup(&self, id: &PartyId) -> Result<Option<IdentityRecord>> {
+        Ok(self.index.read().unwrap().get(id))
+    }
+
+    fn pseudonym(&self, id: &PartyId) -> PartyId {
+        pseudonym_for(&self.index.read().unwrap().salt, id)
+    }
+}
+
+// ============================================================================
+// Reputation
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct ReputationTally {
+    settled: u64,
+    late: u64,
+}
+
+#[derive(Debug, Default)]
+struct ReputationState {
+    sellers: HashMap<String, ReputationTally>,
+
+    /// Settlement txids counted so far, for any seller
+    txids: HashSet<String>,
+}
+
+/// Per-seller fulfillment history built from settlement receipts
+#[derive(Debug, Default)]
+pub struct ReputationStore {
+    state: RwLock<ReputationState>,
+}
+
+impl ReputationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a settled receipt for `seller`
+    ///
+    /// Unfinalized receipts and receipts already recorded (by settlement
+    /// txid, for this or any other seller) are ignored. Returns whether the
+    /// receipt was counted.
+    pub fn record_receipt<R: SettlementReceipt>(&self, seller: &str, receipt: &R) -> bool {
+        let Some(txid) = receipt.settlement_txid().filter(|_| receipt.is_finalized()) else {
+            return false;
+        };
+
+        let mut state = self.state.write().unwrap();
+        if !state.txids.insert(txid.to_string()) {
+            return false;
+        }
+        let tally = state.sellers.entry(seller_key(seller)).or_default();
+        tally.settled += 1;
+        tally.late += u64::from(receipt.late_fulfilled());
+        true
+    }
+
+    /// Seller reputation, `None` without settled history
+    pub fn reputation(&self, seller: &str) -> Option<SellerReputation> {
+        self.state
+            .read()
+            .unwrap()
+            .sellers
+            .get(&seller_key(seller))
+            .map(|tally| SellerReputation::new(tally.settled, tally.late))
+    }
+}
+
+fn seller_key(seller: &str) -> String {
+    PartyId::parse(seller)
+        .map(|id| id.to_string())
+        .unwrap_or_else(|_| seller.to_string())
+}
+
+/// Settlement receipt together with the escrow it settles
+#[derive(Debug, Clone)]
+pub struct EscrowReceipt {
+    /// Seller address the escrow pays
+    pub seller: String,
+
+    /// Escrowed amount
+    pub amount: u64,
+
+    pub receipt: ReceiptMetadata,
+}
+
+/// Settlement receipts by CoreProver order id
+#[async_trait]
+pub trait ReceiptSource: Send + Sync {
+    /// Receipt of the escrow `order_id` (0x-prefixed hex), `None` if the
+    /// escrow is unknown or has no receipt yet
+    async fn receipt(&self, order_id: &str) -> Result<Option<EscrowReceipt>>;
+}
+
+/// Receipts of a CoreProver engine running in the same process
+pub struct EngineReceipts(pub Arc<Mutex<CoreProverEngine>>);
+
+#[async_trait]
+impl ReceiptSource for EngineReceipts {
+    async fn receipt(&self, order_id: &str) -> Result<Option<EscrowReceipt>> {
+        let id: [u8; 32] = order_id
+            .strip_prefix("0x")
+            .and_then(|hex| hex::decode(hex).ok())
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| anyhow::anyhow!("invalid CoreProver order id {}", order_id))?;
+        let engine = self.0.lock().unwrap();
+        let (Some(escrow), Some(receipt)) = (engine.get_escrow_record(&id), engine.get_receipt(&id))
+        else {
+            return Ok(None);
+        };
+        Ok(Some(EscrowReceipt {
+            seller: escrow.seller.clone(),
+            amount: escrow.amount,
+            receipt: receipt.clone(),
+        }))
+    }
+}
+
+// ============================================================================
+// L9Router
+// ============================================================================
+
+/// A party id resolved to its address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedIdentity {
+    pub id: PartyId,
+    pub address: String,
+    pub pseudonym: PartyId,
+
+    /// From the registry record; empty for parties given by address
+    pub jurisdictions: Vec<String>,
+}
+
+/// Both parties of a QUERY
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedParties {
+    pub buyer: ResolvedIdentity,
+    pub seller: ResolvedIdentity,
+}
+
+/// Why the L9 stage refused a QUERY
+#[derive(Debug, Error)]
+pub enum L9Rejection {
+    #[error("{field}: {source}")]
+    InvalidId {
+        field: &'static str,
+        source: UriParseError,
+    },
+
+    #[error("{field}: unknown party {id}")]
+    UnknownParty { field: &'static str, id: String },
+
+    #[error("identity registry failed: {0}")]
+    Registry(anyhow::Error),
+}
+
+impl L9Rejection {
+    /// TGP error code of the rejection
+    pub fn code(&self) -> TgpErrorCode {
+        match self {
+            L9Rejection::InvalidId { .. } => TgpErrorCode::InvalidQuery,
+            L9Rejection::UnknownParty { field: "from", .. } => TgpErrorCode::Unauthenticated,
+            L9Rejection::UnknownParty { .. } => TgpErrorCode::NoRoute,
+            L9Rejection::Registry(_) => TgpErrorCode::Internal,
+        }
+    }
+
+    /// ERROR message answering `query`
+    pub fn to_error_message(&self, query: &QueryMessage) -> ErrorMessage {
+        ErrorMessage::from_code(format!("err-{}", query.id), self.code(), self.to_string())
+            .correlated_to(query.id.clone())
+    }
+}
+
+/// Identity and reputation stage
+///
+/// Cheap to clone; clones share the registry and the reputation store.
+#[derive(Clone)]
+pub struct L9Router {
+    identities: Arc<dyn IdentityRegistry>,
+    reputation: Arc<ReputationStore>,
+}
+
+impl L9Router {
+    pub fn new(identities: Arc<dyn IdentityRegistry>, reputation: Arc<ReputationStore>) -> Self {
+        Self {
+            identities,
+            reputation,
+        }
+    }
+
+    pub fn reputation(&self) -> &Arc<ReputationStore> {
+        &self.reputation
+    }
+
+    /// Resolve one identifier of `field` to its address
+    pub async fn resolve(
+        &self,
+        field: &'static str,
+        id: &str,
+    ) -> Result<ResolvedIdentity, L9Rejection> {
+        let party = PartyId::parse(id).map_err(|source| L9Rejection::InvalidId { field, source })?;
+
+        let (address, jurisdictions) = match party.uri().address() {
+            Some(address) => (address.to_string(), Vec::new()),
+            None => self
+                .identities
+                .lookup(&party)
+                .await
+                .map_err(L9Rejection::Registry)?
+                .map(|record| (record.address, record.jurisdictions))
+                .ok_or_else(|| L9Rejection::UnknownParty {
+                    field,
+                    id: party.to_string(),
+                })?,
+        };
+
+        Ok(ResolvedIdentity {
+            pseudonym: self.identities.pseudonym(&party),
+            id: party,
+            address,
+            jurisdictions,
+        })
+    }
+
+    /// Resolve `from` and `to` of a QUERY
+    pub async fn resolve_parties(
+        &self,
+        query: &QueryMessage,
+    ) -> Result<ResolvedParties, L9Rejection> {
+        Ok(ResolvedParties {
+            buyer: self.resolve("from", &query.from).await?,
+            seller: self.resolve("to", &query.to).await?,
+        })
+    }
+
+    /// Copy of `query` with the buyer replaced by its pseudonym
+    ///
+    /// Unparseable ids are left as-is; validation rejects them elsewhere.
+    pub fn pseudonymize(&self, query: &QueryMessage) -> QueryMessage {
+        let mut forwarded = query.clone();
+        if let Ok(buyer) = PartyId::parse(&query.from) {
+            forwarded.from = self.identities.pseudonym(&buyer).to_string();
+        }
+        forwarded
+    }
+
+    /// Attach the reputation of `seller` to `offer`, if it has history
+    pub fn annotate_offer(&self, offer: OfferMessage, seller: &str) -> OfferMessage {
+        match self.reputation.reputation(seller) {
+            Some(reputation) => offer.with_seller_reputation(reputation),
+            None => offer,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tbc_core::tgp::types::{EconomicEnvelope, ZkProfile};
+
+    const ALICE: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+    const BOB: &str = "0xab5801a7d398351b8be11c439e05c5b3259aec9b";
+
+    #[derive(Serialize)]
+    struct Receipt {
+        txid: Option<String>,
+        late: bool,
+    }
+
+    impl SettlementReceipt for Receipt {
+        fn settlement_txid(&self) -> Option<&str> {
+            self.txid.as_deref()
+        }
+
+        fn settlement_chain_id(&self) -> u64 {
+            369
+        }
+
+        fn late_fulfilled(&self) -> bool {
+            self.late
+        }
+    }
+
+    fn receipt(txid: &str, late: bool) -> Receipt {
+        Receipt {
+            txid: Some(txid.to_string()),
+            late,
+        }
+    }
+
+    fn registry(dir: &tempfile::TempDir) -> FileIdentityRegistry {
+        let path = dir.path().join("identities.json");
+        fs::write(
+            &path,
+            format!(
+                r#"{{"pseudonym_salt": "salt", "identities": [
+                    {{"id": "Buyer://Alice", "address": "{}"}}
+                ]}}"#,
+                ALICE
+            ),
+        )
+        .unwrap();
+        FileIdentityRegistry::open(path).unwrap()
+    }
+
+    fn query(from: &str, to: &str) -> QueryMessage {
+        QueryMessage::new("q-1", from, to, "USDC", 1_000, ZkProfile::Optional)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_parties() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = L9Router::new(Arc::new(registry(&dir)), Arc::new(ReputationStore::new()));
+
+        let caip10 = format!("eip155:369:{}", BOB);
+        let parties = router.resolve_parties(&query("buyer://alice", &caip10)).await.unwrap();
+        assert_eq!(parties.buyer.address, ALICE);
+        assert_eq!(parties.seller.address, BOB);
+        assert!(parties.buyer.pseudonym.to_string().starts_with("buyer://anon-"));
+
+        // The seller only ever sees the pseudonym, which resolves back
+        let forwarded = router.pseudonymize(&query("buyer://alice", "seller://bob"));
+        assert_eq!(forwarded.from, parties.buyer.pseudonym.to_string());
+        assert_eq!(router.resolve("from", &forwarded.from).await.unwrap().address, ALICE);
+
+        let unknown_buyer = query("buyer://mallory", &caip10);
+        let error = router.resolve_parties(&unknown_buyer).await.unwrap_err();
+        assert_eq!(error.code(), TgpErrorCode::Unauthenticated);
+        let unknown_seller = query("buyer://alice", "seller://bob");
+        let error = router.resolve_parties(&unknown_seller).await.unwrap_err();
+        assert_eq!(error.code(), TgpErrorCode::NoRoute);
+        let error = router.resolve("from", "policy://x").await.unwrap_err();
+        assert_eq!(error.code(), TgpErrorCode::InvalidQuery);
+    }
+
+    #[tokio::test]
+    async fn test_file_registry_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = registry(&dir);
+        let checksummed = "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B";
+        registry.register(IdentityRecord::new("seller://bob", checksummed)).unwrap();
+        assert!(registry.register(IdentityRecord::new("seller://eve", "0x12")).is_err());
+
+        let reopened = FileIdentityRegistry::open(registry.path()).unwrap();
+        let bob = PartyId::parse("seller://bob").unwrap();
+        assert_eq!(reopened.lookup(&bob).await.unwrap().unwrap().address, BOB);
+        assert_eq!(reopened.pseudonym(&bob), registry.pseudonym(&bob));
+        assert!(reopened.pseudonym(&bob).is_seller());
+
+        let empty = FileIdentityRegistry::open(dir.path().join("missing.json")).unwrap();
+        assert!(empty.lookup(&bob).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_generated_salt_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identities.json");
+        let bob = PartyId::parse("seller://bob").unwrap();
+
+        let first = FileIdentityRegistry::open(&path).unwrap();
+        let reopened = FileIdentityRegistry::open(&path).unwrap();
+        assert_eq!(first.pseudonym(&bob), reopened.pseudonym(&bob));
+        assert_ne!(first.pseudonym(&bob), pseudonym_for("", &bob));
+
+        // Each deployment gets its own salt
+        let other = FileIdentityRegistry::open(dir.path().join("other.json")).unwrap();
+        assert_ne!(first.pseudonym(&bob), other.pseudonym(&bob));
+    }
+
+    #[test]
+    fn test_reputation() {
+        let store = ReputationStore::new();
+        assert!(store.reputation("seller://bob").is_none());
+
+        assert!(store.record_receipt("seller://bob", &receipt("0x01", false)));
+        assert!(store.record_receipt("Seller://Bob", &receipt("0x02", false)));
+        assert!(store.record_receipt("seller://bob", &receipt("0x03", true)));
+        assert!(store.record_receipt("seller://bob", &receipt("0x04", false)));
+        assert!(!store.record_receipt("seller://bob", &receipt("0x04", false)));
+        assert!(!store.record_receipt("seller://carol", &receipt("0x04", false)));
+        assert!(store.reputation("seller://carol").is_none());
+        assert!(!store.record_receipt("seller://bob", &Receipt { txid: None, late: true }));
+
+        let reputation = store.reputation("seller://bob").unwrap();
+        assert_eq!((reputation.settled, reputation.late), (4, 1));
+        assert_eq!(reputation.on_time_bps, 7_500);
+    }
+
+    #[test]
+    fn test_annotate_offer() {
+        let dir = tempfile::tempdir().unwrap();
+        let reputation = Arc::new(ReputationStore::new());
+        reputation.record_receipt("seller://bob", &receipt("0x01", true));
+        let router = L9Router::new(Arc::new(registry(&dir)), reputation);
+
+        let envelope = EconomicEnvelope::new(50);
+        let offer = OfferMessage::new("offer-1", "q-1", "USDC", 1_000, false, envelope);
+        let annotated = router.annotate_offer(offer.clone(), "seller://bob");
+        assert_eq!(annotated.seller_reputation.unwrap().on_time_bps, 0);
+        assert!(router.annotate_offer(offer, "seller://carol").seller_reputation.is_none());
+    }
+}
@@ -2,9 +2,19 @@
 
 pub mod router;
 pub mod agent;
+pub mod l8;
+pub mod l9;
+pub mod l10;
+pub mod pipeline;
+pub mod peering;
 
 pub use router::Router;
-pub use agent::Agent;
+pub use agent::{Agent, AgentPool, AssignmentStrategy};
+pub use l8::{L8Rejection, L8Router, PriceSource, StaticPriceTable};
+pub use l10::{PolicyDecision, PolicyEngine, PolicyOutcome, PolicySet};
+pub use l9::{FileIdentityRegistry, IdentityRegistry, L9Rejection, L9Router, ReputationStore};
+pub use pipeline::{RoutingContext, RoutingStage, TbcGateway};
+pub use peering::{MultiHopRouter, Peer, PeerTable, PeerTransport};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,816 @@
+//! Multi-hop routing between peered TBC Controllers
+//!
+//! Each Controller governs one Transaction Area, identified by its TAI.
+//! Neighbouring Controllers advertise which sellers, assets and chains they
+//! can reach; a QUERY for a seller served by a peer is forwarded instead of
+//! priced locally.
+//!
+//! # Path Handling (TGP-TAI §A.5)
+//!
+//! The QUERY's `tgp_path` lists the areas it has passed. On ingress a
+//! Controller rejects the QUERY if its own TAI is already on the path
+//! (loop) and appends it otherwise. It forwards only to peers not on the
+//! path and only while the path is shorter than the hop limit. The final
+//! Controller stamps the complete path on the OFFER, which travels back
+//! unchanged.
+//!
+//! # Privacy and Escrow
+//!
+//! With an [`L9Router`] attached, the buyer is replaced by its pseudonym
+//! before a QUERY leaves this Controller. A QUERY for which local policy
+//! demands CoreProver escrow is forwarded with `zk_profile` REQUIRED, and
+//! peer OFFERs without escrow are refused.
+//!
+//! # Fees
+//!
+//! Every forwarding Controller adds its own routing fee to the OFFER's
+//! economic envelope and re-checks the fee cap, so the buyer sees the sum
+//! of all per-hop routing fees.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use tbc_core::tgp::messages::{QueryMessage, TGPMessage};
+//! use tbc_core::tgp::types::ZkProfile;
+//! use tbc_gateway::l8::{L8Router, PriceQuote, StaticPriceTable};
+//! use tbc_gateway::peering::{
+//!     ForwardingStage, InProcessTransport, MultiHopRouter, Peer, PeerTable,
+//! };
+//! use tbc_gateway::pipeline::{EconomicStage, TbcGateway};
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! // Seller's Controller, tai:8bde4411
+//! let prices = StaticPriceTable::new()
+//!     .with_price("seller://bob", "USDC", PriceQuote::new(1_000_000));
+//! let seller_side = MultiHopRouter::new("tai:8bde4411", Arc::new(InProcessTransport::new()))
+//!     .unwrap();
+//! let seller_gateway = TbcGateway::new()
+//!     .with_stage(Arc::new(ForwardingStage(Arc::new(seller_side))))
+//!     .with_stage(Arc::new(EconomicStage(L8Router::new(Arc::new(prices)))));
+//!
+//! // Buyer's Controller, tai:7abf92c6, peered with the seller's
+//! let transport =
+//!     InProcessTransport::new().with_gateway("tai:8bde4411", Arc::new(seller_gateway));
+//! let peers = PeerTable::new()
+//!     .with_peer(Peer::new("tai:8bde4411").with_sellers(vec!["seller://bob".into()]))
+//!     .unwrap();
+//! let buyer_side = MultiHopRouter::new("tai:7abf92c6", Arc::new(transport))
+//!     .unwrap()
+//!     .with_routing_fee_bps(10)
+//!     .with_peers(peers);
+//! let gateway = TbcGateway::new().with_stage(Arc::new(ForwardingStage(Arc::new(buyer_side))));
+//!
+//! let query = QueryMessage::new("q-1", "buyer://alice", "seller://bob", "USDC", 1_000_000,
+//!     ZkProfile::None);
+//! let TGPMessage::Offer(offer) = gateway.handle_query(query).await else { panic!() };
+//! assert_eq!(offer.tgp_path.as_deref(), Some("tai:7abf92c6>tai:8bde4411"));
+//! assert_eq!(offer.economic_envelope.routing_fee.unwrap().amount, "1000");
+//! # });
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tbc_core::tgp::asset::AssetId;
+use tbc_core::tgp::errors::TgpErrorCode;
+use tbc_core::tgp::messages::{ErrorMessage, OfferMessage, QueryMessage, TGPMessage};
+use tbc_core::tgp::path::TgpPath;
+use tbc_core::tgp::types::ZkProfile;
+use tbc_core::tgp::uri::UriParseError;
+use thiserror::Error;
+
+use crate::l9::L9Router;
+use crate::pipeline::{RoutingContext, RoutingStage, StageResult, TbcGateway};
+
+/// Default limit on the number of Transaction Areas a QUERY may traverse
+pub const DEFAULT_MAX_HOPS: usize = 8;
+
+// ============================================================================
+// Peers
+// ============================================================================
+
+/// Neighbouring TBC Controller and what it advertises
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Peer {
+    /// TAI of the peer's Transaction Area
+    pub tai: String,
+
+    /// Where the peer accepts TGP messages
+    #[serde(default)]
+    pub endpoint: String,
+
+    /// Sellers reachable through the peer
+    #[serde(default)]
+    pub sellers: Vec<String>,
+
+    /// Asset symbols or ids the peer routes; empty means any
+    #[serde(default)]
+    pub assets: Vec<String>,
+
+    /// Settlement chains the peer routes; empty means any
+    #[serde(default)]
+    pub chains: Vec<u64>,
+
+    /// Routing fee the peer advertises, used to rank peers
+    #[serde(default)]
+    pub routing_fee_bps: u32,
+}
+
+impl Peer {
+    pub fn new(tai: impl Into<String>) -> Self {
+        Self {
+            tai: tai.into(),
+            endpoint: String::new(),
+            sellers: Vec::new(),
+            assets: Vec::new(),
+            chains: Vec::new(),
+            routing_fee_bps: 0,
+        }
+    }
+
+    /// Builder method to set the peer's endpoint
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Builder method to set the reachable sellers
+    pub fn with_sellers(mut self, sellers: Vec<String>) -> Self {
+        self.sellers = sellers;
+        self
+    }
+
+    /// Builder method to set the routed assets
+    pub fn with_assets(mut self, assets: Vec<String>) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    /// Builder method to set the routed chains
+    pub fn with_chains(mut self, chains: Vec<u64>) -> Self {
+        self.chains = chains;
+        self
+    }
+
+    /// Builder method to set the advertised routing fee
+    pub fn with_routing_fee_bps(mut self, routing_fee_bps: u32) -> Self {
+        self.routing_fee_bps = routing_fee_bps;
+        self
+    }
+
+    /// Whether the peer advertises a route for the QUERY
+    pub fn serves(&self, query: &QueryMessage) -> bool {
+        if !self.sellers.iter().any(|s| s.eq_ignore_ascii_case(&query.to)) {
+            return false;
+        }
+
+        let asset = AssetId::parse(&query.asset).ok();
+        let symbol = match &asset {
+            Some(AssetId::Symbol(symbol)) | Some(AssetId::Evm { symbol, .. }) => Some(symbol),
+            _ => None,
+        };
+        let asset_ok = self.assets.is_empty()
+            || self.assets.iter().any(|a| {
+                a.eq_ignore_ascii_case(&query.asset)
+                    || symbol.is_some_and(|s| a.eq_ignore_ascii_case(s))
+            });
+
+        let chain = asset.as_ref().and_then(AssetId::chain_id);
+        let chain_ok = self.chains.is_empty() || chain.is_none_or(|c| self.chains.contains(&c));
+
+        asset_ok && chain_ok
+    }
+}
+
+/// Peers of this Controller, keyed by normalized TAI
+#[derive(Debug, Clone, Default)]
+pub struct PeerTable {
+    peers: BTreeMap<String, Peer>,
+}
+
+impl PeerTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to add a peer
+    pub fn with_peer(mut self, peer: Peer) -> Result<Self, UriParseError> {
+        self.insert(peer)?;
+        Ok(self)
+    }
+
+    /// Add or replace a peer (a fresh advertisement)
+    pub fn insert(&mut self, mut peer: Peer) -> Result<(), UriParseError> {
+        peer.tai = normalize_tai(&peer.tai)?;
+        self.peers.insert(peer.tai.clone(), peer);
+        Ok(())
+    }
+
+    /// Withdraw a peer
+    pub fn remove(&mut self, tai: &str) -> Option<Peer> {
+        self.peers.remove(&normalize_tai(tai).ok()?)
+    }
+
+    pub fn get(&self, tai: &str) -> Option<&Peer> {
+        self.peers.get(&normalize_tai(tai).ok()?)
+    }
+
+    pub fn peers(&self) -> impl Iterator<Item = &Peer> {
+        self.peers.values()
+    }
+
+    /// Peers serving the QUERY that are not on `path`, cheapest first
+    pub fn candidates(&self, query: &QueryMessage, path: &TgpPath) -> Vec<&Peer> {
+        let mut candidates: Vec<&Peer> = self
+            .peers
+            .values()
+            .filter(|peer| !path.contains(&peer.tai) && peer.serves(query))
+            .collect();
+        candidates.sort_by(|a, b| {
+            a.routing_fee_bps.cmp(&b.routing_fee_bps).then(a.tai.cmp(&b.tai))
+        });
+        candidates
+    }
+}
+
+fn normalize_tai(tai: &str) -> Result<String, UriParseError> {
+    TgpPath::new().extended(tai).map(|path| path.to_string())
+}
+
+// ============================================================================
+// Transport
+// ============================================================================
+
+/// Delivers a forwarded QUERY to a peer and returns its answer
+#[async_trait]
+pub trait PeerTransport: Send + Sync {
+    async fn forward(&self, peer: &Peer, query: &QueryMessage) -> anyhow::Result<TGPMessage>;
+}
+
+/// Transport to gateways running in the same process
+#[derive(Default)]
+pub struct InProcessTransport {
+    gateways: HashMap<String, Arc<TbcGateway>>,
+}
+
+impl InProcessTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method to reach `gateway` as peer `tai`
+    pub fn with_gateway(mut self, tai: &str, gateway: Arc<TbcGateway>) -> Self {
+        let tai = normalize_tai(tai).unwrap_or_else(|_| tai.to_string());
+        self.gateways.insert(tai, gateway);
+        self
+    }
+}
+
+#[async_trait]
+impl PeerTransport for InProcessTransport {
+    async fn forward(&self, peer: &Peer, query: &QueryMessage) -> anyhow::Result<TGPMessage> {
+        let gateway = self
+            .gateways
+            .get(&peer.tai)
+            .ok_or_else(|| anyhow!("no connection to peer {}", peer.tai))?;
+        Ok(gateway.handle_query(query.clone()).await)
+    }
+}
+
+// ============================================================================
+// Rejections
+// ============================================================================
+
+/// Why a QUERY could not be forwarded
+#[derive(Debug, Error)]
+pub enum HopRejection {
+    #[error("invalid tgp_path: {0}")]
+    InvalidPath(UriParseError),
+
+    #[error("routing loop: {tai} is already on the path {path}")]
+    Loop { tai: String, path: String },
+
+    #[error("hop limit {max_hops} reached on path {path}")]
+    HopLimit { max_hops: usize, path: String },
+
+    #[error("peer {peer} returned an OFFER with path {path:?} not extending {expected}")]
+    InconsistentPath {
+        peer: String,
+        path: Option<String>,
+        expected: String,
+    },
+
+    #[error("fee cap exceeded: {0}")]
+    FeeCapExceeded(String),
+
+    #[error("peer {peer} returned an OFFER without the required CoreProver escrow")]
+    EscrowNotOffered { peer: String },
+
+    #[error("{}", .0.message)]
+    Peer(Box<ErrorMessage>),
+
+    #[error("peer {peer} unreachable: {source}")]
+    Transport { peer: String, source: anyhow::Error },
+}
+
+impl HopRejection {
+    /// TGP error code of the rejection
+    pub fn code(&self) -> TgpErrorCode {
+        match self {
+            HopRejection::InvalidPath(_) => TgpErrorCode::InvalidQuery,
+            HopRejection::FeeCapExceeded(_) => TgpErrorCode::FeeCapExceeded,
+            HopRejection::EscrowNotOffered { .. } => TgpErrorCode::PolicyViolation,
+            HopRejection::Peer(error) => error.error_code().unwrap_or(TgpErrorCode::NoRoute),
+            HopRejection::Loop { .. }
+            | HopRejection::HopLimit { .. }
+            | HopRejection::InconsistentPath { .. }
+            | HopRejection::Transport { .. } => TgpErrorCode::NoRoute,
+        }
+    }
+
+    /// ERROR message answering `query`
+    pub fn to_error_message(&self, query: &QueryMessage) -> ErrorMessage {
+        match self {
+            HopRejection::Peer(error) => (**error).clone(),
+            _ => ErrorMessage::from_code(format!("err-{}", query.id), self.code(), self.to_string())
+                .correlated_to(query.id.clone()),
+        }
+    }
+}
+
+// ============================================================================
+// MultiHopRouter
+// ============================================================================
+
+/// Path-vector forwarding of QUERYs to peered Controllers
+pub struct MultiHopRouter {
+    local_tai: String,
+    peers: RwLock<PeerTable>,
+    transport: Arc<dyn PeerTransport>,
+    max_hops: usize,
+    routing_fee_bps: u32,
+    identity: Option<L9Router>,
+}
+
+impl MultiHopRouter {
+    /// Router for the Transaction Area `local_tai`
+    pub fn new(local_tai: &str, transport: Arc<dyn PeerTransport>) -> Result<Self, UriParseError> {
+        Ok(Self {
+            local_tai: normalize_tai(local_tai)?,
+            peers: RwLock::new(PeerTable::new()),
+            transport,
+            max_hops: DEFAULT_MAX_HOPS,
+            routing_fee_bps: 0,
+            identity: None,
+        })
+    }
+
+    /// Builder method to set the initial peer table
+    pub fn with_peers(mut self, peers: PeerTable) -> Self {
+        self.peers = RwLock::new(peers);
+        self
+    }
+
+    /// Builder method to set the hop limit
+    pub fn with_max_hops(mut self, max_hops: usize) -> Self {
+        self.max_hops = max_hops;
+        self
+    }
+
+    /// Builder method to set the routing fee this Controller adds per hop
+    pub fn with_routing_fee_bps(mut self, routing_fee_bps: u32) -> Self {
+        self.routing_fee_bps = routing_fee_bps;
+        self
+    }
+
+    /// Builder method to pseudonymize buyers on egress
+    ///
+    /// Without it QUERYs are forwarded with the buyer's own id.
+    pub fn with_identity(mut self, identity: L9Router) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// TAI of this Controller
+    pub fn local_tai(&self) -> &str {
+        &self.local_tai
+    }
+
+    /// Add or refresh a peer advertisement
+    pub fn add_peer(&self, peer: Peer) -> Result<(), UriParseError> {
+        self.peers.write().unwrap().insert(peer)
+    }
+
+    /// Withdraw a peer
+    pub fn remove_peer(&self, tai: &str) -> Option<Peer> {
+        self.peers.write().unwrap().remove(tai)
+    }
+
+    /// Snapshot of the peer table
+    pub fn peers(&self) -> PeerTable {
+        self.peers.read().unwrap().clone()
+    }
+
+    /// Path of the QUERY with this Controller appended
+    ///
+    /// Fails if the QUERY already passed this Controller.
+    pub fn ingress_path(&self, query: &QueryMessage) -> Result<TgpPath, HopRejection> {
+        let path = match query.tgp_path.as_deref() {
+            Some(path) => TgpPath::parse(path).map_err(HopRejection::InvalidPath)?,
+            None => TgpPath::new(),
+        };
+        if path.contains(&self.local_tai) {
+            return Err(HopRejection::Loop {
+                tai: self.local_tai.clone(),
+                path: path.to_string(),
+            });
+        }
+        path.extended(&self.local_tai).map_err(HopRejection::InvalidPath)
+    }
+
+    /// Forward the QUERY to the best peer serving it
+    ///
+    /// `path` is the ingress path including this Controller. Returns
+    /// `Ok(None)` when no peer serves the QUERY, so it is handled locally.
+    /// Peers are tried cheapest first; the last failure is returned if
+    /// none answers with an acceptable OFFER. A QUERY with `zk_profile`
+    /// REQUIRED only accepts OFFERs carrying CoreProver escrow.
+    pub async fn forward(
+        &self,
+        query: &QueryMessage,
+        path: &TgpPath,
+    ) -> Result<Option<(OfferMessage, String)>, HopRejection> {
+        let candidates: Vec<Peer> = {
+            let peers = self.peers.read().unwrap();
+            peers.candidates(query, path).into_iter().cloned().collect()
+        };
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+        if path.len() >= self.max_hops {
+            return Err(HopRejection::HopLimit {
+                max_hops: self.max_hops,
+                path: path.to_string(),
+            });
+        }
+
+        let forwarded = match &self.identity {
+            Some(identity) => identity.pseudonymize(query),
+            None => query.clone(),
+        };
+        let forwarded = forwarded.with_tgp_path(path.to_string());
+        let mut last = None;
+        for peer in candidates {
+            let result = match self.transport.forward(&peer, &forwarded).await {
+                Ok(TGPMessage::Offer(offer)) => self.accept(offer, query, path, &peer),
+                Ok(TGPMessage::Error(error)) => Err(HopRejection::Peer(Box::new(error))),
+                Ok(other) => Err(HopRejection::Transport {
+                    peer: peer.tai.clone(),
+                    source: anyhow!("unexpected {:?} reply", phase_of(&other)),
+                }),
+                Err(source) => Err(HopRejection::Transport {
+                    peer: peer.tai.clone(),
+                    source,
+                }),
+            };
+            match result {
+                Ok(offer) => return Ok(Some((offer, peer.tai))),
+                Err(rejection) => {
+                    tracing::debug!(query = %query.id, peer = %peer.tai, %rejection, "peer failed");
+                    last = Some(rejection);
+                }
+            }
+        }
+        Err(last.expect("at least one candidate"))
+    }
+
+    /// Check a peer's OFFER and add this hop's routing fee
+    fn accept(
+        &self,
+        mut offer: OfferMessage,
+        query: &QueryMessage,
+        path: &TgpPath,
+        peer: &Peer,
+    ) -> Result<OfferMessage, HopRejection> {
+        let offer_path = offer.tgp_path.as_deref().and_then(|p| TgpPath::parse(p).ok());
+        let extends = offer_path.is_some_and(|p| p.len() > path.len() && p.starts_with(path));
+        if offer.query_id != query.id || !extends {
+            return Err(HopRejection::InconsistentPath {
+                peer: peer.tai.clone(),
+                path: offer.tgp_path,
+                expected: path.to_string(),
+            });
+        }
+        let escrowed = offer.zk_required && offer.coreprover_contract.is_some();
+        if query.zk_profile == ZkProfile::Required && !escrowed {
+            return Err(HopRejection::EscrowNotOffered {
+                peer: peer.tai.clone(),
+            });
+        }
+
+        let fee = (offer.amount as u128 * self.routing_fee_bps as u128).div_ceil(10_000);
+        if fee > 0 {
+            offer
+                .economic_envelope
+                .add_routing_fee(&fee.to_string(), &offer.asset)
+                .map_err(HopRejection::FeeCapExceeded)?;
+        }
+        offer
+            .economic_envelope
+            .check_fee_cap()
+            .map_err(HopRejection::FeeCapExceeded)?;
+        Ok(offer)
+    }
+}
+
+fn phase_of(message: &TGPMessage) -> &'static str {
+    match message {
+        TGPMessage::Query(_) => "QUERY",
+        TGPMessage::Offer(_) => "OFFER",
+        TGPMessage::Settle(_) => "SETTLE",
+        TGPMessage::Error(_) => "ERROR",
+    }
+}
+
+// ============================================================================
+// Pipeline Stage
+// ============================================================================
+
+/// Routing stage forwarding QUERYs to peers
+///
+/// Place it after the identity and policy stages and before the economic
+/// stage: QUERYs no peer serves pass through to be priced locally, with
+/// the ingress path recorded in the context. Escrow demanded by local
+/// policy is required of the peer, and the local seller reputation is
+/// attached when the peer's OFFER has none.
+pub struct ForwardingStage(pub Arc<MultiHopRouter>);
+
+#[async_trait]
+impl RoutingStage for ForwardingStage {
+    fn name(&self) -> &str {
+        "forwarding"
+    }
+
+    async fn process(&self, ctx: &mut RoutingContext) -> StageResult {
+        let path = self
+            .0
+            .ingress_path(&ctx.query)
+            .map_err(|e| e.to_error_message(&ctx.query))?;
+        let mut query = ctx.query.clone();
+        if ctx.requires_escrow() {
+            query.zk_profile = ZkProfile::Required;
+        }
+        let forwarded = self
+            .0
+            .forward(&query, &path)
+            .await
+            .map_err(|e| e.to_error_message(&ctx.query))?;
+        ctx.tgp_path = Some(path);
+
+        Ok(Some(match forwarded {
+            Some((mut offer, peer)) => {
+                if offer.seller_reputation.is_none() {
+                    offer.seller_reputation = ctx.seller_reputation;
+                }
+                ctx.offer = Some(offer);
+                format!("forwarded to {}", peer)
+            }
+            None => "served locally".to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l10::{PolicyContext, PolicyEngine, PolicySet};
+    use crate::l8::{L8Router, PriceQuote, StaticPriceTable};
+    use crate::l9::{FileIdentityRegistry, ReputationStore};
+    use crate::pipeline::EconomicStage;
+    use tbc_core::tgp::types::{EconomicEnvelope, SellerReputation};
+
+    const A: &str = "tai:aa";
+    const B: &str = "tai:bb";
+    const C: &str = "tai:cc";
+
+    fn peer(tai: &str) -> Peer {
+        Peer::new(tai).with_sellers(vec!["seller://bob".into()])
+    }
+
+    fn peers(peers: Vec<Peer>) -> PeerTable {
+        peers.into_iter().fold(PeerTable::new(), |table, peer| table.with_peer(peer).unwrap())
+    }
+
+    fn query(id: &str) -> QueryMessage {
+        QueryMessage::new(id, "buyer://alice", "seller://bob", "USDC", 1_000_000, ZkProfile::None)
+    }
+
+    /// Gateway for `tai` forwarding through `router`, pricing locally if `prices`
+    fn gateway(router: MultiHopRouter, prices: bool) -> Arc<TbcGateway> {
+        let mut gateway = TbcGateway::new().with_stage(Arc::new(ForwardingStage(Arc::new(router))));
+        if prices {
+            let table = StaticPriceTable::new()
+                .with_price("seller://bob", "USDC", PriceQuote::new(1_000_000));
+            gateway = gateway.with_stage(Arc::new(EconomicStage(L8Router::new(Arc::new(table)))));
+        }
+        Arc::new(gateway)
+    }
+
+    fn router(tai: &str, transport: InProcessTransport) -> MultiHopRouter {
+        MultiHopRouter::new(tai, Arc::new(transport)).unwrap()
+    }
+
+    /// A -> B -> C, C prices the QUERY; A and B charge `fees` bps
+    fn chain(fees: (u32, u32)) -> Arc<TbcGateway> {
+        let c = gateway(router(C, InProcessTransport::new()), true);
+        let b = router(B, InProcessTransport::new().with_gateway(C, c))
+            .with_routing_fee_bps(fees.1)
+            .with_peers(peers(vec![peer(C)]));
+        let b = gateway(b, false);
+        let a = router(A, InProcessTransport::new().with_gateway(B, b))
+            .with_routing_fee_bps(fees.0)
+            .with_peers(peers(vec![peer(B)]));
+        gateway(a, false)
+    }
+
+    #[test]
+    fn test_peer_table() {
+        let table = PeerTable::new()
+            .with_peer(peer("TAI:BB").with_routing_fee_bps(5).with_assets(vec!["USDC".into()]))
+            .unwrap()
+            .with_peer(peer("tai:cc").with_routing_fee_bps(1).with_chains(vec![369]))
+            .unwrap()
+            .with_peer(Peer::new("tai:dd"))
+            .unwrap();
+        assert!(table.get("tai:bb").is_some());
+        assert!(PeerTable::new().with_peer(Peer::new("seller://bob")).is_err());
+
+        let empty = TgpPath::new();
+        let tais = |query: &QueryMessage, path: &TgpPath| -> Vec<String> {
+            table.candidates(query, path).iter().map(|p| p.tai.clone()).collect()
+        };
+        assert_eq!(tais(&query("q-1"), &empty), vec!["tai:cc", "tai:bb"]);
+        assert_eq!(tais(&query("q-1"), &TgpPath::parse("tai:cc").unwrap()), vec!["tai:bb"]);
+
+        let mut on_base = query("q-2");
+        on_base.asset = "evm:USDC:8453".into();
+        assert_eq!(tais(&on_base, &empty), vec!["tai:bb"]);
+        on_base.to = "seller://carol".into();
+        assert!(tais(&on_base, &empty).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_multi_hop_offer() {
+        let TGPMessage::Offer(offer) = chain((20, 10)).handle_query(query("q-1")).await else {
+            panic!("expected OFFER");
+        };
+        assert_eq!(offer.tgp_path.as_deref(), Some("tai:aa>tai:bb>tai:cc"));
+        assert_eq!(offer.query_id, "q-1");
+        assert_eq!(offer.economic_envelope.routing_fee.unwrap().amount, "3000");
+        assert_eq!(offer.decisions[0].detail.as_deref(), Some("forwarded to tai:bb"));
+    }
+
+    #[tokio::test]
+    async fn test_fee_cap_across_hops() {
+        let TGPMessage::Error(error) = chain((60, 50)).handle_query(query("q-1")).await else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::FeeCapExceeded));
+        assert_eq!(error.correlation_id.as_deref(), Some("q-1"));
+    }
+
+    #[tokio::test]
+    async fn test_loop_and_hop_limit() {
+        let b = router(B, InProcessTransport::new()).with_peers(peers(vec![peer(A), peer(C)]));
+
+        // A is on the path, so only C is a candidate
+        let path = b.ingress_path(&query("q-1").with_tgp_path(A)).unwrap();
+        assert_eq!(path.to_string(), "tai:aa>tai:bb");
+        let candidates = b.peers().candidates(&query("q-1"), &path).len();
+        assert_eq!(candidates, 1);
+
+        let looped = query("q-1").with_tgp_path("tai:aa>tai:bb");
+        let rejection = b.ingress_path(&looped).unwrap_err();
+        assert!(matches!(rejection, HopRejection::Loop { .. }));
+        assert_eq!(rejection.code(), TgpErrorCode::NoRoute);
+
+        let b = b.with_max_hops(2);
+        let rejection = b.forward(&query("q-1"), &path).await.unwrap_err();
+        assert!(matches!(rejection, HopRejection::HopLimit { max_hops: 2, .. }));
+
+        let garbage = query("q-1").with_tgp_path("bob");
+        assert_eq!(b.ingress_path(&garbage).unwrap_err().code(), TgpErrorCode::InvalidQuery);
+    }
+
+    #[tokio::test]
+    async fn test_failover_between_peers() {
+        // Cheapest peer is unreachable, the next one answers
+        let c = gateway(router(C, InProcessTransport::new()), true);
+        let a = router(A, InProcessTransport::new().with_gateway(C, c))
+            .with_peers(peers(vec![
+                peer(B).with_routing_fee_bps(1),
+                peer(C).with_routing_fee_bps(5),
+            ]));
+        let path = a.ingress_path(&query("q-1")).unwrap();
+        let (offer, via) = a.forward(&query("q-1"), &path).await.unwrap().unwrap();
+        assert_eq!(via, "tai:cc");
+        assert_eq!(offer.tgp_path.as_deref(), Some("tai:aa>tai:cc"));
+
+        a.remove_peer(C).unwrap();
+        let rejection = a.forward(&query("q-1"), &path).await.unwrap_err();
+        assert!(matches!(rejection, HopRejection::Transport { .. }));
+
+        // No peer serves the seller: handled locally
+        let mut carol = query("q-2");
+        carol.to = "seller://carol".into();
+        assert!(a.forward(&carol, &path).await.unwrap().is_none());
+    }
+
+    /// Peer answering every QUERY with `offer`, recording what it received
+    struct Recording {
+        offer: OfferMessage,
+        received: RwLock<Vec<QueryMessage>>,
+    }
+
+    #[async_trait]
+    impl PeerTransport for Recording {
+        async fn forward(&self, peer: &Peer, query: &QueryMessage) -> anyhow::Result<TGPMessage> {
+            self.received.write().unwrap().push(query.clone());
+            let mut offer = self.offer.clone();
+            offer.query_id = query.id.clone();
+            offer.tgp_path = Some(format!("{}>{}", query.tgp_path.as_deref().unwrap(), peer.tai));
+            Ok(TGPMessage::Offer(offer))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_policy_and_privacy_on_egress() {
+        let dir = tempfile::tempdir().unwrap();
+        let identities = FileIdentityRegistry::open(dir.path().join("identities.json")).unwrap();
+        let identity = L9Router::new(Arc::new(identities), Arc::new(ReputationStore::new()));
+        let policy = PolicyEngine::new().with_set(
+            PolicySet::from_json(
+                r#"{
+                    "policy": "policy://test/escrow-v1",
+                    "rules": [
+                        { "id": "zk", "kind": "require_zk", "above": 0, "profile": "REQUIRED" }
+                    ]
+                }"#,
+            )
+            .unwrap(),
+        );
+
+        let forward = |escrowed: bool| {
+            let mut offer = OfferMessage::new(
+                "offer-1",
+                "q-1",
+                "USDC",
+                1_000_000,
+                escrowed,
+                EconomicEnvelope::new(50),
+            );
+            if escrowed {
+                offer.coreprover_contract = Some("0x".to_string() + &"c".repeat(40));
+            }
+            let transport = Arc::new(Recording {
+                offer,
+                received: RwLock::new(Vec::new()),
+            });
+            let router = MultiHopRouter::new(A, transport.clone())
+                .unwrap()
+                .with_identity(identity.clone())
+                .with_peers(peers(vec![peer(B)]));
+
+            let mut optional = query("q-1");
+            optional.zk_profile = ZkProfile::Optional;
+            let mut ctx = RoutingContext::new(optional);
+            ctx.policy = Some(policy.evaluate(&PolicyContext::new(&ctx.query)));
+            ctx.seller_reputation = Some(SellerReputation::new(10, 1));
+            let stage = ForwardingStage(Arc::new(router));
+            async move {
+                let result = stage.process(&mut ctx).await;
+                let received = transport.received.read().unwrap()[0].clone();
+                (result, ctx, received)
+            }
+        };
+
+        // The peer sees the pseudonym and the escrow local policy demands
+        let (result, ctx, received) = forward(true).await;
+        assert!(result.is_ok());
+        assert_eq!(received.from, identity.pseudonymize(&query("q-1")).from);
+        assert!(received.from.starts_with("buyer://anon-"));
+        assert_eq!(received.zk_profile, ZkProfile::Required);
+        let offer = ctx.offer.unwrap();
+        assert_eq!(offer.seller_reputation, Some(SellerReputation::new(10, 1)));
+
+        // A peer OFFER without escrow is refused
+        let (result, ctx, _) = forward(false).await;
+        let error = result.unwrap_err();
+        assert_eq!(error.error_code(), Some(TgpErrorCode::PolicyViolation));
+        assert!(ctx.offer.is_none());
+    }
+}
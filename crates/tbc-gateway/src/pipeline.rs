@@ -0,0 +1,1479 @@
+//! Composable routing pipeline and the TBC gateway
+//!
+//! A [`TbcGateway`] answers a QUERY by running it through an ordered list
+//! of [`RoutingStage`]s sharing one [`RoutingContext`]. The standard
+//! pipeline is identity (L9) → policy (L10) → economic (L8); operators can
+//! insert their own stages anywhere.
+//!
+//! Each stage records a [`RoutingDecision`]. The first stage to reject
+//! short-circuits the pipeline and its ERROR is returned; otherwise the
+//! OFFER produced by the stages is returned. Either way the decisions made
+//! so far are attached to the answer.
+//!
+//! As a [`TgpRouter`] the gateway sits behind the TxIP handlers: QUERYs
+//! received on a TxIP session are answered with the OFFER or ERROR, and
+//! SETTLEs from the agent that sent the QUERY close its order; a SETTLE
+//! from another session's agent is refused. With a [`TGPSessionManager`]
+//! attached, the policy rule deciding each QUERY and the OFFER are recorded
+//! on the TGP session of the same id, owned by the session's agent; a HELLO
+//! from that agent naming the session resumes it and gets the OFFER
+//! replayed. With a [`ReceiptSource`] attached, the
+//! CoreProver receipt of each successful SETTLE is fed into the seller's
+//! reputation, which the identity stage attaches to later OFFERs from that
+//! seller.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use async_trait::async_trait;
+//! use tbc_core::tgp::messages::{ErrorMessage, QueryMessage, TGPMessage};
+//! use tbc_core::tgp::types::ZkProfile;
+//! use tbc_gateway::pipeline::{RoutingContext, RoutingStage, StageResult, TbcGateway};
+//!
+//! /// Operator stage refusing QUERYs to one seller
+//! struct Maintenance;
+//!
+//! #[async_trait]
+//! impl RoutingStage for Maintenance {
+//!     fn name(&self) -> &str {
+//!         "maintenance"
+//!     }
+//!
+//!     async fn process(&self, ctx: &mut RoutingContext) -> StageResult {
+//!         if ctx.query.to == "seller://bob" {
+//!             return Err(ErrorMessage::new("err-1", "NO_ROUTE", "seller in maintenance"));
+//!         }
+//!         Ok(None)
+//!     }
+//! }
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let gateway = TbcGateway::new().with_stage(Arc::new(Maintenance));
+//! let query = QueryMessage::new("q-1", "buyer://alice", "seller://bob", "USDC", 1_000,
+//!     ZkProfile::Optional);
+//!
+//! let TGPMessage::Error(error) = gateway.handle_query(query).await else { panic!() };
+//! assert_eq!(error.decisions[0].stage, "maintenance");
+//! # });
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tbc_core::gateway::{Gateway, GatewayStatus};
+use tbc_core::tgp::errors::TgpErrorCode;
+use tbc_core::tgp::messages::{
+    error_codes, ErrorMessage, OfferMessage, QueryMessage, SettleMessage, TGPMessage,
+};
+use tbc_core::tgp::asset::AssetId;
+use tbc_core::tgp::clock::{Clock, SystemClock};
+use tbc_core::tgp::path::TgpPath;
+use tbc_core::tgp::state::{TGPState, TransitionActor};
+use tbc_core::tgp::store::{SessionStoreError, TGPSessionManager};
+use tbc_core::tgp::types::{RoutingDecision, SellerReputation};
+use tokio::task::JoinHandle;
+use txip::{SessionInfo, TgpRouter};
+
+use crate::agent::AgentPool;
+use crate::l10::{PolicyContext, PolicyDecision, PolicyEngine, PolicyOutcome};
+use crate::l8::L8Router;
+use crate::l9::{EscrowReceipt, L9Router, ReceiptSource, ReputationStore, ResolvedParties};
+
+// ============================================================================
+// Stages
+// ============================================================================
+
+/// State shared by the stages while routing one QUERY
+#[derive(Debug, Clone)]
+pub struct RoutingContext {
+    pub query: QueryMessage,
+
+    /// Session the QUERY arrived on
+    pub session_id: Option<String>,
+
+    /// Authenticated agent of that session
+    pub agent_id: Option<String>,
+
+    /// Settlement chain, from a chain-pinned asset or the session
+    pub chain_id: Option<u64>,
+
+    /// Discount codes presented with the QUERY
+    pub discount_codes: Vec<String>,
+
+    /// Jurisdiction tags of the parties, filled from their L9 identity records
+    pub jurisdictions: Vec<String>,
+
+    /// Set by the identity stage
+    pub parties: Option<ResolvedParties>,
+
+    /// Set by the identity stage
+    pub seller_reputation: Option<SellerReputation>,
+
+    /// Set by the policy stage
+    pub policy: Option<PolicyDecision>,
+
+    /// Set by the forwarding stage: traversed TAIs including this Controller
+    pub tgp_path: Option<TgpPath>,
+
+    /// Set by the forwarding or economic stage
+    pub offer: Option<OfferMessage>,
+
+    /// Verdicts of the stages run so far
+    pub decisions: Vec<RoutingDecision>,
+}
+
+impl RoutingContext {
+    pub fn new(query: QueryMessage) -> Self {
+        let chain_id = AssetId::parse(&query.asset).ok().and_then(|id| id.chain_id());
+        Self {
+            query,
+            session_id: None,
+            agent_id: None,
+            chain_id,
+            discount_codes: Vec::new(),
+            jurisdictions: Vec::new(),
+            parties: None,
+            seller_reputation: None,
+            policy: None,
+            tgp_path: None,
+            offer: None,
+            decisions: Vec::new(),
+        }
+    }
+
+    /// Builder method to set the session the QUERY arrived on
+    pub fn with_session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Builder method to set the agent the QUERY came from
+    pub fn with_agent(mut self, agent_id: impl Into<String>) -> Self {
+        self.agent_id = Some(agent_id.into());
+        self
+    }
+
+    /// Builder method to set the settlement chain
+    pub fn with_chain(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Builder method to set the presented discount codes
+    pub fn with_discount_codes(mut self, codes: Vec<String>) -> Self {
+        self.discount_codes = codes;
+        self
+    }
+
+    /// Builder method to set the jurisdiction tags
+    pub fn with_jurisdictions(mut self, jurisdictions: Vec<String>) -> Self {
+        self.jurisdictions = jurisdictions;
+        self
+    }
+
+    /// Whether an earlier stage demanded CoreProver escrow
+    pub fn requires_escrow(&self) -> bool {
+        self.policy.as_ref().is_some_and(|p| p.requires_escrow())
+    }
+}
+
+/// `Ok(detail)` lets the QUERY through, `Err` rejects it with that ERROR
+pub type StageResult = Result<Option<String>, ErrorMessage>;
+
+/// One step of the routing pipeline
+#[async_trait]
+pub trait RoutingStage: Send + Sync {
+    /// Stage name recorded in [`RoutingDecision::stage`]
+    fn name(&self) -> &str;
+
+    /// Inspect or enrich the context
+    async fn process(&self, ctx: &mut RoutingContext) -> StageResult;
+}
+
+/// L9: resolve the parties and look up the seller's reputation
+pub struct IdentityStage(pub L9Router);
+
+#[async_trait]
+impl RoutingStage for IdentityStage {
+    fn name(&self) -> &str {
+        "identity"
+    }
+
+    async fn process(&self, ctx: &mut RoutingContext) -> StageResult {
+        let parties = self
+            .0
+            .resolve_parties(&ctx.query)
+            .await
+            .map_err(|e| e.to_error_message(&ctx.query))?;
+        ctx.seller_reputation = self.0.reputation().reputation(&ctx.query.to);
+        for tag in parties.buyer.jurisdictions.iter().chain(&parties.seller.jurisdictions) {
+            if !ctx.jurisdictions.contains(tag) {
+                ctx.jurisdictions.push(tag.clone());
+            }
+        }
+        let detail = format!("buyer as {}", parties.buyer.pseudonym);
+        ctx.parties = Some(parties);
+        Ok(Some(detail))
+    }
+}
+
+/// L10: evaluate the operator policy
+///
+/// `allowed_chains` rules see the context's chain; QUERYs without one (a
+/// bare asset symbol on a session that negotiated several chains) are not
+/// checked against them.
+pub struct PolicyStage(pub PolicyEngine);
+
+#[async_trait]
+impl RoutingStage for PolicyStage {
+    fn name(&self) -> &str {
+        "policy"
+    }
+
+    async fn process(&self, ctx: &mut RoutingContext) -> StageResult {
+        let mut policy_ctx =
+            PolicyContext::new(&ctx.query).with_jurisdictions(ctx.jurisdictions.clone());
+        if let Some(chain_id) = ctx.chain_id {
+            policy_ctx = policy_ctx.with_chain(chain_id);
+        }
+        let decision = self.0.evaluate(&policy_ctx);
+        if let Some(error) = decision.to_error_message(&ctx.query) {
+            ctx.policy = Some(decision);
+            return Err(error);
+        }
+
+        let detail = decision.rule_id.as_ref().map(|rule| match decision.outcome {
+            PolicyOutcome::RequireEscrow => format!("escrow required by rule {}", rule),
+            _ => format!("rule {}", rule),
+        });
+        ctx.policy = Some(decision);
+        Ok(detail)
+    }
+}
+
+/// L8: price the QUERY and produce the OFFER
+///
+/// Skipped when a peer already answered the QUERY.
+pub struct EconomicStage(pub L8Router);
+
+#[async_trait]
+impl RoutingStage for EconomicStage {
+    fn name(&self) -> &str {
+        "economic"
+    }
+
+    async fn process(&self, ctx: &mut RoutingContext) -> StageResult {
+        if ctx.offer.is_some() {
+            return Ok(Some("priced by peer".to_string()));
+        }
+
+        let codes: Vec<&str> = ctx.discount_codes.iter().map(String::as_str).collect();
+        let reputation = ctx.seller_reputation.as_ref();
+        let (mut offer, settlement) = self
+            .0
+            .route_explained(&ctx.query, &codes, reputation, ctx.requires_escrow())
+            .await
+            .map_err(|e| e.to_error_message(&ctx.query))?;
+        if let Some(reputation) = ctx.seller_reputation {
+            offer = offer.with_seller_reputation(reputation);
+        }
+        if let Some(path) = &ctx.tgp_path {
+            offer = offer.with_tgp_path(path.to_string());
+        }
+
+        let detail = format!("{} {} via {}", offer.amount, offer.asset, settlement);
+        ctx.offer = Some(offer);
+        Ok(Some(detail))
+    }
+}
+
+// ============================================================================
+// TbcGateway
+// ============================================================================
+
+/// Gateway answering QUERYs through a routing pipeline
+///
+/// Every OFFER issued is tracked as an active order, keyed by QUERY id,
+/// until a SETTLE for it arrives or the order expires: at the OFFER's
+/// envelope `expiry`, else [`DEFAULT_ORDER_TTL_SECS`] after it was issued.
+/// Expired orders are dropped by [`TbcGateway::expire_orders`], which
+/// [`spawn_order_sweeper`] runs periodically. A QUERY reusing the id of
+/// another agent's unexpired order is refused. With an [`AgentPool`] each
+/// active order is also assigned to an agent and released on settlement
+/// or expiry.
+pub struct TbcGateway {
+    stages: Vec<Arc<dyn RoutingStage>>,
+    active: RwLock<HashMap<String, ActiveOrder>>,
+    agents: Option<Arc<AgentPool>>,
+    sessions: Option<Arc<TGPSessionManager>>,
+    receipts: Option<Arc<dyn ReceiptSource>>,
+    reputation: Option<Arc<ReputationStore>>,
+    clock: Arc<dyn Clock>,
+    order_ttl_secs: u64,
+}
+
+/// Lifetime of an active order whose OFFER carries no `expiry`
+pub const DEFAULT_ORDER_TTL_SECS: u64 = 3600;
+
+/// How often [`spawn_order_sweeper`] is meant to run in a controller
+pub const ORDER_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// An OFFER awaiting its SETTLE
+struct ActiveOrder {
+    offer: OfferMessage,
+    seller: String,
+    /// Seller wallet resolved by the identity stage
+    seller_address: Option<String>,
+    /// Agent whose session the QUERY arrived on
+    agent_id: Option<String>,
+    expires_unix: u64,
+}
+
+impl ActiveOrder {
+    /// Whether `escrow` pays this order's seller the OFFER's amount
+    fn is_settled_by(&self, escrow: &EscrowReceipt) -> bool {
+        let seller = self.seller_address.as_deref().unwrap_or(&self.seller);
+        escrow.seller.eq_ignore_ascii_case(seller) && escrow.amount == self.offer.amount
+    }
+}
+
+impl Default for TbcGateway {
+    fn default() -> Self {
+        Self {
+            stages: Vec::new(),
+            active: RwLock::new(HashMap::new()),
+            agents: None,
+            sessions: None,
+            receipts: None,
+            reputation: None,
+            clock: Arc::new(SystemClock),
+            order_ttl_secs: DEFAULT_ORDER_TTL_SECS,
+        }
+    }
+}
+
+impl TbcGateway {
+    /// Gateway with an empty pipeline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gateway running identity → policy → economic
+    ///
+    /// Settled receipts are recorded in the reputation store of `identity`.
+    pub fn standard(identity: L9Router, policy: PolicyEngine, economic: L8Router) -> Self {
+        Self::new()
+            .with_reputation_store(identity.reputation().clone())
+            .with_stage(Arc::new(IdentityStage(identity)))
+            .with_stage(Arc::new(PolicyStage(policy)))
+            .with_stage(Arc::new(EconomicStage(economic)))
+    }
+
+    /// Builder method to append a stage
+    pub fn with_stage(mut self, stage: Arc<dyn RoutingStage>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Builder method to assign active orders to agents of `pool`
+    pub fn with_agent_pool(mut self, pool: Arc<AgentPool>) -> Self {
+        self.agents = Some(pool);
+        self
+    }
+
+    /// Builder method to record policy decisions and OFFERs on the TGP
+    /// sessions of `manager`
+    pub fn with_session_manager(mut self, manager: Arc<TGPSessionManager>) -> Self {
+        self.sessions = Some(manager);
+        self
+    }
+
+    /// Builder method to look up the receipt of each successful SETTLE
+    pub fn with_receipt_source(mut self, receipts: Arc<dyn ReceiptSource>) -> Self {
+        self.receipts = Some(receipts);
+        self
+    }
+
+    /// Builder method to record settled receipts in `store`
+    pub fn with_reputation_store(mut self, store: Arc<ReputationStore>) -> Self {
+        self.reputation = Some(store);
+        self
+    }
+
+    /// Builder method to inject the clock used for order expiry
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Builder method to set the lifetime of orders without an OFFER expiry
+    pub fn with_order_ttl(mut self, secs: u64) -> Self {
+        self.order_ttl_secs = secs;
+        self
+    }
+
+    /// Insert a stage at `index`, e.g. before the economic stage
+    pub fn insert_stage(&mut self, index: usize, stage: Arc<dyn RoutingStage>) {
+        self.stages.insert(index.min(self.stages.len()), stage);
+    }
+
+    /// Names of the stages in order
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    /// Answer a QUERY with an OFFER or ERROR
+    pub async fn handle_query(&self, query: QueryMessage) -> TGPMessage {
+        self.handle(RoutingContext::new(query)).await
+    }
+
+    /// Answer a QUERY with a prepared context
+    pub async fn handle(&self, mut ctx: RoutingContext) -> TGPMessage {
+        let report = ctx.query.validation_report();
+        if !report.is_valid() {
+            let error = ErrorMessage::from_report(
+                format!("err-{}", ctx.query.id),
+                error_codes::INVALID_QUERY,
+                &report,
+            );
+            return TGPMessage::Error(error.correlated_to(ctx.query.id.clone()));
+        }
+        if self.is_taken(&self.active.read().unwrap(), &ctx.query.id, &ctx.agent_id) {
+            return TGPMessage::Error(query_id_taken(&ctx.query.id));
+        }
+
+        for stage in &self.stages {
+            match stage.process(&mut ctx).await {
+                Ok(detail) => {
+                    let decision = RoutingDecision::pass(stage.name());
+                    ctx.decisions.push(match detail {
+                        Some(detail) => decision.with_detail(detail),
+                        None => decision,
+                    });
+                }
+                Err(error) => {
+                    self.record_policy_rule(&ctx);
+                    ctx.decisions
+                        .push(RoutingDecision::reject(stage.name()).with_detail(&error.message));
+                    tracing::debug!(query = %ctx.query.id, stage = stage.name(), "QUERY rejected");
+                    return TGPMessage::Error(error.with_decisions(ctx.decisions));
+                }
+            }
+        }
+
+        self.record_policy_rule(&ctx);
+        match ctx.offer.take() {
+            Some(offer) => {
+                if let Some(pool) = &self.agents {
+                    if let Err(e) = pool.assign(&offer.query_id) {
+                        let error = ErrorMessage::from_code(
+                            format!("err-{}", ctx.query.id),
+                            TgpErrorCode::Internal,
+                            e.to_string(),
+                        )
+                        .correlated_to(ctx.query.id.clone());
+                        return TGPMessage::Error(error.with_decisions(ctx.decisions));
+                    }
+                }
+
+                let offer = offer.with_decisions(std::mem::take(&mut ctx.decisions));
+                let order = ActiveOrder {
+                    expires_unix: self.expiry_of(&offer),
+                    offer: offer.clone(),
+                    seller: ctx.query.to.clone(),
+                    seller_address: ctx.parties.as_ref().map(|p| p.seller.address.clone()),
+                    agent_id: ctx.agent_id.clone(),
+                };
+                let mut active = self.active.write().unwrap();
+                if self.is_taken(&active, &offer.query_id, &order.agent_id) {
+                    return TGPMessage::Error(query_id_taken(&offer.query_id));
+                }
+                active.insert(offer.query_id.clone(), order);
+                drop(active);
+                self.record_offer(&ctx, &offer);
+                TGPMessage::Offer(offer)
+            }
+            None => {
+                let error = ErrorMessage::from_code(
+                    format!("err-{}", ctx.query.id),
+                    TgpErrorCode::NoRoute,
+                    "no routing stage produced an OFFER",
+                )
+                .correlated_to(ctx.query.id.clone());
+                TGPMessage::Error(error.with_decisions(ctx.decisions))
+            }
+        }
+    }
+
+    /// Whether `query_id` names an unexpired order of another agent
+    fn is_taken(
+        &self,
+        active: &HashMap<String, ActiveOrder>,
+        query_id: &str,
+        agent_id: &Option<String>,
+    ) -> bool {
+        let now = self.clock.now_unix();
+        active
+            .get(query_id)
+            .is_some_and(|order| now < order.expires_unix && order.agent_id != *agent_id)
+    }
+
+    /// Record the deciding policy rule on the QUERY's TGP session
+    fn record_policy_rule(&self, ctx: &RoutingContext) {
+        let (Some(sessions), Some(session_id)) = (&self.sessions, &ctx.session_id) else {
+            return;
+        };
+        let Some(rule_id) = ctx.policy.as_ref().and_then(|p| p.rule_id.as_deref()) else {
+            return;
+        };
+
+        let recorded = open_session(sessions, session_id, ctx)
+            .and_then(|()| sessions.record_policy_rule(session_id, rule_id));
+        if let Err(e) = recorded {
+            tracing::warn!(session = %session_id, rule = rule_id, "policy rule not recorded: {e}");
+        }
+    }
+
+    /// Record the OFFER on the QUERY's TGP session, to replay on resume
+    ///
+    /// Only the first QUERY of a session is recorded.
+    fn record_offer(&self, ctx: &RoutingContext, offer: &OfferMessage) {
+        let (Some(sessions), Some(session_id)) = (&self.sessions, &ctx.session_id) else {
+            return;
+        };
+
+        let query_id = Some(ctx.query.id.as_str());
+        let recorded = open_session(sessions, session_id, ctx)
+            .and_then(|()| {
+                let (state, actor) = (TGPState::QuerySent, TransitionActor::Buyer);
+                sessions.transition_by(session_id, state, actor, query_id)
+            })
+            .and_then(|_| sessions.record_offer(session_id, offer.clone()));
+        if let Err(e) = recorded {
+            tracing::debug!(session = %session_id, offer = %offer.id, "OFFER not recorded: {e}");
+        }
+    }
+
+    /// Unix time an order for `offer` expires
+    fn expiry_of(&self, offer: &OfferMessage) -> u64 {
+        offer
+            .economic_envelope
+            .expiry
+            .as_deref()
+            .and_then(|expiry| chrono::DateTime::parse_from_rfc3339(expiry).ok())
+            .map(|expiry| expiry.timestamp().max(0) as u64)
+            .unwrap_or_else(|| self.clock.now_unix().saturating_add(self.order_ttl_secs))
+    }
+
+    /// The OFFER issued for an active order
+    pub fn active_offer(&self, query_id: &str) -> Option<OfferMessage> {
+        let now = self.clock.now_unix();
+        self.active
+            .read()
+            .unwrap()
+            .get(query_id)
+            .filter(|order| now < order.expires_unix)
+            .map(|order| order.offer.clone())
+    }
+
+    /// Drop the active orders past their expiry
+    ///
+    /// Releases their agent assignments and returns their QUERY ids.
+    pub fn expire_orders(&self) -> Vec<String> {
+        let now = self.clock.now_unix();
+        let mut active = self.active.write().unwrap();
+        let mut expired: Vec<String> = active
+            .iter()
+            .filter(|(_, order)| now >= order.expires_unix)
+            .map(|(query_id, _)| query_id.clone())
+            .collect();
+        expired.sort();
+
+        for query_id in &expired {
+            active.remove(query_id);
+            if let Some(pool) = &self.agents {
+                pool.release(query_id);
+            }
+        }
+        if !expired.is_empty() {
+            tracing::debug!(count = expired.len(), "active orders expired");
+        }
+        expired
+    }
+
+    /// Close the order a SETTLE refers to (by QUERY or OFFER id)
+    ///
+    /// Returns the OFFER of the closed order.
+    pub fn settle(&self, settle: &SettleMessage) -> Option<OfferMessage> {
+        self.close_order(settle, None).ok().flatten().map(|order| order.offer)
+    }
+
+    /// Close the order a SETTLE refers to and record its receipt
+    ///
+    /// A successful SETTLE naming its CoreProver `session_id` has the
+    /// receipt of that escrow fetched from the [`ReceiptSource`] and
+    /// counted towards the seller's reputation, provided the escrow pays the
+    /// order's seller the OFFER's amount.
+    pub async fn settle_and_record(&self, settle: &SettleMessage) -> Option<OfferMessage> {
+        let order = self.close_order(settle, None).ok().flatten()?;
+        self.record_settlement(&order, settle).await;
+        Some(order.offer)
+    }
+
+    async fn record_settlement(&self, order: &ActiveOrder, settle: &SettleMessage) {
+        if let (true, Some(escrow), Some(receipts), Some(reputation)) = (
+            settle.success,
+            &settle.session_id,
+            &self.receipts,
+            &self.reputation,
+        ) {
+            match receipts.receipt(escrow).await {
+                Ok(Some(settled)) if !order.is_settled_by(&settled) => {
+                    tracing::warn!(
+                        escrow = %escrow,
+                        order = %order.offer.query_id,
+                        "escrow does not match the order; receipt ignored"
+                    );
+                }
+                Ok(Some(settled)) => {
+                    if !reputation.record_receipt(&order.seller, &settled.receipt) {
+                        tracing::debug!(escrow = %escrow, "receipt not counted");
+                    }
+                }
+                Ok(None) => tracing::debug!(escrow = %escrow, "no receipt for SETTLE"),
+                Err(e) => tracing::warn!(escrow = %escrow, "receipt lookup failed: {e}"),
+            }
+        }
+    }
+
+    /// Remove the unexpired order `settle` refers to
+    ///
+    /// With `agent` set, an order placed from another agent's session is
+    /// left open and its QUERY id returned as the error.
+    fn close_order(
+        &self,
+        settle: &SettleMessage,
+        agent: Option<&str>,
+    ) -> Result<Option<ActiveOrder>, String> {
+        let now = self.clock.now_unix();
+        let mut active = self.active.write().unwrap();
+        let Some((key, order)) = active.iter().find(|(query_id, order)| {
+            **query_id == settle.query_or_offer_id || order.offer.id == settle.query_or_offer_id
+        }) else {
+            return Ok(None);
+        };
+        if now >= order.expires_unix {
+            return Ok(None);
+        }
+        if let (Some(agent), Some(owner)) = (agent, &order.agent_id) {
+            if agent != owner {
+                return Err(key.clone());
+            }
+        }
+
+        let key = key.clone();
+        if let Some(pool) = &self.agents {
+            pool.release(&key);
+        }
+        Ok(active.remove(&key))
+    }
+}
+
+/// Create the TGP session of `ctx` for its agent, unless it exists
+fn open_session(
+    sessions: &TGPSessionManager,
+    session_id: &str,
+    ctx: &RoutingContext,
+) -> Result<(), SessionStoreError> {
+    let created = match &ctx.agent_id {
+        Some(agent_id) => sessions.create_for(session_id, agent_id),
+        None => sessions.create(session_id),
+    };
+    match created {
+        Ok(_) | Err(SessionStoreError::AlreadyExists(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+fn query_id_taken(query_id: &str) -> ErrorMessage {
+    ErrorMessage::from_code(
+        format!("err-{}", query_id),
+        TgpErrorCode::InvalidQuery,
+        format!("QUERY id {} is already in use", query_id),
+    )
+    .correlated_to(query_id)
+}
+
+/// Expire the active orders of `gateway` every `every`
+pub fn spawn_order_sweeper(gateway: Arc<TbcGateway>, every: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            gateway.expire_orders();
+        }
+    })
+}
+
+#[async_trait]
+impl Gateway for TbcGateway {
+    /// Id of the OFFER issued for the order (QUERY id)
+    async fn route_order(&self, order_id: &str) -> Result<String> {
+        self.active_offer(order_id)
+            .map(|offer| offer.id)
+            .ok_or_else(|| anyhow!("no active order {}", order_id))
+    }
+
+    async fn status(&self) -> Result<GatewayStatus> {
+        Ok(GatewayStatus {
+            active_orders: {
+                let now = self.clock.now_unix();
+                let active = self.active.read().unwrap();
+                active.values().filter(|order| now < order.expires_unix).count()
+            },
+            ..GatewayStatus::default()
+        })
+    }
+}
+
+#[async_trait]
+impl TgpRouter for TbcGateway {
+    async fn route_message(
+        &self,
+        session: &SessionInfo,
+        message: TGPMessage,
+    ) -> Option<TGPMessage> {
+        match message {
+            TGPMessage::Query(query) => {
+                let mut ctx = RoutingContext::new(query)
+                    .with_session(session.session_id.clone())
+                    .with_agent(session.agent_id.clone());
+                if let ([chain_id], None) = (session.negotiated.chains.as_slice(), ctx.chain_id) {
+                    ctx = ctx.with_chain(*chain_id);
+                }
+                Some(self.handle(ctx).await)
+            }
+            TGPMessage::Settle(settle) => {
+                match self.close_order(&settle, Some(&session.agent_id)) {
+                    Ok(Some(order)) => self.record_settlement(&order, &settle).await,
+                    Ok(None) => tracing::debug!(
+                        session = %session.session_id,
+                        order = %settle.query_or_offer_id,
+                        "SETTLE for unknown order"
+                    ),
+                    Err(order) => {
+                        tracing::warn!(
+                            session = %session.session_id,
+                            order = %order,
+                            "SETTLE refused: order belongs to another agent"
+                        );
+                        let error = ErrorMessage::from_code(
+                            format!("err-{}", settle.id),
+                            TgpErrorCode::Unauthorized,
+                            format!("order {} was not placed by {}", order, session.agent_id),
+                        )
+                        .correlated_to(settle.id);
+                        return Some(TGPMessage::Error(error));
+                    }
+                }
+                None
+            }
+            TGPMessage::Offer(offer) => {
+                let error = ErrorMessage::from_code(
+                    format!("err-{}", offer.id),
+                    TgpErrorCode::InvalidMessage,
+                    "OFFERs are issued by the controller, not sent to it",
+                )
+                .correlated_to(offer.id);
+                Some(TGPMessage::Error(error))
+            }
+            TGPMessage::Error(_) => None,
+        }
+    }
+
+    async fn withdraw_reply(&self, session: &SessionInfo, reply: &TGPMessage) {
+        let TGPMessage::Offer(offer) = reply else {
+            return;
+        };
+        let mut active = self.active.write().unwrap();
+        let placed = active.get(&offer.query_id).is_some_and(|order| {
+            order.offer.id == offer.id && order.agent_id.as_deref() == Some(&session.agent_id)
+        });
+        if !placed {
+            return;
+        }
+
+        active.remove(&offer.query_id);
+        if let Some(pool) = &self.agents {
+            pool.release(&offer.query_id);
+        }
+
+        // The refused OFFER must not be replayed on resume
+        if let Some(sessions) = &self.sessions {
+            let recorded = sessions
+                .get(&session.session_id)
+                .is_ok_and(|record| record.session.offer_id.as_deref() == Some(&offer.id));
+            if recorded {
+                let (state, actor) = (TGPState::Errored, TransitionActor::Controller);
+                let errored =
+                    sessions.transition_by(&session.session_id, state, actor, Some(&offer.id));
+                if let Err(e) = errored {
+                    tracing::warn!(session = %session.session_id, "session not closed: {e}");
+                }
+            }
+        }
+        tracing::debug!(
+            session = %session.session_id,
+            order = %offer.query_id,
+            "Withdrew refused OFFER"
+        );
+    }
+
+    fn resume_session(&self, session_id: &str, agent_id: &str) -> Option<TGPMessage> {
+        let sessions = self.sessions.as_ref()?;
+        match sessions.resume(session_id, agent_id) {
+            Ok(record) => record.offer.map(TGPMessage::Offer),
+            Err(e) => {
+                tracing::debug!(session = %session_id, agent = %agent_id, "not resumed: {e}");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::Agent;
+    use crate::l10::PolicySet;
+    use crate::l8::{PriceQuote, StaticPriceTable};
+    use crate::l9::{FileIdentityRegistry, IdentityRecord, ReputationStore};
+    use tbc_core::tgp::clock::ManualClock;
+    use tbc_core::tgp::routes::RouteOption;
+    use tbc_core::tgp::store::InMemorySessionStore;
+    use tbc_core::tgp::types::{DecisionOutcome, SettleSource, ZkProfile};
+
+    const CONTRACT: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb0";
+    const ALICE: &str = "0x742d35cc6634c0532925a3b844bc9e7595f0beb0";
+    const BOB: &str = "0xab5801a7d398351b8be11c439e05c5b3259aec9b";
+
+    fn gateway(dir: &tempfile::TempDir) -> TbcGateway {
+        let identities = FileIdentityRegistry::open(dir.path().join("ids.json")).unwrap();
+        identities.register(IdentityRecord::new("buyer://alice", ALICE)).unwrap();
+        identities.register(IdentityRecord::new("seller://bob", BOB)).unwrap();
+        let identity = L9Router::new(Arc::new(identities), Arc::new(ReputationStore::new()));
+
+        let policy = PolicyEngine::new().with_set(
+            PolicySet::from_json(
+                r#"{"policy": "policy://test", "rules": [
+                    {"id": "cap", "kind": "amount_cap", "asset": "USDC", "max_amount": 1000000},
+                    {"id": "escrow", "kind": "require_zk", "above": 500000, "profile": "REQUIRED"}
+                ]}"#,
+            )
+            .unwrap(),
+        );
+
+        let prices = StaticPriceTable::new()
+            .with_price("seller://bob", "USDC", PriceQuote::new(1_000))
+            .with_price("seller://bob", "WBTC", PriceQuote::new(1_000));
+        TbcGateway::standard(identity, policy, L8Router::new(Arc::new(prices)))
+    }
+
+    fn query(id: &str, amount: u64, zk_profile: ZkProfile) -> QueryMessage {
+        QueryMessage::with_escrow_from_402(
+            id,
+            "buyer://alice",
+            "seller://bob",
+            "USDC",
+            amount,
+            CONTRACT,
+            zk_profile,
+        )
+    }
+
+    fn stages(decisions: &[RoutingDecision]) -> Vec<(&str, DecisionOutcome)> {
+        decisions.iter().map(|d| (d.stage.as_str(), d.outcome)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_offer_through_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        let gateway = gateway(&dir);
+        assert_eq!(gateway.stage_names(), vec!["identity", "policy", "economic"]);
+
+        let TGPMessage::Offer(offer) =
+            gateway.handle_query(query("q-1", 1_000, ZkProfile::None)).await
+        else {
+            panic!("expected OFFER");
+        };
+        assert_eq!(
+            stages(&offer.decisions),
+            vec![
+                ("identity", DecisionOutcome::Pass),
+                ("policy", DecisionOutcome::Pass),
+                ("economic", DecisionOutcome::Pass)
+            ]
+        );
+        assert!(offer.coreprover_contract.is_none());
+        assert_eq!(
+            offer.decisions[2].detail.as_deref(),
+            Some("1000 USDC via direct x402 (buyer declined escrow)")
+        );
+
+        assert_eq!(gateway.status().await.unwrap().active_orders, 1);
+        assert_eq!(gateway.route_order("q-1").await.unwrap(), offer.id);
+        assert!(gateway.route_order("q-2").await.is_err());
+
+        let settle = SettleMessage::new("settle-1", &offer.id, true, SettleSource::BuyerNotify);
+        assert_eq!(gateway.settle(&settle).unwrap().id, offer.id);
+        assert_eq!(gateway.status().await.unwrap().active_orders, 0);
+    }
+
+    #[tokio::test]
+    async fn test_policy_escrow_reaches_offer() {
+        let dir = tempfile::tempdir().unwrap();
+        let TGPMessage::Offer(offer) =
+            gateway(&dir).handle_query(query("q-1", 600_000, ZkProfile::Optional)).await
+        else {
+            panic!("expected OFFER");
+        };
+        assert!(!offer.zk_required);
+        assert_eq!(offer.coreprover_contract.as_deref(), Some(CONTRACT));
+        assert_eq!(offer.decisions[1].detail.as_deref(), Some("escrow required by rule escrow"));
+        assert_eq!(
+            offer.decisions[2].detail.as_deref(),
+            Some("1000 USDC via escrow (policy requires escrow)")
+        );
+
+        let TGPMessage::Offer(offer) =
+            gateway(&dir).handle_query(query("q-2", 600_000, ZkProfile::Required)).await
+        else {
+            panic!("expected OFFER");
+        };
+        assert!(offer.zk_required);
+    }
+
+    #[tokio::test]
+    async fn test_policy_rule_recorded_on_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = Arc::new(TGPSessionManager::new(
+            Arc::new(InMemorySessionStore::new()),
+            Arc::new(ManualClock::new(1_731_600_000)),
+        ));
+        let gateway = gateway(&dir).with_session_manager(sessions.clone());
+
+        let escrow = RoutingContext::new(query("q-1", 600_000, ZkProfile::Optional));
+        let reply = gateway.handle(escrow.with_session("sess-1")).await;
+        assert!(matches!(reply, TGPMessage::Offer(_)));
+        let session = sessions.get("sess-1").unwrap().session;
+        assert_eq!(session.policy_rule.as_deref(), Some("escrow"));
+
+        let denied = RoutingContext::new(query("q-2", 2_000_000, ZkProfile::Required));
+        let reply = gateway.handle(denied.with_session("sess-1")).await;
+        assert!(matches!(reply, TGPMessage::Error(_)));
+        let session = sessions.get("sess-1").unwrap().session;
+        assert_eq!(session.policy_rule.as_deref(), Some("cap"));
+
+        // No rule objected, only the OFFER is recorded
+        let plain = RoutingContext::new(query("q-3", 1_000, ZkProfile::None));
+        gateway.handle(plain.with_session("sess-2")).await;
+        let record = sessions.get("sess-2").unwrap();
+        assert!(record.session.policy_rule.is_none());
+        assert_eq!(record.offer.unwrap().query_id, "q-3");
+    }
+
+    #[tokio::test]
+    async fn test_allowed_chains_sees_context_chain() {
+        let policy = PolicyEngine::new().with_set(
+            PolicySet::from_json(
+                r#"{"policy": "policy://test", "rules": [
+                    {"id": "chains", "kind": "allowed_chains", "chains": [1]}
+                ]}"#,
+            )
+            .unwrap(),
+        );
+        let gateway = TbcGateway::new().with_stage(Arc::new(PolicyStage(policy)));
+        let query = query("q-1", 1_000, ZkProfile::None);
+
+        let on_bsc = gateway.handle(RoutingContext::new(query.clone()).with_chain(56)).await;
+        let TGPMessage::Error(error) = on_bsc else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::PolicyViolation));
+
+        let mut pinned = query.clone();
+        pinned.asset = "evm:USDC:56".to_string();
+        let TGPMessage::Error(error) = gateway.handle_query(pinned).await else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::PolicyViolation));
+
+        // Allowed: the pipeline only lacks an economic stage
+        let on_mainnet = gateway.handle(RoutingContext::new(query).with_chain(1)).await;
+        let TGPMessage::Error(error) = on_mainnet else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::NoRoute));
+    }
+
+    #[tokio::test]
+    async fn test_jurisdictions_from_identity_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let identities = FileIdentityRegistry::open(dir.path().join("ids.json")).unwrap();
+        let tagged = |id: &str, address: &str, tags: &[&str]| {
+            let tags = tags.iter().map(|tag| tag.to_string()).collect();
+            IdentityRecord::new(id, address).with_jurisdictions(tags)
+        };
+        identities.register(tagged("buyer://alice", ALICE, &["us-ca"])).unwrap();
+        identities.register(tagged("buyer://erin", ALICE, &[])).unwrap();
+        identities.register(tagged("seller://bob", BOB, &["us-ny"])).unwrap();
+        identities.register(tagged("seller://carol", CONTRACT, &["kp"])).unwrap();
+        identities.register(tagged("seller://dave", BOB, &[])).unwrap();
+        let identity = L9Router::new(Arc::new(identities), Arc::new(ReputationStore::new()));
+        let policy = PolicyEngine::new().with_set(
+            PolicySet::from_json(
+                r#"{"policy": "policy://test", "rules": [
+                    {"id": "region", "kind": "jurisdiction",
+                     "allowed": ["us-ca", "us-ny"], "blocked": ["kp"]}
+                ]}"#,
+            )
+            .unwrap(),
+        );
+        let prices =
+            StaticPriceTable::new().with_price("seller://bob", "USDC", PriceQuote::new(1_000));
+        let gateway = TbcGateway::standard(identity, policy, L8Router::new(Arc::new(prices)));
+
+        let TGPMessage::Offer(_) = gateway.handle_query(query("q-1", 1_000, ZkProfile::None)).await
+        else {
+            panic!("expected OFFER");
+        };
+
+        let mut blocked = query("q-2", 1_000, ZkProfile::None);
+        blocked.to = "seller://carol".to_string();
+        let TGPMessage::Error(error) = gateway.handle_query(blocked).await else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::PolicyViolation));
+        assert!(error.message.contains("jurisdiction kp is blocked"));
+
+        let mut untagged = query("q-3", 1_000, ZkProfile::None);
+        untagged.from = "buyer://erin".to_string();
+        untagged.to = "seller://dave".to_string();
+        let TGPMessage::Error(error) = gateway.handle_query(untagged).await else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::PolicyViolation));
+        assert!(error.message.contains("no permitted jurisdiction"));
+    }
+
+    #[tokio::test]
+    async fn test_rejection_short_circuits() {
+        let dir = tempfile::tempdir().unwrap();
+        let gateway = gateway(&dir);
+
+        let TGPMessage::Error(error) =
+            gateway.handle_query(query("q-1", 2_000_000, ZkProfile::Required)).await
+        else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::PolicyViolation));
+        assert_eq!(
+            stages(&error.decisions),
+            vec![("identity", DecisionOutcome::Pass), ("policy", DecisionOutcome::Reject)]
+        );
+
+        let mut unknown = query("q-2", 1_000, ZkProfile::None);
+        unknown.from = "buyer://mallory".to_string();
+        let TGPMessage::Error(error) = gateway.handle_query(unknown).await else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::Unauthenticated));
+        assert_eq!(error.decisions.len(), 1);
+
+        let TGPMessage::Error(error) = gateway.handle_query(query("", 1_000, ZkProfile::None)).await
+        else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.code, error_codes::INVALID_QUERY);
+        assert!(error.decisions.is_empty());
+        assert_eq!(gateway.status().await.unwrap().active_orders, 0);
+    }
+
+    #[tokio::test]
+    async fn test_agent_assignment() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = Arc::new(AgentPool::new());
+        pool.register(Agent::new("agent-a".to_string())).unwrap();
+        let gateway = gateway(&dir).with_agent_pool(pool.clone());
+
+        let TGPMessage::Offer(offer) =
+            gateway.handle_query(query("q-1", 1_000, ZkProfile::None)).await
+        else {
+            panic!("expected OFFER");
+        };
+        assert_eq!(pool.agent_for("q-1").as_deref(), Some("agent-a"));
+
+        let TGPMessage::Error(error) =
+            gateway.handle_query(query("q-2", 1_000, ZkProfile::None)).await
+        else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::Internal));
+        assert_eq!(gateway.status().await.unwrap().active_orders, 1);
+
+        let settle = SettleMessage::new("settle-1", &offer.id, true, SettleSource::BuyerNotify);
+        gateway.settle(&settle).unwrap();
+        assert_eq!(pool.active_orders(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_query_id_reuse_by_other_agent_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let gateway = gateway(&dir);
+        let from = |agent: &str| {
+            RoutingContext::new(query("q-1", 1_000, ZkProfile::None)).with_agent(agent)
+        };
+
+        let TGPMessage::Offer(offer) = gateway.handle(from("buyer://alice")).await else {
+            panic!("expected OFFER");
+        };
+        let TGPMessage::Error(error) = gateway.handle(from("buyer://mallory")).await else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::InvalidQuery));
+
+        // alice's order is untouched and still hers to settle
+        assert_eq!(gateway.active_offer("q-1").unwrap().id, offer.id);
+        let settle = SettleMessage::new("settle-1", "q-1", true, SettleSource::BuyerNotify);
+        assert_eq!(gateway.close_order(&settle, Some("buyer://mallory")).err().unwrap(), "q-1");
+
+        // alice may re-quote her own QUERY
+        let TGPMessage::Offer(_) = gateway.handle(from("buyer://alice")).await else {
+            panic!("expected OFFER");
+        };
+        assert!(gateway.close_order(&settle, Some("buyer://alice")).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_orders_expire() {
+        let dir = tempfile::tempdir().unwrap();
+        let clock = Arc::new(ManualClock::new(1_000));
+        let pool = Arc::new(AgentPool::new());
+        pool.register(Agent::new("agent-a".to_string())).unwrap();
+        let gateway = gateway(&dir)
+            .with_agent_pool(pool.clone())
+            .with_clock(clock.clone())
+            .with_order_ttl(300);
+
+        let TGPMessage::Offer(offer) =
+            gateway.handle_query(query("q-1", 1_000, ZkProfile::None)).await
+        else {
+            panic!("expected OFFER");
+        };
+        clock.advance(299);
+        assert!(gateway.expire_orders().is_empty());
+        assert!(gateway.active_offer("q-1").is_some());
+
+        clock.advance(1);
+        assert!(gateway.active_offer("q-1").is_none());
+        assert_eq!(gateway.status().await.unwrap().active_orders, 0);
+        let settle = SettleMessage::new("settle-1", &offer.id, true, SettleSource::BuyerNotify);
+        assert!(gateway.settle(&settle).is_none());
+
+        assert_eq!(gateway.expire_orders(), vec!["q-1"]);
+        assert_eq!(pool.active_orders(), 0);
+
+        // The OFFER's own expiry wins over the TTL
+        let mut offer = offer;
+        offer.economic_envelope.expiry = Some("1970-01-01T00:30:00Z".to_string());
+        assert_eq!(gateway.expiry_of(&offer), 1_800);
+    }
+
+    #[tokio::test]
+    async fn test_settled_receipt_feeds_reputation() {
+        use crate::l9::EngineReceipts;
+        use coreprover_service::engine::CoreProverEngine;
+        use coreprover_service::types::PaymentProfile;
+        use std::sync::Mutex;
+
+        // Escrows fulfilled past their one-hour window and claimed
+        let mut engine = CoreProverEngine::new(8453, 2, 1_700_000_000);
+        let mut escrow = |seller: &str, amount: u64, txid: &str| {
+            let id = engine
+                .buyer_commit(
+                    ALICE.to_string(),
+                    seller.to_string(),
+                    amount,
+                    PaymentProfile::pizza_delivery(),
+                    8453,
+                    format!("{txid}0"),
+                )
+                .unwrap();
+            engine.seller_accept(&id, format!("{txid}1")).unwrap();
+            engine.advance_time(4_000);
+            engine.seller_fulfill(&id, format!("{txid}2")).unwrap();
+            engine.seller_claim(&id, format!("{txid}3")).unwrap();
+            format!("0x{}", hex::encode(id))
+        };
+        let to_bob = escrow(BOB, 1_000, "0xb");
+        let to_carol = escrow(CONTRACT, 1_000, "0xc");
+        let wrong_amount = escrow(BOB, 5, "0xd");
+
+        let dir = tempfile::tempdir().unwrap();
+        let receipts = EngineReceipts(Arc::new(Mutex::new(engine)));
+        let gateway = gateway(&dir).with_receipt_source(Arc::new(receipts));
+        let settle_with = |query_id: &str, escrow: &str| {
+            SettleMessage::new("settle-1", query_id, true, SettleSource::BuyerNotify)
+                .with_session(escrow)
+        };
+        let reputation = |query_id: &str| {
+            let query = query(query_id, 1_000, ZkProfile::None);
+            let gateway = &gateway;
+            async move {
+                let TGPMessage::Offer(offer) = gateway.handle_query(query).await else {
+                    panic!("expected OFFER");
+                };
+                offer.seller_reputation
+            }
+        };
+
+        // Escrows paying someone else, or another amount, do not count
+        assert!(reputation("q-1").await.is_none());
+        gateway.settle_and_record(&settle_with("q-1", &to_carol)).await.unwrap();
+        assert!(reputation("q-2").await.is_none());
+        gateway.settle_and_record(&settle_with("q-2", &wrong_amount)).await.unwrap();
+        assert!(reputation("q-3").await.is_none());
+
+        gateway.settle_and_record(&settle_with("q-3", &to_bob)).await.unwrap();
+        assert_eq!(reputation("q-4").await, Some(SellerReputation::new(1, 1)));
+
+        // Replayed and unknown escrows are not counted
+        gateway.settle_and_record(&settle_with("q-4", &to_bob)).await.unwrap();
+        assert_eq!(reputation("q-5").await, Some(SellerReputation::new(1, 1)));
+        let unknown = format!("0x{}", "00".repeat(32));
+        gateway.settle_and_record(&settle_with("q-5", &unknown)).await.unwrap();
+        assert_eq!(reputation("q-6").await, Some(SellerReputation::new(1, 1)));
+    }
+
+    #[tokio::test]
+    async fn test_custom_stage_and_empty_pipeline() {
+        struct Tag;
+
+        #[async_trait]
+        impl RoutingStage for Tag {
+            fn name(&self) -> &str {
+                "tag"
+            }
+
+            async fn process(&self, ctx: &mut RoutingContext) -> StageResult {
+                ctx.jurisdictions.push("us-ca".to_string());
+                Ok(Some("tagged".to_string()))
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut gateway = gateway(&dir);
+        gateway.insert_stage(1, Arc::new(Tag));
+        assert_eq!(gateway.stage_names(), vec!["identity", "tag", "policy", "economic"]);
+
+        let TGPMessage::Error(error) =
+            TbcGateway::new().handle_query(query("q-1", 1_000, ZkProfile::None)).await
+        else {
+            panic!("expected ERROR");
+        };
+        assert_eq!(error.error_code(), Some(TgpErrorCode::NoRoute));
+    }
+
+    struct FixedTime;
+
+    impl txip::TimestampProvider for FixedTime {
+        fn now(&self) -> txip::TripleTimestamp {
+            self.at_mono(1_000)
+        }
+
+        fn at_unix(&self, unix: u64) -> txip::TripleTimestamp {
+            txip::TripleTimestamp::new(1_000, unix, "2024-11-14T12:00:00Z".to_string())
+        }
+
+        fn at_mono(&self, mono: u64) -> txip::TripleTimestamp {
+            txip::TripleTimestamp::new(mono, 1_731_600_000, "2024-11-14T12:00:00Z".to_string())
+        }
+    }
+
+    /// TxIP handlers in front of `gateway`, with a fresh TxIP session table
+    fn txip_state(gateway: Arc<TbcGateway>) -> Arc<txip::HttpHandlerState<FixedTime>> {
+        use txip::*;
+
+        let provider = Arc::new(FixedTime);
+        Arc::new(HttpHandlerState {
+            session_manager: Arc::new(SessionManager::new(SessionConfig::default(), provider)),
+            tbc_id: "tbc://test".to_string(),
+            authenticators: Arc::new(Authenticators::open()),
+            rate_limiter: Arc::new(tbc_core::tgp::ratelimit::RateLimiter::default()),
+            tgp_router: Some(gateway as Arc<dyn TgpRouter>),
+        })
+    }
+
+    fn hello(msg_id: &str, session_id: &str, agent_id: &str) -> txip::TxipEnvelope {
+        use txip::*;
+
+        let hello = HelloPayload {
+            agent_id: agent_id.to_string(),
+            supported_tgp_versions: vec!["TGP-01".to_string()],
+            supported_transports: vec!["HTTP".to_string()],
+            supported_chains: vec![1],
+            supported_assets: vec!["USDC".to_string()],
+            features: Features {
+                zk_discount_proofs: false,
+                receipt_ownership_proofs: false,
+                late_discount_support: false,
+                cross_chain_support: false,
+            },
+            auth: AuthInfo {
+                scheme: AuthScheme::None,
+                token: None,
+            },
+        };
+        TxipEnvelope::new(
+            msg_id.to_string(),
+            session_id.to_string(),
+            Direction::ClientToTbc,
+            Role::BuyerAgent,
+            MessageType::Control,
+            TgpPhase::None,
+            FixedTime.now(),
+            Payload::Control(ControlPayload::Hello(hello)),
+        )
+    }
+
+    fn query_envelope(msg_id: &str, session_id: &str, query: QueryMessage) -> txip::TxipEnvelope {
+        use txip::*;
+
+        TxipEnvelope::tgp(
+            msg_id.to_string(),
+            session_id.to_string(),
+            Direction::ClientToTbc,
+            Role::BuyerAgent,
+            TgpPhase::Query,
+            FixedTime.now(),
+            serde_json::to_value(TGPMessage::Query(query)).unwrap(),
+        )
+    }
+
+    async fn send(
+        state: &Arc<txip::HttpHandlerState<FixedTime>>,
+        envelope: txip::TxipEnvelope,
+    ) -> serde_json::Value {
+        use axum::extract::{Json, State};
+
+        let response = txip::handle_txip_message(State(state.clone()), Json(envelope)).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_txip_session_reaches_pipeline() {
+        use txip::*;
+
+        /// Settles `q-cross` on Base, which the sessions do not negotiate
+        struct BaseRoute;
+
+        #[async_trait]
+        impl RoutingStage for BaseRoute {
+            fn name(&self) -> &str {
+                "base-route"
+            }
+
+            async fn process(&self, ctx: &mut RoutingContext) -> StageResult {
+                if ctx.query.id == "q-cross" {
+                    let route = RouteOption::direct("base", "USDC", 1_000, 8453);
+                    ctx.offer = ctx.offer.take().map(|offer| offer.with_routes(vec![route]));
+                }
+                Ok(None)
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let pool = Arc::new(AgentPool::new());
+        pool.register(Agent::new("agent-a".to_string())).unwrap();
+        pool.register(Agent::new("agent-b".to_string())).unwrap();
+        let gateway = gateway(&dir).with_agent_pool(pool.clone()).with_stage(Arc::new(BaseRoute));
+        let gateway = Arc::new(gateway);
+        let state = txip_state(gateway.clone());
+
+        let welcome = send(&state, hello("msg-1", "sess-1", "buyer://alice")).await;
+        assert_eq!(welcome["payload"]["control_type"], "WELCOME");
+        let session_id = welcome["session_id"].as_str().unwrap();
+
+        let query_envelope = |msg_id: &str, query_id: &str| {
+            query_envelope(msg_id, session_id, query(query_id, 1_000, ZkProfile::None))
+        };
+        let reply = send(&state, query_envelope("msg-2", "q-1")).await;
+        assert_eq!(reply["tgp_phase"], "OFFER");
+        assert_eq!(reply["payload"]["tgp"]["phase"], "OFFER");
+        assert_eq!(reply["payload"]["tgp"]["query_id"], "q-1");
+        assert!(gateway.active_offer("q-1").is_some());
+
+        // An OFFER the session cannot accept leaves no order behind
+        let reply = send(&state, query_envelope("msg-2b", "q-cross")).await;
+        assert_eq!(reply["payload"]["tgp"]["code"], "UNSUPPORTED_CHAIN");
+        assert!(gateway.active_offer("q-cross").is_none());
+        assert_eq!(pool.active_orders(), 1);
+
+        // Another agent's session cannot settle alice's order
+        let welcome = send(&state, hello("msg-3", "sess-2", "buyer://mallory")).await;
+        let mallory_session = welcome["session_id"].as_str().unwrap();
+
+        let settle = |msg_id: &str, session_id: &str| {
+            let settle = SettleMessage::new("settle-1", "q-1", true, SettleSource::BuyerNotify);
+            TxipEnvelope::tgp(
+                msg_id.to_string(),
+                session_id.to_string(),
+                Direction::ClientToTbc,
+                Role::BuyerAgent,
+                TgpPhase::Settle,
+                FixedTime.now(),
+                serde_json::to_value(TGPMessage::Settle(settle)).unwrap(),
+            )
+        };
+        let reply = send(&state, settle("msg-4", mallory_session)).await;
+        assert_eq!(reply["payload"]["tgp"]["code"], "UNAUTHORIZED");
+        assert!(gateway.active_offer("q-1").is_some());
+
+        send(&state, settle("msg-5", session_id)).await;
+        assert!(gateway.active_offer("q-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_hello_resumes_session_with_offer() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = Arc::new(TGPSessionManager::new(
+            Arc::new(InMemorySessionStore::new()),
+            Arc::new(ManualClock::new(1_731_600_000)),
+        ));
+        let gateway = Arc::new(gateway(&dir).with_session_manager(sessions));
+
+        let state = txip_state(gateway.clone());
+        let welcome = send(&state, hello("msg-1", "sess-1", "buyer://alice")).await;
+        let session_id = welcome["session_id"].as_str().unwrap().to_string();
+        let query = query("q-1", 1_000, ZkProfile::None);
+        let reply = send(&state, query_envelope("msg-2", &session_id, query)).await;
+        assert_eq!(reply["payload"]["tgp"]["phase"], "OFFER");
+
+        // The TxIP layer restarts and forgets its sessions
+        let state = txip_state(gateway);
+        let stolen = send(&state, hello("msg-3", &session_id, "buyer://mallory")).await;
+        assert_ne!(stolen["session_id"], session_id.as_str());
+        assert!(stolen["payload"].get("replay").is_none());
+
+        let resumed = send(&state, hello("msg-4", &session_id, "buyer://alice")).await;
+        assert_eq!(resumed["payload"]["control_type"], "WELCOME");
+        assert_eq!(resumed["session_id"], session_id.as_str());
+        assert_eq!(resumed["payload"]["replay"]["phase"], "OFFER");
+        assert_eq!(resumed["payload"]["replay"]["query_id"], "q-1");
+    }
+}
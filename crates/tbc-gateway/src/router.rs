@@ -1,31 +1,40 @@
 //! Order routing logic
 
+use std::sync::Arc;
+
 use tbc_core::{Order, Route};
 use anyhow::Result;
 
+use crate::agent::AgentPool;
+
 /// Order router
+///
+/// Orders are assigned to agents of the pool the router is built with, so
+/// the agents must be registered there (and kept alive, see
+/// [`spawn_heartbeat_monitor`](crate::agent::spawn_heartbeat_monitor)).
+/// An empty pool fails every order with `NoCapacity`.
 pub struct Router {
-    // Router state
+    pool: Arc<AgentPool>,
 }
 
 impl Router {
-    pub fn new() -> Self {
-        Self {}
+    /// Router assigning orders from a shared agent pool
+    pub fn new(pool: Arc<AgentPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Agent pool the router assigns from
+    pub fn pool(&self) -> &Arc<AgentPool> {
+        &self.pool
     }
-    
+
     /// Route an order to an appropriate seller
     pub async fn route(&self, order: Order) -> Result<Route> {
-        // Routing logic placeholder
+        let agent_id = self.pool.assign(&order.id)?;
         Ok(Route {
             order_id: order.id,
             seller_address: order.seller,
-            agent_id: "agent-001".to_string(),
+            agent_id,
         })
     }
 }
-
-impl Default for Router {
-    fn default() -> Self {
-        Self::new()
-    }
-}
\ No newline at end of file
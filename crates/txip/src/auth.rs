@@ -0,0 +1,777 @@
+// crates/txip/src/auth.rs
+// TxIP Authentication and Authorization
+//
+// HELLO carries an AuthInfo; the authenticator registered for its scheme
+// verifies it and returns the agent's identity and scopes:
+//
+//   BEARER_JWT  HS256 or ES256 token checked against a local key set;
+//               `sub` is the agent id
+//   API_KEY     key looked up by SHA-256 hash in a local store
+//   MTLS        client certificate verified by rustls in the TLS handshake,
+//               mapped to an agent by subject name
+//
+// Agents failing authentication get TXIP_UNAUTHENTICATED. Scopes list the
+// TGP phases an agent may send; other phases get TXIP_UNAUTHORIZED.
+//
+// Token expiry is checked against the engine's unix time, not SystemTime.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tbc_core::tgp::ratelimit::LimitKey;
+use thiserror::Error;
+use webpki::{EndEntityCert, SubjectNameRef};
+
+use crate::types::{AuthInfo, AuthScheme, ErrorCode, HelloPayload, TgpPhase};
+
+/// Every TGP phase an agent can send
+pub const ALL_SCOPES: &[TgpPhase] = &[
+    TgpPhase::Query,
+    TgpPhase::Offer,
+    TgpPhase::Settle,
+    TgpPhase::Event,
+];
+
+// ============================================================================
+// Identity
+// ============================================================================
+
+/// What the transport knows about the client
+#[derive(Debug, Clone, Default)]
+pub struct PeerInfo {
+    /// DER client certificate chain from the TLS handshake, end-entity first
+    ///
+    /// Already verified against the client roots by rustls; empty without
+    /// mTLS.
+    pub client_certificates: Vec<Vec<u8>>,
+
+    /// Remote address of the connection, if the transport knows it
+    pub address: Option<IpAddr>,
+}
+
+/// Agent identity established in HELLO
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedAgent {
+    pub agent_id: String,
+    pub scheme: AuthScheme,
+
+    /// TGP phases the agent may send
+    pub scopes: Vec<TgpPhase>,
+
+    /// Hash of the verified API key, for API key authentication
+    pub key_id: Option<String>,
+}
+
+impl AuthenticatedAgent {
+    pub fn new(agent_id: impl Into<String>, scheme: AuthScheme, scopes: Vec<TgpPhase>) -> Self {
+        Self {
+            agent_id: agent_id.into(),
+            scheme,
+            scopes,
+            key_id: None,
+        }
+    }
+
+    /// Builder method to record the verified API key
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Rate limit keys of the agent's sessions: the authenticated agent id
+    /// and, for API key authentication, the verified key
+    pub fn limit_keys(&self) -> Vec<LimitKey> {
+        let mut keys = vec![LimitKey::agent(self.agent_id.clone())];
+        if let Some(key_id) = &self.key_id {
+            keys.push(LimitKey::api_key(key_id.clone()));
+        }
+        keys
+    }
+}
+
+/// Authentication failure or authenticator misconfiguration
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("auth scheme {0:?} is not accepted")]
+    SchemeNotAccepted(AuthScheme),
+
+    #[error("no credentials presented")]
+    MissingCredentials,
+
+    #[error("invalid token: {0}")]
+    InvalidToken(String),
+
+    #[error("token expired at {0}")]
+    Expired(u64),
+
+    #[error("unknown API key")]
+    UnknownApiKey,
+
+    #[error("no client certificate presented")]
+    MissingCertificate,
+
+    #[error("invalid client certificate: {0}")]
+    InvalidCertificate(String),
+
+    #[error("client certificate subject is not mapped to an agent")]
+    UnknownSubject,
+
+    #[error("credentials belong to {authenticated}, not {claimed}")]
+    AgentMismatch {
+        claimed: String,
+        authenticated: String,
+    },
+
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid configuration: {0}")]
+    Config(String),
+}
+
+impl AuthError {
+    /// TxIP error code HELLO is refused with
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            AuthError::Io(_) | AuthError::Config(_) => ErrorCode::TxipInternalError,
+            _ => ErrorCode::TxipUnauthenticated,
+        }
+    }
+}
+
+/// Verifies the credentials of one auth scheme
+pub trait Authenticator: Send + Sync {
+    /// Scheme this authenticator verifies
+    fn scheme(&self) -> AuthScheme;
+
+    /// Verify credentials at engine time `now_unix`
+    ///
+    /// Returns the agent the credentials belong to; `Authenticators` checks
+    /// it against the HELLO's `agent_id`.
+    fn authenticate(
+        &self,
+        auth: &AuthInfo,
+        peer: &PeerInfo,
+        now_unix: u64,
+    ) -> Result<AuthenticatedAgent, AuthError>;
+}
+
+// ============================================================================
+// Authenticator Set
+// ============================================================================
+
+/// Authenticators consulted for every HELLO, one per scheme
+#[derive(Clone, Default)]
+pub struct Authenticators {
+    authenticators: Vec<Arc<dyn Authenticator>>,
+
+    /// Scopes of agents using scheme NONE; `None` refuses them
+    anonymous_scopes: Option<Vec<TgpPhase>>,
+}
+
+impl Authenticators {
+    /// Accept no scheme until authenticators are added
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept unauthenticated agents with every scope
+    ///
+    /// For development and tests; TxIP behaves as if authentication did not
+    /// exist.
+    pub fn open() -> Self {
+        Self::new().with_anonymous(ALL_SCOPES.to_vec())
+    }
+
+    /// Add an authenticator, replacing any other for its scheme
+    pub fn with(mut self, authenticator: impl Authenticator + 'static) -> Self {
+        let scheme = authenticator.scheme();
+        self.authenticators.retain(|existing| existing.scheme() != scheme);
+        self.authenticators.push(Arc::new(authenticator));
+        self
+    }
+
+    /// Accept scheme NONE with `scopes`
+    pub fn with_anonymous(mut self, scopes: Vec<TgpPhase>) -> Self {
+        self.anonymous_scopes = Some(scopes);
+        self
+    }
+
+    /// Authenticate a HELLO
+    pub fn authenticate(
+        &self,
+        hello: &HelloPayload,
+        peer: &PeerInfo,
+        now_unix: u64,
+    ) -> Result<AuthenticatedAgent, AuthError> {
+        let scheme = &hello.auth.scheme;
+        let agent = if *scheme == AuthScheme::None {
+            let scopes = self
+                .anonymous_scopes
+                .clone()
+                .ok_or(AuthError::SchemeNotAccepted(AuthScheme::None))?;
+            AuthenticatedAgent::new(hello.agent_id.clone(), AuthScheme::None, scopes)
+        } else {
+            self.authenticators
+                .iter()
+                .find(|authenticator| authenticator.scheme() == *scheme)
+                .ok_or_else(|| AuthError::SchemeNotAccepted(scheme.clone()))?
+                .authenticate(&hello.auth, peer, now_unix)?
+        };
+
+        if agent.agent_id != hello.agent_id {
+            return Err(AuthError::AgentMismatch {
+                claimed: hello.agent_id.clone(),
+                authenticated: agent.agent_id,
+            });
+        }
+        Ok(agent)
+    }
+}
+
+// ============================================================================
+// BEARER_JWT
+// ============================================================================
+
+/// BEARER_JWT: HS256 or ES256 tokens checked against a local key set
+///
+/// Tokens name the agent in `sub` and may restrict it with a `tgp_scopes`
+/// claim (e.g. `["QUERY", "SETTLE"]`); without it the default scopes apply.
+pub struct JwtAuthenticator {
+    keys: Vec<JwtKey>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    default_scopes: Vec<TgpPhase>,
+    leeway_secs: u64,
+}
+
+struct JwtKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+#[derive(Deserialize)]
+struct JwtClaims {
+    sub: String,
+    exp: u64,
+    #[serde(default)]
+    nbf: Option<u64>,
+    #[serde(default)]
+    tgp_scopes: Option<Vec<TgpPhase>>,
+}
+
+impl Default for JwtAuthenticator {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            issuer: None,
+            audience: None,
+            default_scopes: ALL_SCOPES.to_vec(),
+            leeway_secs: 30,
+        }
+    }
+}
+
+impl JwtAuthenticator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an HS256 shared secret
+    pub fn with_hs256_secret(mut self, kid: Option<&str>, secret: &[u8]) -> Self {
+        self.keys.push(JwtKey {
+            kid: kid.map(String::from),
+            algorithm: Algorithm::HS256,
+            key: DecodingKey::from_secret(secret),
+        });
+        self
+    }
+
+    /// Add an ES256 (P-256) public key in PEM form
+    pub fn with_es256_public_key(
+        mut self,
+        kid: Option<&str>,
+        pem: &[u8],
+    ) -> Result<Self, AuthError> {
+        let key = DecodingKey::from_ec_pem(pem).map_err(|e| AuthError::Config(e.to_string()))?;
+        self.keys.push(JwtKey {
+            kid: kid.map(String::from),
+            algorithm: Algorithm::ES256,
+            key,
+        });
+        Ok(self)
+    }
+
+    /// Require the `iss` claim
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Require the `aud` claim
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Scopes of tokens without a `tgp_scopes` claim
+    pub fn with_default_scopes(mut self, scopes: Vec<TgpPhase>) -> Self {
+        self.default_scopes = scopes;
+        self
+    }
+
+    /// Clock skew tolerated on `exp` and `nbf`
+    pub fn with_leeway(mut self, leeway_secs: u64) -> Self {
+        self.leeway_secs = leeway_secs;
+        self
+    }
+
+    fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        // Checked against engine time in `authenticate`
+        validation.validate_exp = false;
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &self.audience {
+            validation.set_audience(&[audience]);
+        }
+        validation
+    }
+}
+
+impl Authenticator for JwtAuthenticator {
+    fn scheme(&self) -> AuthScheme {
+        AuthScheme::BearerJwt
+    }
+
+    fn authenticate(
+        &self,
+        auth: &AuthInfo,
+        _peer: &PeerInfo,
+        now_unix: u64,
+    ) -> Result<AuthenticatedAgent, AuthError> {
+        let token = auth.token.as_deref().ok_or(AuthError::MissingCredentials)?;
+        let header = decode_header(token).map_err(|e| AuthError::InvalidToken(e.to_string()))?;
+        let validation = self.validation(header.alg);
+
+        let mut result = Err(AuthError::InvalidToken(format!("no {:?} key matches", header.alg)));
+        for key in self.keys.iter().filter(|key| {
+            key.algorithm == header.alg && (header.kid.is_none() || key.kid == header.kid)
+        }) {
+            result = decode::<JwtClaims>(token, &key.key, &validation)
+                .map_err(|e| AuthError::InvalidToken(e.to_string()));
+            if result.is_ok() {
+                break;
+            }
+        }
+        let claims = result?.claims;
+
+        if now_unix > claims.exp + self.leeway_secs {
+            return Err(AuthError::Expired(claims.exp));
+        }
+        if claims.nbf.is_some_and(|nbf| now_unix + self.leeway_secs < nbf) {
+            return Err(AuthError::InvalidToken("token is not valid yet".to_string()));
+        }
+
+        let scopes = claims.tgp_scopes.unwrap_or_else(|| self.default_scopes.clone());
+        Ok(AuthenticatedAgent::new(claims.sub, AuthScheme::BearerJwt, scopes))
+    }
+}
+
+// ============================================================================
+// API_KEY
+// ============================================================================
+
+/// Entry of the API key store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    /// Hex SHA-256 of the key; the key itself is never stored
+    pub key_sha256: String,
+    pub agent_id: String,
+    #[serde(default = "all_scopes")]
+    pub scopes: Vec<TgpPhase>,
+}
+
+/// API_KEY: keys looked up by SHA-256 hash in a local store
+///
+/// ```json
+/// { "keys": [
+///     { "key_sha256": "9f86d0…", "agent_id": "buyer://alice", "scopes": ["QUERY", "SETTLE"] }
+/// ] }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyAuthenticator {
+    records: HashMap<String, ApiKeyRecord>,
+}
+
+#[derive(Deserialize)]
+struct ApiKeyStore {
+    keys: Vec<ApiKeyRecord>,
+}
+
+impl ApiKeyAuthenticator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a stored record
+    pub fn with_record(mut self, mut record: ApiKeyRecord) -> Self {
+        record.key_sha256 = record.key_sha256.to_ascii_lowercase();
+        self.records.insert(record.key_sha256.clone(), record);
+        self
+    }
+
+    /// Hash and add a plaintext key
+    pub fn with_key(self, key: &str, agent_id: impl Into<String>, scopes: Vec<TgpPhase>) -> Self {
+        self.with_record(ApiKeyRecord {
+            key_sha256: hash_api_key(key),
+            agent_id: agent_id.into(),
+            scopes,
+        })
+    }
+
+    /// Parse a JSON key store
+    pub fn from_json(json: &str) -> Result<Self, AuthError> {
+        let store: ApiKeyStore =
+            serde_json::from_str(json).map_err(|e| AuthError::Config(e.to_string()))?;
+
+        let mut authenticator = Self::new();
+        for (i, record) in store.keys.into_iter().enumerate() {
+            let valid_hash = record.key_sha256.len() == 64
+                && record.key_sha256.bytes().all(|b| b.is_ascii_hexdigit());
+            if !valid_hash {
+                return Err(AuthError::Config(format!(
+                    "keys[{}]: key_sha256 must be 64 hex digits",
+                    i
+                )));
+            }
+            authenticator = authenticator.with_record(record);
+        }
+        Ok(authenticator)
+    }
+
+    /// Load a JSON key store file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AuthError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+impl Authenticator for ApiKeyAuthenticator {
+    fn scheme(&self) -> AuthScheme {
+        AuthScheme::ApiKey
+    }
+
+    fn authenticate(
+        &self,
+        auth: &AuthInfo,
+        _peer: &PeerInfo,
+        _now_unix: u64,
+    ) -> Result<AuthenticatedAgent, AuthError> {
+        let key = auth.token.as_deref().ok_or(AuthError::MissingCredentials)?;
+        let key_id = hash_api_key(key);
+        let record = self.records.get(&key_id).ok_or(AuthError::UnknownApiKey)?;
+        let scopes = record.scopes.clone();
+        let agent = AuthenticatedAgent::new(record.agent_id.clone(), AuthScheme::ApiKey, scopes);
+        Ok(agent.with_key_id(key_id))
+    }
+}
+
+/// Hex SHA-256 of an API key, as kept in the key store
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn all_scopes() -> Vec<TgpPhase> {
+    ALL_SCOPES.to_vec()
+}
+
+// ============================================================================
+// MTLS
+// ============================================================================
+
+/// MTLS: client certificates mapped to agents by subject name
+///
+/// Subjects are DNS names or IP addresses the certificate must be valid for
+/// (subjectAltName). The chain itself is verified by rustls; see
+/// [`mtls_server_config`].
+#[derive(Debug, Clone, Default)]
+pub struct MtlsAuthenticator {
+    subjects: Vec<SubjectMapping>,
+}
+
+#[derive(Debug, Clone)]
+struct SubjectMapping {
+    subject: String,
+    agent: AuthenticatedAgent,
+}
+
+impl MtlsAuthenticator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map certificates valid for `subject` to `agent_id`
+    pub fn with_subject(
+        mut self,
+        subject: impl Into<String>,
+        agent_id: impl Into<String>,
+        scopes: Vec<TgpPhase>,
+    ) -> Self {
+        self.subjects.push(SubjectMapping {
+            subject: subject.into(),
+            agent: AuthenticatedAgent::new(agent_id, AuthScheme::Mtls, scopes),
+        });
+        self
+    }
+}
+
+impl Authenticator for MtlsAuthenticator {
+    fn scheme(&self) -> AuthScheme {
+        AuthScheme::Mtls
+    }
+
+    fn authenticate(
+        &self,
+        _auth: &AuthInfo,
+        peer: &PeerInfo,
+        _now_unix: u64,
+    ) -> Result<AuthenticatedAgent, AuthError> {
+        let der = peer
+            .client_certificates
+            .first()
+            .ok_or(AuthError::MissingCertificate)?;
+        let cert = EndEntityCert::try_from(der.as_slice())
+            .map_err(|e| AuthError::InvalidCertificate(format!("{:?}", e)))?;
+
+        self.subjects
+            .iter()
+            .find(|mapping| {
+                SubjectNameRef::try_from_ascii_str(&mapping.subject)
+                    .is_ok_and(|name| cert.verify_is_valid_for_subject_name(name).is_ok())
+            })
+            .map(|mapping| mapping.agent.clone())
+            .ok_or(AuthError::UnknownSubject)
+    }
+}
+
+/// rustls server configuration requiring client certificates issued by
+/// `client_roots_pem`
+///
+/// The key must be PKCS#8.
+pub fn mtls_server_config(
+    cert_chain_pem: &[u8],
+    key_pem: &[u8],
+    client_roots_pem: &[u8],
+) -> Result<rustls::ServerConfig, AuthError> {
+    let config_error = |e: rustls::Error| AuthError::Config(e.to_string());
+
+    let cert_chain = rustls_pemfile::certs(&mut &cert_chain_pem[..])?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect();
+    let key = rustls_pemfile::pkcs8_private_keys(&mut &key_pem[..])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AuthError::Config("no PKCS#8 private key".to_string()))?;
+
+    let mut roots = rustls::RootCertStore::empty();
+    for root in rustls_pemfile::certs(&mut &client_roots_pem[..])? {
+        roots.add(&rustls::Certificate(root)).map_err(config_error)?;
+    }
+    if roots.is_empty() {
+        return Err(AuthError::Config("no client root certificates".to_string()));
+    }
+
+    rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(rustls::server::AllowAnyAuthenticatedClient::new(roots).boxed())
+        .with_single_cert(cert_chain, rustls::PrivateKey(key))
+        .map_err(config_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Features;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const NOW: u64 = 1_731_600_000;
+
+    fn hello(agent_id: &str, scheme: AuthScheme, token: Option<&str>) -> HelloPayload {
+        HelloPayload {
+            agent_id: agent_id.to_string(),
+            supported_tgp_versions: vec!["TGP-01".to_string()],
+            supported_transports: vec!["HTTP".to_string()],
+            supported_chains: vec![369],
+            supported_assets: vec!["USDC".to_string()],
+            features: Features {
+                zk_discount_proofs: false,
+                receipt_ownership_proofs: false,
+                late_discount_support: false,
+                cross_chain_support: false,
+            },
+            auth: AuthInfo {
+                scheme,
+                token: token.map(String::from),
+            },
+        }
+    }
+
+    fn hs256_token(secret: &[u8], claims: serde_json::Value) -> String {
+        encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    #[test]
+    fn test_anonymous_refused_unless_enabled() {
+        let anonymous = hello("buyer://alice", AuthScheme::None, None);
+
+        let strict = Authenticators::new();
+        assert!(matches!(
+            strict.authenticate(&anonymous, &PeerInfo::default(), NOW),
+            Err(AuthError::SchemeNotAccepted(AuthScheme::None))
+        ));
+
+        let agent = Authenticators::open()
+            .authenticate(&anonymous, &PeerInfo::default(), NOW)
+            .unwrap();
+        assert_eq!(agent.agent_id, "buyer://alice");
+        assert_eq!(agent.scopes, ALL_SCOPES);
+    }
+
+    #[test]
+    fn test_jwt_hs256() {
+        let auth = Authenticators::new()
+            .with(JwtAuthenticator::new().with_hs256_secret(None, b"secret").with_issuer("tbc"));
+        let peer = PeerInfo::default();
+        let claims = |sub: &str, exp: u64| {
+            json!({ "sub": sub, "exp": exp, "iss": "tbc", "tgp_scopes": ["QUERY"] })
+        };
+
+        let token = hs256_token(b"secret", claims("buyer://alice", NOW + 60));
+        let agent = auth
+            .authenticate(&hello("buyer://alice", AuthScheme::BearerJwt, Some(&token)), &peer, NOW)
+            .unwrap();
+        assert_eq!(agent.scopes, vec![TgpPhase::Query]);
+
+        // Wrong secret, expired token and a token for another agent
+        let forged = hs256_token(b"guess", claims("buyer://alice", NOW + 60));
+        let expired = hs256_token(b"secret", claims("buyer://alice", NOW - 60));
+        let bob = hs256_token(b"secret", claims("buyer://bob", NOW + 60));
+        let attempt = |token: &str| {
+            let hello = hello("buyer://alice", AuthScheme::BearerJwt, Some(token));
+            auth.authenticate(&hello, &peer, NOW)
+        };
+        assert!(matches!(attempt(&forged), Err(AuthError::InvalidToken(_))));
+        assert!(matches!(attempt(&expired), Err(AuthError::Expired(_))));
+        assert!(matches!(attempt(&bob), Err(AuthError::AgentMismatch { .. })));
+
+        let missing = hello("buyer://alice", AuthScheme::BearerJwt, None);
+        let error = auth.authenticate(&missing, &peer, NOW).unwrap_err();
+        assert!(matches!(error, AuthError::MissingCredentials));
+        assert_eq!(error.error_code(), ErrorCode::TxipUnauthenticated);
+    }
+
+    #[test]
+    fn test_jwt_es256() {
+        let public = include_bytes!("../tests/fixtures/jwt_es256.pub.pem");
+        let private = include_bytes!("../tests/fixtures/jwt_es256.key");
+        let jwt = JwtAuthenticator::new()
+            .with_es256_public_key(Some("k1"), public)
+            .unwrap()
+            .with_default_scopes(vec![TgpPhase::Query, TgpPhase::Settle]);
+
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some("k1".to_string());
+        let claims = json!({ "sub": "buyer://alice", "exp": NOW + 60 });
+        let token = encode(&header, &claims, &EncodingKey::from_ec_pem(private).unwrap()).unwrap();
+
+        let auth = AuthInfo {
+            scheme: AuthScheme::BearerJwt,
+            token: Some(token),
+        };
+        let agent = jwt.authenticate(&auth, &PeerInfo::default(), NOW).unwrap();
+        assert_eq!(agent.agent_id, "buyer://alice");
+        assert_eq!(agent.scopes, vec![TgpPhase::Query, TgpPhase::Settle]);
+
+        // HS256 tokens are not accepted by an ES256-only key set
+        let auth = AuthInfo {
+            scheme: AuthScheme::BearerJwt,
+            token: Some(hs256_token(b"secret", claims)),
+        };
+        assert!(jwt.authenticate(&auth, &PeerInfo::default(), NOW).is_err());
+    }
+
+    #[test]
+    fn test_api_key_store() {
+        let store = json!({ "keys": [{
+            "key_sha256": hash_api_key("key-alice").to_uppercase(),
+            "agent_id": "buyer://alice",
+            "scopes": ["QUERY", "SETTLE"],
+        }] });
+        let auth = Authenticators::new()
+            .with(ApiKeyAuthenticator::from_json(&store.to_string()).unwrap());
+        let peer = PeerInfo::default();
+
+        let alice = hello("buyer://alice", AuthScheme::ApiKey, Some("key-alice"));
+        let agent = auth.authenticate(&alice, &peer, NOW).unwrap();
+        assert_eq!(agent.scopes, vec![TgpPhase::Query, TgpPhase::Settle]);
+        assert_eq!(
+            agent.limit_keys(),
+            vec![LimitKey::agent("buyer://alice"), LimitKey::api_key(hash_api_key("key-alice"))]
+        );
+
+        let unknown = hello("buyer://alice", AuthScheme::ApiKey, Some("key-mallory"));
+        assert!(matches!(
+            auth.authenticate(&unknown, &peer, NOW),
+            Err(AuthError::UnknownApiKey)
+        ));
+
+        let bad_store = json!({ "keys": [{ "key_sha256": "abc", "agent_id": "buyer://alice" }] });
+        assert!(matches!(
+            ApiKeyAuthenticator::from_json(&bad_store.to_string()),
+            Err(AuthError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_mtls_subject_mapping() {
+        let pem = include_bytes!("../tests/fixtures/client.pem");
+        let der = rustls_pemfile::certs(&mut &pem[..]).unwrap().remove(0);
+        let auth = Authenticators::new().with(
+            MtlsAuthenticator::new()
+                .with_subject("bob.agents.tbc", "buyer://bob", ALL_SCOPES.to_vec())
+                .with_subject("alice.agents.tbc", "buyer://alice", vec![TgpPhase::Query]),
+        );
+        let mtls = hello("buyer://alice", AuthScheme::Mtls, None);
+
+        let peer = PeerInfo {
+            client_certificates: vec![der],
+            ..PeerInfo::default()
+        };
+        let agent = auth.authenticate(&mtls, &peer, NOW).unwrap();
+        assert_eq!(agent.agent_id, "buyer://alice");
+        assert_eq!(agent.scheme, AuthScheme::Mtls);
+
+        assert!(matches!(
+            auth.authenticate(&mtls, &PeerInfo::default(), NOW),
+            Err(AuthError::MissingCertificate)
+        ));
+
+        let unmapped = Authenticators::new()
+            .with(MtlsAuthenticator::new().with_subject("bob.agents.tbc", "buyer://bob", vec![]));
+        assert!(matches!(
+            unmapped.authenticate(&mtls, &peer, NOW),
+            Err(AuthError::UnknownSubject)
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-// crates/tbc-gateway/src/txip/blockchain_types_v03.rs
+// crates/txip/src/blockchain.rs
 // FINAL - CoreProver v0.3 Blockchain Provenance Types
 //
 // This module defines TXID tracking and chain provenance for v0.3
@@ -1,4 +1,4 @@
-// crates/tbc-gateway/src/txip/coreprover_types_v03.rs
+// crates/txip/src/coreprover.rs
 // FINAL -- CoreProver v0.3 Receipt + EscrowView (READ-ONLY)
 //
 // IMPORTANT:
@@ -9,16 +9,18 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::timestamp_types_v03::TripleTimestamp;
-use super::blockchain_types_v03::{BuyerTxIds, ChainId, SellerTxIds};
+use crate::timestamp::{TimestampProvider, TripleTimestamp};
+use crate::blockchain::{BuyerTxIds, ChainId, SellerTxIds};
 
-// IMPORTANT: use the engine’s real escrow state.
+// IMPORTANT: use the engine’s real escrow record and state.
 // No shadow enums.
-use coreprover_service::engine::EscrowState;
+pub use coreprover_service::types::{Escrow, EscrowState};
+use coreprover_service::engine::{CoreProverEngine, TimeTruth};
+use tbc_core::tgp::pos::SettlementReceipt;
 
-/// =======================================================================
-/// COREPROVER RECEIPT -- CANONICAL & UNCHANGED
-/// =======================================================================
+// =======================================================================
+// COREPROVER RECEIPT -- CANONICAL & UNCHANGED
+// =======================================================================
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CoreProverReceipt {
@@ -172,33 +174,50 @@ impl CoreProverReceipt {
     }
 }
 
-/// =======================================================================
-/// ESCROW VIEW -- READ-ONLY MIRROR OF ENGINE STATE
-/// =======================================================================
+impl SettlementReceipt for CoreProverReceipt {
+    fn settlement_txid(&self) -> Option<&str> {
+        self.seller_claim_txid
+            .as_deref()
+            .or(self.seller_refund_txid.as_deref())
+    }
+
+    fn settlement_chain_id(&self) -> u64 {
+        self.seller_chain_id
+    }
+
+    fn is_finalized(&self) -> bool {
+        self.settlement_unix > 0 && self.validate().is_ok()
+    }
+}
+
+// =======================================================================
+// ESCROW VIEW -- READ-ONLY MIRROR OF ENGINE STATE
+// =======================================================================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EscrowView {
+    /// 0x-prefixed hex of the engine's 32-byte order id
     pub order_id: String,
-    pub session_id: String,
+    pub buyer: String,
+    pub seller: String,
     pub state: EscrowState,
     pub amount: u128,
-    pub asset: String,
 
     // engine-produced timestamps
-    pub created_at: TripleTimestamp,
+    pub buyer_commit_time: TripleTimestamp,
     pub acceptance_deadline: TripleTimestamp,
     pub fulfillment_deadline: Option<TripleTimestamp>,
-    pub claim_deadline: Option<TripleTimestamp>,
 
     // provenance fields
-    pub buyer_txids: Option<BuyerTxIds>,
+    pub buyer_txids: BuyerTxIds,
+    /// Present once the seller has both accepted and fulfilled
     pub seller_txids: Option<SellerTxIds>,
 
-    // discount applied by engine
-    pub late_fulfilled: bool,
-    pub discount_pct: u8,
+    // discount configured by the payment profile (0 if disabled)
+    pub late_discount_pct: u8,
 
     // optional times from engine
+    pub seller_accept_time: Option<TripleTimestamp>,
     pub fulfillment_time: Option<TripleTimestamp>,
     pub settlement_time: Option<TripleTimestamp>,
 
@@ -209,29 +228,87 @@ pub struct EscrowView {
 impl EscrowView {
     /// Convert engine escrow → view snapshot.
     /// IMPORTANT: This does NOT compute ANY logic.
-    pub fn from_engine(e: &coreprover_service::engine::Escrow) -> Self {
+    ///
+    /// The engine records monotonic seconds only; `timestamps` expands them
+    /// into triple-clock timestamps.
+    pub fn from_engine<T: TimestampProvider>(e: &Escrow, timestamps: &T) -> Self {
+        let at = |mono: u64| timestamps.at_mono(mono);
+
+        let mut buyer_txids = BuyerTxIds::new(e.buyer_chain_id, e.buyer_commit_txid.clone());
+        buyer_txids.withdraw_txid = e.buyer_withdraw_txid.clone();
+
+        let seller_txids = match (&e.seller_accept_txid, &e.seller_fulfill_txid) {
+            (Some(accept), Some(fulfill)) => {
+                let mut txids = SellerTxIds::new(
+                    e.seller_chain_id,
+                    accept.clone(),
+                    fulfill.clone(),
+                    e.seller_block_height.unwrap_or_default(),
+                );
+                txids.claim_txid = e.seller_claim_txid.clone();
+                txids.refund_txid = e.seller_refund_txid.clone();
+                Some(txids)
+            }
+            _ => None,
+        };
+
+        let late_discount_pct = if e.profile.enables_late_discount {
+            e.profile.late_discount_pct
+        } else {
+            0
+        };
+
         Self {
-            order_id: hex::encode(&e.order_id),
-            session_id: e.session_id.clone(),
+            order_id: format!("0x{}", hex::encode(e.order_id)),
+            buyer: e.buyer.clone(),
+            seller: e.seller.clone(),
             state: e.state,
             amount: e.amount as u128,
-            asset: e.asset.clone(),
 
-            created_at: e.created_at.clone(),
-            acceptance_deadline: e.acceptance_deadline.clone(),
-            fulfillment_deadline: e.fulfillment_deadline.clone(),
-            claim_deadline: e.claim_deadline.clone(),
+            buyer_commit_time: at(e.buyer_commit_mono),
+            acceptance_deadline: at(e.acceptance_deadline_mono),
+            fulfillment_deadline: e.fulfillment_deadline_mono.map(at),
 
-            buyer_txids: e.buyer_txids.clone(),
-            seller_txids: e.seller_txids.clone(),
+            buyer_txids,
+            seller_txids,
 
-            late_fulfilled: e.late_fulfilled,
-            discount_pct: e.discount_pct,
+            late_discount_pct,
 
-            fulfillment_time: e.fulfillment_time.clone(),
-            settlement_time: e.settlement_time.clone(),
+            seller_accept_time: e.seller_accept_mono.map(at),
+            fulfillment_time: e.fulfillment_mono.map(at),
+            settlement_time: e.settlement_mono.map(at),
 
             seller_block_height: e.seller_block_height,
         }
     }
-}
\ No newline at end of file
+}
+
+// =======================================================================
+// ENGINE CLOCK -- COREPROVER ENGINE AS TIMESTAMP PROVIDER
+// =======================================================================
+
+impl From<TimeTruth> for TripleTimestamp {
+    fn from(t: TimeTruth) -> Self {
+        TripleTimestamp::new(t.mono, t.unix, t.iso)
+    }
+}
+
+/// The engine's monotonic and unix clocks advance together, so either
+/// one determines the other.
+impl TimestampProvider for CoreProverEngine {
+    fn now(&self) -> TripleTimestamp {
+        CoreProverEngine::now(self).into()
+    }
+
+    fn at_unix(&self, unix: u64) -> TripleTimestamp {
+        let now = CoreProverEngine::now(self);
+        let mono = (now.mono + unix).saturating_sub(now.unix);
+        TimeTruth::new(mono, unix).into()
+    }
+
+    fn at_mono(&self, mono: u64) -> TripleTimestamp {
+        let now = CoreProverEngine::now(self);
+        let unix = (now.unix + mono).saturating_sub(now.mono);
+        TimeTruth::new(mono, unix).into()
+    }
+}
@@ -0,0 +1,1121 @@
+// crates/txip/src/http_handler.rs
+// FINAL - TxIP HTTP Handler with CoreProver v0.3 Compatibility
+//
+// HTTP handler that accepts engine-provided timestamps.
+// NO timing logic - all timestamps come from TimestampProvider.
+// NO Instant, Duration, or SystemTime usage.
+
+use axum::{
+    extract::{Json, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::auth::{Authenticators, PeerInfo};
+use crate::blockchain::ChainId;
+use crate::negotiation::pinned_chain;
+use crate::timestamp::TimestampProvider;
+use crate::router::TgpRouter;
+use crate::session::{SessionInfo, SessionManager};
+use crate::types::*;
+use tbc_core::tgp::messages::TGPMessage;
+use tbc_core::tgp::ratelimit::{LimitKey, RateLimitRejection, RateLimiter};
+
+/// Shared HTTP handler state
+#[derive(Clone)]
+pub struct HttpHandlerState<T: TimestampProvider> {
+    pub session_manager: Arc<SessionManager<T>>,
+    pub tbc_id: String,
+    /// Verifies HELLO credentials and assigns per-agent scopes
+    pub authenticators: Arc<Authenticators>,
+    /// Per-agent, per-buyer and per-API-key admission control
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Routing layer for TGP messages; `None` only acknowledges them
+    pub tgp_router: Option<Arc<dyn TgpRouter>>,
+}
+
+/// HTTP response for successful message acceptance
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageAcceptedResponse {
+    pub status: String,
+    pub msg_id: String,
+}
+
+/// Outcome of processing one TxIP message
+///
+/// Transport independent: the HTTP handler turns it into a response, the
+/// WebSocket transport sends the envelope as a frame.
+#[derive(Debug)]
+pub enum TxipReply {
+    /// Accepted without a reply envelope
+    Accepted { msg_id: String },
+
+    /// Reply envelope (WELCOME, TGP reply or ERROR) and its HTTP status
+    Envelope { status: StatusCode, envelope: TxipEnvelope },
+
+    /// TXIP_RATE_LIMITED error, sent with `Retry-After`
+    RateLimited { envelope: TxipEnvelope, retry_after_secs: u64 },
+
+    /// Session closed by the client
+    Closed,
+}
+
+impl TxipReply {
+    /// HTTP status of the reply
+    pub fn status(&self) -> StatusCode {
+        match self {
+            TxipReply::Accepted { .. } | TxipReply::Closed => StatusCode::OK,
+            TxipReply::Envelope { status, .. } => *status,
+            TxipReply::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    /// Envelope to send back, if any
+    pub fn envelope(&self) -> Option<&TxipEnvelope> {
+        match self {
+            TxipReply::Envelope { envelope, .. } | TxipReply::RateLimited { envelope, .. } => {
+                Some(envelope)
+            }
+            TxipReply::Accepted { .. } | TxipReply::Closed => None,
+        }
+    }
+}
+
+impl IntoResponse for TxipReply {
+    fn into_response(self) -> Response {
+        match self {
+            TxipReply::Accepted { msg_id } => {
+                let response = MessageAcceptedResponse {
+                    status: "accepted".to_string(),
+                    msg_id,
+                };
+                (StatusCode::OK, Json(response)).into_response()
+            }
+            TxipReply::Envelope { status, envelope } => (status, Json(envelope)).into_response(),
+            TxipReply::RateLimited {
+                envelope,
+                retry_after_secs,
+            } => {
+                let mut response =
+                    (StatusCode::TOO_MANY_REQUESTS, Json(envelope)).into_response();
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+                response
+            }
+            // Return 200 with no body
+            TxipReply::Closed => StatusCode::OK.into_response(),
+        }
+    }
+}
+
+/// Handle incoming TxIP message via HTTP POST
+/// 
+/// Timestamps are validated from the envelope (client-provided)
+/// but session timing uses engine's TimestampProvider. Plain HTTP carries
+/// no client certificate, so MTLS agents connect over the WebSocket
+/// transport.
+pub async fn handle_txip_message<T: TimestampProvider + Send + Sync + 'static>(
+    State(state): State<Arc<HttpHandlerState<T>>>,
+    Json(envelope): Json<TxipEnvelope>,
+) -> Response {
+    process_message(state, envelope, &PeerInfo::default()).await.into_response()
+}
+
+/// Process one TxIP message received on any transport
+///
+/// `peer` is what the transport knows about the client, used to
+/// authenticate HELLO.
+pub async fn process_message<T: TimestampProvider + Send + Sync + 'static>(
+    state: Arc<HttpHandlerState<T>>,
+    envelope: TxipEnvelope,
+    peer: &PeerInfo,
+) -> TxipReply {
+    // Validate envelope structure
+    if let Err(e) = envelope.validate() {
+        return error_response(
+            &state,
+            &envelope.session_id,
+            ErrorCode::TxipInvalidEnvelope,
+            400,
+            Some(envelope.msg_id.clone()),
+            e,
+            false,
+        );
+    }
+
+    // Check for duplicate message (idempotency)
+    if state.session_manager.is_duplicate_message(&envelope.session_id, &envelope.msg_id) {
+        // Return success for idempotent request
+        return accepted_response(&envelope.msg_id);
+    }
+
+    // Route based on message type
+    match &envelope.message_type {
+        MessageType::Control => handle_control_message(state, envelope, peer).await,
+        MessageType::Tgp => handle_tgp_message(state, envelope).await,
+        MessageType::Error => {
+            // Clients shouldn't send ERROR messages to us, but we can log it
+            tracing::warn!("Received ERROR message from client: {:?}", envelope);
+            accepted_response(&envelope.msg_id)
+        }
+    }
+}
+
+/// Handle CONTROL messages
+async fn handle_control_message<T: TimestampProvider + Send + Sync + 'static>(
+    state: Arc<HttpHandlerState<T>>,
+    envelope: TxipEnvelope,
+    peer: &PeerInfo,
+) -> TxipReply {
+    match &envelope.payload {
+        Payload::Control(control) => match control {
+            ControlPayload::Hello(hello) => handle_hello(
+                state,
+                envelope.session_id,
+                envelope.msg_id,
+                hello,
+                envelope.role,
+                peer,
+            ),
+            ControlPayload::Heartbeat(_heartbeat) => {
+                handle_heartbeat(state, envelope.session_id, envelope.msg_id)
+            }
+            ControlPayload::Close(close) => {
+                handle_close(state, envelope.session_id, close)
+            }
+            ControlPayload::Welcome(_) => {
+                // Clients shouldn't send WELCOME to us
+                error_response(
+                    &state,
+                    &envelope.session_id,
+                    ErrorCode::TxipInvalidEnvelope,
+                    400,
+                    Some(envelope.msg_id),
+                    "WELCOME messages are sent by TBC, not received".to_string(),
+                    false,
+                )
+            }
+        },
+        _ => error_response(
+            &state,
+            &envelope.session_id,
+            ErrorCode::TxipInvalidEnvelope,
+            400,
+            Some(envelope.msg_id),
+            "Invalid payload for CONTROL message".to_string(),
+            false,
+        ),
+    }
+}
+
+/// Handle HELLO message
+fn handle_hello<T: TimestampProvider + Send + Sync + 'static>(
+    state: Arc<HttpHandlerState<T>>,
+    session_id: String,
+    msg_id: String,
+    hello: &HelloPayload,
+    role: Role,
+    peer: &PeerInfo,
+) -> TxipReply {
+    // Until HELLO is authenticated only the connection can be charged: the
+    // agent id and key in it are whatever the client claims
+    if let Some(address) = peer.address {
+        let keys = [LimitKey::peer(address.to_string())];
+        if let Err(rejection) = state.rate_limiter.check(&keys) {
+            return rate_limited_response(&state, &session_id, Some(msg_id), &rejection);
+        }
+    }
+
+    let now_unix = state.session_manager.now().unix;
+    let agent = match state.authenticators.authenticate(hello, peer, now_unix) {
+        Ok(agent) => agent,
+        Err(e) => {
+            tracing::info!("Refused HELLO from {}: {}", hello.agent_id, e);
+            let error_code = e.error_code();
+            let http_status = error_code.http_status();
+            return error_response(
+                &state,
+                &session_id,
+                error_code,
+                http_status,
+                Some(msg_id),
+                format!("Authentication failed: {}", e),
+                false,
+            );
+        }
+    };
+
+    let keys = agent.limit_keys();
+    if let Err(rejection) = state.rate_limiter.check(&keys) {
+        return rate_limited_response(&state, &session_id, Some(msg_id), &rejection);
+    }
+
+    // Session ids are issued by TBC and returned in WELCOME. A HELLO naming
+    // an existing session resumes it (if the same agent opened it), as does
+    // one naming a TGP session the router can resume for this agent; any
+    // other id the client picked only correlates the reply.
+    let previous = state.session_manager.get_session(&session_id);
+    let replay = state
+        .tgp_router
+        .as_ref()
+        .and_then(|router| router.resume_session(&session_id, &agent.agent_id));
+    let session_id = match (&previous, &replay) {
+        (None, None) => generate_session_id(),
+        _ => session_id,
+    };
+
+    // A repeated HELLO keeps its session slot unless it changes identity
+    let previous_keys = previous.map(|session| session.limit_keys);
+    let opens_slot = previous_keys.as_ref() != Some(&keys);
+    if opens_slot {
+        if let Err(rejection) = state.rate_limiter.open_session(&keys) {
+            state.rate_limiter.refund(&keys);
+            return rate_limited_response(&state, &session_id, Some(msg_id), &rejection);
+        }
+    }
+
+    // Create or update session (uses engine timestamp internally)
+    match state.session_manager.handle_hello(hello, session_id.clone(), role, &agent) {
+        Ok(session_info) => {
+            if let (true, Some(previous_keys)) = (opens_slot, &previous_keys) {
+                state.rate_limiter.close_session(previous_keys);
+            }
+
+            // Record message
+            let _ = state.session_manager.record_message(&session_id, &msg_id);
+
+            // Get current timestamp from engine for WELCOME response
+            let now = state.session_manager.now();
+
+            // Create WELCOME response
+            let negotiated = session_info.negotiated.clone();
+            let mut welcome = TxipEnvelope::welcome(
+                generate_msg_id(),
+                session_id,
+                state.tbc_id.clone(),
+                session_info.negotiated,
+                state.session_manager.heartbeat_interval_sec(),
+                now,
+            );
+            if let Some(replay) = replay {
+                welcome = welcome.with_replay(negotiated.adapt_reply(replay));
+            }
+
+            TxipReply::Envelope {
+                status: StatusCode::OK,
+                envelope: welcome,
+            }
+        }
+        Err(e) => {
+            if opens_slot {
+                state.rate_limiter.close_session(&keys);
+            }
+            let error_code = e.error_code();
+            let http_status = error_code.http_status();
+            error_response(
+                &state,
+                &session_id,
+                error_code,
+                http_status,
+                Some(msg_id),
+                format!("HELLO refused: {}", e),
+                false,
+            )
+        }
+    }
+}
+
+/// Handle HEARTBEAT message
+fn handle_heartbeat<T: TimestampProvider + Send + Sync + 'static>(
+    state: Arc<HttpHandlerState<T>>,
+    session_id: String,
+    msg_id: String,
+) -> TxipReply {
+    // Update session activity (uses engine timestamp internally)
+    if let Err(e) = state.session_manager.touch_session(&session_id) {
+        return error_response(
+            &state,
+            &session_id,
+            ErrorCode::TxipInternalError,
+            500,
+            Some(msg_id.clone()),
+            format!("Failed to update session: {}", e),
+            true,
+        );
+    }
+
+    // Record message
+    let _ = state.session_manager.record_message(&session_id, &msg_id);
+
+    accepted_response(&msg_id)
+}
+
+/// Handle CLOSE message
+fn handle_close<T: TimestampProvider + Send + Sync + 'static>(
+    state: Arc<HttpHandlerState<T>>,
+    session_id: String,
+    _close: &ClosePayload,
+) -> TxipReply {
+    release_session(&state, &session_id);
+
+    TxipReply::Closed
+}
+
+/// Close a session and return its rate limiter slot
+pub(crate) fn release_session<T: TimestampProvider + Send + Sync + 'static>(
+    state: &HttpHandlerState<T>,
+    session_id: &str,
+) {
+    if let Ok(Some(session)) = state.session_manager.close_session(session_id) {
+        state.rate_limiter.close_session(&session.limit_keys);
+    }
+}
+
+/// Handle TGP messages
+async fn handle_tgp_message<T: TimestampProvider + Send + Sync + 'static>(
+    state: Arc<HttpHandlerState<T>>,
+    envelope: TxipEnvelope,
+) -> TxipReply {
+    // Verify session exists
+    let Some(session) = state.session_manager.get_session(&envelope.session_id) else {
+        return error_response(
+            &state,
+            &envelope.session_id,
+            ErrorCode::TxipUnauthenticated,
+            401,
+            Some(envelope.msg_id.clone()),
+            "Session not found. Send HELLO first.".to_string(),
+            false,
+        );
+    };
+
+    // Admission control before the message is recorded, so a rejected
+    // msg_id can be retried after Retry-After
+    let keys = tgp_limit_keys(&session, &envelope.payload);
+    if let Err(rejection) = state.rate_limiter.check(&keys) {
+        return rate_limited_response(
+            &state,
+            &envelope.session_id,
+            Some(envelope.msg_id),
+            &rejection,
+        );
+    }
+
+    // Update session activity (uses engine timestamp internally)
+    let _ = state.session_manager.touch_session(&envelope.session_id);
+
+    // Record message
+    let _ = state.session_manager.record_message(&envelope.session_id, &envelope.msg_id);
+
+    // Extract TGP payload
+    match &envelope.payload {
+        Payload::Tgp(tgp_payload) => {
+            let message: TGPMessage = match serde_json::from_value(tgp_payload.tgp.clone()) {
+                Ok(message) => message,
+                Err(e) => {
+                    return error_response(
+                        &state,
+                        &envelope.session_id,
+                        ErrorCode::TxipMalformedTgpPayload,
+                        400,
+                        Some(envelope.msg_id),
+                        format!("TGP payload could not be decoded: {}", e),
+                        false,
+                    );
+                }
+            };
+
+            let report = message.validation_report();
+            if !report.is_valid() {
+                let now = state.session_manager.now();
+                let error = TxipEnvelope::malformed_tgp_payload(
+                    generate_msg_id(),
+                    envelope.session_id.clone(),
+                    Some(envelope.msg_id),
+                    &report,
+                    now,
+                );
+                return TxipReply::Envelope {
+                    status: StatusCode::BAD_REQUEST,
+                    envelope: error,
+                };
+            }
+
+            let phase = message_phase(&message);
+            if !session.may_send(&phase) {
+                return error_response(
+                    &state,
+                    &envelope.session_id,
+                    ErrorCode::TxipUnauthorized,
+                    403,
+                    Some(envelope.msg_id),
+                    format!("Agent {} may not send {:?} messages", session.agent_id, phase),
+                    false,
+                );
+            }
+
+            tracing::info!(
+                "Received TGP message: phase={:?}, session={}, msg_id={}, chain={:?}",
+                envelope.tgp_phase,
+                envelope.session_id,
+                envelope.msg_id,
+                envelope.origin_chain_id,
+            );
+
+            // Messages outside the negotiated set never reach the router
+            let origin_chain = envelope.origin_chain_id.or_else(|| query_chain(&message));
+            let message_id = message.id().to_string();
+            if let Err(violation) = session.negotiated.check_inbound(&message, origin_chain) {
+                let error = TGPMessage::Error(violation.to_error(&message_id));
+                return tgp_response(&state, &envelope.session_id, &error);
+            }
+
+            // Without a routing layer the message is only acknowledged
+            let Some(router) = &state.tgp_router else {
+                return accepted_response(&envelope.msg_id);
+            };
+
+            match router.route_message(&session, message).await {
+                Some(reply) => {
+                    let reply = match session.negotiated.check_reply(&reply, origin_chain) {
+                        Ok(()) => reply,
+                        Err(violation) => {
+                            tracing::info!(
+                                "Refused {} reply on session {}: {}",
+                                reply.phase(),
+                                envelope.session_id,
+                                violation
+                            );
+                            router.withdraw_reply(&session, &reply).await;
+                            TGPMessage::Error(violation.to_error(&message_id))
+                        }
+                    };
+                    let reply = session.negotiated.adapt_reply(reply);
+                    tgp_response(&state, &envelope.session_id, &reply)
+                }
+                None => accepted_response(&envelope.msg_id),
+            }
+        }
+        _ => error_response(
+            &state,
+            &envelope.session_id,
+            ErrorCode::TxipInvalidEnvelope,
+            400,
+            Some(envelope.msg_id),
+            "Invalid payload for TGP message".to_string(),
+            false,
+        ),
+    }
+}
+
+/// Remove timed-out sessions and return their rate limiter slots
+///
+/// Also prunes refilled rate limiter buckets. Call periodically from the
+/// engine's maintenance loop. Returns the removed sessions.
+pub fn cleanup_expired_sessions<T: TimestampProvider + Send + Sync + 'static>(
+    state: &HttpHandlerState<T>,
+) -> Vec<SessionInfo> {
+    let expired = state.session_manager.cleanup_expired();
+    for session in &expired {
+        state.rate_limiter.close_session(&session.limit_keys);
+    }
+    state.rate_limiter.prune();
+    expired
+}
+
+/// TGP phase of a decoded message; ERROR has none
+fn message_phase(message: &TGPMessage) -> TgpPhase {
+    match message {
+        TGPMessage::Query(_) => TgpPhase::Query,
+        TGPMessage::Offer(_) => TgpPhase::Offer,
+        TGPMessage::Settle(_) => TgpPhase::Settle,
+        TGPMessage::Error(_) => TgpPhase::None,
+    }
+}
+
+/// Chain a QUERY's asset identifier pins the payment to
+fn query_chain(message: &TGPMessage) -> Option<ChainId> {
+    match message {
+        TGPMessage::Query(query) => pinned_chain(&query.asset),
+        _ => None,
+    }
+}
+
+/// Rate limit keys of a TGP message: the session's keys plus the buyer of
+/// a QUERY
+fn tgp_limit_keys(session: &SessionInfo, payload: &Payload) -> Vec<LimitKey> {
+    let mut keys = session.limit_keys.clone();
+    if let Payload::Tgp(tgp_payload) = payload {
+        let tgp = &tgp_payload.tgp;
+        if tgp.get("phase").and_then(Value::as_str) == Some("QUERY") {
+            if let Some(buyer) = tgp.get("from").and_then(Value::as_str) {
+                keys.push(LimitKey::buyer(buyer));
+            }
+        }
+    }
+    keys
+}
+
+/// Create a success response
+fn accepted_response(msg_id: &str) -> TxipReply {
+    TxipReply::Accepted {
+        msg_id: msg_id.to_string(),
+    }
+}
+
+/// Create a response carrying the routing layer's TGP reply
+fn tgp_response<T: TimestampProvider + Send + Sync + 'static>(
+    state: &Arc<HttpHandlerState<T>>,
+    session_id: &str,
+    reply: &TGPMessage,
+) -> TxipReply {
+    let payload = match serde_json::to_value(reply) {
+        Ok(payload) => payload,
+        Err(e) => {
+            return error_response(
+                state,
+                session_id,
+                ErrorCode::TxipInternalError,
+                500,
+                None,
+                format!("TGP reply could not be encoded: {}", e),
+                true,
+            );
+        }
+    };
+
+    let envelope = TxipEnvelope::tgp(
+        generate_msg_id(),
+        session_id.to_string(),
+        Direction::TbcToClient,
+        Role::Tbc,
+        TgpPhase::of(reply),
+        state.session_manager.now(),
+        payload,
+    );
+
+    TxipReply::Envelope {
+        status: StatusCode::OK,
+        envelope,
+    }
+}
+
+/// Create an error response with engine timestamp
+fn error_response<T: TimestampProvider + Send + Sync + 'static>(
+    state: &Arc<HttpHandlerState<T>>,
+    session_id: &str,
+    error_code: ErrorCode,
+    http_status: u16,
+    related_msg_id: Option<String>,
+    details: String,
+    retryable: bool,
+) -> TxipReply {
+    let now = state.session_manager.now();
+    
+    let envelope = TxipEnvelope::error(
+        generate_msg_id(),
+        session_id.to_string(),
+        Direction::TbcToClient,
+        error_code,
+        http_status,
+        related_msg_id,
+        details,
+        retryable,
+        now,
+    );
+
+    let status = StatusCode::from_u16(http_status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    TxipReply::Envelope { status, envelope }
+}
+
+/// Create a 429 TXIP_RATE_LIMITED response with a `Retry-After` header
+fn rate_limited_response<T: TimestampProvider + Send + Sync + 'static>(
+    state: &Arc<HttpHandlerState<T>>,
+    session_id: &str,
+    related_msg_id: Option<String>,
+    rejection: &RateLimitRejection,
+) -> TxipReply {
+    tracing::warn!("Rate limited session {}: {}", session_id, rejection);
+
+    let envelope = TxipEnvelope::rate_limited(
+        generate_msg_id(),
+        session_id.to_string(),
+        related_msg_id,
+        rejection,
+        state.session_manager.now(),
+    );
+
+    TxipReply::RateLimited {
+        envelope,
+        retry_after_secs: rejection.retry_after_secs,
+    }
+}
+
+/// Generate an unguessable session ID
+///
+/// The session id is all HTTP requests after HELLO are authorized by, so it
+/// comes from the thread-local CSPRNG with 128 bits of entropy.
+pub(crate) fn generate_session_id() -> String {
+    format!("sess-{:032x}", rand::random::<u128>())
+}
+
+/// Generate a message ID (UUID v4)
+pub(crate) fn generate_msg_id() -> String {
+    // In production, use uuid crate
+    // For now, return a simple placeholder
+    format!("msg-{}", rand::random::<u64>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{ApiKeyAuthenticator, AuthenticatedAgent};
+    use crate::timestamp::TripleTimestamp;
+    use tbc_core::tgp::ratelimit::{LimitKind, RateLimit, RateLimitConfig};
+
+    /// Test timestamp provider for unit tests
+    struct TestTimestampProvider {
+        current_mono: std::sync::atomic::AtomicU64,
+        current_unix: std::sync::atomic::AtomicU64,
+    }
+
+    impl TestTimestampProvider {
+        fn new(mono: u64, unix: u64) -> Self {
+            Self {
+                current_mono: std::sync::atomic::AtomicU64::new(mono),
+                current_unix: std::sync::atomic::AtomicU64::new(unix),
+            }
+        }
+    }
+
+    impl TimestampProvider for TestTimestampProvider {
+        fn now(&self) -> TripleTimestamp {
+            let mono = self.current_mono.load(std::sync::atomic::Ordering::SeqCst);
+            let unix = self.current_unix.load(std::sync::atomic::Ordering::SeqCst);
+            TripleTimestamp::new(
+                mono,
+                unix,
+                format!("2024-11-14T12:00:{}Z", mono % 60),
+            )
+        }
+
+        fn at_unix(&self, unix: u64) -> TripleTimestamp {
+            let mono = self.current_mono.load(std::sync::atomic::Ordering::SeqCst);
+            TripleTimestamp::new(mono, unix, "2024-11-14T12:00:00Z".to_string())
+        }
+
+        fn at_mono(&self, mono: u64) -> TripleTimestamp {
+            let unix = self.current_unix.load(std::sync::atomic::Ordering::SeqCst);
+            TripleTimestamp::new(mono, unix, "2024-11-14T12:00:00Z".to_string())
+        }
+    }
+
+    fn create_test_state() -> Arc<HttpHandlerState<TestTimestampProvider>> {
+        create_limited_state(RateLimitConfig::default())
+    }
+
+    fn create_limited_state(
+        config: RateLimitConfig,
+    ) -> Arc<HttpHandlerState<TestTimestampProvider>> {
+        create_state(config, Authenticators::open())
+    }
+
+    fn create_state(
+        config: RateLimitConfig,
+        authenticators: Authenticators,
+    ) -> Arc<HttpHandlerState<TestTimestampProvider>> {
+        let provider = Arc::new(TestTimestampProvider::new(1000, 1731600000));
+        let session_manager = Arc::new(SessionManager::new(Default::default(), provider));
+        
+        Arc::new(HttpHandlerState {
+            session_manager,
+            tbc_id: "tbc://test".to_string(),
+            authenticators: Arc::new(authenticators),
+            rate_limiter: Arc::new(RateLimiter::new(config)),
+            tgp_router: None,
+        })
+    }
+
+    fn create_test_hello() -> HelloPayload {
+        HelloPayload {
+            agent_id: "buyer://alice".to_string(),
+            supported_tgp_versions: vec!["TGP-01".to_string()],
+            supported_transports: vec!["HTTP".to_string()],
+            supported_chains: vec![1, 369],
+            supported_assets: vec!["USDC".to_string()],
+            features: Features {
+                zk_discount_proofs: true,
+                receipt_ownership_proofs: true,
+                late_discount_support: true,
+                cross_chain_support: true,
+            },
+            auth: AuthInfo {
+                scheme: AuthScheme::None,
+                token: None,
+            },
+        }
+    }
+
+    /// Session id TBC issued in a WELCOME reply
+    fn issued_session_id(reply: &TxipReply) -> String {
+        match reply.envelope() {
+            Some(envelope) if matches!(
+                envelope.payload,
+                Payload::Control(ControlPayload::Welcome(_))
+            ) => envelope.session_id.clone(),
+            _ => panic!("expected WELCOME, got {:?}", reply),
+        }
+    }
+
+    #[test]
+    fn test_hello_response() {
+        let state = create_test_state();
+        let hello_payload = create_test_hello();
+
+        let response = handle_hello(
+            state.clone(),
+            "sess-456".to_string(),
+            "msg-123".to_string(),
+            &hello_payload,
+            Role::BuyerAgent,
+            &PeerInfo::default(),
+        );
+
+        // Verify WELCOME was returned (status 200)
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_session_id_issued_by_server() {
+        let state = create_test_state();
+        let hello = create_test_hello();
+        let hello_on = |session_id: &str| {
+            handle_hello(
+                state.clone(),
+                session_id.to_string(),
+                "msg-1".to_string(),
+                &hello,
+                Role::BuyerAgent,
+                &PeerInfo::default(),
+            )
+        };
+
+        // A client-picked id is not adopted, so it cannot be guessed
+        let session_id = issued_session_id(&hello_on("sess-1"));
+        assert_ne!(session_id, "sess-1");
+        assert!(state.session_manager.get_session("sess-1").is_none());
+        assert!(state.session_manager.get_session(&session_id).is_some());
+
+        // Repeating HELLO on the issued id resumes the session
+        assert_eq!(issued_session_id(&hello_on(&session_id)), session_id);
+    }
+
+    #[test]
+    fn test_hello_from_other_agent_cannot_take_over_session() {
+        let state = create_state(
+            RateLimitConfig::default(),
+            Authenticators::new()
+                .with(ApiKeyAuthenticator::new()
+                    .with_key("key-alice", "buyer://alice", vec![TgpPhase::Query])
+                    .with_key("key-mallory", "buyer://mallory", vec![TgpPhase::Query])),
+        );
+        let hello_as = |agent: &str, key: &str, session_id: &str| {
+            let mut hello = create_test_hello();
+            hello.agent_id = agent.to_string();
+            hello.auth = AuthInfo {
+                scheme: AuthScheme::ApiKey,
+                token: Some(key.to_string()),
+            };
+            handle_hello(
+                state.clone(),
+                session_id.to_string(),
+                "msg-1".to_string(),
+                &hello,
+                Role::BuyerAgent,
+                &PeerInfo::default(),
+            )
+        };
+
+        let session_id =
+            issued_session_id(&hello_as("buyer://alice", "key-alice", "sess-1"));
+
+        let reply = hello_as("buyer://mallory", "key-mallory", &session_id);
+        assert_eq!(reply.status(), StatusCode::UNAUTHORIZED);
+        let Some(Payload::Error(error)) = reply.envelope().map(|e| &e.payload) else {
+            panic!("expected ERROR, got {:?}", reply);
+        };
+        assert_eq!(error.error_code, ErrorCode::TxipUnauthenticated);
+
+        let session = state.session_manager.get_session(&session_id).unwrap();
+        assert_eq!(session.agent_id, "buyer://alice");
+        assert_eq!(
+            state.rate_limiter.open_sessions(&LimitKey::agent("buyer://mallory")),
+            0
+        );
+    }
+
+    #[test]
+    fn test_session_quota_per_agent() {
+        let state = create_limited_state(
+            RateLimitConfig::new().with_max_sessions(LimitKind::Agent, 1),
+        );
+        let hello = create_test_hello();
+        let agent = LimitKey::agent("buyer://alice");
+
+        let hello_on = |session_id: &str| {
+            handle_hello(
+                state.clone(),
+                session_id.to_string(),
+                "msg-1".to_string(),
+                &hello,
+                Role::BuyerAgent,
+                &PeerInfo::default(),
+            )
+        };
+
+        let session_id = issued_session_id(&hello_on("sess-1"));
+        // Repeating HELLO on the same session does not take a second slot
+        assert_eq!(hello_on(&session_id).status(), StatusCode::OK);
+        assert_eq!(state.rate_limiter.open_sessions(&agent), 1);
+
+        let rejected = hello_on("sess-2").into_response();
+        assert_eq!(rejected.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            rejected.headers().get(header::RETRY_AFTER).unwrap(),
+            &HeaderValue::from(5u64)
+        );
+
+        let close = ClosePayload {
+            reason: CloseReason::ClientShutdown,
+        };
+        handle_close(state.clone(), session_id, &close);
+        assert_eq!(state.rate_limiter.open_sessions(&agent), 0);
+        assert_eq!(hello_on("sess-2").status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_tgp_limit_keys_include_query_buyer() {
+        let state = create_test_state();
+        let hello = create_test_hello();
+        let agent = AuthenticatedAgent::new("buyer://alice", AuthScheme::None, vec![]);
+        state
+            .session_manager
+            .handle_hello(&hello, "sess-1".to_string(), Role::BuyerAgent, &agent)
+            .unwrap();
+        let session = state.session_manager.get_session("sess-1").unwrap();
+
+        let query = Payload::Tgp(TgpPayload {
+            tgp: serde_json::json!({"phase": "QUERY", "from": "buyer://carol"}),
+        });
+        assert_eq!(
+            tgp_limit_keys(&session, &query),
+            vec![LimitKey::agent("buyer://alice"), LimitKey::buyer("buyer://carol")]
+        );
+
+        let settle = Payload::Tgp(TgpPayload {
+            tgp: serde_json::json!({"phase": "SETTLE", "from": "buyer://carol"}),
+        });
+        assert_eq!(tgp_limit_keys(&session, &settle), session.limit_keys);
+    }
+
+    #[test]
+    fn test_hello_without_overlap_rejected() {
+        let state = create_test_state();
+        let mut hello = create_test_hello();
+        hello.supported_chains = vec![56];
+
+        let reply = handle_hello(
+            state.clone(),
+            "sess-1".to_string(),
+            "msg-1".to_string(),
+            &hello,
+            Role::BuyerAgent,
+            &PeerInfo::default(),
+        );
+
+        assert_eq!(reply.status(), StatusCode::BAD_REQUEST);
+        let Some(Payload::Error(error)) = reply.envelope().map(|e| &e.payload) else {
+            panic!("expected ERROR, got {:?}", reply);
+        };
+        assert_eq!(error.error_code, ErrorCode::TxipUnsupportedVersion);
+        assert!(state.session_manager.get_session("sess-1").is_none());
+        assert_eq!(state.rate_limiter.open_sessions(&LimitKey::agent("buyer://alice")), 0);
+    }
+
+    #[test]
+    fn test_hello_with_bad_credentials_unauthenticated() {
+        let state = create_state(
+            RateLimitConfig::default(),
+            Authenticators::new().with(ApiKeyAuthenticator::new().with_key(
+                "key-alice",
+                "buyer://alice",
+                vec![TgpPhase::Query],
+            )),
+        );
+        let mut hello = create_test_hello();
+
+        // Anonymous agents and unknown keys are both refused
+        for auth in [(AuthScheme::None, None), (AuthScheme::ApiKey, Some("key-bob"))] {
+            hello.auth = AuthInfo {
+                scheme: auth.0,
+                token: auth.1.map(String::from),
+            };
+            let reply = handle_hello(
+                state.clone(),
+                "sess-1".to_string(),
+                "msg-1".to_string(),
+                &hello,
+                Role::BuyerAgent,
+                &PeerInfo::default(),
+            );
+
+            assert_eq!(reply.status(), StatusCode::UNAUTHORIZED);
+            let Some(Payload::Error(error)) = reply.envelope().map(|e| &e.payload) else {
+                panic!("expected ERROR, got {:?}", reply);
+            };
+            assert_eq!(error.error_code, ErrorCode::TxipUnauthenticated);
+            assert!(state.session_manager.get_session("sess-1").is_none());
+        }
+        assert_eq!(state.rate_limiter.open_sessions(&LimitKey::agent("buyer://alice")), 0);
+
+        hello.auth.token = Some("key-alice".to_string());
+        let reply = handle_hello(
+            state.clone(),
+            "sess-1".to_string(),
+            "msg-1".to_string(),
+            &hello,
+            Role::BuyerAgent,
+            &PeerInfo::default(),
+        );
+        assert_eq!(reply.status(), StatusCode::OK);
+        let session = state.session_manager.get_session(&issued_session_id(&reply)).unwrap();
+        assert_eq!(session.auth_scheme, AuthScheme::ApiKey);
+        assert_eq!(session.scopes, vec![TgpPhase::Query]);
+    }
+
+    #[test]
+    fn test_spoofed_hellos_do_not_drain_agent_bucket() {
+        let state = create_state(
+            RateLimitConfig::new()
+                .with_rate(LimitKind::Agent, RateLimit::per_minute(1).with_burst(1))
+                .with_rate(LimitKind::Peer, RateLimit::per_minute(1).with_burst(3)),
+            Authenticators::new().with(ApiKeyAuthenticator::new().with_key(
+                "key-alice",
+                "buyer://alice",
+                vec![TgpPhase::Query],
+            )),
+        );
+        let hello_from = |peer: &str, token: &str| {
+            let mut hello = create_test_hello();
+            hello.auth = AuthInfo {
+                scheme: AuthScheme::ApiKey,
+                token: Some(token.to_string()),
+            };
+            let peer = PeerInfo {
+                address: Some(peer.parse().unwrap()),
+                ..PeerInfo::default()
+            };
+            handle_hello(
+                state.clone(),
+                "sess-1".to_string(),
+                "msg-1".to_string(),
+                &hello,
+                Role::BuyerAgent,
+                &peer,
+            )
+            .status()
+        };
+
+        // Claiming alice's id with a bad key only costs the attacker's peer
+        for _ in 0..3 {
+            assert_eq!(hello_from("10.0.0.66", "key-mallory"), StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(hello_from("10.0.0.66", "key-mallory"), StatusCode::TOO_MANY_REQUESTS);
+
+        assert_eq!(hello_from("10.0.0.1", "key-alice"), StatusCode::OK);
+        assert_eq!(hello_from("10.0.0.1", "key-alice"), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    fn query_envelope(
+        state: &HttpHandlerState<TestTimestampProvider>,
+        session_id: &str,
+        asset: &str,
+    ) -> TxipEnvelope {
+        let query = tbc_core::tgp::messages::QueryMessage::new(
+            "q-1",
+            "buyer://alice",
+            "seller://bob",
+            asset,
+            1_000,
+            tbc_core::tgp::types::ZkProfile::Optional,
+        );
+        TxipEnvelope::tgp(
+            "msg-2".to_string(),
+            session_id.to_string(),
+            Direction::ClientToTbc,
+            Role::BuyerAgent,
+            TgpPhase::Query,
+            state.session_manager.now(),
+            serde_json::to_value(TGPMessage::Query(query)).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_phase_outside_scopes_unauthorized() {
+        let state = create_state(
+            RateLimitConfig::default(),
+            Authenticators::new().with_anonymous(vec![TgpPhase::Settle]),
+        );
+        let welcome = handle_hello(
+            state.clone(),
+            "sess-1".to_string(),
+            "msg-1".to_string(),
+            &create_test_hello(),
+            Role::BuyerAgent,
+            &PeerInfo::default(),
+        );
+
+        let envelope = query_envelope(&state, &issued_session_id(&welcome), "USDC");
+        let reply = process_message(state, envelope, &PeerInfo::default()).await;
+
+        assert_eq!(reply.status(), StatusCode::FORBIDDEN);
+        let Some(Payload::Error(error)) = reply.envelope().map(|e| &e.payload) else {
+            panic!("expected ERROR, got {:?}", reply);
+        };
+        assert_eq!(error.error_code, ErrorCode::TxipUnauthorized);
+        assert_eq!(error.related_msg_id.as_deref(), Some("msg-2"));
+    }
+
+    #[tokio::test]
+    async fn test_query_outside_negotiated_set_refused() {
+        let state = create_test_state();
+        let welcome = handle_hello(
+            state.clone(),
+            "sess-1".to_string(),
+            "msg-1".to_string(),
+            &create_test_hello(),
+            Role::BuyerAgent,
+            &PeerInfo::default(),
+        );
+
+        let envelope = query_envelope(&state, &issued_session_id(&welcome), "ETH");
+        let reply = process_message(state, envelope, &PeerInfo::default()).await;
+        let Some(Payload::Tgp(tgp)) = reply.envelope().map(|e| &e.payload) else {
+            panic!("expected TGP ERROR, got {:?}", reply);
+        };
+        assert_eq!(tgp.tgp["phase"], "ERROR");
+        assert_eq!(tgp.tgp["code"], "UNSUPPORTED_ASSET");
+        assert_eq!(tgp.tgp["correlation_id"], "q-1");
+    }
+
+    #[test]
+    fn test_accepted_response() {
+        let response = accepted_response("msg-123");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -1,60 +1,83 @@
-// crates/tbc-gateway/src/txip/mod_v03.rs
-// FINAL - TxIP Module Root with CoreProver v0.3 Exports
+// crates/txip/src/lib.rs
+// FINAL - TxIP Crate Root with CoreProver v0.3 Exports
 //
-// This module exports all TxIP v0.2 types with CoreProver v0.3 compatibility.
+// This crate exports all TxIP v0.2 types with CoreProver v0.3 compatibility.
 // NO Instant, Duration, or SystemTime usage throughout.
 // All timing via TimestampProvider trait from engine.
 
-pub mod timestamp_types_v03;
-pub mod blockchain_types_v03;
-pub mod coreprover_types_v03;
-pub mod txip_types_v03;
-pub mod txip_session_v03;
-pub mod txip_http_handler_v03;
-pub mod txip_websocket_handler_v03;
+pub mod timestamp;
+pub mod blockchain;
+pub mod coreprover;
+pub mod types;
+pub mod auth;
+pub mod negotiation;
+pub mod session;
+pub mod router;
+pub mod http_handler;
+pub mod websocket;
 
 // Re-export timestamp types
-pub use timestamp_types_v03::{
+pub use timestamp::{
     TripleTimestamp, TimestampProvider, Deadline, TimeWindow,
 };
 
 // Re-export blockchain types
-pub use blockchain_types_v03::{
+pub use blockchain::{
     TxId, ChainId, BlockHeight, TxRef, BuyerTxIds, SellerTxIds, TxIdProvenance,
 };
 
 // Re-export CoreProver types
-pub use coreprover_types_v03::{
-    CoreProverReceipt, EscrowState, EscrowRecord,
+pub use coreprover::{
+    CoreProverReceipt, EscrowState, EscrowView,
 };
 
 // Re-export TxIP types
-pub use txip_types_v03::{
+pub use types::{
     TxipEnvelope, Direction, Role, MessageType, TgpPhase, Payload,
     ControlPayload, TgpPayload, ErrorPayload, HelloPayload, WelcomePayload,
     HeartbeatPayload, ClosePayload, Features, AuthInfo, AuthScheme,
     ErrorCode, CloseReason, TXIP_VERSION,
 };
 
+// Re-export authentication types
+pub use auth::{
+    ApiKeyAuthenticator, ApiKeyRecord, AuthError, AuthenticatedAgent, Authenticator,
+    Authenticators, JwtAuthenticator, MtlsAuthenticator, PeerInfo, mtls_server_config,
+};
+
+// Re-export negotiation types
+pub use negotiation::{
+    Capabilities, CapabilityViolation, NegotiatedCapabilities, NegotiationError,
+};
+
 // Re-export session types
-pub use txip_session_v03::{SessionManager, SessionInfo, SessionConfig};
+pub use session::{HelloError, SessionManager, SessionInfo, SessionConfig};
+
+// Re-export routing hook
+pub use router::TgpRouter;
 
 // Re-export handler types
-pub use txip_http_handler_v03::{HttpHandlerState, handle_txip_message, MessageAcceptedResponse};
-pub use txip_websocket_handler_v03::{WebSocketHandlerState, handle_websocket_upgrade};
+pub use http_handler::{
+    HttpHandlerState, handle_txip_message, process_message, MessageAcceptedResponse, TxipReply,
+};
+
+// Re-export WebSocket transport
+pub use websocket::{WebSocketTransport, WsConfig};
 
 /// Prelude module for common imports
 pub mod prelude {
-    pub use super::timestamp_types_v03::{TripleTimestamp, TimestampProvider};
-    pub use super::blockchain_types_v03::{TxId, ChainId, TxIdProvenance};
-    pub use super::coreprover_types_v03::{CoreProverReceipt, EscrowState};
-    pub use super::txip_types_v03::{
+    pub use super::timestamp::{TripleTimestamp, TimestampProvider};
+    pub use super::blockchain::{TxId, ChainId, TxIdProvenance};
+    pub use super::coreprover::{CoreProverReceipt, EscrowState};
+    pub use super::types::{
         TxipEnvelope, Direction, Role, MessageType, TgpPhase,
         ErrorCode, TXIP_VERSION,
     };
-    pub use super::txip_session_v03::{SessionManager, SessionConfig};
-    pub use super::txip_http_handler_v03::HttpHandlerState;
-    pub use super::txip_websocket_handler_v03::WebSocketHandlerState;
+    pub use super::auth::{Authenticators, PeerInfo};
+    pub use super::session::{SessionManager, SessionConfig};
+    pub use super::router::TgpRouter;
+    pub use super::http_handler::HttpHandlerState;
+    pub use super::websocket::WebSocketTransport;
 }
 
 #[cfg(test)]
@@ -171,48 +194,54 @@ mod integration_tests {
     }
 
     #[test]
-    fn test_escrow_state_machine() {
-        let ts = TripleTimestamp::new(
-            1000,
-            1731600000,
-            "2024-11-14T12:00:00Z".to_string(),
-        );
-
-        let mut escrow = EscrowRecord::new(
-            "order-123".to_string(),
-            "sess-456".to_string(),
+    fn test_escrow_view_from_engine() {
+        use coreprover_service::engine::CoreProverEngine;
+        use coreprover_service::types::PaymentProfile;
+
+        let mut engine = CoreProverEngine::new(369, 10, 1731600000);
+        let order_id = engine.buyer_commit(
+            "buyer://alice".to_string(),
+            "seller://pizza".to_string(),
             30000000,
-            "USDC".to_string(),
-            ts.clone(),
-            ts.clone(),
+            PaymentProfile::pizza_delivery(),
             1,
             "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef".to_string(),
-        );
-
-        assert_eq!(escrow.state, EscrowState::BuyerCommitted);
+        ).unwrap();
 
-        // Seller accepts
-        escrow.seller_accept(
-            369,
+        let view = EscrowView::from_engine(engine.get_escrow_record(&order_id).unwrap(), &engine);
+        assert_eq!(view.state, EscrowState::BuyerCommitted);
+        assert_eq!(view.order_id, format!("0x{}", hex::encode(order_id)));
+        assert_eq!(view.buyer_commit_time.unix, 1731600000);
+        assert_eq!(view.acceptance_deadline.mono, 1800);
+        assert_eq!(view.acceptance_deadline.unix, 1731601800);
+        assert!(view.acceptance_deadline.validate_iso().is_ok());
+        assert!(view.seller_txids.is_none());
+        assert_eq!(view.late_discount_pct, 10);
+
+        // Seller accepts and fulfills
+        engine.advance_time(60);
+        engine.seller_accept(
+            &order_id,
             "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_string(),
-            ts.clone(),
-            3600,
         ).unwrap();
-        assert_eq!(escrow.state, EscrowState::SellerAccepted);
-
-        // Seller fulfills
-        escrow.seller_fulfill(
+        engine.advance_time(600);
+        engine.seller_fulfill(
+            &order_id,
             "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".to_string(),
-            12345,
-            ts.clone(),
         ).unwrap();
-        assert_eq!(escrow.state, EscrowState::SellerFulfilled);
 
-        // Seller claims
-        escrow.seller_claim(
-            "0xcccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc".to_string(),
-        ).unwrap();
-        assert_eq!(escrow.state, EscrowState::SellerClaimed);
+        let view = EscrowView::from_engine(engine.get_escrow_record(&order_id).unwrap(), &engine);
+        assert_eq!(view.state, EscrowState::SellerFulfilled);
+        assert_eq!(view.seller_accept_time.as_ref().unwrap().mono, 60);
+        assert_eq!(view.fulfillment_time.as_ref().unwrap().unix, 1731600660);
+        let seller = view.seller_txids.unwrap();
+        assert_eq!(seller.chain_id, 369);
+        assert!(!seller.was_paid());
+
+        // The engine itself is the timestamp provider
+        let now = TimestampProvider::now(&engine);
+        assert_eq!((now.mono, now.unix), (660, 1731600660));
+        assert_eq!(engine.at_unix(1731600000).mono, 0);
     }
 
     #[test]
@@ -238,10 +267,12 @@ mod integration_tests {
             },
         };
 
+        let agent = AuthenticatedAgent::new("buyer://alice", AuthScheme::None, vec![]);
         let session_info = manager.handle_hello(
             &hello,
             "sess-123".to_string(),
             Role::BuyerAgent,
+            &agent,
         ).unwrap();
 
         assert_eq!(session_info.session_id, "sess-123");
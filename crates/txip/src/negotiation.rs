@@ -0,0 +1,468 @@
+// crates/txip/src/negotiation.rs
+// TxIP HELLO/WELCOME Capability Negotiation
+//
+// HELLO lists what an agent supports; WELCOME answers with the intersection
+// with this controller's Capabilities. TGP versions, chains and assets must
+// overlap or the HELLO is refused with TXIP_UNSUPPORTED_VERSION. A feature is
+// negotiated only if both sides support it.
+//
+// The negotiated set is recorded on SessionInfo and enforced on every TGP
+// message of the session, in both directions: a QUERY for an asset or chain
+// outside the set is answered with a TGP ERROR, and so is an OFFER the agent
+// could not settle (e.g. a cross-chain route without cross_chain_support).
+// Replies are written in the negotiated TGP version, so TGP-00 agents get
+// OFFERs without the TGP-01 fields.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::blockchain::ChainId;
+use crate::types::{ErrorCode, Features, HelloPayload};
+use tbc_core::tgp::asset::AssetId;
+use tbc_core::tgp::errors::TgpErrorCode;
+use tbc_core::tgp::messages::{ErrorMessage, TGPMessage};
+use tbc_core::tgp::version::{
+    negotiate_version, TgpVersion, VersionError, VersionedMessage, SUPPORTED_TGP_VERSIONS,
+};
+
+// ============================================================================
+// Controller Capabilities
+// ============================================================================
+
+/// What this controller supports, intersected with every HELLO
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub tgp_versions: Vec<TgpVersion>,
+    pub chains: Vec<ChainId>,
+    pub assets: Vec<String>,
+    pub features: Features,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            tgp_versions: SUPPORTED_TGP_VERSIONS.to_vec(),
+            chains: vec![1, 369, 943, 8453],
+            assets: ["USDC", "USDT", "DAI", "ETH", "PLS"].map(String::from).to_vec(),
+            features: Features {
+                zk_discount_proofs: true,
+                receipt_ownership_proofs: true,
+                late_discount_support: true,
+                cross_chain_support: true,
+            },
+        }
+    }
+}
+
+impl Capabilities {
+    /// Restrict the supported TGP versions
+    pub fn with_tgp_versions(mut self, versions: Vec<TgpVersion>) -> Self {
+        self.tgp_versions = versions;
+        self
+    }
+
+    /// Set the chains this controller settles on
+    pub fn with_chains(mut self, chains: Vec<ChainId>) -> Self {
+        self.chains = chains;
+        self
+    }
+
+    /// Set the assets this controller routes
+    pub fn with_assets(mut self, assets: Vec<String>) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    /// Set the features this controller offers
+    pub fn with_features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Intersect a HELLO with these capabilities
+    ///
+    /// Chains and assets keep the agent's order and spelling.
+    pub fn negotiate(
+        &self,
+        hello: &HelloPayload,
+    ) -> Result<NegotiatedCapabilities, NegotiationError> {
+        let tgp_version = negotiate_version(&self.tgp_versions, &hello.supported_tgp_versions)?;
+
+        let chains: Vec<ChainId> = hello
+            .supported_chains
+            .iter()
+            .copied()
+            .filter(|chain| self.chains.contains(chain))
+            .collect();
+        if chains.is_empty() {
+            return Err(NegotiationError::NoCommonChain {
+                local: join(&self.chains),
+                remote: join(&hello.supported_chains),
+            });
+        }
+
+        let local_assets: Vec<String> = self.assets.iter().map(|a| asset_key(a)).collect();
+        let assets: Vec<String> = hello
+            .supported_assets
+            .iter()
+            .filter(|asset| local_assets.contains(&asset_key(asset)))
+            .cloned()
+            .collect();
+        if assets.is_empty() {
+            return Err(NegotiationError::NoCommonAsset {
+                local: self.assets.join(","),
+                remote: hello.supported_assets.join(","),
+            });
+        }
+
+        Ok(NegotiatedCapabilities {
+            tgp_version: tgp_version.to_string(),
+            chains,
+            assets,
+            features: self.features.intersect(&hello.features),
+        })
+    }
+}
+
+/// HELLO without overlap with the controller's capabilities
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum NegotiationError {
+    #[error(transparent)]
+    Version(#[from] VersionError),
+
+    #[error("no common chain (controller: {local}, agent: {remote})")]
+    NoCommonChain { local: String, remote: String },
+
+    #[error("no common asset (controller: {local}, agent: {remote})")]
+    NoCommonAsset { local: String, remote: String },
+}
+
+impl NegotiationError {
+    /// TxIP error code HELLO is refused with
+    pub fn error_code(&self) -> ErrorCode {
+        ErrorCode::TxipUnsupportedVersion
+    }
+}
+
+// ============================================================================
+// Negotiated Set
+// ============================================================================
+
+/// Outcome of HELLO/WELCOME, recorded on the session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegotiatedCapabilities {
+    pub tgp_version: String,
+    pub chains: Vec<ChainId>,
+    pub assets: Vec<String>,
+    pub features: Features,
+}
+
+impl NegotiatedCapabilities {
+    /// Whether `chain_id` was negotiated
+    pub fn supports_chain(&self, chain_id: ChainId) -> bool {
+        self.chains.contains(&chain_id)
+    }
+
+    /// Whether `asset` names a negotiated asset, in any accepted form
+    pub fn supports_asset(&self, asset: &str) -> bool {
+        let key = asset_key(asset);
+        self.assets.iter().any(|negotiated| asset_key(negotiated) == key)
+    }
+
+    /// Check a TGP message sent by the agent
+    ///
+    /// `origin_chain` is the chain the agent pays from, if known.
+    pub fn check_inbound(
+        &self,
+        message: &TGPMessage,
+        origin_chain: Option<ChainId>,
+    ) -> Result<(), CapabilityViolation> {
+        if let Some(chain_id) = origin_chain {
+            if !self.supports_chain(chain_id) {
+                return Err(CapabilityViolation::new(
+                    TgpErrorCode::UnsupportedChain,
+                    format!("chain {} was not negotiated", chain_id),
+                ));
+            }
+        }
+
+        if let TGPMessage::Query(query) = message {
+            if !self.supports_asset(&query.asset) {
+                return Err(CapabilityViolation::new(
+                    TgpErrorCode::UnsupportedAsset,
+                    format!("asset {} was not negotiated", query.asset),
+                ));
+            }
+            if let Some(chain_id) = pinned_chain(&query.asset) {
+                if !self.supports_chain(chain_id) {
+                    return Err(CapabilityViolation::new(
+                        TgpErrorCode::UnsupportedChain,
+                        format!(
+                            "asset {} is on chain {}, which was not negotiated",
+                            query.asset, chain_id
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a TGP reply before it is sent to the agent
+    ///
+    /// Every OFFER route must settle on a negotiated chain, and on
+    /// `origin_chain` unless cross-chain support was negotiated.
+    pub fn check_reply(
+        &self,
+        reply: &TGPMessage,
+        origin_chain: Option<ChainId>,
+    ) -> Result<(), CapabilityViolation> {
+        let TGPMessage::Offer(offer) = reply else {
+            return Ok(());
+        };
+
+        for route in &offer.routes {
+            if !self.supports_chain(route.chain_id) {
+                return Err(CapabilityViolation::new(
+                    TgpErrorCode::UnsupportedChain,
+                    format!(
+                        "route {} settles on chain {}, which was not negotiated",
+                        route.route_id, route.chain_id
+                    ),
+                ));
+            }
+
+            let cross_chain = origin_chain.is_some_and(|origin| origin != route.chain_id);
+            if cross_chain && !self.features.cross_chain_support {
+                return Err(CapabilityViolation::new(
+                    TgpErrorCode::UnsupportedChain,
+                    format!(
+                        "route {} is cross-chain but cross_chain_support was not negotiated",
+                        route.route_id
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a TGP reply in the negotiated version
+    ///
+    /// A TGP-00 session gets the reply through `VersionedMessage::downgrade`,
+    /// which strips routes, decisions and the other TGP-01 fields.
+    pub fn adapt_reply(&self, reply: TGPMessage) -> TGPMessage {
+        match self.tgp_version.parse::<TgpVersion>() {
+            Ok(version) => VersionedMessage::current(reply).downgrade(version).message.message,
+            Err(_) => reply,
+        }
+    }
+}
+
+/// TGP message outside the negotiated capabilities
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("{message}")]
+pub struct CapabilityViolation {
+    pub code: TgpErrorCode,
+    pub message: String,
+}
+
+impl CapabilityViolation {
+    pub fn new(code: TgpErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// TGP ERROR answering the message with id `correlation_id`
+    pub fn to_error(&self, correlation_id: &str) -> ErrorMessage {
+        let id = format!("err-{}", correlation_id);
+        ErrorMessage::from_code(id, self.code, self.message.clone()).correlated_to(correlation_id)
+    }
+}
+
+/// Chain an asset identifier pins the asset to (`evm:USDC:8453`, CAIP-19)
+pub fn pinned_chain(asset: &str) -> Option<ChainId> {
+    AssetId::parse(asset).ok().and_then(|id| id.chain_id())
+}
+
+/// Key assets are compared by: the symbol, or the whole CAIP-19 id
+fn asset_key(asset: &str) -> String {
+    match AssetId::parse(asset) {
+        Ok(AssetId::Symbol(symbol)) | Ok(AssetId::Evm { symbol, .. }) => symbol,
+        Ok(id) => id.to_string(),
+        Err(_) => asset.trim().to_uppercase(),
+    }
+}
+
+fn join(chains: &[ChainId]) -> String {
+    chains.iter().map(ChainId::to_string).collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AuthInfo, AuthScheme};
+    use tbc_core::tgp::messages::{OfferMessage, QueryMessage};
+    use tbc_core::tgp::routes::RouteOption;
+    use tbc_core::tgp::types::{EconomicEnvelope, ZkProfile};
+
+    fn features(cross_chain_support: bool) -> Features {
+        Features {
+            zk_discount_proofs: true,
+            receipt_ownership_proofs: false,
+            late_discount_support: true,
+            cross_chain_support,
+        }
+    }
+
+    fn hello(versions: &[&str], chains: &[ChainId], assets: &[&str]) -> HelloPayload {
+        HelloPayload {
+            agent_id: "buyer://alice".to_string(),
+            supported_tgp_versions: versions.iter().map(|v| v.to_string()).collect(),
+            supported_transports: vec!["HTTP".to_string()],
+            supported_chains: chains.to_vec(),
+            supported_assets: assets.iter().map(|a| a.to_string()).collect(),
+            features: features(true),
+            auth: AuthInfo {
+                scheme: AuthScheme::None,
+                token: None,
+            },
+        }
+    }
+
+    fn query(asset: &str) -> TGPMessage {
+        TGPMessage::Query(QueryMessage::new(
+            "q-1",
+            "buyer://alice",
+            "seller://bob",
+            asset,
+            1_000,
+            ZkProfile::Optional,
+        ))
+    }
+
+    #[test]
+    fn test_negotiate_intersection() {
+        let capabilities = Capabilities::default()
+            .with_chains(vec![369, 8453])
+            .with_assets(vec!["USDC".to_string(), "PLS".to_string()])
+            .with_features(features(false));
+
+        let negotiated = capabilities
+            .negotiate(&hello(
+                &["TGP-00", "TGP-01", "TGP-09"],
+                &[1, 8453, 369],
+                &["evm:usdc:8453", "DAI"],
+            ))
+            .unwrap();
+
+        assert_eq!(negotiated.tgp_version, "TGP-01");
+        assert_eq!(negotiated.chains, vec![8453, 369]);
+        assert_eq!(negotiated.assets, vec!["evm:usdc:8453"]);
+        assert!(negotiated.features.zk_discount_proofs);
+        assert!(!negotiated.features.receipt_ownership_proofs);
+        assert!(!negotiated.features.cross_chain_support);
+    }
+
+    #[test]
+    fn test_negotiate_rejects_no_overlap() {
+        let capabilities = Capabilities::default().with_chains(vec![369]);
+
+        let version = capabilities.negotiate(&hello(&["TGP-09"], &[369], &["USDC"])).unwrap_err();
+        assert!(matches!(version, NegotiationError::Version(_)));
+
+        let chain = capabilities.negotiate(&hello(&["TGP-01"], &[1], &["USDC"])).unwrap_err();
+        assert_eq!(
+            chain,
+            NegotiationError::NoCommonChain {
+                local: "369".to_string(),
+                remote: "1".to_string()
+            }
+        );
+
+        let asset = capabilities.negotiate(&hello(&["TGP-01"], &[369], &["XYZ"])).unwrap_err();
+        assert!(matches!(asset, NegotiationError::NoCommonAsset { .. }));
+        assert_eq!(asset.error_code(), ErrorCode::TxipUnsupportedVersion);
+    }
+
+    #[test]
+    fn test_legacy_version_negotiates_tgp00() {
+        let capabilities = Capabilities::default().with_chains(vec![369]);
+
+        let negotiated = capabilities.negotiate(&hello(&["2.0"], &[369], &["USDC"])).unwrap();
+        assert_eq!(negotiated.tgp_version, "TGP-00");
+    }
+
+    #[test]
+    fn test_inbound_query_checked() {
+        let negotiated = Capabilities::default()
+            .negotiate(&hello(&["TGP-01"], &[369], &["USDC"]))
+            .unwrap();
+
+        assert!(negotiated.check_inbound(&query("usdc"), Some(369)).is_ok());
+        assert_eq!(
+            negotiated.check_inbound(&query("ETH"), None).unwrap_err().code,
+            TgpErrorCode::UnsupportedAsset
+        );
+        assert_eq!(
+            negotiated.check_inbound(&query("evm:USDC:8453"), None).unwrap_err().code,
+            TgpErrorCode::UnsupportedChain
+        );
+        assert_eq!(
+            negotiated.check_inbound(&query("USDC"), Some(1)).unwrap_err().code,
+            TgpErrorCode::UnsupportedChain
+        );
+    }
+
+    #[test]
+    fn test_cross_chain_offer_refused_without_feature() {
+        let mut hello = hello(&["TGP-01"], &[369, 8453], &["USDC"]);
+        hello.features = features(false);
+        let negotiated = Capabilities::default().negotiate(&hello).unwrap();
+
+        let offer = |chain_id: ChainId| {
+            let envelope = EconomicEnvelope::new(50);
+            TGPMessage::Offer(
+                OfferMessage::new("offer-1", "q-1", "USDC", 1_000, false, envelope)
+                    .with_routes(vec![RouteOption::direct("route-1", "USDC", 1_000, chain_id)]),
+            )
+        };
+
+        assert!(negotiated.check_reply(&offer(369), Some(369)).is_ok());
+        assert!(negotiated.check_reply(&offer(8453), None).is_ok());
+
+        let violation = negotiated.check_reply(&offer(8453), Some(369)).unwrap_err();
+        assert_eq!(violation.code, TgpErrorCode::UnsupportedChain);
+        assert!(violation.message.contains("cross_chain_support"));
+
+        let error = violation.to_error("q-1");
+        assert_eq!(error.correlation_id.as_deref(), Some("q-1"));
+        assert_eq!(error.error_code(), Some(TgpErrorCode::UnsupportedChain));
+
+        // Routes must stay on negotiated chains either way
+        assert!(negotiated.check_reply(&offer(1), None).is_err());
+    }
+
+    #[test]
+    fn test_reply_downgraded_for_tgp00_session() {
+        let offer = TGPMessage::Offer(
+            OfferMessage::new("offer-1", "q-1", "USDC", 1_000, false, EconomicEnvelope::new(50))
+                .with_routes(vec![RouteOption::direct("route-1", "USDC", 1_000, 369)]),
+        );
+
+        let legacy = Capabilities::default()
+            .negotiate(&hello(&["TGP-00"], &[369], &["USDC"]))
+            .unwrap();
+        let TGPMessage::Offer(downgraded) = legacy.adapt_reply(offer.clone()) else {
+            panic!("expected OFFER");
+        };
+        assert!(downgraded.routes.is_empty());
+        assert_eq!(downgraded.economic_envelope.max_fees_bps, 50);
+
+        let current = Capabilities::default()
+            .negotiate(&hello(&["TGP-00", "TGP-01"], &[369], &["USDC"]))
+            .unwrap();
+        assert_eq!(current.adapt_reply(offer.clone()), offer);
+    }
+}
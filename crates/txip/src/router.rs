@@ -0,0 +1,44 @@
+// crates/txip/src/router.rs
+// TGP Routing Hook
+//
+// TxIP only transports TGP messages. Every decoded and validated TGP
+// message of an established session is handed to a TgpRouter; its reply
+// (an OFFER or ERROR for a QUERY, for example) goes back to the client in
+// the same response. The routing layer (tbc-gateway) implements this trait.
+// A reply that breaks the session's negotiated capabilities is not sent;
+// the router is told to withdraw it instead. A HELLO naming a session the
+// router still knows (e.g. from before a restart) resumes it, and the
+// router's pending reply is replayed in WELCOME.
+
+use async_trait::async_trait;
+
+use crate::session::SessionInfo;
+use tbc_core::tgp::messages::TGPMessage;
+
+/// Routing layer behind the TxIP handlers
+#[async_trait]
+pub trait TgpRouter: Send + Sync {
+    /// Route a TGP message received on `session`
+    ///
+    /// Returns the TGP message to send back, or `None` if the message is
+    /// only acknowledged.
+    async fn route_message(&self, session: &SessionInfo, message: TGPMessage)
+        -> Option<TGPMessage>;
+
+    /// Drop the state behind a reply that was refused instead of sent
+    ///
+    /// Called with a reply of `route_message` that failed the session's
+    /// capability check, e.g. an OFFER settling on a chain the session did
+    /// not negotiate. The default does nothing.
+    async fn withdraw_reply(&self, _session: &SessionInfo, _reply: &TGPMessage) {}
+
+    /// Resume `session_id` for `agent_id` after a reconnect
+    ///
+    /// Called for every authenticated HELLO. Returns the reply to replay,
+    /// e.g. the session's pending OFFER, or `None` if the session is unknown,
+    /// belongs to another agent or cannot be resumed. The default resumes
+    /// nothing.
+    fn resume_session(&self, _session_id: &str, _agent_id: &str) -> Option<TGPMessage> {
+        None
+    }
+}
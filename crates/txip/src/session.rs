@@ -1,4 +1,4 @@
-// crates/tbc-gateway/src/txip/txip_session_v03.rs
+// crates/txip/src/session.rs
 // FINAL - TxIP Session Management with CoreProver v0.3 Compatibility
 //
 // Session management using triple-clock timestamps from engine.
@@ -10,9 +10,13 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
-use super::blockchain_types_v03::ChainId;
-use super::timestamp_types_v03::{TimestampProvider, TripleTimestamp};
-use super::txip_types_v03::*;
+use thiserror::Error;
+
+use crate::auth::AuthenticatedAgent;
+use crate::negotiation::{Capabilities, NegotiatedCapabilities, NegotiationError};
+use crate::timestamp::{TimestampProvider, TripleTimestamp};
+use crate::types::*;
+use tbc_core::tgp::ratelimit::LimitKey;
 
 /// Session information
 #[derive(Debug, Clone)]
@@ -31,9 +35,17 @@ pub struct SessionInfo {
     pub last_activity_unix: u64,
     pub last_activity_iso: String,
     
-    pub negotiated_tgp_version: String,
-    pub negotiated_chains: Vec<ChainId>,
-    pub features: Features,
+    /// TGP version, chains, assets and features agreed in HELLO/WELCOME
+    pub negotiated: NegotiatedCapabilities,
+
+    /// How the agent authenticated in HELLO
+    pub auth_scheme: AuthScheme,
+
+    /// TGP phases the agent may send
+    pub scopes: Vec<TgpPhase>,
+
+    /// Keys the session is counted against by the rate limiter
+    pub limit_keys: Vec<LimitKey>,
 }
 
 impl SessionInfo {
@@ -59,6 +71,33 @@ impl SessionInfo {
             self.created_iso.clone(),
         )
     }
+
+    /// Check whether the agent may send a TGP message of `phase`
+    ///
+    /// ERROR messages (phase NONE) are always allowed.
+    pub fn may_send(&self, phase: &TgpPhase) -> bool {
+        *phase == TgpPhase::None || self.scopes.contains(phase)
+    }
+}
+
+/// Why a HELLO did not open or resume a session
+#[derive(Debug, Error)]
+pub enum HelloError {
+    #[error("capability negotiation failed: {0}")]
+    Negotiation(#[from] NegotiationError),
+
+    #[error("session {session_id} belongs to another agent")]
+    SessionOwnedByOther { session_id: String },
+}
+
+impl HelloError {
+    /// TxIP error code HELLO is refused with
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            HelloError::Negotiation(e) => e.error_code(),
+            HelloError::SessionOwnedByOther { .. } => ErrorCode::TxipUnauthenticated,
+        }
+    }
 }
 
 /// Session manager
@@ -74,6 +113,9 @@ pub struct SessionManager<T: TimestampProvider> {
     
     /// Timestamp provider (engine)
     timestamp_provider: Arc<T>,
+
+    /// Controller capabilities every HELLO is negotiated against
+    capabilities: Capabilities,
 }
 
 /// Session configuration
@@ -107,27 +149,43 @@ impl<T: TimestampProvider> SessionManager<T> {
             message_cache: Arc::new(RwLock::new(HashMap::new())),
             config,
             timestamp_provider,
+            capabilities: Capabilities::default(),
         }
     }
 
-    /// Create or update a session from a HELLO message
+    /// Negotiate HELLOs against `capabilities` instead of the defaults
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Controller capabilities offered in negotiation
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Create or update a session from an authenticated HELLO message
+    ///
+    /// Fails without touching an existing session if the HELLO has no
+    /// overlap with the controller's capabilities, or if the session was
+    /// opened by another agent.
     pub fn handle_hello(
         &self,
         hello: &HelloPayload,
         session_id: String,
         role: Role,
-    ) -> Result<SessionInfo, String> {
+        agent: &AuthenticatedAgent,
+    ) -> Result<SessionInfo, HelloError> {
         let now = self.timestamp_provider.now();
-        
-        // Negotiate TGP version
-        let negotiated_tgp_version = Self::negotiate_tgp_version(&hello.supported_tgp_versions)?;
-        
-        // Negotiate chains
-        let negotiated_chains = Self::negotiate_chains(&hello.supported_chains)?;
-        
+
+        // Picks the highest common TGP version, so agents that only speak
+        // TGP-00 keep working; replies to them are downgraded by
+        // `NegotiatedCapabilities::adapt_reply` before sending.
+        let negotiated = self.capabilities.negotiate(hello)?;
+
         let session_info = SessionInfo {
             session_id: session_id.clone(),
-            agent_id: hello.agent_id.clone(),
+            agent_id: agent.agent_id.clone(),
             role,
             created_mono: now.mono,
             created_unix: now.unix,
@@ -135,13 +193,19 @@ impl<T: TimestampProvider> SessionManager<T> {
             last_activity_mono: now.mono,
             last_activity_unix: now.unix,
             last_activity_iso: now.iso,
-            negotiated_tgp_version,
-            negotiated_chains,
-            features: hello.features.clone(),
+            negotiated,
+            auth_scheme: agent.scheme.clone(),
+            scopes: agent.scopes.clone(),
+            limit_keys: agent.limit_keys(),
         };
 
-        // Store session
+        // Store session; only the agent that opened it may resume it
         let mut sessions = self.sessions.write().unwrap();
+        if let Some(existing) = sessions.get(&session_id) {
+            if existing.agent_id != agent.agent_id {
+                return Err(HelloError::SessionOwnedByOther { session_id });
+            }
+        }
         sessions.insert(session_id.clone(), session_info.clone());
 
         // Initialize message cache for this session
@@ -167,7 +231,7 @@ impl<T: TimestampProvider> SessionManager<T> {
         let mut cache = self.message_cache.write().unwrap();
         
         let msg_ids = cache.entry(session_id.to_string())
-            .or_insert_with(HashSet::new);
+            .or_default();
         
         msg_ids.insert(msg_id.to_string());
         
@@ -196,31 +260,44 @@ impl<T: TimestampProvider> SessionManager<T> {
     }
 
     /// Close a session
-    pub fn close_session(&self, session_id: &str) -> Result<(), String> {
+    ///
+    /// Returns the closed session, if it existed, so the caller can return
+    /// its rate limiter slot.
+    pub fn close_session(&self, session_id: &str) -> Result<Option<SessionInfo>, String> {
         let mut sessions = self.sessions.write().unwrap();
         let mut cache = self.message_cache.write().unwrap();
         
-        sessions.remove(session_id);
+        let closed = sessions.remove(session_id);
         cache.remove(session_id);
         
-        Ok(())
+        Ok(closed)
     }
 
     /// Clean up expired sessions based on current time from provider
-    pub fn cleanup_expired(&self) {
+    ///
+    /// Returns the removed sessions.
+    pub fn cleanup_expired(&self) -> Vec<SessionInfo> {
         let now = self.timestamp_provider.now();
         
         // Remove expired sessions
         let mut sessions = self.sessions.write().unwrap();
-        sessions.retain(|_, session| {
-            !session.is_timed_out(now.mono, self.config.session_timeout_seconds)
-        });
+        let expired: Vec<String> = sessions
+            .values()
+            .filter(|session| session.is_timed_out(now.mono, self.config.session_timeout_seconds))
+            .map(|session| session.session_id.clone())
+            .collect();
+        let removed: Vec<SessionInfo> = expired
+            .iter()
+            .filter_map(|session_id| sessions.remove(session_id))
+            .collect();
 
         // Remove message caches for inactive sessions
         let active_sessions: HashSet<String> = sessions.keys().cloned().collect();
         
         let mut cache = self.message_cache.write().unwrap();
         cache.retain(|session_id, _| active_sessions.contains(session_id));
+
+        removed
     }
 
     /// Get heartbeat interval for negotiation
@@ -232,32 +309,12 @@ impl<T: TimestampProvider> SessionManager<T> {
     pub fn now(&self) -> TripleTimestamp {
         self.timestamp_provider.now()
     }
-
-    /// Negotiate TGP version
-    fn negotiate_tgp_version(supported: &[String]) -> Result<String, String> {
-        // For now, only support TGP 2.0
-        if supported.contains(&"2.0".to_string()) {
-            Ok("2.0".to_string())
-        } else {
-            Err("No compatible TGP version found".to_string())
-        }
-    }
-
-    /// Negotiate chains
-    fn negotiate_chains(supported: &[ChainId]) -> Result<Vec<ChainId>, String> {
-        // For now, accept any chains the client supports
-        // In production, intersect with TBC's supported chains
-        if supported.is_empty() {
-            Err("Client must support at least one chain".to_string())
-        } else {
-            Ok(supported.to_vec())
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::ALL_SCOPES;
 
     /// Test timestamp provider for unit tests
     struct TestTimestampProvider {
@@ -295,7 +352,7 @@ mod tests {
             TripleTimestamp::new(
                 mono,
                 unix,
-                format!("2024-11-14T12:00:00Z"),
+                "2024-11-14T12:00:00Z".to_string(),
             )
         }
 
@@ -304,7 +361,7 @@ mod tests {
             TripleTimestamp::new(
                 mono,
                 unix,
-                format!("2024-11-14T12:00:00Z"),
+                "2024-11-14T12:00:00Z".to_string(),
             )
         }
     }
@@ -329,13 +386,18 @@ mod tests {
         }
     }
 
+    fn anonymous() -> AuthenticatedAgent {
+        AuthenticatedAgent::new("buyer://alice", AuthScheme::None, ALL_SCOPES.to_vec())
+    }
+
     #[test]
     fn test_session_creation() {
         let provider = Arc::new(TestTimestampProvider::new(1000, 1731600000));
         let manager = SessionManager::new(SessionConfig::default(), provider);
         let hello = create_test_hello();
         
-        let result = manager.handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent);
+        let result =
+            manager.handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent, &anonymous());
         assert!(result.is_ok());
         
         let session = manager.get_session("sess-123");
@@ -346,13 +408,98 @@ mod tests {
         assert_eq!(session.created_mono, 1000);
     }
 
+    #[test]
+    fn test_session_scopes() {
+        let provider = Arc::new(TestTimestampProvider::new(1000, 1731600000));
+        let manager = SessionManager::new(SessionConfig::default(), provider);
+        let agent = AuthenticatedAgent::new(
+            "buyer://alice",
+            AuthScheme::ApiKey,
+            vec![TgpPhase::Query],
+        );
+
+        let session = manager
+            .handle_hello(&create_test_hello(), "sess-123".to_string(), Role::BuyerAgent, &agent)
+            .unwrap();
+        assert_eq!(session.auth_scheme, AuthScheme::ApiKey);
+        assert!(session.may_send(&TgpPhase::Query));
+        assert!(session.may_send(&TgpPhase::None));
+        assert!(!session.may_send(&TgpPhase::Settle));
+    }
+
+    #[test]
+    fn test_hello_records_negotiated_set() {
+        let provider = Arc::new(TestTimestampProvider::new(1000, 1731600000));
+        let mut capabilities_features = create_test_hello().features;
+        capabilities_features.cross_chain_support = false;
+        let manager = SessionManager::new(SessionConfig::default(), provider)
+            .with_capabilities(
+                Capabilities::default()
+                    .with_chains(vec![369])
+                    .with_features(capabilities_features),
+            );
+
+        let session = manager
+            .handle_hello(
+                &create_test_hello(),
+                "sess-123".to_string(),
+                Role::BuyerAgent,
+                &anonymous(),
+            )
+            .unwrap();
+        // "2.0" agents predate TGP-01
+        assert_eq!(session.negotiated.tgp_version, "TGP-00");
+        assert_eq!(session.negotiated.chains, vec![369]);
+        assert_eq!(session.negotiated.assets, vec!["USDC"]);
+        assert!(!session.negotiated.features.cross_chain_support);
+
+        // A HELLO without overlap leaves the existing session alone
+        let mut hello = create_test_hello();
+        hello.supported_chains = vec![1];
+        let error = manager
+            .handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent, &anonymous())
+            .unwrap_err();
+        assert!(matches!(error, HelloError::Negotiation(NegotiationError::NoCommonChain { .. })));
+        assert_eq!(manager.get_session("sess-123").unwrap().negotiated, session.negotiated);
+    }
+
+    #[test]
+    fn test_hello_from_other_agent_cannot_take_over_session() {
+        let provider = Arc::new(TestTimestampProvider::new(1000, 1731600000));
+        let manager = SessionManager::new(SessionConfig::default(), provider);
+        let session = manager
+            .handle_hello(
+                &create_test_hello(),
+                "sess-123".to_string(),
+                Role::BuyerAgent,
+                &anonymous(),
+            )
+            .unwrap();
+
+        let mut hello = create_test_hello();
+        hello.agent_id = "buyer://mallory".to_string();
+        let mallory =
+            AuthenticatedAgent::new("buyer://mallory", AuthScheme::None, ALL_SCOPES.to_vec());
+        let error = manager
+            .handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent, &mallory)
+            .unwrap_err();
+        assert!(matches!(error, HelloError::SessionOwnedByOther { .. }));
+        assert_eq!(error.error_code(), ErrorCode::TxipUnauthenticated);
+
+        let kept = manager.get_session("sess-123").unwrap();
+        assert_eq!(kept.agent_id, "buyer://alice");
+        assert_eq!(kept.limit_keys, session.limit_keys);
+    }
+
     #[test]
     fn test_idempotency() {
         let provider = Arc::new(TestTimestampProvider::new(1000, 1731600000));
         let manager = SessionManager::new(SessionConfig::default(), provider);
         let hello = create_test_hello();
         
-        manager.handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent).unwrap();
+        manager
+            .handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent, &anonymous())
+            .unwrap();
         
         // First message should not be duplicate
         assert!(!manager.is_duplicate_message("sess-123", "msg-1"));
@@ -367,13 +514,17 @@ mod tests {
     #[test]
     fn test_session_timeout() {
         let provider = Arc::new(TestTimestampProvider::new(1000, 1731600000));
-        let mut config = SessionConfig::default();
-        config.session_timeout_seconds = 60; // 1 minute timeout
+        let config = SessionConfig {
+            session_timeout_seconds: 60, // 1 minute timeout
+            ..Default::default()
+        };
         
         let manager = SessionManager::new(config, provider.clone());
         let hello = create_test_hello();
         
-        manager.handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent).unwrap();
+        manager
+            .handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent, &anonymous())
+            .unwrap();
         
         let session = manager.get_session("sess-123").unwrap();
         assert!(!session.is_timed_out(1000, 60));
@@ -384,20 +535,26 @@ mod tests {
     #[test]
     fn test_cleanup_expired() {
         let provider = Arc::new(TestTimestampProvider::new(1000, 1731600000));
-        let mut config = SessionConfig::default();
-        config.session_timeout_seconds = 60;
+        let config = SessionConfig {
+            session_timeout_seconds: 60,
+            ..Default::default()
+        };
         
         let manager = SessionManager::new(config, provider.clone());
         let hello = create_test_hello();
         
-        manager.handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent).unwrap();
+        manager
+            .handle_hello(&hello, "sess-123".to_string(), Role::BuyerAgent, &anonymous())
+            .unwrap();
         assert!(manager.get_session("sess-123").is_some());
         
         // Advance time past timeout
         provider.advance(61);
         
         // Cleanup should remove expired session
-        manager.cleanup_expired();
+        let removed = manager.cleanup_expired();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].limit_keys, vec![LimitKey::agent("buyer://alice")]);
         assert!(manager.get_session("sess-123").is_none());
     }
 }
@@ -1,4 +1,4 @@
-// crates/tbc-gateway/src/txip/timestamp_types_v03.rs
+// crates/txip/src/timestamp.rs
 // FINAL - CoreProver v0.3 Triple-Clock Timestamp Types
 //
 // This module defines the canonical triple-clock timestamp model:
@@ -43,7 +43,9 @@ impl TripleTimestamp {
             return Err("ISO timestamp must contain 'T' separator".to_string());
         }
         
-        if !self.iso.ends_with('Z') && !self.iso.contains('+') && !self.iso.contains('-') {
+        // The offset follows the time part; the date's own '-' does not count
+        let time = self.iso.split_once('T').map(|(_, time)| time).unwrap_or_default();
+        if !time.ends_with('Z') && !time.contains('+') && !time.contains('-') {
             return Err("ISO timestamp must have timezone (Z or +/-)".to_string());
         }
         
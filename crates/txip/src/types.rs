@@ -0,0 +1,794 @@
+// crates/txip/src/types.rs
+// FINAL - TxIP v0.2 Envelope Types with CoreProver v0.3 Compatibility
+//
+// This module defines TxIP message envelopes that transport TGP messages
+// with proper triple-clock timestamps and TXID provenance tracking.
+//
+// NO Instant, Duration, or SystemTime usage - all timestamps from engine.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::blockchain::ChainId;
+use crate::negotiation::NegotiatedCapabilities;
+use crate::timestamp::TripleTimestamp;
+use tbc_core::tgp::errors::{RetryHint, TgpErrorCode};
+use tbc_core::tgp::messages::TGPMessage;
+use tbc_core::tgp::ratelimit::RateLimitRejection;
+use tbc_core::tgp::validation::{ValidationIssue, ValidationReport};
+
+/// TxIP protocol version
+pub const TXIP_VERSION: &str = "0.2";
+
+/// TxIP message envelope with v0.3 compatibility
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxipEnvelope {
+    /// Protocol version
+    pub txip_version: String,
+    
+    /// Unique message identifier (UUID v4 recommended)
+    pub msg_id: String,
+    
+    /// Session identifier tying related messages together
+    pub session_id: String,
+    
+    /// Message direction hint
+    pub direction: Direction,
+    
+    /// Sender's role
+    pub role: Role,
+    
+    /// Message timestamp (triple-clock from engine)
+    pub timestamp_mono: u64,
+    pub timestamp_unix: u64,
+    pub timestamp_iso: String,
+    
+    /// Type of message
+    pub message_type: MessageType,
+    
+    /// TGP phase if applicable
+    pub tgp_phase: TgpPhase,
+    
+    /// Specific TGP event type if applicable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tgp_type: Option<String>,
+    
+    /// Origin chain ID for blockchain-aware routing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_chain_id: Option<ChainId>,
+    
+    /// Message payload
+    pub payload: Payload,
+}
+
+/// Message direction
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Direction {
+    ClientToTbc,
+    TbcToClient,
+    TbcToTbc,
+}
+
+/// Sender role
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Role {
+    BuyerAgent,
+    SellerAgent,
+    Tbc,
+    Watcher,
+}
+
+/// Message type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MessageType {
+    Control,
+    Tgp,
+    Error,
+}
+
+/// TGP phase
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TgpPhase {
+    Query,
+    Offer,
+    Settle,
+    Event,
+    None,
+}
+
+impl TgpPhase {
+    /// Phase of a TGP message
+    ///
+    /// ERROR messages carry no phase of their own.
+    pub fn of(message: &TGPMessage) -> Self {
+        match message {
+            TGPMessage::Query(_) => TgpPhase::Query,
+            TGPMessage::Offer(_) => TgpPhase::Offer,
+            TGPMessage::Settle(_) => TgpPhase::Settle,
+            TGPMessage::Error(_) => TgpPhase::None,
+        }
+    }
+}
+
+/// Message payload variants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Payload {
+    Control(ControlPayload),
+    Tgp(TgpPayload),
+    Error(ErrorPayload),
+}
+
+/// Control message payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "control_type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ControlPayload {
+    Hello(HelloPayload),
+    Welcome(WelcomePayload),
+    Heartbeat(HeartbeatPayload),
+    Close(ClosePayload),
+}
+
+/// HELLO control message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HelloPayload {
+    pub agent_id: String,
+    pub supported_tgp_versions: Vec<String>,
+    pub supported_transports: Vec<String>,
+    pub supported_chains: Vec<ChainId>,
+    pub supported_assets: Vec<String>,
+    pub features: Features,
+    pub auth: AuthInfo,
+}
+
+/// Feature flags
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Features {
+    pub zk_discount_proofs: bool,
+    pub receipt_ownership_proofs: bool,
+    pub late_discount_support: bool,
+    pub cross_chain_support: bool,
+}
+
+impl Features {
+    /// Features both sides support
+    pub fn intersect(&self, other: &Features) -> Features {
+        Features {
+            zk_discount_proofs: self.zk_discount_proofs && other.zk_discount_proofs,
+            receipt_ownership_proofs: self.receipt_ownership_proofs
+                && other.receipt_ownership_proofs,
+            late_discount_support: self.late_discount_support && other.late_discount_support,
+            cross_chain_support: self.cross_chain_support && other.cross_chain_support,
+        }
+    }
+}
+
+/// Authentication information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthInfo {
+    pub scheme: AuthScheme,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Authentication scheme
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuthScheme {
+    BearerJwt,
+    ApiKey,
+    Mtls,
+    None,
+}
+
+/// WELCOME control message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WelcomePayload {
+    pub tbc_id: String,
+    pub session_id: String,
+    pub negotiated_tgp_version: String,
+    pub negotiated_chains: Vec<ChainId>,
+    #[serde(default)]
+    pub negotiated_assets: Vec<String>,
+    pub negotiated_features: Features,
+    pub heartbeat_interval_sec: u64,
+
+    /// Pending TGP reply of a resumed session, e.g. its OFFER
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<Box<TGPMessage>>,
+}
+
+/// HEARTBEAT control message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatPayload {
+    pub seq: u64,
+}
+
+/// CLOSE control message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosePayload {
+    pub reason: CloseReason,
+}
+
+/// Close reason
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    IdleTimeout,
+    ClientShutdown,
+    ProtocolError,
+    Other,
+}
+
+/// TGP message payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TgpPayload {
+    /// Raw TGP message as defined in TGP-00
+    pub tgp: Value,
+}
+
+/// Error message payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorPayload {
+    pub error_code: ErrorCode,
+    pub http_status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_msg_id: Option<String>,
+    pub details: String,
+    pub retryable: bool,
+    /// Field-level TGP validation issues (TXIP_MALFORMED_TGP_PAYLOAD)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<ValidationIssue>,
+    /// Seconds to wait before retrying (TXIP_RATE_LIMITED)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+/// TxIP error codes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    TxipInvalidEnvelope,
+    TxipUnsupportedVersion,
+    TxipUnauthenticated,
+    TxipUnauthorized,
+    TxipInternalError,
+    TxipRateLimited,
+    TxipUpstreamUnavailable,
+    TxipMalformedTgpPayload,
+}
+
+impl ErrorCode {
+    /// Default HTTP status for this error
+    pub fn http_status(&self) -> u16 {
+        match self {
+            ErrorCode::TxipInvalidEnvelope
+            | ErrorCode::TxipUnsupportedVersion
+            | ErrorCode::TxipMalformedTgpPayload => 400,
+            ErrorCode::TxipUnauthenticated => 401,
+            ErrorCode::TxipUnauthorized => 403,
+            ErrorCode::TxipRateLimited => 429,
+            ErrorCode::TxipInternalError => 500,
+            ErrorCode::TxipUpstreamUnavailable => 503,
+        }
+    }
+
+    /// Whether the client may retry the same envelope
+    pub fn is_retryable(&self) -> bool {
+        self.to_tgp().is_retryable()
+    }
+
+    /// Transport-level code for a TGP error
+    ///
+    /// Returns `None` for business errors (no route, policy rejection, ...),
+    /// which travel as a TGP ERROR inside a normal TGP payload.
+    pub fn from_tgp(code: TgpErrorCode) -> Option<ErrorCode> {
+        match code {
+            TgpErrorCode::InvalidQuery | TgpErrorCode::InvalidMessage => {
+                Some(ErrorCode::TxipMalformedTgpPayload)
+            }
+            TgpErrorCode::UnsupportedVersion => Some(ErrorCode::TxipUnsupportedVersion),
+            TgpErrorCode::Unauthenticated => Some(ErrorCode::TxipUnauthenticated),
+            TgpErrorCode::Unauthorized => Some(ErrorCode::TxipUnauthorized),
+            TgpErrorCode::RateLimited => Some(ErrorCode::TxipRateLimited),
+            TgpErrorCode::EscrowUnavailable => Some(ErrorCode::TxipUpstreamUnavailable),
+            TgpErrorCode::Internal => Some(ErrorCode::TxipInternalError),
+            _ => None,
+        }
+    }
+
+    /// TGP code a client should surface for this transport error
+    pub fn to_tgp(&self) -> TgpErrorCode {
+        match self {
+            ErrorCode::TxipInvalidEnvelope | ErrorCode::TxipMalformedTgpPayload => {
+                TgpErrorCode::InvalidMessage
+            }
+            ErrorCode::TxipUnsupportedVersion => TgpErrorCode::UnsupportedVersion,
+            ErrorCode::TxipUnauthenticated => TgpErrorCode::Unauthenticated,
+            ErrorCode::TxipUnauthorized => TgpErrorCode::Unauthorized,
+            ErrorCode::TxipInternalError => TgpErrorCode::Internal,
+            ErrorCode::TxipRateLimited => TgpErrorCode::RateLimited,
+            ErrorCode::TxipUpstreamUnavailable => TgpErrorCode::EscrowUnavailable,
+        }
+    }
+}
+
+impl TxipEnvelope {
+    /// Create a new TxIP envelope with engine-provided timestamp
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        msg_id: String,
+        session_id: String,
+        direction: Direction,
+        role: Role,
+        message_type: MessageType,
+        tgp_phase: TgpPhase,
+        timestamp: TripleTimestamp,
+        payload: Payload,
+    ) -> Self {
+        Self {
+            txip_version: TXIP_VERSION.to_string(),
+            msg_id,
+            session_id,
+            direction,
+            role,
+            timestamp_mono: timestamp.mono,
+            timestamp_unix: timestamp.unix,
+            timestamp_iso: timestamp.iso,
+            message_type,
+            tgp_phase,
+            tgp_type: None,
+            origin_chain_id: None,
+            payload,
+        }
+    }
+
+    /// Create a TGP message envelope
+    pub fn tgp(
+        msg_id: String,
+        session_id: String,
+        direction: Direction,
+        role: Role,
+        tgp_phase: TgpPhase,
+        timestamp: TripleTimestamp,
+        tgp_message: Value,
+    ) -> Self {
+        Self::new(
+            msg_id,
+            session_id,
+            direction,
+            role,
+            MessageType::Tgp,
+            tgp_phase,
+            timestamp,
+            Payload::Tgp(TgpPayload { tgp: tgp_message }),
+        )
+    }
+
+    /// Create an error envelope
+    #[allow(clippy::too_many_arguments)]
+    pub fn error(
+        msg_id: String,
+        session_id: String,
+        direction: Direction,
+        error_code: ErrorCode,
+        http_status: u16,
+        related_msg_id: Option<String>,
+        details: String,
+        retryable: bool,
+        timestamp: TripleTimestamp,
+    ) -> Self {
+        Self::new(
+            msg_id,
+            session_id,
+            direction,
+            Role::Tbc,
+            MessageType::Error,
+            TgpPhase::None,
+            timestamp,
+            Payload::Error(ErrorPayload {
+                error_code,
+                http_status,
+                related_msg_id,
+                details,
+                retryable,
+                violations: Vec::new(),
+                retry_after_secs: None,
+            }),
+        )
+    }
+
+    /// Create an error envelope for a transport-level TGP error
+    ///
+    /// Returns `None` if `code` is a business error that belongs in a TGP
+    /// ERROR payload instead (see [`ErrorCode::from_tgp`]).
+    pub fn tgp_error(
+        msg_id: String,
+        session_id: String,
+        code: TgpErrorCode,
+        related_msg_id: Option<String>,
+        details: String,
+        timestamp: TripleTimestamp,
+    ) -> Option<Self> {
+        let error_code = ErrorCode::from_tgp(code)?;
+        Some(Self::error(
+            msg_id,
+            session_id,
+            Direction::TbcToClient,
+            error_code.clone(),
+            code.http_status(),
+            related_msg_id,
+            details,
+            code.retry_hint() == RetryHint::Backoff,
+            timestamp,
+        ))
+    }
+
+    /// Create a TXIP_RATE_LIMITED error carrying the `Retry-After` hint
+    pub fn rate_limited(
+        msg_id: String,
+        session_id: String,
+        related_msg_id: Option<String>,
+        rejection: &RateLimitRejection,
+        timestamp: TripleTimestamp,
+    ) -> Self {
+        let mut envelope = Self::error(
+            msg_id,
+            session_id,
+            Direction::TbcToClient,
+            ErrorCode::TxipRateLimited,
+            ErrorCode::TxipRateLimited.http_status(),
+            related_msg_id,
+            rejection.to_string(),
+            true,
+            timestamp,
+        );
+        if let Payload::Error(error) = &mut envelope.payload {
+            error.retry_after_secs = Some(rejection.retry_after_secs);
+        }
+        envelope
+    }
+
+    /// Create a TXIP_MALFORMED_TGP_PAYLOAD error listing every TGP violation
+    pub fn malformed_tgp_payload(
+        msg_id: String,
+        session_id: String,
+        related_msg_id: Option<String>,
+        report: &ValidationReport,
+        timestamp: TripleTimestamp,
+    ) -> Self {
+        Self::new(
+            msg_id,
+            session_id,
+            Direction::TbcToClient,
+            Role::Tbc,
+            MessageType::Error,
+            TgpPhase::None,
+            timestamp,
+            Payload::Error(ErrorPayload {
+                error_code: ErrorCode::TxipMalformedTgpPayload,
+                http_status: ErrorCode::TxipMalformedTgpPayload.http_status(),
+                related_msg_id,
+                details: report.summary(),
+                retryable: false,
+                violations: report.issues.clone(),
+                retry_after_secs: None,
+            }),
+        )
+    }
+
+    /// Create a WELCOME control message carrying the negotiated set
+    pub fn welcome(
+        msg_id: String,
+        session_id: String,
+        tbc_id: String,
+        negotiated: NegotiatedCapabilities,
+        heartbeat_interval_sec: u64,
+        timestamp: TripleTimestamp,
+    ) -> Self {
+        Self::new(
+            msg_id,
+            session_id.clone(),
+            Direction::TbcToClient,
+            Role::Tbc,
+            MessageType::Control,
+            TgpPhase::None,
+            timestamp,
+            Payload::Control(ControlPayload::Welcome(WelcomePayload {
+                tbc_id,
+                session_id,
+                negotiated_tgp_version: negotiated.tgp_version,
+                negotiated_chains: negotiated.chains,
+                negotiated_assets: negotiated.assets,
+                negotiated_features: negotiated.features,
+                heartbeat_interval_sec,
+                replay: None,
+            })),
+        )
+    }
+
+    /// Builder method to replay a TGP reply in a WELCOME
+    pub fn with_replay(mut self, replay: TGPMessage) -> Self {
+        if let Payload::Control(ControlPayload::Welcome(welcome)) = &mut self.payload {
+            welcome.replay = Some(Box::new(replay));
+        }
+        self
+    }
+
+    /// Create a HEARTBEAT control message sent by TBC
+    pub fn heartbeat(
+        msg_id: String,
+        session_id: String,
+        seq: u64,
+        timestamp: TripleTimestamp,
+    ) -> Self {
+        Self::new(
+            msg_id,
+            session_id,
+            Direction::TbcToClient,
+            Role::Tbc,
+            MessageType::Control,
+            TgpPhase::None,
+            timestamp,
+            Payload::Control(ControlPayload::Heartbeat(HeartbeatPayload { seq })),
+        )
+    }
+
+    /// Create a CLOSE control message sent by TBC
+    pub fn close(
+        msg_id: String,
+        session_id: String,
+        reason: CloseReason,
+        timestamp: TripleTimestamp,
+    ) -> Self {
+        Self::new(
+            msg_id,
+            session_id,
+            Direction::TbcToClient,
+            Role::Tbc,
+            MessageType::Control,
+            TgpPhase::None,
+            timestamp,
+            Payload::Control(ControlPayload::Close(ClosePayload { reason })),
+        )
+    }
+
+    /// Create a TGP EVENT pushed by TBC, e.g. an escrow state change
+    pub fn event(
+        msg_id: String,
+        session_id: String,
+        tgp_type: String,
+        timestamp: TripleTimestamp,
+        event: Value,
+    ) -> Self {
+        Self::tgp(
+            msg_id,
+            session_id,
+            Direction::TbcToClient,
+            Role::Tbc,
+            TgpPhase::Event,
+            timestamp,
+            event,
+        )
+        .with_tgp_type(tgp_type)
+    }
+
+    /// Add origin chain ID for blockchain-aware routing
+    pub fn with_origin_chain(mut self, chain_id: ChainId) -> Self {
+        self.origin_chain_id = Some(chain_id);
+        self
+    }
+
+    /// Add TGP event type
+    pub fn with_tgp_type(mut self, tgp_type: String) -> Self {
+        self.tgp_type = Some(tgp_type);
+        self
+    }
+
+    /// Get timestamp as TripleTimestamp
+    pub fn timestamp(&self) -> TripleTimestamp {
+        TripleTimestamp::new(
+            self.timestamp_mono,
+            self.timestamp_unix,
+            self.timestamp_iso.clone(),
+        )
+    }
+
+    /// Validate envelope structure
+    pub fn validate(&self) -> Result<(), String> {
+        if self.txip_version != TXIP_VERSION {
+            return Err(format!("Unsupported TxIP version: {}", self.txip_version));
+        }
+
+        if self.msg_id.is_empty() {
+            return Err("msg_id cannot be empty".to_string());
+        }
+
+        if self.session_id.is_empty() {
+            return Err("session_id cannot be empty".to_string());
+        }
+
+        // Validate timestamp ISO format
+        let ts = self.timestamp();
+        ts.validate_iso()?;
+
+        // Validate message_type matches payload
+        match (&self.message_type, &self.payload) {
+            (MessageType::Control, Payload::Control(_)) => Ok(()),
+            (MessageType::Tgp, Payload::Tgp(_)) => Ok(()),
+            (MessageType::Error, Payload::Error(_)) => Ok(()),
+            _ => Err("message_type does not match payload".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tbc_core::tgp::ratelimit::{LimitKey, RejectionReason};
+
+    fn create_test_timestamp() -> TripleTimestamp {
+        TripleTimestamp::new(
+            1000,
+            1731600000,
+            "2024-11-14T12:00:00Z".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_envelope_creation() {
+        let ts = create_test_timestamp();
+        let envelope = TxipEnvelope::tgp(
+            "msg-123".to_string(),
+            "sess-456".to_string(),
+            Direction::ClientToTbc,
+            Role::BuyerAgent,
+            TgpPhase::Query,
+            ts,
+            json!({"phase": "QUERY", "id": "q-123"}),
+        );
+
+        assert_eq!(envelope.msg_id, "msg-123");
+        assert_eq!(envelope.timestamp_mono, 1000);
+        assert_eq!(envelope.timestamp_unix, 1731600000);
+    }
+
+    #[test]
+    fn test_envelope_with_chain_id() {
+        let ts = create_test_timestamp();
+        let envelope = TxipEnvelope::tgp(
+            "msg-123".to_string(),
+            "sess-456".to_string(),
+            Direction::ClientToTbc,
+            Role::BuyerAgent,
+            TgpPhase::Query,
+            ts,
+            json!({"phase": "QUERY"}),
+        )
+        .with_origin_chain(369);
+
+        assert_eq!(envelope.origin_chain_id, Some(369));
+    }
+
+    #[test]
+    fn test_envelope_validation() {
+        let ts = create_test_timestamp();
+        let valid = TxipEnvelope::tgp(
+            "msg-123".to_string(),
+            "sess-456".to_string(),
+            Direction::ClientToTbc,
+            Role::BuyerAgent,
+            TgpPhase::Query,
+            ts,
+            json!({"phase": "QUERY"}),
+        );
+
+        assert!(valid.validate().is_ok());
+    }
+
+    #[test]
+    fn test_error_envelope() {
+        let ts = create_test_timestamp();
+        let error = TxipEnvelope::error(
+            "err-123".to_string(),
+            "sess-456".to_string(),
+            Direction::TbcToClient,
+            ErrorCode::TxipInvalidEnvelope,
+            400,
+            Some("msg-789".to_string()),
+            "Test error".to_string(),
+            false,
+            ts,
+        );
+
+        assert_eq!(error.message_type, MessageType::Error);
+        assert!(error.validate().is_ok());
+    }
+
+    #[test]
+    fn test_close_and_event_envelopes() {
+        let close = TxipEnvelope::close(
+            "msg-1".to_string(),
+            "sess-456".to_string(),
+            CloseReason::IdleTimeout,
+            create_test_timestamp(),
+        );
+        let json = serde_json::to_value(&close).unwrap();
+        assert_eq!(json["payload"], json!({"control_type": "CLOSE", "reason": "idle_timeout"}));
+
+        let decoded: TxipEnvelope = serde_json::from_value(json).unwrap();
+        assert!(matches!(
+            decoded.payload,
+            Payload::Control(ControlPayload::Close(ClosePayload {
+                reason: CloseReason::IdleTimeout
+            }))
+        ));
+
+        let event = TxipEnvelope::event(
+            "msg-2".to_string(),
+            "sess-456".to_string(),
+            "ESCROW_SETTLED".to_string(),
+            create_test_timestamp(),
+            json!({"order_id": "0xabc"}),
+        );
+        assert_eq!(event.tgp_phase, TgpPhase::Event);
+        assert_eq!(event.tgp_type.as_deref(), Some("ESCROW_SETTLED"));
+        assert!(event.validate().is_ok());
+    }
+
+    #[test]
+    fn test_tgp_error_code_mapping() {
+        for code in TgpErrorCode::ALL {
+            if let Some(txip) = ErrorCode::from_tgp(*code) {
+                assert_eq!(txip.http_status(), code.http_status());
+                assert_eq!(ErrorCode::from_tgp(txip.to_tgp()), Some(txip));
+            }
+        }
+        assert_eq!(ErrorCode::from_tgp(TgpErrorCode::NoRoute), None);
+
+        let envelope = TxipEnvelope::tgp_error(
+            "msg-err".to_string(),
+            "sess-456".to_string(),
+            TgpErrorCode::RateLimited,
+            None,
+            "slow down".to_string(),
+            create_test_timestamp(),
+        )
+        .unwrap();
+        match envelope.payload {
+            Payload::Error(error) => {
+                assert_eq!(error.error_code, ErrorCode::TxipRateLimited);
+                assert_eq!(error.http_status, 429);
+                assert!(error.retryable);
+            }
+            _ => panic!("expected error payload"),
+        }
+    }
+
+    #[test]
+    fn test_rate_limited_envelope() {
+        let rejection = RateLimitRejection {
+            key: LimitKey::agent("buyer://alice"),
+            reason: RejectionReason::Rate { requests_per_minute: 60 },
+            retry_after_secs: 7,
+        };
+        let envelope = TxipEnvelope::rate_limited(
+            "msg-err".to_string(),
+            "sess-456".to_string(),
+            Some("msg-123".to_string()),
+            &rejection,
+            create_test_timestamp(),
+        );
+
+        let json = serde_json::to_value(&envelope).unwrap();
+        assert_eq!(json["payload"]["error_code"], "TXIP_RATE_LIMITED");
+        assert_eq!(json["payload"]["http_status"], 429);
+        assert_eq!(json["payload"]["retry_after_secs"], 7);
+        assert_eq!(json["payload"]["retryable"], true);
+    }
+}
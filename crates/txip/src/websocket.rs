@@ -0,0 +1,556 @@
+// crates/txip/src/websocket.rs
+// TxIP WebSocket Transport
+//
+// Carries TxIP envelopes as JSON text frames, one session per connection.
+// Messages go through the same processing as the HTTP handler; the
+// connection adds the HELLO/WELCOME handshake, server HEARTBEATs, CLOSE with
+// a reason, and TGP EVENTs pushed by the controller.
+//
+// Ordered delivery: each connection has a single outbound queue drained by
+// one writer, and inbound messages are processed one at a time, so replies,
+// heartbeats and events reach the client in the order they were produced.
+//
+// Over TLS (`serve_tls`) the client certificate verified in the handshake
+// authenticates MTLS agents.
+//
+// The tokio timers only pace heartbeats and expiry sweeps. Whether a session
+// has timed out is decided by SessionManager::cleanup_expired on engine
+// timestamps.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+use crate::auth::PeerInfo;
+use crate::http_handler::{
+    cleanup_expired_sessions, generate_msg_id, process_message, release_session,
+    HttpHandlerState, TxipReply,
+};
+use crate::timestamp::TimestampProvider;
+use crate::types::*;
+
+/// WebSocket transport configuration
+#[derive(Debug, Clone)]
+pub struct WsConfig {
+    /// Seconds a new connection has to send HELLO
+    pub hello_timeout_seconds: u64,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            hello_timeout_seconds: 10,
+        }
+    }
+}
+
+/// Frame queued for a connection's writer
+enum Outbound {
+    Envelope(TxipEnvelope),
+
+    /// Send CLOSE, then close the socket
+    Close(TxipEnvelope),
+}
+
+/// Connection of an established session
+struct Connection {
+    agent_id: String,
+    role: Role,
+    sender: UnboundedSender<Outbound>,
+}
+
+/// TxIP over WebSocket
+///
+/// Shares `HttpHandlerState` with the HTTP handler, so sessions, rate limits
+/// and the TGP router are the same on both transports.
+pub struct WebSocketTransport<T: TimestampProvider> {
+    state: Arc<HttpHandlerState<T>>,
+    config: WsConfig,
+
+    /// Connections of established sessions, by session id
+    connections: RwLock<HashMap<String, Connection>>,
+}
+
+impl<T: TimestampProvider + Send + Sync + 'static> WebSocketTransport<T> {
+    /// Create a transport over the shared handler state
+    pub fn new(state: Arc<HttpHandlerState<T>>) -> Self {
+        Self {
+            state,
+            config: WsConfig::default(),
+            connections: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Override the transport configuration
+    pub fn with_config(mut self, config: WsConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Accept connections until the listener fails
+    ///
+    /// Also sweeps idle sessions once per heartbeat interval.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> std::io::Result<()> {
+        self.accept_loop(listener, None).await
+    }
+
+    /// Accept TLS connections until the listener fails
+    ///
+    /// With a config from [`mtls_server_config`](crate::auth::mtls_server_config)
+    /// clients must present a certificate, which MTLS agents authenticate
+    /// with.
+    pub async fn serve_tls(
+        self: Arc<Self>,
+        listener: TcpListener,
+        tls_config: Arc<rustls::ServerConfig>,
+    ) -> std::io::Result<()> {
+        self.accept_loop(listener, Some(TlsAcceptor::from(tls_config))).await
+    }
+
+    async fn accept_loop(
+        self: Arc<Self>,
+        listener: TcpListener,
+        tls: Option<TlsAcceptor>,
+    ) -> std::io::Result<()> {
+        let sweeper = tokio::spawn(self.clone().sweep_loop());
+
+        loop {
+            let (stream, addr) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    sweeper.abort();
+                    return Err(e);
+                }
+            };
+            tracing::debug!("TxIP WebSocket connection from {}", addr);
+
+            let Some(acceptor) = tls.clone() else {
+                let peer = PeerInfo {
+                    address: Some(addr.ip()),
+                    ..PeerInfo::default()
+                };
+                tokio::spawn(self.clone().handle_connection(stream, peer));
+                continue;
+            };
+            let transport = self.clone();
+            let handshake_timeout = Duration::from_secs(self.config.hello_timeout_seconds);
+            tokio::spawn(async move {
+                let handshake = acceptor.accept(stream);
+                let stream = match tokio::time::timeout(handshake_timeout, handshake).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        tracing::debug!("TLS handshake with {} failed: {}", addr, e);
+                        return;
+                    }
+                    Err(_) => {
+                        tracing::debug!("TLS handshake with {} timed out", addr);
+                        return;
+                    }
+                };
+                let peer = PeerInfo {
+                    client_certificates: stream
+                        .get_ref()
+                        .1
+                        .peer_certificates()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|cert| cert.0.clone())
+                        .collect(),
+                    address: Some(addr.ip()),
+                };
+                transport.handle_connection(stream, peer).await;
+            });
+        }
+    }
+
+    /// Serve one connection: WebSocket upgrade, HELLO/WELCOME, then the
+    /// session until either side closes it
+    ///
+    /// `peer` carries the client certificates if the stream is TLS.
+    pub async fn handle_connection<S>(self: Arc<Self>, stream: S, peer: PeerInfo)
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let socket = match tokio_tungstenite::accept_async(stream).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::warn!("TxIP WebSocket upgrade failed: {}", e);
+                return;
+            }
+        };
+        let (mut sink, mut frames) = socket.split();
+
+        // Everything sent on this connection goes through one queue
+        let (sender, mut queue) = mpsc::unbounded_channel();
+        let mut writer = tokio::spawn(async move {
+            while let Some(outbound) = queue.recv().await {
+                let (envelope, last) = match outbound {
+                    Outbound::Envelope(envelope) => (envelope, false),
+                    Outbound::Close(envelope) => (envelope, true),
+                };
+                let text = match serde_json::to_string(&envelope) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        tracing::error!("TxIP envelope could not be encoded: {}", e);
+                        continue;
+                    }
+                };
+                if sink.send(Message::Text(text)).await.is_err() || last {
+                    break;
+                }
+            }
+            let _ = sink.close().await;
+        });
+
+        let hello_timeout = Duration::from_secs(self.config.hello_timeout_seconds);
+        let handshake = self.handshake(&mut frames, &sender, &peer);
+        match tokio::time::timeout(hello_timeout, handshake).await {
+            Ok(Some(session_id)) => {
+                self.run_session(&session_id, &mut frames, &sender, &mut writer, &peer).await;
+                self.disconnect(&session_id, &sender);
+            }
+            Ok(None) => {}
+            Err(_) => tracing::debug!(
+                "TxIP WebSocket closed: no HELLO within {}s",
+                self.config.hello_timeout_seconds
+            ),
+        }
+
+        drop(sender);
+        if !writer.is_finished() {
+            let _ = writer.await;
+        }
+    }
+
+    /// Push a TGP EVENT to every connected session of a buyer agent
+    ///
+    /// Returns the number of sessions the event was queued for.
+    pub fn push_event(&self, agent_id: &str, tgp_type: &str, event: Value) -> usize {
+        let now = self.state.session_manager.now();
+        let connections = self.connections.read().unwrap();
+
+        connections
+            .iter()
+            .filter(|(_, connection)| {
+                connection.role == Role::BuyerAgent && connection.agent_id == agent_id
+            })
+            .filter(|(session_id, connection)| {
+                let envelope = TxipEnvelope::event(
+                    generate_msg_id(),
+                    session_id.to_string(),
+                    tgp_type.to_string(),
+                    now.clone(),
+                    event.clone(),
+                );
+                connection.sender.send(Outbound::Envelope(envelope)).is_ok()
+            })
+            .count()
+    }
+
+    /// Send CLOSE with `reason` and close the session's connection
+    ///
+    /// Returns false if the session has no open connection.
+    pub fn close_session(&self, session_id: &str, reason: CloseReason) -> bool {
+        let connections = self.connections.read().unwrap();
+        match connections.get(session_id) {
+            Some(connection) => self.send_close(&connection.sender, session_id, reason),
+            None => false,
+        }
+    }
+
+    /// Remove timed-out sessions and close their connections with
+    /// `idle_timeout`
+    ///
+    /// Returns the number of sessions removed, on any transport.
+    pub fn sweep_expired(&self) -> usize {
+        let expired = cleanup_expired_sessions(&self.state);
+        for session in &expired {
+            if self.close_session(&session.session_id, CloseReason::IdleTimeout) {
+                tracing::info!("Closed idle TxIP session {}", session.session_id);
+            }
+        }
+        expired.len()
+    }
+
+    /// Whether a session has an open connection
+    pub fn is_connected(&self, session_id: &str) -> bool {
+        self.connections.read().unwrap().contains_key(session_id)
+    }
+
+    /// Number of open connections
+    pub fn connection_count(&self) -> usize {
+        self.connections.read().unwrap().len()
+    }
+
+    async fn sweep_loop(self: Arc<Self>) {
+        let period = self.heartbeat_period();
+        let mut ticker = interval_at(Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            self.sweep_expired();
+        }
+    }
+
+    /// Wait for HELLO and answer it
+    ///
+    /// Returns the session id TBC issued in WELCOME once it is queued, or
+    /// `None` if the connection ends first.
+    async fn handshake<St>(
+        &self,
+        frames: &mut St,
+        sender: &UnboundedSender<Outbound>,
+        peer: &PeerInfo,
+    ) -> Option<String>
+    where
+        St: Stream<Item = Result<Message, WsError>> + Unpin,
+    {
+        let envelope = match next_envelope(frames).await? {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                tracing::debug!("TxIP WebSocket closed before HELLO: {}", e);
+                return None;
+            }
+        };
+        let session_id = envelope.session_id.clone();
+
+        if !matches!(envelope.payload, Payload::Control(ControlPayload::Hello(_))) {
+            self.send_error(
+                sender,
+                &session_id,
+                ErrorCode::TxipUnauthenticated,
+                Some(envelope.msg_id),
+                "Send HELLO first.".to_string(),
+            );
+            self.send_close(sender, &session_id, CloseReason::ProtocolError);
+            return None;
+        }
+
+        let reply = process_message(self.state.clone(), envelope, peer).await;
+        let (session_id, reason) = match &reply {
+            TxipReply::Envelope { envelope, .. } if is_welcome(envelope) => {
+                (envelope.session_id.clone(), None)
+            }
+            TxipReply::Envelope { status, .. } if status.is_client_error() => {
+                (session_id, Some(CloseReason::ProtocolError))
+            }
+            _ => (session_id, Some(CloseReason::Other)),
+        };
+
+        if let Some(envelope) = reply.envelope() {
+            let _ = sender.send(Outbound::Envelope(envelope.clone()));
+        }
+        if let Some(reason) = reason {
+            self.send_close(sender, &session_id, reason);
+            return None;
+        }
+
+        // Registered after WELCOME is queued, so pushed events follow it
+        self.register(&session_id, sender);
+        Some(session_id)
+    }
+
+    /// Process messages and send heartbeats until the session ends
+    async fn run_session<St>(
+        &self,
+        session_id: &str,
+        frames: &mut St,
+        sender: &UnboundedSender<Outbound>,
+        writer: &mut JoinHandle<()>,
+        peer: &PeerInfo,
+    ) where
+        St: Stream<Item = Result<Message, WsError>> + Unpin,
+    {
+        let period = self.heartbeat_period();
+        let mut heartbeat = interval_at(Instant::now() + period, period);
+        heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut seq = 0;
+
+        loop {
+            tokio::select! {
+                frame = next_envelope(frames) => match frame {
+                    Some(Ok(envelope)) => {
+                        if !self.handle_envelope(session_id, envelope, sender, peer).await {
+                            break;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        tracing::warn!("Protocol error on TxIP session {}: {}", session_id, e);
+                        self.send_close(sender, session_id, CloseReason::ProtocolError);
+                        break;
+                    }
+                    None => break,
+                },
+                _ = heartbeat.tick() => {
+                    seq += 1;
+                    let envelope = TxipEnvelope::heartbeat(
+                        generate_msg_id(),
+                        session_id.to_string(),
+                        seq,
+                        self.state.session_manager.now(),
+                    );
+                    let _ = sender.send(Outbound::Envelope(envelope));
+                }
+                // CLOSE was sent or the socket failed
+                _ = &mut *writer => break,
+            }
+        }
+    }
+
+    /// Process one message of an established session
+    ///
+    /// Returns false once the client has closed the session.
+    async fn handle_envelope(
+        &self,
+        session_id: &str,
+        envelope: TxipEnvelope,
+        sender: &UnboundedSender<Outbound>,
+        peer: &PeerInfo,
+    ) -> bool {
+        if envelope.session_id != session_id {
+            self.send_error(
+                sender,
+                session_id,
+                ErrorCode::TxipInvalidEnvelope,
+                Some(envelope.msg_id),
+                format!("session_id {} does not match this connection", envelope.session_id),
+            );
+            return true;
+        }
+
+        match process_message(self.state.clone(), envelope, peer).await {
+            TxipReply::Closed => false,
+            TxipReply::Accepted { .. } => true,
+            TxipReply::Envelope { envelope, .. } | TxipReply::RateLimited { envelope, .. } => {
+                let _ = sender.send(Outbound::Envelope(envelope));
+                true
+            }
+        }
+    }
+
+    /// Register the connection of an established session
+    ///
+    /// A connection resuming the session replaces the previous one; HELLO
+    /// already refused resumption by any agent but the session's own.
+    fn register(&self, session_id: &str, sender: &UnboundedSender<Outbound>) {
+        let Some(session) = self.state.session_manager.get_session(session_id) else {
+            return;
+        };
+
+        let connection = Connection {
+            agent_id: session.agent_id,
+            role: session.role,
+            sender: sender.clone(),
+        };
+        let previous = self
+            .connections
+            .write()
+            .unwrap()
+            .insert(session_id.to_string(), connection);
+
+        if let Some(previous) = previous {
+            self.send_close(&previous.sender, session_id, CloseReason::Other);
+        }
+    }
+
+    /// Unregister the connection and end its session
+    fn disconnect(&self, session_id: &str, sender: &UnboundedSender<Outbound>) {
+        let mut connections = self.connections.write().unwrap();
+
+        // A newer connection may have taken the session over
+        let owned = connections
+            .get(session_id)
+            .is_some_and(|connection| connection.sender.same_channel(sender));
+        if !owned {
+            return;
+        }
+        connections.remove(session_id);
+        drop(connections);
+
+        release_session(&self.state, session_id);
+    }
+
+    fn send_close(
+        &self,
+        sender: &UnboundedSender<Outbound>,
+        session_id: &str,
+        reason: CloseReason,
+    ) -> bool {
+        let close = TxipEnvelope::close(
+            generate_msg_id(),
+            session_id.to_string(),
+            reason,
+            self.state.session_manager.now(),
+        );
+        sender.send(Outbound::Close(close)).is_ok()
+    }
+
+    fn send_error(
+        &self,
+        sender: &UnboundedSender<Outbound>,
+        session_id: &str,
+        error_code: ErrorCode,
+        related_msg_id: Option<String>,
+        details: String,
+    ) {
+        let error = TxipEnvelope::error(
+            generate_msg_id(),
+            session_id.to_string(),
+            Direction::TbcToClient,
+            error_code.clone(),
+            error_code.http_status(),
+            related_msg_id,
+            details,
+            error_code.is_retryable(),
+            self.state.session_manager.now(),
+        );
+        let _ = sender.send(Outbound::Envelope(error));
+    }
+
+    fn heartbeat_period(&self) -> Duration {
+        Duration::from_secs(self.state.session_manager.heartbeat_interval_sec().max(1))
+    }
+}
+
+/// Next envelope from the socket, skipping ping and pong frames
+///
+/// Returns `None` once the socket is closed.
+async fn next_envelope<St>(frames: &mut St) -> Option<Result<TxipEnvelope, String>>
+where
+    St: Stream<Item = Result<Message, WsError>> + Unpin,
+{
+    while let Some(frame) = frames.next().await {
+        match frame {
+            Ok(Message::Text(text)) => {
+                return Some(
+                    serde_json::from_str(&text)
+                        .map_err(|e| format!("Invalid TxIP envelope: {}", e)),
+                );
+            }
+            Ok(Message::Binary(_)) => {
+                return Some(Err("Binary frames are not supported".to_string()));
+            }
+            Ok(Message::Close(_)) => return None,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::debug!("TxIP WebSocket read failed: {}", e);
+                return None;
+            }
+        }
+    }
+    None
+}
+
+fn is_welcome(envelope: &TxipEnvelope) -> bool {
+    matches!(envelope.payload, Payload::Control(ControlPayload::Welcome(_)))
+}
@@ -0,0 +1,389 @@
+// crates/txip/tests/websocket.rs
+// TxIP WebSocket transport over a real socket
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use tbc_core::tgp::ratelimit::{LimitKey, RateLimitConfig, RateLimiter};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_rustls::{rustls, TlsConnector};
+use tokio_tungstenite::{client_async, connect_async, MaybeTlsStream, WebSocketStream};
+use txip::*;
+
+type Client<S = MaybeTlsStream<TcpStream>> = WebSocketStream<S>;
+type TlsClient = Client<tokio_rustls::client::TlsStream<TcpStream>>;
+
+/// Engine clock the test moves by hand
+struct ManualClock {
+    mono: AtomicU64,
+}
+
+impl ManualClock {
+    fn advance(&self, seconds: u64) {
+        self.mono.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+impl TimestampProvider for ManualClock {
+    fn now(&self) -> TripleTimestamp {
+        let mono = self.mono.load(Ordering::SeqCst);
+        TripleTimestamp::new(mono, 1731600000 + mono, "2024-11-14T12:00:00Z".to_string())
+    }
+
+    fn at_unix(&self, unix: u64) -> TripleTimestamp {
+        let mono = self.mono.load(Ordering::SeqCst);
+        TripleTimestamp::new(mono, unix, "2024-11-14T12:00:00Z".to_string())
+    }
+
+    fn at_mono(&self, mono: u64) -> TripleTimestamp {
+        TripleTimestamp::new(mono, 1731600000 + mono, "2024-11-14T12:00:00Z".to_string())
+    }
+}
+
+const CA: &[u8] = include_bytes!("fixtures/ca.pem");
+const SERVER_CERT: &[u8] = include_bytes!("fixtures/server.pem");
+const SERVER_KEY: &[u8] = include_bytes!("fixtures/server.key");
+const CLIENT_CERT: &[u8] = include_bytes!("fixtures/client.pem");
+const CLIENT_KEY: &[u8] = include_bytes!("fixtures/client.key");
+
+struct Harness {
+    transport: Arc<WebSocketTransport<ManualClock>>,
+    state: Arc<HttpHandlerState<ManualClock>>,
+    clock: Arc<ManualClock>,
+    url: String,
+}
+
+async fn serve(heartbeat_interval_seconds: u64) -> Harness {
+    serve_with(heartbeat_interval_seconds, Authenticators::open(), None).await
+}
+
+async fn serve_with(
+    heartbeat_interval_seconds: u64,
+    authenticators: Authenticators,
+    tls: Option<rustls::ServerConfig>,
+) -> Harness {
+    let clock = Arc::new(ManualClock {
+        mono: AtomicU64::new(1000),
+    });
+    let config = SessionConfig {
+        session_timeout_seconds: 60,
+        message_cache_ttl_seconds: 600,
+        heartbeat_interval_seconds,
+    };
+    let state = Arc::new(HttpHandlerState {
+        session_manager: Arc::new(SessionManager::new(config, clock.clone())),
+        tbc_id: "tbc://test".to_string(),
+        authenticators: Arc::new(authenticators),
+        rate_limiter: Arc::new(RateLimiter::new(RateLimitConfig::default())),
+        tgp_router: None,
+    });
+    let transport = Arc::new(WebSocketTransport::new(state.clone()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    match tls {
+        Some(tls) => tokio::spawn(transport.clone().serve_tls(listener, Arc::new(tls))),
+        None => tokio::spawn(transport.clone().serve(listener)),
+    };
+
+    Harness {
+        transport,
+        state,
+        clock,
+        url,
+    }
+}
+
+fn client_envelope(msg_id: &str, message_type: MessageType, payload: Payload) -> TxipEnvelope {
+    TxipEnvelope::new(
+        msg_id.to_string(),
+        "sess-1".to_string(),
+        Direction::ClientToTbc,
+        Role::BuyerAgent,
+        message_type,
+        TgpPhase::None,
+        TripleTimestamp::new(1000, 1731600000, "2024-11-14T12:00:00Z".to_string()),
+        payload,
+    )
+}
+
+fn hello() -> TxipEnvelope {
+    hello_with(AuthScheme::None)
+}
+
+fn hello_with(scheme: AuthScheme) -> TxipEnvelope {
+    client_envelope(
+        "msg-hello",
+        MessageType::Control,
+        Payload::Control(ControlPayload::Hello(HelloPayload {
+            agent_id: "buyer://alice".to_string(),
+            supported_tgp_versions: vec!["TGP-01".to_string()],
+            supported_transports: vec!["WEBSOCKET".to_string()],
+            supported_chains: vec![1, 369],
+            supported_assets: vec!["USDC".to_string()],
+            features: Features {
+                zk_discount_proofs: false,
+                receipt_ownership_proofs: false,
+                late_discount_support: false,
+                cross_chain_support: false,
+            },
+            auth: AuthInfo {
+                scheme,
+                token: None,
+            },
+        })),
+    )
+}
+
+async fn send<S>(client: &mut Client<S>, envelope: &TxipEnvelope)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let text = serde_json::to_string(envelope).unwrap();
+    client.send(Message::Text(text)).await.unwrap();
+}
+
+/// Next envelope from the server, or `None` once the socket is closed
+async fn recv<S>(client: &mut Client<S>) -> Option<TxipEnvelope>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        let frame = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .expect("no frame within 5s");
+        match frame {
+            Some(Ok(Message::Text(text))) => return Some(serde_json::from_str(&text).unwrap()),
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return None,
+            Some(Ok(_)) => continue,
+        }
+    }
+}
+
+fn control(envelope: &TxipEnvelope) -> Option<&ControlPayload> {
+    match &envelope.payload {
+        Payload::Control(control) => Some(control),
+        _ => None,
+    }
+}
+
+/// Connect and HELLO, returning the client and the session id TBC issued
+async fn connect(harness: &Harness) -> (Client, String) {
+    let (mut client, _) = connect_async(harness.url.as_str()).await.unwrap();
+    send(&mut client, &hello()).await;
+
+    let welcome = recv(&mut client).await.unwrap();
+    let Some(ControlPayload::Welcome(welcome)) = control(&welcome) else {
+        panic!("expected WELCOME, got {:?}", welcome);
+    };
+    assert_ne!(welcome.session_id, "sess-1");
+    assert_eq!(welcome.tbc_id, "tbc://test");
+    (client, welcome.session_id.clone())
+}
+
+#[tokio::test]
+async fn test_events_and_heartbeats_in_order() {
+    let harness = serve(1).await;
+    let (mut client, session_id) = connect(&harness).await;
+    assert!(harness.transport.is_connected(&session_id));
+
+    for seq in 0..3 {
+        let queued = harness
+            .transport
+            .push_event("buyer://alice", "ESCROW_UPDATED", json!({ "seq": seq }));
+        assert_eq!(queued, 1);
+    }
+    assert_eq!(harness.transport.push_event("buyer://bob", "ESCROW_UPDATED", json!({})), 0);
+
+    let mut events = Vec::new();
+    let mut heartbeats = Vec::new();
+    while heartbeats.len() < 2 {
+        let envelope = recv(&mut client).await.unwrap();
+        match (&envelope.payload, envelope.tgp_phase) {
+            (Payload::Tgp(tgp), TgpPhase::Event) => {
+                assert_eq!(envelope.tgp_type.as_deref(), Some("ESCROW_UPDATED"));
+                events.push(tgp.tgp["seq"].as_u64().unwrap());
+            }
+            (Payload::Control(ControlPayload::Heartbeat(heartbeat)), _) => {
+                heartbeats.push(heartbeat.seq)
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+    assert_eq!(events, vec![0, 1, 2]);
+    assert_eq!(heartbeats, vec![1, 2]);
+
+    // Client CLOSE ends the session and the connection
+    let mut close = client_envelope(
+        "msg-close",
+        MessageType::Control,
+        Payload::Control(ControlPayload::Close(ClosePayload {
+            reason: CloseReason::ClientShutdown,
+        })),
+    );
+    close.session_id = session_id.clone();
+    send(&mut client, &close).await;
+    while recv(&mut client).await.is_some() {}
+
+    assert!(!harness.transport.is_connected(&session_id));
+    assert!(harness.state.session_manager.get_session(&session_id).is_none());
+}
+
+#[tokio::test]
+async fn test_hello_from_other_agent_cannot_take_over_session() {
+    let harness = serve(30).await;
+    let (_alice, session_id) = connect(&harness).await;
+
+    let (mut mallory, _) = connect_async(harness.url.as_str()).await.unwrap();
+    let mut takeover = hello();
+    takeover.msg_id = "msg-hello-2".to_string();
+    takeover.session_id = session_id.clone();
+    if let Payload::Control(ControlPayload::Hello(hello)) = &mut takeover.payload {
+        hello.agent_id = "buyer://mallory".to_string();
+    }
+    send(&mut mallory, &takeover).await;
+
+    let error = recv(&mut mallory).await.unwrap();
+    let Payload::Error(error) = &error.payload else {
+        panic!("expected ERROR, got {:?}", error);
+    };
+    assert_eq!(error.error_code, ErrorCode::TxipUnauthenticated);
+    while recv(&mut mallory).await.is_some() {}
+
+    assert!(harness.transport.is_connected(&session_id));
+    let session = harness.state.session_manager.get_session(&session_id).unwrap();
+    assert_eq!(session.agent_id, "buyer://alice");
+    assert_eq!(harness.transport.connection_count(), 1);
+}
+
+#[tokio::test]
+async fn test_idle_session_closed_with_reason() {
+    let harness = serve(30).await;
+    let (mut client, _) = connect(&harness).await;
+    let agent = LimitKey::agent("buyer://alice");
+    assert_eq!(harness.state.rate_limiter.open_sessions(&agent), 1);
+
+    harness.clock.advance(61);
+    assert_eq!(harness.transport.sweep_expired(), 1);
+
+    let close = recv(&mut client).await.unwrap();
+    let Some(ControlPayload::Close(close)) = control(&close) else {
+        panic!("expected CLOSE, got {:?}", close);
+    };
+    assert_eq!(close.reason, CloseReason::IdleTimeout);
+    assert!(recv(&mut client).await.is_none());
+
+    assert_eq!(harness.state.rate_limiter.open_sessions(&agent), 0);
+    assert_eq!(harness.transport.connection_count(), 0);
+}
+
+#[tokio::test]
+async fn test_message_before_hello_is_rejected() {
+    let harness = serve(30).await;
+    let (mut client, _) = connect_async(harness.url.as_str()).await.unwrap();
+
+    let query = client_envelope(
+        "msg-query",
+        MessageType::Tgp,
+        Payload::Tgp(TgpPayload {
+            tgp: json!({"phase": "QUERY", "id": "q-1"}),
+        }),
+    );
+    send(&mut client, &query).await;
+
+    let error = recv(&mut client).await.unwrap();
+    let Payload::Error(error) = &error.payload else {
+        panic!("expected ERROR, got {:?}", error);
+    };
+    assert_eq!(error.error_code, ErrorCode::TxipUnauthenticated);
+    assert_eq!(error.related_msg_id.as_deref(), Some("msg-query"));
+
+    let close = recv(&mut client).await.unwrap();
+    assert!(matches!(
+        control(&close),
+        Some(ControlPayload::Close(ClosePayload {
+            reason: CloseReason::ProtocolError
+        }))
+    ));
+    assert!(recv(&mut client).await.is_none());
+    assert_eq!(harness.transport.connection_count(), 0);
+}
+
+/// TLS client trusting the fixture CA, presenting the client certificate
+/// if `with_certificate`
+async fn connect_tls(harness: &Harness, with_certificate: bool) -> Option<TlsClient> {
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut &CA[..]).unwrap() {
+        roots.add(&rustls::Certificate(cert)).unwrap();
+    }
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+    let config = if with_certificate {
+        let chain = rustls_pemfile::certs(&mut &CLIENT_CERT[..])
+            .unwrap()
+            .into_iter()
+            .map(rustls::Certificate)
+            .collect();
+        let key = rustls_pemfile::pkcs8_private_keys(&mut &CLIENT_KEY[..]).unwrap().remove(0);
+        builder.with_client_auth_cert(chain, rustls::PrivateKey(key)).unwrap()
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    let addr = harness.url.trim_start_matches("ws://");
+    let tcp = TcpStream::connect(addr).await.unwrap();
+    let server_name = rustls::ServerName::try_from("localhost").unwrap();
+    let tls = TlsConnector::from(Arc::new(config)).connect(server_name, tcp).await.ok()?;
+
+    // With TLS 1.3 a refused client certificate only surfaces on the first
+    // read, in the WebSocket upgrade
+    let (client, _) = client_async(format!("wss://{}", addr), tls).await.ok()?;
+    Some(client)
+}
+
+#[tokio::test]
+async fn test_mtls_agent_authenticated_by_certificate() {
+    let authenticators = Authenticators::new().with(MtlsAuthenticator::new().with_subject(
+        "alice.agents.tbc",
+        "buyer://alice",
+        vec![TgpPhase::Query],
+    ));
+    let tls = mtls_server_config(SERVER_CERT, SERVER_KEY, CA).unwrap();
+    let harness = serve_with(30, authenticators, Some(tls)).await;
+
+    let mut client = connect_tls(&harness, true).await.expect("mTLS connection refused");
+    send(&mut client, &hello_with(AuthScheme::Mtls)).await;
+
+    let welcome = recv(&mut client).await.unwrap();
+    assert!(
+        matches!(control(&welcome), Some(ControlPayload::Welcome(_))),
+        "expected WELCOME, got {:?}",
+        welcome
+    );
+    let session_id = welcome.session_id;
+    let session = harness.state.session_manager.get_session(&session_id).unwrap();
+    assert_eq!(session.auth_scheme, AuthScheme::Mtls);
+    assert_eq!(session.scopes, vec![TgpPhase::Query]);
+
+    // Resuming the session without credentials is refused
+    let mut second = connect_tls(&harness, true).await.unwrap();
+    let mut anonymous = hello();
+    anonymous.msg_id = "msg-hello-2".to_string();
+    anonymous.session_id = session_id.clone();
+    send(&mut second, &anonymous).await;
+    let error = recv(&mut second).await.unwrap();
+    let Payload::Error(error) = &error.payload else {
+        panic!("expected ERROR, got {:?}", error);
+    };
+    assert_eq!(error.error_code, ErrorCode::TxipUnauthenticated);
+    assert!(harness.transport.is_connected(&session_id));
+
+    // Without a client certificate the TLS handshake fails
+    assert!(connect_tls(&harness, false).await.is_none());
+}